@@ -1,8 +1,14 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
 use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+#[cfg(feature = "datrie")]
+use aleph_alpha_tokenizer::MatcherBackend;
 #[cfg(feature = "huggingface")]
-use tokenizers::{models::wordpiece::WordPiece, tokenizer::Model};
+use tokenizers::{
+    models::wordpiece::WordPiece,
+    pre_tokenizers::whitespace::WhitespaceSplit,
+    tokenizer::{Encoding, Model, PreTokenizedString, PreTokenizer},
+};
 
 static TEXT_LIST: &[&str] = &[
     "Ich esse Steak.",
@@ -20,26 +26,54 @@ static TEXT_LIST: &[&str] = &[
 
 fn compare_aleph_wordpiece(c: &mut Criterion) {
     #[cfg(feature = "huggingface")]
-    let wordpiece = WordPiece::from_files("vocab.txt")
+    let wordpiece = WordPiece::from_file("vocab.txt")
         .unk_token("[UNK]".to_string())
         .build()
         .unwrap();
     let aleph_alpha = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    #[cfg(feature = "datrie")]
+    let datrie = AlephAlphaTokenizer::builder()
+        .vocab_path("vocab.txt")
+        .matcher_backend(MatcherBackend::DoubleArray)
+        .build()
+        .unwrap();
     let mut group = c.benchmark_group("Tokenizer");
     for (i, text) in TEXT_LIST.iter().cloned().enumerate() {
-        let mut words = Vec::new();
-        let mut o = 0;
-        for word in text.split(' ') {
-            words.push((word.to_string(), (o, o + word.len())));
-            o += word.len() + 1;
-        }
+        #[cfg(feature = "huggingface")]
+        let words: Vec<&str> = text.split(' ').collect();
+        #[cfg(feature = "huggingface")]
+        let tokenize = |model: &dyn Fn(&str) -> tokenizers::Result<Vec<_>>| {
+            words
+                .iter()
+                .map(|word| model(black_box(word)))
+                .collect::<tokenizers::Result<Vec<_>>>()
+        };
         #[cfg(feature = "huggingface")]
         group.bench_with_input(BenchmarkId::new("wordpiece", i), &i, |b, _| {
-            b.iter(|| wordpiece.tokenize(black_box(words.clone())))
+            b.iter(|| tokenize(&|word| wordpiece.tokenize(word)))
         });
         #[cfg(feature = "huggingface")]
         group.bench_with_input(BenchmarkId::new("aleph_alpha_model", i), &i, |b, _| {
-            b.iter(|| aleph_alpha.tokenize(black_box(words.clone())))
+            b.iter(|| tokenize(&|word| aleph_alpha.tokenize(word)))
+        });
+        #[cfg(feature = "huggingface")]
+        group.bench_with_input(
+            BenchmarkId::new("aleph_alpha_model_encoding", i),
+            &i,
+            |b, _| {
+                b.iter(|| {
+                    let tokens = tokenize(&|word| aleph_alpha.tokenize(word)).unwrap();
+                    Encoding::from_tokens(tokens.into_iter().flatten().collect(), 0)
+                })
+            },
+        );
+        #[cfg(feature = "huggingface")]
+        let mut pre_tokenized = PreTokenizedString::from(text);
+        #[cfg(feature = "huggingface")]
+        WhitespaceSplit.pre_tokenize(&mut pre_tokenized).unwrap();
+        #[cfg(feature = "huggingface")]
+        group.bench_with_input(BenchmarkId::new("aleph_alpha_encoding", i), &i, |b, _| {
+            b.iter(|| aleph_alpha.tokenize_into_encoding(black_box(pre_tokenized.clone()), 0))
         });
         group.bench_with_input(BenchmarkId::new("aleph_alpha", i), &i, |b, _| {
             let mut ids: Vec<u64> = Vec::new();
@@ -50,14 +84,199 @@ fn compare_aleph_wordpiece(c: &mut Criterion) {
                 black_box(&ranges);
             })
         });
+        #[cfg(feature = "datrie")]
+        group.bench_with_input(BenchmarkId::new("aleph_alpha_datrie", i), &i, |b, _| {
+            let mut ids: Vec<u64> = Vec::new();
+            let mut ranges = Vec::new();
+            b.iter(|| {
+                datrie.tokens_into(black_box(text), &mut ids, &mut ranges, None);
+                black_box(&ids);
+                black_box(&ranges);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("aleph_alpha_ids", i), &i, |b, _| {
+            let mut ids: Vec<u64> = Vec::new();
+            b.iter(|| {
+                aleph_alpha.ids_into(black_box(text), &mut ids);
+                black_box(&ids);
+            })
+        });
     }
     group.finish();
 }
 
+fn compare_batch(c: &mut Criterion) {
+    let aleph_alpha = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    let texts: Vec<&str> = TEXT_LIST
+        .iter()
+        .cycle()
+        .take(TEXT_LIST.len() * 2000)
+        .cloned()
+        .collect();
+    let mut group = c.benchmark_group("Batch");
+    let mut ids: Vec<Vec<u64>> = Vec::new();
+    let mut ranges = Vec::new();
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            aleph_alpha.tokens_into_batch(black_box(&texts), &mut ids, &mut ranges);
+            black_box(&ids);
+        })
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            aleph_alpha.par_tokens_into_batch(black_box(&texts), &mut ids, &mut ranges);
+            black_box(&ids);
+        })
+    });
+    group.finish();
+}
+
+fn compare_load(c: &mut Criterion) {
+    let binary_path = std::env::temp_dir().join("aat-bench-vocab.bin");
+    AlephAlphaTokenizer::from_vocab("vocab.txt")
+        .unwrap()
+        .save_binary(&binary_path)
+        .unwrap();
+    let mut group = c.benchmark_group("Load");
+    group.bench_function("from_vocab", |b| {
+        b.iter(|| AlephAlphaTokenizer::from_vocab(black_box("vocab.txt")).unwrap())
+    });
+    let vocab = std::fs::read("vocab.txt").unwrap();
+    group.bench_function("from_vocab_bytes", |b| {
+        b.iter(|| AlephAlphaTokenizer::from_vocab_bytes(black_box(&vocab)).unwrap())
+    });
+    group.bench_function("from_binary", |b| {
+        b.iter(|| AlephAlphaTokenizer::from_binary(black_box(&binary_path)).unwrap())
+    });
+    group.bench_function("from_binary_unchecked", |b| {
+        b.iter(|| AlephAlphaTokenizer::from_binary_unchecked(black_box(&binary_path)).unwrap())
+    });
+    group.finish();
+}
+
+fn compare_construction(c: &mut Criterion) {
+    // a vocabulary about the size of a multilingual one, from copies of the
+    // tokens with a private use character and a digit appended
+    let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    let mut large = vocab.clone();
+    for i in 0..8 {
+        for line in vocab.lines().filter(|line| !line.starts_with('[')) {
+            large.push_str(&format!("{}\u{e000}{}\n", line, i));
+        }
+    }
+    let large_path = std::env::temp_dir().join("aat-bench-large-vocab.txt");
+    std::fs::write(&large_path, &large).unwrap();
+    let large_path = large_path.to_str().unwrap();
+    let mut group = c.benchmark_group("Construction");
+    for (name, path) in &[("vocab", "vocab.txt"), ("large_vocab", large_path)] {
+        group.bench_with_input(BenchmarkId::new("serial", name), path, |b, path| {
+            b.iter(|| AlephAlphaTokenizer::from_vocab(black_box(path)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("parallel_build", name), path, |b, path| {
+            b.iter(|| {
+                AlephAlphaTokenizer::builder()
+                    .vocab_path(black_box(path))
+                    .parallel_build(true)
+                    .build()
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compare_whitespace(c: &mut Criterion) {
+    let aleph_alpha = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    let mut group = c.benchmark_group("Whitespace");
+    for &len in &[1 << 16, 1 << 20] {
+        // blank padding around a sentence should take time linear in `len`
+        let padding = " ".repeat(len);
+        let text = format!("{}Ich esse Steak.{}", padding, padding);
+        let mut ids: Vec<u64> = Vec::new();
+        let mut ranges = Vec::new();
+        group.bench_with_input(BenchmarkId::new("padding", len), &len, |b, _| {
+            b.iter(|| {
+                aleph_alpha.tokens_into(black_box(&text), &mut ids, &mut ranges, None);
+                black_box(&ids);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compare_ascii(c: &mut Criterion) {
+    let fast = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    let slow = AlephAlphaTokenizer::builder()
+        .vocab_path("vocab.txt")
+        .ascii_fast_path(false)
+        .build()
+        .unwrap();
+    // a long document of the ASCII sentences
+    let sentences: Vec<&str> = TEXT_LIST
+        .iter()
+        .cloned()
+        .filter(|text| text.is_ascii())
+        .collect();
+    let text = sentences.join("\n").repeat(2000);
+    let mut group = c.benchmark_group("Ascii");
+    for (name, tokenizer) in &[("fast", &fast), ("general", &slow)] {
+        let mut ids: Vec<u64> = Vec::new();
+        let mut ranges = Vec::new();
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                tokenizer.tokens_into(black_box(&text), &mut ids, &mut ranges, None);
+                black_box(&ids);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compare_unknown_word(c: &mut Criterion) {
+    let unbounded = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    let bounded = AlephAlphaTokenizer::builder()
+        .vocab_path("vocab.txt")
+        .max_tokens_per_word(Some(100))
+        .build()
+        .unwrap();
+    // a base64 blob of 100 kB without whitespace
+    let text = "QmFzZTY0IGlzIG5vIEdlcm1hbg".repeat(4_000);
+    let mut group = c.benchmark_group("UnknownWord");
+    for (name, tokenizer) in &[("unbounded", &unbounded), ("bounded", &bounded)] {
+        let mut ids: Vec<u64> = Vec::new();
+        let mut ranges = Vec::new();
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                tokenizer.tokens_into(black_box(&text), &mut ids, &mut ranges, None);
+                black_box(&ids);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compare_is_special(c: &mut Criterion) {
+    let mut aleph_alpha = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    let added: Vec<String> = (0..1000).map(|i| format!("<special{}>", i)).collect();
+    let added: Vec<&str> = added.iter().map(String::as_str).collect();
+    aleph_alpha.add_special_tokens(&added).unwrap();
+    let len = aleph_alpha.vocab_size() as u64;
+    let ids: Vec<u64> = (0..1_000_000u64).map(|i| i * 7919 % len).collect();
+    c.bench_function("is_special", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter(|&&id| aleph_alpha.is_special(black_box(id)))
+                .count()
+        })
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(10);
-    targets = compare_aleph_wordpiece
+    targets = compare_aleph_wordpiece, compare_batch, compare_load, compare_construction,
+        compare_whitespace, compare_ascii, compare_unknown_word, compare_is_special
 }
 
 criterion_main!(benches);
@@ -0,0 +1,41 @@
+#![no_main]
+
+use std::sync::{Arc, RwLock};
+use aleph_alpha_tokenizer::{AlephAlphaTokenizer, HfTokenizer};
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use tokenizers::{
+    normalizers::bert::BertNormalizer,
+    pre_tokenizers::bert::BertPreTokenizer,
+    tokenizer::{NormalizedString, Normalizer},
+};
+
+fn normalizer() -> BertNormalizer {
+    BertNormalizer::new(true, true, Some(true), true)
+}
+
+static ALEPH: Lazy<Arc<RwLock<HfTokenizer>>> = Lazy::new(|| {
+    let mut tokenizer = HfTokenizer::new(AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap());
+    tokenizer.with_normalizer(Some(normalizer())).unwrap();
+    tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
+    Arc::new(RwLock::new(tokenizer))
+});
+
+// every token's offsets must point at the characters of the original text it
+// was matched from
+fuzz_target!(|s: String| {
+    let encoding = match ALEPH.read().unwrap().encode_char_offsets(s.as_str(), false) {
+        Ok(encoding) => encoding,
+        Err(_) => return,
+    };
+    let chars: Vec<char> = s.chars().collect();
+    for (token, &(start, end)) in encoding.get_tokens().iter().zip(encoding.get_offsets()) {
+        assert!(start < end && end <= chars.len(), "{:?} at {:?}", token, (start, end));
+        if token == "[UNK]" {
+            continue;
+        }
+        let mut source = NormalizedString::from(chars[start..end].iter().collect::<String>());
+        normalizer().normalize(&mut source).unwrap();
+        assert_eq!(source.get().trim(), token.trim_start_matches("##"));
+    }
+});
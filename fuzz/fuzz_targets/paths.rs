@@ -0,0 +1,15 @@
+#![no_main]
+
+use aleph_alpha_tokenizer::{compare_paths, AlephAlphaTokenizer};
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+
+static ALEPH: Lazy<AlephAlphaTokenizer> =
+    Lazy::new(|| AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap());
+
+// the standalone tokenization and the `Model` must agree on ids and offsets
+fuzz_target!(|s: String| {
+    if let Err(divergence) = compare_paths(&ALEPH, &s) {
+        panic!("{:?}: {}", s, divergence);
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use aleph_alpha_tokenizer::{compare_paths, AlephAlphaTokenizer};
+use libfuzzer_sys::fuzz_target;
+
+// like `paths`, but with a vocabulary made up of the fuzzed tokens
+fuzz_target!(|input: (Vec<String>, String)| {
+    let (tokens, text) = input;
+    let mut vocab = String::from("[UNK]\n[CLS]\n[SEP]\n");
+    for token in tokens {
+        // a line holds one token without surrounding whitespace
+        if !token.is_empty() && !token.contains(char::is_whitespace) {
+            vocab.push_str(&token);
+            vocab.push('\n');
+        }
+    }
+    let tokenizer = match AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()) {
+        Ok(tokenizer) => tokenizer,
+        Err(_) => return,
+    };
+    if let Err(divergence) = compare_paths(&tokenizer, &text) {
+        panic!("{:?} with {:?}: {}", text, vocab, divergence);
+    }
+});
@@ -0,0 +1,89 @@
+#ifndef ALEPH_ALPHA_TOKENIZER_H
+#define ALEPH_ALPHA_TOKENIZER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A required pointer argument was null.
+#define AA_ERROR_NULL_POINTER -1
+
+// The text was not valid UTF-8.
+#define AA_ERROR_INVALID_UTF8 -2
+
+// The output buffer was too small; nothing useful was written to it.
+#define AA_ERROR_BUFFER_TOO_SMALL -3
+
+// The token id is not in the vocabulary.
+#define AA_ERROR_UNKNOWN_ID -4
+
+// The tokenizer panicked.
+#define AA_ERROR_PANIC -5
+
+// The opaque tokenizer handle of the C interface.
+typedef struct AaTokenizer AaTokenizer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Loads a tokenizer from a vocabulary file with one token per line, or
+// returns null if the path is null or not UTF-8, or the file cannot be
+// loaded.
+//
+// # Safety
+//
+// `vocab_path` must be null or point to a NUL-terminated string.
+struct AaTokenizer *aa_tokenizer_new(const char *vocab_path);
+
+// Frees a tokenizer from [`aa_tokenizer_new`]; null is ignored.
+//
+// # Safety
+//
+// `tokenizer` must be null or come from [`aa_tokenizer_new`], and must not
+// be used afterwards.
+void aa_tokenizer_free(struct AaTokenizer *tokenizer);
+
+// Tokenizes the `text_len` bytes of UTF-8 text at `text`, writing up to
+// `capacity` token ids to `out_ids` and their byte ranges to `out_offsets`
+// as `start, end` pairs (so it must hold `2 * capacity` values).
+//
+// Returns the number of tokens, or [`AA_ERROR_BUFFER_TOO_SMALL`] if there
+// are more than `capacity`. A `capacity` of `text_len + 2` always suffices.
+// `out_offsets` may be null if the offsets aren't needed.
+//
+// # Safety
+//
+// `tokenizer` must come from [`aa_tokenizer_new`], `text` must point to
+// `text_len` readable bytes, `out_ids` to `capacity` and `out_offsets` (if
+// not null) to `2 * capacity` writable values.
+ptrdiff_t aa_tokenize(const struct AaTokenizer *tokenizer,
+                      const uint8_t *text,
+                      size_t text_len,
+                      int64_t *out_ids,
+                      size_t *out_offsets,
+                      size_t capacity);
+
+// Copies the text of the token with the given id to `buf` as a
+// NUL-terminated string of at most `buf_len` bytes.
+//
+// Returns the length of the text without the NUL,
+// [`AA_ERROR_UNKNOWN_ID`] if the id is not in the vocabulary, or
+// [`AA_ERROR_BUFFER_TOO_SMALL`] if the text and NUL don't fit.
+//
+// # Safety
+//
+// `tokenizer` must come from [`aa_tokenizer_new`] and `buf` must point to
+// `buf_len` writable bytes.
+ptrdiff_t aa_id_to_token(const struct AaTokenizer *tokenizer,
+                         int64_t id,
+                         char *buf,
+                         size_t buf_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ALEPH_ALPHA_TOKENIZER_H */
@@ -0,0 +1,17 @@
+//! The `aleph_alpha_tokenizer` Python module, built with
+//! [maturin](https://github.com/PyO3/maturin):
+//!
+//! ```text
+//! cd python
+//! maturin develop --release
+//! python -c 'import aleph_alpha_tokenizer'
+//! ```
+
+use tokenizer::PyAlephAlphaTokenizer;
+use pyo3::prelude::*;
+
+#[pymodule]
+#[pyo3(name = "aleph_alpha_tokenizer")]
+fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyAlephAlphaTokenizer>()
+}
@@ -0,0 +1,303 @@
+use crate::{
+    push_keys, AlephAlphaTokenizer, ConsistencyError, FstData, FstKeys, SpecialTokens,
+    TokenizerError,
+};
+use fst::raw::Fst;
+use fst::Streamer;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+// the keys of the FST together with the new keys, sorted
+fn merged_keys(fst: &Fst<FstData>, new: FstKeys<'_>) -> Vec<(Vec<u8>, u64)> {
+    let mut keys = Vec::with_capacity(fst.len() + new.len());
+    let mut stream = fst.stream();
+    while let Some((key, output)) = stream.next() {
+        keys.push((key.to_vec(), output.value()));
+    }
+    keys.extend(new.into_iter().map(|(key, id)| (key.to_vec(), id)));
+    keys.sort_by(|(k, _), (j, _)| k.cmp(j));
+    keys
+}
+
+impl AlephAlphaTokenizer {
+    /// Appends tokens to the vocabulary, returning their new ids in the
+    /// given order.
+    ///
+    /// Like in a vocabulary file, tokens starting with the
+    /// [continuing subword prefix](AlephAlphaTokenizer::continuing_subword_prefix)
+    /// continue a word, all others start one (or, with an
+    /// [end-of-word suffix](crate::MarkerScheme::EndOfWordSuffix), those
+    /// ending with it end a word). Only the FSTs that get new
+    /// tokens are rebuilt. [`save_vocab`](AlephAlphaTokenizer::save_vocab)
+    /// writes the extended vocabulary, so loading it again gives the same
+    /// ids.
+    ///
+    /// Tokens that are already in the vocabulary (or given twice) are
+    /// rejected with [`TokenizerError::DuplicateToken`], empty tokens and
+    /// tokens containing whitespace with [`TokenizerError::InvalidVocab`]. On
+    /// error, the vocabulary stays unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let mut tokenizer = examples::tokenizer();
+    /// let text = "Batman Robin Aspirin";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Bat ##man [UNK] [UNK] [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// assert_eq!(vec![19, 20], tokenizer.add_tokens(&["Robin", "Aspirin"]).unwrap());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Bat ##man Robin Aspirin [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[2, 18, 17, 19, 20, 3], &ids[..]);
+    /// assert_eq!(&[0..0, 0..3, 3..6, 7..12, 13..20, 20..20], &ranges[..]);
+    ///
+    /// // followers continue words
+    /// assert_eq!(vec![21], tokenizer.add_tokens(&["##son"]).unwrap());
+    /// tokenizer.tokens_into("Robinson", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 19, 21, 3], &ids[..]);
+    ///
+    /// // a reload of the saved vocabulary gives the same ids
+    /// let path = std::env::temp_dir().join("aat-added-vocab.txt");
+    /// tokenizer.save_vocab(path.clone()).unwrap();
+    /// let reloaded = AlephAlphaTokenizer::from_vocab(path.to_str().unwrap()).unwrap();
+    /// let mut reloaded_ids = Vec::<u64>::new();
+    /// reloaded.tokens_into("Robinson", &mut reloaded_ids, &mut ranges, None);
+    /// assert_eq!(ids, reloaded_ids);
+    /// assert_eq!(Some(20), reloaded.id_of("Aspirin"));
+    ///
+    /// let error = tokenizer.add_tokens(&["Joker", "Bat"]).unwrap_err();
+    /// assert!(matches!(
+    ///     error,
+    ///     TokenizerError::DuplicateToken { ref token, first_line: 19, second_line: 24 } if token == "Bat"
+    /// ));
+    /// assert!(tokenizer.add_tokens(&["Joker", "Joker"]).is_err());
+    /// assert!(tokenizer.add_tokens(&["Two-Face", "Bruce Wayne"]).is_err());
+    /// assert_eq!(None, tokenizer.id_of("Joker"));
+    /// assert_eq!(22, tokenizer.vocab_size());
+    /// ```
+    pub fn add_tokens(&mut self, tokens: &[&str]) -> Result<Vec<u64>, TokenizerError> {
+        self.add_tokens_as(tokens, false)
+    }
+
+    /// Like [`add_tokens`](AlephAlphaTokenizer::add_tokens), but registers
+    /// the tokens as special tokens, which always start a word and are
+    /// reported by [`is_special`](AlephAlphaTokenizer::is_special).
+    ///
+    /// Loading a saved vocabulary only makes the tokens special again if
+    /// they are in square brackets (or named in the [`SpecialTokens`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let mut tokenizer = examples::tokenizer();
+    /// assert_eq!(vec![19, 20], tokenizer.add_special_tokens(&["[BOS]", "[EOS]"]).unwrap());
+    /// assert!(tokenizer.is_special(19u64) && tokenizer.is_special(20u64));
+    /// assert_eq!(Some(20), tokenizer.id_of("[EOS]"));
+    /// assert_eq!(&[0, 1, 2, 3, 4, 19, 20], tokenizer.special_token_ids());
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("[BOS] Ich [EOS]", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 19, 10, 20, 3], &ids[..]);
+    /// ```
+    ///
+    /// [`SpecialTokens`]: crate::SpecialTokens
+    pub fn add_special_tokens(&mut self, tokens: &[&str]) -> Result<Vec<u64>, TokenizerError> {
+        self.add_tokens_as(tokens, true)
+    }
+
+    /// Creates a tokenizer with the tokens of the base and the additional
+    /// tokens that the base lacks, returning the ids of the latter.
+    ///
+    /// The base tokens keep their ids, and the new tokens are appended in
+    /// the given order, like with
+    /// [`add_tokens`](AlephAlphaTokenizer::add_tokens). Tokens the base
+    /// already has, duplicates and blank lines are skipped, so the additions
+    /// can be the lines of another vocabulary file. New tokens in square
+    /// brackets (other than `[unused…]`) become special tokens.
+    ///
+    /// The BERT and RoBERTa names of `[UNK]`, `[CLS]`, `[SEP]`, `[PAD]` and
+    /// `[MASK]` (see [`SpecialTokens`]) must have the base's spelling: an
+    /// addition `<unk>` for a base with `[UNK]` fails with
+    /// [`TokenizerError::SpecialTokenConflict`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let base = examples::tokenizer();
+    /// let domain = "[UNK]\nAspirin\n##tablette\nIch\n\n[DOSIS]\n";
+    /// let (merged, added) =
+    ///     AlephAlphaTokenizer::merge(&base, domain.lines().map(String::from)).unwrap();
+    /// assert_eq!(vec![19, 20, 21], added);
+    /// assert!(merged.is_special(21u64));
+    ///
+    /// // the base ids stay the same
+    /// for id in 0..base.vocab_size() as u64 {
+    ///     assert_eq!(base.text_of(id), merged.text_of(id));
+    /// }
+    /// let text = "Ich esse Steak. Aspirintablette [DOSIS]";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// base.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 10, 11, 12, 13, 14, 15, 1, 1, 3], &ids[..]);
+    /// merged.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 10, 11, 12, 13, 14, 15, 19, 20, 21, 3], &ids[..]);
+    ///
+    /// let error = AlephAlphaTokenizer::merge(&base, vec!["<unk>".to_string()]).err().unwrap();
+    /// assert!(matches!(
+    ///     error,
+    ///     TokenizerError::SpecialTokenConflict { ref base, ref other } if base == "[UNK]" && other == "<unk>"
+    /// ));
+    /// assert_eq!(
+    ///     "special token \"[UNK]\" is \"<unk>\" in the other vocabulary",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn merge(
+        base: &AlephAlphaTokenizer,
+        additions: impl IntoIterator<Item = String>,
+    ) -> Result<(AlephAlphaTokenizer, Vec<u64>), TokenizerError> {
+        let (bert, roberta) = (SpecialTokens::default(), SpecialTokens::roberta());
+        let roles = [
+            (Some(base.unk_id), [Some(bert.unk), Some(roberta.unk)]),
+            (base.prefix, [bert.cls, roberta.cls]),
+            (base.suffix, [bert.sep, roberta.sep]),
+            (base.pad_id, [bert.pad, roberta.pad]),
+            (base.mask_id, [bert.mask, roberta.mask]),
+        ];
+        let mut seen: HashSet<String> = base.tokens.iter().map(|t| t.trim().to_string()).collect();
+        let mut new: Vec<(String, bool)> = Vec::new();
+        for token in additions {
+            let token = token.trim();
+            if token.is_empty() || !seen.insert(token.to_string()) {
+                continue;
+            }
+            let mut special =
+                token.starts_with('[') && token.ends_with(']') && !token.starts_with("[unused");
+            for (id, names) in &roles {
+                if names.iter().flatten().any(|&name| name == token) {
+                    if let Some(id) = id {
+                        return Err(TokenizerError::SpecialTokenConflict {
+                            base: base.text_of(*id).trim().to_string(),
+                            other: token.to_string(),
+                        });
+                    }
+                    special = true;
+                }
+            }
+            new.push((token.to_string(), special));
+        }
+
+        // a copy of the base, as pruning nothing keeps every id
+        let (mut merged, _) = base.prune(&|_, _| true);
+        let mut ids = Vec::with_capacity(new.len());
+        let mut start = 0;
+        while start < new.len() {
+            // add each run of special or other tokens at once
+            let special = new[start].1;
+            let len = new[start..]
+                .iter()
+                .take_while(|(_, s)| *s == special)
+                .count();
+            let run: Vec<&str> = new[start..start + len]
+                .iter()
+                .map(|(token, _)| token.as_str())
+                .collect();
+            ids.extend(merged.add_tokens_as(&run, special)?);
+            start += len;
+        }
+        Ok((merged, ids))
+    }
+
+    fn add_tokens_as(
+        &mut self,
+        tokens: &[&str],
+        special: bool,
+    ) -> Result<Vec<u64>, TokenizerError> {
+        let first_id = self.tokens.len();
+        let mut new: HashMap<&str, usize> = HashMap::with_capacity(tokens.len());
+        for (i, &token) in tokens.iter().enumerate() {
+            if token.is_empty() || token.contains(char::is_whitespace) {
+                return Err(TokenizerError::InvalidVocab(format!(
+                    "token {:?} is empty or contains whitespace",
+                    token
+                )));
+            }
+            if let Some(first) = new.insert(token, i) {
+                return Err(TokenizerError::DuplicateToken {
+                    token: token.to_string(),
+                    first_line: first_id + first + 1,
+                    second_line: first_id + i + 1,
+                });
+            }
+        }
+        if let Some((id, i)) = self
+            .tokens
+            .iter()
+            .enumerate()
+            .find_map(|(id, token)| new.get(token.trim()).map(|&i| (id, i)))
+        {
+            return Err(TokenizerError::DuplicateToken {
+                token: tokens[i].to_string(),
+                first_line: id + 1,
+                second_line: first_id + i + 1,
+            });
+        }
+        let max_len = u64::from(u32::MAX) + 1;
+        if (first_id + tokens.len()) as u64 > max_len {
+            return Err(TokenizerError::Inconsistent(ConsistencyError::IdTooLarge {
+                key: tokens[(max_len as usize) - first_id].to_string(),
+                id: max_len,
+            }));
+        }
+
+        let (mut starter, mut follower): (FstKeys<'_>, FstKeys<'_>) = (Vec::new(), Vec::new());
+        let markers = (
+            self.continuing_subword_prefix.as_str(),
+            self.end_of_word_suffix.as_str(),
+        );
+        for (i, &token) in tokens.iter().enumerate() {
+            let id = (first_id + i) as u64;
+            if special {
+                starter.push((token.as_bytes(), id));
+            } else {
+                push_keys(token, id, markers, &mut starter, &mut follower);
+            }
+        }
+        // build both FSTs before changing anything
+        let starters = if starter.is_empty() {
+            None
+        } else {
+            let keys = merged_keys(&self.starters, starter);
+            Some(Fst::from_iter_map(keys)?.map_data(FstData::Owned)?)
+        };
+        let followers = if follower.is_empty() {
+            None
+        } else {
+            let keys = merged_keys(&self.followers, follower);
+            Some(Fst::from_iter_map(keys)?.map_data(FstData::Owned)?)
+        };
+        if let Some(starters) = starters {
+            self.starters = Arc::new(starters);
+        }
+        if let Some(followers) = followers {
+            self.followers = Arc::new(followers);
+        }
+
+        let ids: Vec<u64> = (first_id..first_id + tokens.len())
+            .map(|id| id as u64)
+            .collect();
+        // clones keep the vocabulary they had
+        Arc::make_mut(&mut self.tokens).extend(tokens.iter().cloned());
+        if special {
+            Arc::make_mut(&mut self.special_tokens).extend_from_slice(&ids);
+        }
+        self.indexes = Arc::default();
+        Ok(ids)
+    }
+}
@@ -0,0 +1,278 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::ops::Range;
+
+/// The tokens of a whitespace-separated word, as returned by
+/// [`alignment`](AlephAlphaTokenizer::alignment).
+///
+/// With the `serde` feature, this can be serialized, e.g. to hand it to a
+/// visualization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordAlignment {
+    /// The index of the word within the text
+    pub word_index: usize,
+    /// The byte range of the word in the text
+    pub text_range: Range<usize>,
+    /// The char range of the word in the text
+    pub char_range: Range<usize>,
+    /// The indices of the word's tokens, counting the `[CLS]` token (if any)
+    pub token_indices: Range<usize>,
+    /// Whether the word (or a part of it) is `[UNK]`
+    pub is_unknown: bool,
+}
+
+/// Maps between tokens and words, built from the `words` ranges of
+/// [`tokens_into`](AlephAlphaTokenizer::tokens_into).
+///
+/// Building it takes one pass over the tokens, after which each lookup takes
+/// constant time.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{examples, Alignment};
+///
+/// let tokenizer = examples::tokenizer();
+/// let text = "Ein Xylophon Beispiel";
+/// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+/// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+/// assert_eq!("[CLS] Ein [UNK] Bei ##spiel [SEP]", tokenizer.texts_of(&ids).join(" "));
+///
+/// let alignment = Alignment::new(&words, ids.len());
+/// assert_eq!(3, alignment.word_count());
+/// // the [CLS] and [SEP] tokens belong to no word
+/// assert_eq!(None, alignment.token_to_word(0));
+/// assert_eq!(None, alignment.token_to_word(5));
+/// assert_eq!(None, alignment.token_to_word(6));
+/// // the [UNK] token is a word of its own
+/// assert_eq!(Some(1), alignment.token_to_word(2));
+/// assert_eq!(2..3, alignment.word_to_tokens(1));
+/// assert_eq!(Some(2), alignment.token_to_word(4));
+/// assert_eq!(3..5, alignment.word_to_tokens(2));
+/// assert_eq!("Beispiel", &text[alignment.char_range_of_word(2, &ranges)]);
+/// assert_eq!("Xylophon", &text[alignment.char_range_of_word(1, &ranges)]);
+///
+/// // with the ranges of `tokens_into_chars`, the ranges are in chars
+/// let text = "Grüße Beispiel";
+/// tokenizer.tokens_into_chars(text, &mut ids, &mut ranges, Some(&mut words));
+/// let alignment = Alignment::new(&words, ids.len());
+/// assert_eq!(6..14, alignment.char_range_of_word(1, &ranges));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Alignment {
+    words: Vec<Range<usize>>,
+    // the word of each token
+    token_words: Vec<Option<usize>>,
+}
+
+impl Alignment {
+    /// Builds the alignment of `token_count` tokens and their `words`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a word has tokens beyond `token_count`.
+    pub fn new(words: &[Range<usize>], token_count: usize) -> Self {
+        let mut token_words = vec![None; token_count];
+        for (i, word) in words.iter().enumerate() {
+            for token_word in &mut token_words[word.clone()] {
+                *token_word = Some(i);
+            }
+        }
+        Alignment {
+            words: words.to_vec(),
+            token_words,
+        }
+    }
+
+    /// Gets the number of words.
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Gets the index of the word of a token, or `None` for the `[CLS]` and
+    /// `[SEP]` tokens and indices beyond the tokens.
+    pub fn token_to_word(&self, token_index: usize) -> Option<usize> {
+        self.token_words.get(token_index).copied().flatten()
+    }
+
+    /// Gets the indices of the tokens of a word.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no word at `word_index`.
+    pub fn word_to_tokens(&self, word_index: usize) -> Range<usize> {
+        self.words[word_index].clone()
+    }
+
+    /// Gets the range of a word within the text, from the start of its first
+    /// token to the end of its last.
+    ///
+    /// The range is in the unit of the `token_ranges`, i.e. in chars for the
+    /// ranges of
+    /// [`tokens_into_chars`](AlephAlphaTokenizer::tokens_into_chars) and in
+    /// bytes for those of [`tokens_into`](AlephAlphaTokenizer::tokens_into).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no word at `word_index` or its tokens have no
+    /// ranges.
+    pub fn char_range_of_word(
+        &self,
+        word_index: usize,
+        token_ranges: &[Range<usize>],
+    ) -> Range<usize> {
+        let ranges = &token_ranges[self.word_to_tokens(word_index)];
+        // lowercased tokens may overlap, so take the furthest end
+        let end = ranges.iter().map(|range| range.end).max().unwrap_or(0);
+        ranges.first().map_or(0, |range| range.start)..end
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes the text and gets the tokens of each word, along with its
+    /// byte and char ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, WordAlignment};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let alignment = tokenizer.alignment("Grüße ☃ Steak");
+    /// assert_eq!(
+    ///     WordAlignment {
+    ///         word_index: 1,
+    ///         text_range: 8..11,
+    ///         char_range: 6..7,
+    ///         token_indices: 3..4,
+    ///         is_unknown: true,
+    ///     },
+    ///     alignment[1]
+    /// );
+    ///# #[cfg(feature = "serde")] {
+    /// assert_eq!(
+    ///     concat!(
+    ///         r#"[{"word_index":0,"text_range":{"start":0,"end":7},"char_range":{"start":0,"end":5},"#,
+    ///         r#""token_indices":{"start":1,"end":3},"is_unknown":false},"#,
+    ///         r#"{"word_index":1,"text_range":{"start":8,"end":11},"char_range":{"start":6,"end":7},"#,
+    ///         r#""token_indices":{"start":3,"end":4},"is_unknown":true},"#,
+    ///         r#"{"word_index":2,"text_range":{"start":12,"end":17},"char_range":{"start":8,"end":13},"#,
+    ///         r#""token_indices":{"start":4,"end":6},"is_unknown":false}]"#,
+    ///     ),
+    ///     serde_json::to_string(&alignment).unwrap()
+    /// );
+    ///# }
+    /// ```
+    pub fn alignment(&self, text: &str) -> Vec<WordAlignment> {
+        let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+        self.tokens_into(text, &mut ids, &mut ranges, None);
+        let body_start = (self.options.add_prefix && self.prefix.is_some()) as usize;
+        let body_end = ids.len() - (self.options.add_suffix && self.suffix.is_some()) as usize;
+        let unk = u64::from(self.unk_id);
+        let mut alignment: Vec<WordAlignment> = Vec::new();
+        let (mut last_byte, mut last_char) = (0, 0);
+        for i in body_start..body_end {
+            let range = ranges[i].clone();
+            // the tokens of a word are adjacent (or overlap when lowercased),
+            // while there's whitespace between words
+            match alignment.last_mut() {
+                Some(word) if range.start <= word.text_range.end => {
+                    if range.end > word.text_range.end {
+                        word.char_range.end += text[word.text_range.end..range.end].chars().count();
+                        word.text_range.end = range.end;
+                    }
+                    word.token_indices.end = i + 1;
+                    word.is_unknown |= ids[i] == unk;
+                    continue;
+                }
+                _ => {}
+            }
+            last_char += text[last_byte..range.start].chars().count();
+            let end_char = last_char + text[range.clone()].chars().count();
+            alignment.push(WordAlignment {
+                word_index: alignment.len(),
+                char_range: last_char..end_char,
+                text_range: range.clone(),
+                token_indices: i..i + 1,
+                is_unknown: ids[i] == unk,
+            });
+            last_byte = range.start;
+        }
+        alignment
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but also
+    /// fills the index of the word of each token, e.g. for whole-word
+    /// masking or to label only the first token of each word.
+    ///
+    /// The words are those of the `words` ranges of `tokens_into`, so an
+    /// `[UNK]` token replacing a word is a word of its own. The `[CLS]` and
+    /// `[SEP]` tokens belong to no word and get `None`, like the `word_ids`
+    /// of huggingface's encodings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let (mut ids, mut ranges, mut word_ids) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into_with_words("Ein Xylophon Beispiel", &mut ids, &mut ranges, &mut word_ids);
+    /// assert_eq!("[CLS] Ein [UNK] Bei ##spiel [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[None, Some(0), Some(1), Some(2), Some(2), None], &word_ids[..]);
+    ///
+    /// // the first token of each word
+    /// let starts: Vec<bool> = (0..word_ids.len())
+    ///     .map(|i| word_ids[i].is_some() && (i == 0 || word_ids[i - 1] != word_ids[i]))
+    ///     .collect();
+    /// assert_eq!(&[false, true, true, true, false, false], &starts[..]);
+    ///
+    /// // the same as the `words` ranges
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let mut words = Vec::new();
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+    ///         FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "",
+    /// ] {
+    ///     tokenizer.tokens_into_with_words(text, &mut ids, &mut ranges, &mut word_ids);
+    ///     let mut expected = vec![None; ids.len()];
+    ///     let (mut other_ids, mut other_ranges) = (Vec::<u32>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut other_ids, &mut other_ranges, Some(&mut words));
+    ///     for (i, word) in words.iter().enumerate() {
+    ///         for id in &mut expected[word.clone()] {
+    ///             *id = Some(i as u32);
+    ///         }
+    ///     }
+    ///     assert_eq!((&other_ids, &other_ranges), (&ids, &ranges));
+    ///     assert_eq!(expected, word_ids, "{:?}", text);
+    /// }
+    /// ```
+    pub fn tokens_into_with_words<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        word_ids: &mut Vec<Option<u32>>,
+    ) {
+        let mut words = Vec::new();
+        self.tokens_into(text, token_ids, token_ranges, Some(&mut words));
+        word_ids.clear();
+        word_ids.resize(token_ids.len(), None);
+        for (i, word) in words.into_iter().enumerate() {
+            for id in &mut word_ids[word] {
+                *id = Some(i as u32);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::AlephAlphaTokenizer;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How well the vocabulary covers a corpus, as returned by
+/// [`analyze`](AlephAlphaTokenizer::analyze).
+///
+/// The tokens are those of the words, without `[CLS]` and `[SEP]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoverageReport {
+    /// The number of lines
+    pub num_lines: usize,
+    /// The number of words
+    pub num_words: usize,
+    /// The number of tokens of the words
+    pub num_tokens: usize,
+    /// The number of words the vocabulary doesn't cover
+    pub num_unk_words: usize,
+    /// The most common words the vocabulary doesn't cover with their counts,
+    /// the most common first (and then in alphabetical order)
+    pub top_unk_words: Vec<(String, usize)>,
+    /// The number of words with each number of tokens, i.e. the number of
+    /// words with `n` tokens is at index `n`
+    pub tokens_per_word: Vec<usize>,
+}
+
+impl CoverageReport {
+    /// Gets the share of the words the vocabulary doesn't cover, or `0` if
+    /// there are no words.
+    pub fn unk_rate(&self) -> f64 {
+        ratio(self.num_unk_words, self.num_words)
+    }
+
+    /// Gets the average number of tokens per word (the fertility), or `0` if
+    /// there are no words.
+    pub fn fertility(&self) -> f64 {
+        ratio(self.num_tokens, self.num_words)
+    }
+}
+
+fn ratio(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 / total as f64
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} words in {} lines, {} tokens ({:.2} per word), {} unknown words ({:.2}%)",
+            self.num_words,
+            self.num_lines,
+            self.num_tokens,
+            self.fertility(),
+            self.num_unk_words,
+            self.unk_rate() * 100.0,
+        )?;
+        f.write_str("most common unknown words:")?;
+        for (word, count) in &self.top_unk_words {
+            write!(f, " {:?} ({})", word, count)?;
+        }
+        f.write_str("\ntokens per word:")?;
+        for (tokens, &words) in self.tokens_per_word.iter().enumerate() {
+            if words > 0 {
+                write!(f, " {}: {}", tokens, words)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a corpus line by line and reports how well the vocabulary
+    /// covers it: the share of words that the vocabulary doesn't cover, the
+    /// `top_n` most common of them, and the number of tokens per word.
+    ///
+    /// The lines are tokenized as they come, so the corpus needn't fit into
+    /// memory, only its distinct unknown words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, CoverageReport};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let corpus = "Ich esse Steak.\nBatman isst Steak.\nRobin und Batman\nisst Robin Robin\n";
+    /// let report = tokenizer.analyze(corpus.lines(), 2);
+    /// assert_eq!(
+    ///     CoverageReport {
+    ///         num_lines: 4,
+    ///         num_words: 12,
+    ///         num_tokens: 19,
+    ///         num_unk_words: 6,
+    ///         top_unk_words: vec![("Robin".to_string(), 3), ("isst".to_string(), 2)],
+    ///         tokens_per_word: vec![0, 7, 3, 2],
+    ///     },
+    ///     report
+    /// );
+    /// assert_eq!(0.5, report.unk_rate());
+    /// assert!((report.fertility() - 19.0 / 12.0).abs() < 1e-9);
+    /// assert_eq!(
+    ///     "12 words in 4 lines, 19 tokens (1.58 per word), 6 unknown words (50.00%)\n\
+    ///      most common unknown words: \"Robin\" (3) \"isst\" (2)\n\
+    ///      tokens per word: 1: 7 2: 3 3: 2",
+    ///     report.to_string()
+    /// );
+    ///
+    /// // e.g. streaming the lines of a file
+    /// use std::io::BufRead;
+    /// let reader = std::io::Cursor::new(corpus);
+    /// let streamed = tokenizer.analyze(reader.lines().map(Result::unwrap), 2);
+    /// assert_eq!(report, streamed);
+    ///
+    /// assert_eq!(CoverageReport::default(), tokenizer.analyze(Vec::<String>::new(), 2));
+    /// ```
+    pub fn analyze<I>(&self, lines: I, top_n: usize) -> CoverageReport
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut report = CoverageReport::default();
+        let mut unk_counts: HashMap<String, usize> = HashMap::new();
+        let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+        for line in lines {
+            let line = line.as_ref();
+            let stats = self.tokens_into_with_stats(line, &mut ids, &mut ranges, Some(&mut words));
+            report.num_lines += 1;
+            report.num_words += stats.num_words;
+            report.num_unk_words += stats.num_unk_words;
+            for word in &words {
+                report.num_tokens += word.len();
+                if report.tokens_per_word.len() <= word.len() {
+                    report.tokens_per_word.resize(word.len() + 1, 0);
+                }
+                report.tokens_per_word[word.len()] += 1;
+            }
+            for range in stats.unk_word_ranges {
+                *unk_counts.entry(line[range].to_string()).or_insert(0) += 1;
+            }
+        }
+        let mut unk_words: Vec<_> = unk_counts.into_iter().collect();
+        unk_words.sort_unstable_by(|(w, n), (v, m)| m.cmp(n).then_with(|| w.cmp(v)));
+        unk_words.truncate(top_n);
+        report.top_unk_words = unk_words;
+        report
+    }
+}
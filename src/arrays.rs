@@ -0,0 +1,63 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use ndarray::Array2;
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a batch of texts into the `input_ids` and `attention_mask`
+    /// arrays of a model, with the `ndarray` feature.
+    ///
+    /// Both arrays have the shape `(batch, seq)` and are padded and truncated
+    /// by [`encode_batch_padded`], whose buffers become the arrays.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `[CLS]` and `[SEP]` don't fit into `max_length`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "ndarray")] {
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use ndarray::array;
+    ///
+    /// // the [PAD] token needn't have the id 0
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\n[PAD]\nSuper\n##man\nBat\n";
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// let (input_ids, attention_mask) =
+    ///     tokenizer.encode_to_arrays::<u32>(&["Superman Bat", "Bat"], None);
+    /// assert_eq!(array![[1, 4, 5, 6, 2], [1, 6, 2, 3, 3]], input_ids);
+    /// assert_eq!(array![[1, 1, 1, 1, 1], [1, 1, 1, 0, 0]], attention_mask);
+    ///
+    /// let (input_ids, _) = tokenizer.encode_to_arrays::<i64>(&["Superman Bat", "Bat"], Some(4));
+    /// assert_eq!(array![[1, 4, 5, 2], [1, 6, 2, 3]], input_ids);
+    /// let (input_ids, _) = tokenizer.encode_to_arrays::<i64>(&[], None);
+    /// assert_eq!(&[0, 0], input_ids.shape());
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = ["Ich esse Steak.", "Der Hund spielt im Garten.", ""];
+    /// let (input_ids, attention_mask) = tokenizer.encode_to_arrays::<i64>(&texts, None);
+    /// let seq_len = input_ids.ncols();
+    /// assert_eq!((3, seq_len), attention_mask.dim());
+    /// let (mut ids, mut ranges, mut attns) = (Vec::<i64>::new(), Vec::new(), Vec::<i64>::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     ids.resize(seq_len, 0);
+    ///     AlephAlphaTokenizer::attentions_into(&ids, &mut attns);
+    ///     assert_eq!(ids, input_ids.row(i).to_vec());
+    ///     assert_eq!(attns, attention_mask.row(i).to_vec());
+    /// }
+    ///# }
+    /// ```
+    ///
+    /// [`encode_batch_padded`]: AlephAlphaTokenizer::encode_batch_padded
+    pub fn encode_to_arrays<T: TokenID>(
+        &self,
+        texts: &[&str],
+        max_length: Option<usize>,
+    ) -> (Array2<T>, Array2<T>) {
+        let batch = self.encode_batch_padded(texts, max_length);
+        let shape = (texts.len(), batch.seq_len);
+        // `encode_batch_padded` returns `texts.len()` rows of `seq_len` values
+        let array = |values| Array2::from_shape_vec(shape, values).unwrap();
+        (array(batch.ids), array(batch.attention_mask))
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{AlephAlphaTokenizer, TokenID, TokenSink};
+use arrow_array::builder::{
+    ArrayBuilder, ListBuilder, PrimitiveBuilder, StructBuilder, UInt64Builder,
+};
+use arrow_array::types::ArrowPrimitiveType;
+use arrow_array::ListArray;
+use arrow_schema::{DataType, Field, Fields};
+use std::mem;
+use std::ops::Range;
+
+// appends the tokens of a text to the current lists of the builders; the
+// builders can't take tokens back, so those of a word wait in `word` until
+// it ends
+struct ArrowRow<'b, P: ArrowPrimitiveType> {
+    ids: &'b mut ListBuilder<PrimitiveBuilder<P>>,
+    offsets: Option<&'b mut ListBuilder<StructBuilder>>,
+    word: &'b mut Vec<(u64, Range<usize>)>,
+    in_word: bool,
+}
+
+impl<P: ArrowPrimitiveType> ArrowRow<'_, P>
+where
+    P::Native: TokenID,
+{
+    fn append(&mut self, id: u64, range: Range<usize>) {
+        self.ids.values().append_value(P::Native::coerce(id));
+        if let Some(offsets) = self.offsets.as_mut() {
+            let offsets = offsets.values();
+            for (field, offset) in [range.start, range.end].iter().enumerate() {
+                offsets
+                    .field_builder::<UInt64Builder>(field)
+                    .expect("the offsets are `UInt64`s")
+                    .append_value(*offset as u64);
+            }
+            offsets.append(true);
+        }
+    }
+}
+
+impl<P: ArrowPrimitiveType> TokenSink for ArrowRow<'_, P>
+where
+    P::Native: TokenID,
+{
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        if self.in_word {
+            self.word.push((id, range));
+        } else {
+            self.append(id, range);
+        }
+    }
+
+    #[inline]
+    fn begin_word(&mut self) {
+        self.in_word = true;
+    }
+
+    fn end_word(&mut self) {
+        self.in_word = false;
+        // keep the buffer (and its capacity) for the next word
+        let mut word = mem::take(self.word);
+        for (id, range) in word.drain(..) {
+            self.append(id, range);
+        }
+        *self.word = word;
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.word.truncate(self.word.len() - tokens);
+    }
+}
+
+// a builder of lists of `{start, end}` structs with `tokens` values
+fn offsets_builder(rows: usize, tokens: usize) -> ListBuilder<StructBuilder> {
+    let fields = Fields::from(vec![
+        Field::new("start", DataType::UInt64, false),
+        Field::new("end", DataType::UInt64, false),
+    ]);
+    let columns: Vec<Box<dyn ArrayBuilder>> = vec![
+        Box::new(UInt64Builder::with_capacity(tokens)),
+        Box::new(UInt64Builder::with_capacity(tokens)),
+    ];
+    let values = StructBuilder::new(fields.clone(), columns);
+    ListBuilder::with_capacity(values, rows)
+        .with_field(Field::new_list_field(DataType::Struct(fields), false))
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a batch of texts into an Apache Arrow `ListArray` with the
+    /// ids of each text as one list, with the `arrow` feature.
+    ///
+    /// The ids are of the Arrow type `P`, e.g. `Int32Type` or `Int64Type`,
+    /// and each list has the ids of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into). The tokens go
+    /// straight into the Arrow builders as they are found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "arrow")] {
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use arrow_array::cast::AsArray;
+    /// use arrow_array::types::{Int32Type, Int64Type};
+    /// use arrow_array::Array;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "",
+    /// ];
+    /// let batch = tokenizer.encode_batch_arrow::<Int64Type>(&texts);
+    /// assert_eq!(texts.len(), batch.len());
+    /// assert_eq!(0, batch.null_count());
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     assert_eq!(&ids[..], batch.value(i).as_primitive::<Int64Type>().values());
+    /// }
+    ///
+    /// let batch = tokenizer.encode_batch_arrow::<Int32Type>(&texts[..1]);
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 26914, 4], batch.value(0).as_primitive::<Int32Type>().values());
+    /// assert!(tokenizer.encode_batch_arrow::<Int32Type>(&[]).is_empty());
+    ///# }
+    /// ```
+    pub fn encode_batch_arrow<P: ArrowPrimitiveType>(&self, texts: &[&str]) -> ListArray
+    where
+        P::Native: TokenID,
+    {
+        self.encode_rows_arrow::<P>(texts.iter().map(|&text| Some(text)), false)
+            .0
+    }
+
+    /// Like [`encode_batch_arrow`](AlephAlphaTokenizer::encode_batch_arrow),
+    /// but also returns the byte ranges of the tokens in their texts, as a
+    /// `ListArray` of `{start, end}` structs of `UInt64`s with the same
+    /// lengths as the lists of ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "arrow")] {
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use arrow_array::cast::AsArray;
+    /// use arrow_array::types::{Int32Type, UInt64Type};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = [
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "Ich Superman☃ ☃",
+    /// ];
+    /// let (batch, offsets) = tokenizer.encode_batch_arrow_with_offsets::<Int32Type>(&texts);
+    /// let (mut ids, mut ranges) = (Vec::<i32>::new(), Vec::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     assert_eq!(&ids[..], batch.value(i).as_primitive::<Int32Type>().values());
+    ///     let row = offsets.value(i);
+    ///     let (starts, ends) = (row.as_struct().column(0), row.as_struct().column(1));
+    ///     let (starts, ends) = (starts.as_primitive::<UInt64Type>(), ends.as_primitive::<UInt64Type>());
+    ///     let row_ranges: Vec<_> = starts
+    ///         .values()
+    ///         .iter()
+    ///         .zip(ends.values())
+    ///         .map(|(&start, &end)| start as usize..end as usize)
+    ///         .collect();
+    ///     assert_eq!(ranges, row_ranges);
+    /// }
+    ///# }
+    /// ```
+    pub fn encode_batch_arrow_with_offsets<P: ArrowPrimitiveType>(
+        &self,
+        texts: &[&str],
+    ) -> (ListArray, ListArray)
+    where
+        P::Native: TokenID,
+    {
+        let (ids, offsets) =
+            self.encode_rows_arrow::<P>(texts.iter().map(|&text| Some(text)), true);
+        (ids, offsets.expect("the offsets were built"))
+    }
+
+    /// Like [`encode_batch_arrow_with_offsets`], but with a null list of
+    /// ids and of offsets for each missing (`None`) text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "arrow")] {
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use arrow_array::cast::AsArray;
+    /// use arrow_array::types::Int64Type;
+    /// use arrow_array::Array;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = [Some("Ich esse Steak."), None, Some("")];
+    /// let (batch, offsets) = tokenizer.encode_batch_arrow_nullable::<Int64Type>(&texts);
+    /// assert_eq!((1, 1), (batch.null_count(), offsets.null_count()));
+    /// assert!(batch.is_null(1) && offsets.is_null(1));
+    /// assert_eq!(0, batch.value_length(1));
+    /// // an empty text still has `[CLS]` and `[SEP]`
+    /// assert_eq!(&[3, 4], batch.value(2).as_primitive::<Int64Type>().values());
+    /// // the other rows are those of the texts
+    /// let dense = tokenizer.encode_batch_arrow::<Int64Type>(&["Ich esse Steak.", ""]);
+    /// assert_eq!((dense.value(0), dense.value(1)), (batch.value(0), batch.value(2)));
+    ///# }
+    /// ```
+    ///
+    /// [`encode_batch_arrow_with_offsets`]: AlephAlphaTokenizer::encode_batch_arrow_with_offsets
+    pub fn encode_batch_arrow_nullable<P: ArrowPrimitiveType>(
+        &self,
+        texts: &[Option<&str>],
+    ) -> (ListArray, ListArray)
+    where
+        P::Native: TokenID,
+    {
+        let (ids, offsets) = self.encode_rows_arrow::<P>(texts.iter().copied(), true);
+        (ids, offsets.expect("the offsets were built"))
+    }
+
+    fn encode_rows_arrow<'t, P: ArrowPrimitiveType>(
+        &self,
+        texts: impl Iterator<Item = Option<&'t str>> + Clone,
+        with_offsets: bool,
+    ) -> (ListArray, Option<ListArray>)
+    where
+        P::Native: TokenID,
+    {
+        let rows = texts.clone().count();
+        let estimate = texts
+            .clone()
+            .flatten()
+            .map(|text| self.estimated_tokens(text.len()))
+            .sum();
+        let mut ids =
+            ListBuilder::with_capacity(PrimitiveBuilder::<P>::with_capacity(estimate), rows)
+                .with_field(Field::new_list_field(P::DATA_TYPE, false));
+        let mut offsets = if with_offsets {
+            Some(offsets_builder(rows, estimate))
+        } else {
+            None
+        };
+        let mut word = Vec::new();
+        for text in texts {
+            let valid = text.is_some();
+            if let Some(text) = text {
+                let mut row = ArrowRow {
+                    ids: &mut ids,
+                    offsets: offsets.as_mut(),
+                    word: &mut word,
+                    in_word: false,
+                };
+                self.tokenize_to(text, &mut row);
+            }
+            ids.append(valid);
+            if let Some(offsets) = offsets.as_mut() {
+                offsets.append(valid);
+            }
+        }
+        (ids.finish(), offsets.as_mut().map(ListBuilder::finish))
+    }
+}
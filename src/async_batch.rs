@@ -0,0 +1,180 @@
+use crate::{AlephAlphaTokenizer, EncodeError, EncodeOptions, Encoding};
+use futures_util::stream::{Stream, StreamExt};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// tells the blocking task to stop once the future is dropped
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl AlephAlphaTokenizer {
+    // like `encode`, but checking and truncating with the options
+    fn encode_with(
+        &self,
+        text: &str,
+        options: &EncodeOptions,
+    ) -> Result<Encoding<i64>, EncodeError> {
+        let mut encoding = Encoding::default();
+        let Encoding {
+            ids,
+            ranges,
+            words,
+            attention,
+            type_ids,
+            special_tokens_mask,
+        } = &mut encoding;
+        self.tokens_into_with(text, ids, ranges, Some(words), options)?;
+        self.attentions_into_for(ids, attention);
+        type_ids.resize(ids.len(), 0);
+        self.special_tokens_mask_into(ids, special_tokens_mask);
+        Ok(encoding)
+    }
+
+    /// Encodes the texts with the options, like
+    /// [`tokens_into_with`](AlephAlphaTokenizer::tokens_into_with), on
+    /// tokio's blocking threads, so a large batch doesn't block the async
+    /// tasks of a service.
+    ///
+    /// The results are in the order of the texts. The work starts when the
+    /// future is first polled; dropping the future stops it after the text
+    /// being tokenized. If tokenizing panics, so does the future. This
+    /// requires the `tokio` feature, and must run within a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeOptions};
+    /// use std::sync::Arc;
+    ///
+    /// let tokenizer = Arc::new(AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap());
+    /// let texts: Vec<String> = (0..200)
+    ///     .map(|i| format!("Der Hund {} spielt im Garten {}.", i, "mit dem Ball ".repeat(i % 7)))
+    ///     .collect();
+    /// let options = EncodeOptions { max_length: Some(16), ..EncodeOptions::default() };
+    ///
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    /// runtime.block_on(async {
+    ///     // concurrent calls from several tasks share the tokenizer
+    ///     let tasks: Vec<_> = (0..4)
+    ///         .map(|_| {
+    ///             let batch = tokenizer.clone().encode_batch_async(texts.clone(), options.clone());
+    ///             tokio::spawn(batch)
+    ///         })
+    ///         .collect();
+    ///     let (mut ids, mut ranges, mut words) = (Vec::<i64>::new(), Vec::new(), Vec::new());
+    ///     for task in tasks {
+    ///         let encodings = task.await.unwrap();
+    ///         assert_eq!(texts.len(), encodings.len());
+    ///         for (text, encoding) in texts.iter().zip(encodings) {
+    ///             let encoding = encoding.unwrap();
+    ///             let words_out = Some(&mut words);
+    ///             tokenizer.tokens_into_with(text, &mut ids, &mut ranges, words_out, &options).unwrap();
+    ///             assert_eq!((&ids, &ranges, &words), (&encoding.ids, &encoding.ranges, &encoding.words));
+    ///             assert_eq!(ids.len(), encoding.attention.len());
+    ///         }
+    ///     }
+    ///
+    ///     let limited = EncodeOptions { max_input_bytes: Some(30), ..EncodeOptions::default() };
+    ///     let results = tokenizer.clone().encode_batch_async(texts.clone(), limited).await;
+    ///     assert_eq!(texts.len(), results.len());
+    ///     for (text, result) in texts.iter().zip(&results) {
+    ///         assert_eq!(text.len() > 30, result.is_err(), "{:?}", text);
+    ///     }
+    ///
+    ///     // a dropped batch stops early
+    ///     drop(tokenizer.clone().encode_batch_async(texts.clone(), options.clone()));
+    /// });
+    /// ```
+    pub async fn encode_batch_async(
+        self: Arc<Self>,
+        texts: Vec<String>,
+        options: EncodeOptions,
+    ) -> Vec<Result<Encoding<i64>, EncodeError>> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel = CancelOnDrop(cancelled.clone());
+        let task = tokio::task::spawn_blocking(move || {
+            let mut encodings = Vec::with_capacity(texts.len());
+            for text in &texts {
+                if cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                encodings.push(self.encode_with(text, &options));
+            }
+            encodings
+        });
+        match task.await {
+            Ok(encodings) => encodings,
+            Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+            Err(e) => panic!("the tokenizing task failed: {}", e),
+        }
+    }
+
+    /// Encodes a stream of texts on tokio's blocking threads, like
+    /// [`encode_batch_async`](AlephAlphaTokenizer::encode_batch_async),
+    /// with at most `concurrency` texts in flight, e.g. to tokenize
+    /// documents as they are ingested.
+    ///
+    /// The encodings come in the order of the texts. Texts that are already
+    /// being tokenized when the stream is dropped are finished, but no
+    /// others are started. This requires the `tokio` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrency` is zero, or (when polled) if tokenizing a
+    /// text panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeOptions};
+    /// use futures_util::stream::{self, StreamExt};
+    /// use std::sync::Arc;
+    ///
+    /// let tokenizer = Arc::new(AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap());
+    /// let texts: Vec<String> = (0..100).map(|i| format!("Ich esse {} Steaks.", i)).collect();
+    ///
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    /// let encodings: Vec<_> = runtime.block_on(
+    ///     tokenizer
+    ///         .clone()
+    ///         .encode_stream(stream::iter(texts.clone()), EncodeOptions::default(), 8)
+    ///         .collect(),
+    /// );
+    /// assert_eq!(texts.len(), encodings.len());
+    /// for (text, encoding) in texts.iter().zip(encodings) {
+    ///     assert_eq!(tokenizer.encode::<i64>(text), encoding.unwrap());
+    /// }
+    /// ```
+    pub fn encode_stream<S>(
+        self: Arc<Self>,
+        texts: S,
+        options: EncodeOptions,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<Encoding<i64>, EncodeError>> + Send
+    where
+        S: Stream<Item = String> + Send,
+    {
+        assert!(concurrency > 0, "the concurrency must not be zero");
+        let options = Arc::new(options);
+        texts
+            .map(move |text| {
+                let (tokenizer, options) = (self.clone(), options.clone());
+                async move {
+                    let task =
+                        tokio::task::spawn_blocking(move || tokenizer.encode_with(&text, &options));
+                    match task.await {
+                        Ok(encoding) => encoding,
+                        Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+                        Err(e) => panic!("the tokenizing task failed: {}", e),
+                    }
+                }
+            })
+            .buffered(concurrency)
+    }
+}
@@ -0,0 +1,392 @@
+use crate::{AlephAlphaTokenizer, EncodeOptions, TokenID};
+use std::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// A batch of token ids padded to the same length, as returned by
+/// [`encode_batch_padded`](AlephAlphaTokenizer::encode_batch_padded).
+///
+/// The `ids` and `attention_mask` hold `lengths.len()` rows of `seq_len`
+/// values each, one row after the other, so row `i` is
+/// `ids[i * seq_len..(i + 1) * seq_len]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PaddedBatch<T> {
+    /// The token ids, followed by `[PAD]` ids up to `seq_len` in each row
+    pub ids: Vec<T>,
+    /// `1` for each token and `0` for each padding
+    pub attention_mask: Vec<T>,
+    /// The length of each row
+    pub seq_len: usize,
+    /// The number of tokens (without padding) of each row
+    pub lengths: Vec<usize>,
+}
+
+/// The tokens of a batch of texts back to back, as returned by
+/// [`encode_batch_packed`](AlephAlphaTokenizer::encode_batch_packed).
+///
+/// Unlike a [`PaddedBatch`], this takes no room for padding, and unlike
+/// one `Vec` per text, it takes no allocation per text. The tokens of text
+/// `i` are at `row_offsets[i]..row_offsets[i + 1]`, see
+/// [`row`](PackedBatch::row).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackedBatch<T> {
+    /// The token ids of all texts
+    pub ids: Vec<T>,
+    /// The byte ranges of the tokens, each within its own text
+    pub ranges: Vec<Range<usize>>,
+    /// Where the tokens of each text start, followed by the number of
+    /// tokens, so there is one more offset than texts
+    pub row_offsets: Vec<usize>,
+}
+
+impl<T> Default for PackedBatch<T> {
+    fn default() -> Self {
+        PackedBatch {
+            ids: Vec::new(),
+            ranges: Vec::new(),
+            row_offsets: vec![0],
+        }
+    }
+}
+
+impl<T> PackedBatch<T> {
+    /// Gets the number of texts.
+    pub fn len(&self) -> usize {
+        self.row_offsets.len() - 1
+    }
+
+    /// Determines whether the batch has no texts.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the token ids and ranges of the text with this index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn row(&self, i: usize) -> (&[T], &[Range<usize>]) {
+        let tokens = self.row_offsets[i]..self.row_offsets[i + 1];
+        (&self.ids[tokens.clone()], &self.ranges[tokens])
+    }
+
+    // appends the rows of the other batch
+    #[cfg(feature = "rayon")]
+    fn append(&mut self, mut other: PackedBatch<T>) {
+        let start = self.ids.len();
+        self.ids.append(&mut other.ids);
+        self.ranges.append(&mut other.ranges);
+        self.row_offsets
+            .extend(other.row_offsets[1..].iter().map(|offset| offset + start));
+    }
+}
+
+// make the outer `Vec`s as long as the batch, keeping the inner `Vec`s
+fn resize_outputs<T>(len: usize, ids: &mut Vec<Vec<T>>, ranges: &mut Vec<Vec<Range<usize>>>) {
+    ids.truncate(len);
+    ids.resize_with(len, Vec::new);
+    ranges.truncate(len);
+    ranges.resize_with(len, Vec::new);
+}
+
+impl AlephAlphaTokenizer {
+    // make room for the estimated number of tokens
+    fn reserve_for<T>(&self, text: &str, ids: &mut Vec<T>, ranges: &mut Vec<Range<usize>>) {
+        let estimate = self.estimated_tokens(text.len());
+        ids.clear();
+        ids.reserve(estimate);
+        ranges.clear();
+        ranges.reserve(estimate);
+    }
+
+    /// Tokenizes a batch of texts, filling one `Vec` of ids and one `Vec` of
+    /// ranges per text, in the order of the input texts.
+    ///
+    /// The outer `Vec`s are resized to the length of the batch. The inner
+    /// `Vec`s are cleared and re-used, so calling this repeatedly with the
+    /// same outputs avoids most allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let texts = ["Ein interessantes Beispiel", "Super"];
+    /// let mut ids: Vec<Vec<i32>> = Vec::new();
+    /// let mut ranges = Vec::new();
+    /// tokenizer.tokens_into_batch(&texts, &mut ids, &mut ranges);
+    /// assert_eq!(vec![vec![2, 5, 6, 7, 8, 9, 3], vec![2, 16, 3]], ids);
+    /// assert_eq!(vec![0..0, 0..5, 5..5], ranges[1]);
+    /// ```
+    pub fn tokens_into_batch<T: TokenID>(
+        &self,
+        texts: &[&str],
+        token_ids: &mut Vec<Vec<T>>,
+        token_ranges: &mut Vec<Vec<Range<usize>>>,
+    ) {
+        resize_outputs(texts.len(), token_ids, token_ranges);
+        for ((text, ids), ranges) in texts.iter().zip(token_ids).zip(token_ranges) {
+            self.reserve_for(text, ids, ranges);
+            self.tokens_into(text, ids, ranges, None);
+        }
+    }
+
+    /// Tokenizes a batch of texts into model inputs: the ids padded to the
+    /// length of the longest row, and the attention mask.
+    ///
+    /// With a `max_length`, longer rows are truncated (keeping `[CLS]` and
+    /// `[SEP]`) as [`EncodeOptions::max_length`] does. The padding is the id
+    /// of the `[PAD]` token, or `0` if the vocabulary has none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `[CLS]` and `[SEP]` don't fit into `max_length`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, PaddedBatch};
+    ///
+    /// // the [PAD] token needn't have the id 0
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\n[PAD]\nSuper\n##man\nBat\n";
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// let batch = tokenizer.encode_batch_padded::<u32>(&["Superman Bat", "Bat", ""], None);
+    /// assert_eq!(
+    ///     PaddedBatch {
+    ///         ids: vec![1, 4, 5, 6, 2, 1, 6, 2, 3, 3, 1, 2, 3, 3, 3],
+    ///         attention_mask: vec![1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0],
+    ///         seq_len: 5,
+    ///         lengths: vec![5, 3, 2],
+    ///     },
+    ///     batch
+    /// );
+    ///
+    /// let batch = tokenizer.encode_batch_padded::<u32>(&["Superman Bat", "Bat"], Some(4));
+    /// assert_eq!(vec![1, 4, 5, 2, 1, 6, 2, 3], batch.ids);
+    /// assert_eq!((4, vec![4, 3]), (batch.seq_len, batch.lengths));
+    /// ```
+    ///
+    /// The rows start with the tokens of [`tokens_into`]:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+    ///         FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    /// ];
+    /// let batch = tokenizer.encode_batch_padded::<i64>(&texts, None);
+    /// assert_eq!(texts.len() * batch.seq_len, batch.ids.len());
+    /// assert_eq!(batch.ids.len(), batch.attention_mask.len());
+    /// assert_eq!(batch.seq_len, *batch.lengths.iter().max().unwrap());
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     let row = &batch.ids[i * batch.seq_len..(i + 1) * batch.seq_len];
+    ///     let mask = &batch.attention_mask[i * batch.seq_len..(i + 1) * batch.seq_len];
+    ///     assert_eq!(ids.len(), batch.lengths[i]);
+    ///     assert_eq!(&ids[..], &row[..ids.len()]);
+    ///     assert!(row[ids.len()..].iter().all(|&id| id == 0));
+    ///     assert!(mask[..ids.len()].iter().all(|&m| m == 1));
+    ///     assert!(mask[ids.len()..].iter().all(|&m| m == 0));
+    /// }
+    /// ```
+    ///
+    /// [`tokens_into`]: AlephAlphaTokenizer::tokens_into
+    pub fn encode_batch_padded<T: TokenID>(
+        &self,
+        texts: &[&str],
+        max_length: Option<usize>,
+    ) -> PaddedBatch<T> {
+        let options = EncodeOptions {
+            max_length,
+            ..EncodeOptions::default()
+        };
+        // collect the tokens of all texts back to back, then pad each row
+        let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+        let (mut tokens, mut lengths) = (Vec::new(), Vec::with_capacity(texts.len()));
+        for text in texts {
+            if let Err(e) = self.tokens_into_with(text, &mut ids, &mut ranges, None, &options) {
+                panic!("{}", e);
+            }
+            lengths.push(ids.len());
+            tokens.append(&mut ids);
+        }
+        let seq_len = lengths.iter().copied().max().unwrap_or(0);
+        let pad = T::coerce(u64::from(self.pad_id.unwrap_or(0)));
+        let mut batch = PaddedBatch {
+            ids: Vec::with_capacity(texts.len() * seq_len),
+            attention_mask: Vec::with_capacity(texts.len() * seq_len),
+            seq_len,
+            lengths,
+        };
+        let mut tokens = tokens.into_iter();
+        for &len in &batch.lengths {
+            batch.ids.extend(tokens.by_ref().take(len));
+            batch
+                .ids
+                .resize(batch.ids.len() + seq_len - len, pad.clone());
+            batch
+                .attention_mask
+                .resize(batch.attention_mask.len() + len, T::coerce(1));
+            batch
+                .attention_mask
+                .resize(batch.attention_mask.len() + seq_len - len, T::zero());
+        }
+        batch
+    }
+
+    /// Tokenizes a batch of texts into one [`PackedBatch`], with the tokens
+    /// of each text after those of the text before, e.g. to fill the
+    /// contiguous buffers of a GPU pipeline or an Arrow array.
+    ///
+    /// Each row has the ids and ranges of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into). The texts are
+    /// tokenized in one pass straight into the packed `Vec`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, PackedBatch};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let batch = tokenizer.encode_batch_packed::<u32>(&["Ein interessantes Beispiel", "Super", ""]);
+    /// assert_eq!(vec![2, 5, 6, 7, 8, 9, 3, 2, 16, 3, 2, 3], batch.ids);
+    /// assert_eq!(vec![0, 7, 10, 12], batch.row_offsets);
+    /// assert_eq!(3, batch.len());
+    /// assert_eq!((&[2, 16, 3][..], &[0..0, 0..5, 5..5][..]), batch.row(1));
+    ///
+    /// assert_eq!(PackedBatch::default(), tokenizer.encode_batch_packed::<u32>(&[]));
+    /// assert!(PackedBatch::<u32>::default().is_empty());
+    ///
+    /// // each row is the output of `tokens_into`
+    /// let texts = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Grüße aus dem Krankenhaus ☃",
+    /// ];
+    /// let batch = tokenizer.encode_batch_packed::<i64>(&texts);
+    /// assert_eq!(texts.len() + 1, batch.row_offsets.len());
+    /// assert!(batch.row_offsets.windows(2).all(|w| w[0] <= w[1]));
+    /// assert_eq!(Some(&batch.ids.len()), batch.row_offsets.last());
+    /// assert_eq!(batch.ids.len(), batch.ranges.len());
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     assert_eq!((&ids[..], &ranges[..]), batch.row(i), "{:?}", text);
+    /// }
+    /// ```
+    pub fn encode_batch_packed<T: TokenID>(&self, texts: &[&str]) -> PackedBatch<T> {
+        let estimate = texts
+            .iter()
+            .map(|text| self.estimated_tokens(text.len()))
+            .sum();
+        let mut batch = PackedBatch {
+            ids: Vec::with_capacity(estimate),
+            ranges: Vec::with_capacity(estimate),
+            row_offsets: Vec::with_capacity(texts.len() + 1),
+        };
+        batch.row_offsets.push(0);
+        let (add_prefix, add_suffix) = (self.options.add_prefix, self.options.add_suffix);
+        for text in texts {
+            let (ids, ranges) = (&mut batch.ids, &mut batch.ranges);
+            self.extend_tokens_into(text, ids, ranges, None, add_prefix, add_suffix);
+            batch.row_offsets.push(batch.ids.len());
+        }
+        batch
+    }
+
+    /// Like [`encode_batch_packed`](AlephAlphaTokenizer::encode_batch_packed),
+    /// but tokenizes the texts in parallel using rayon.
+    ///
+    /// Each thread packs a run of texts into its own `Vec`s, which are then
+    /// joined in the order of the texts. This requires the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let texts: Vec<String> = (0..100).map(|i| "Ein Beispiel ".repeat(i % 7)).collect();
+    /// let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+    /// assert_eq!(
+    ///     tokenizer.encode_batch_packed::<u32>(&texts),
+    ///     tokenizer.par_encode_batch_packed::<u32>(&texts)
+    /// );
+    /// assert_eq!(
+    ///     tokenizer.encode_batch_packed::<u32>(&[]),
+    ///     tokenizer.par_encode_batch_packed::<u32>(&[])
+    /// );
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_encode_batch_packed<T: TokenID + Send>(&self, texts: &[&str]) -> PackedBatch<T> {
+        let chunk_len = texts.len().div_ceil(rayon::current_num_threads()).max(1);
+        let chunks: Vec<PackedBatch<T>> = texts
+            .par_chunks(chunk_len)
+            .map(|chunk| self.encode_batch_packed(chunk))
+            .collect();
+        let mut batch = PackedBatch::default();
+        let len = chunks.iter().map(|chunk| chunk.ids.len()).sum();
+        batch.ids.reserve(len);
+        batch.ranges.reserve(len);
+        batch.row_offsets.reserve(texts.len());
+        for chunk in chunks {
+            batch.append(chunk);
+        }
+        batch
+    }
+
+    /// Like [`tokens_into_batch`](AlephAlphaTokenizer::tokens_into_batch),
+    /// but tokenizes the texts in parallel using rayon.
+    ///
+    /// The outputs are in the order of the input texts. This requires the
+    /// `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = vec!["Ein interessantes Beispiel"; 100];
+    /// let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+    /// let (mut par_ids, mut par_ranges) = (Vec::new(), Vec::new());
+    /// tokenizer.tokens_into_batch::<u64>(&texts, &mut ids, &mut ranges);
+    /// tokenizer.par_tokens_into_batch::<u64>(&texts, &mut par_ids, &mut par_ranges);
+    /// assert_eq!(ids, par_ids);
+    /// assert_eq!(ranges, par_ranges);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_tokens_into_batch<T: TokenID + Send>(
+        &self,
+        texts: &[&str],
+        token_ids: &mut Vec<Vec<T>>,
+        token_ranges: &mut Vec<Vec<Range<usize>>>,
+    ) {
+        resize_outputs(texts.len(), token_ids, token_ranges);
+        texts
+            .par_iter()
+            .zip(token_ids.par_iter_mut())
+            .zip(token_ranges.par_iter_mut())
+            .for_each(|((text, ids), ranges)| {
+                self.reserve_for(text, ids, ranges);
+                self.tokens_into(text, ids, ranges, None)
+            });
+    }
+}
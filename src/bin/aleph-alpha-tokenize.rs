@@ -0,0 +1,322 @@
+//! Tokenizes newline-delimited text from files or stdin, writing one line
+//! of tokens per input line.
+//!
+//! This requires the `cli` feature.
+
+use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode};
+use rayon::prelude::*;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "usage: aleph-alpha-tokenize --vocab VOCAB [--output FILE] \
+[--no-special-tokens] [--output-format ids|tokens|json] [--threads N] [FILE...]
+       aleph-alpha-tokenize bench-modes --vocab VOCAB [--modes MODE,...] [FILE...]
+       aleph-alpha-tokenize --version [--verbose] [--vocab VOCAB]
+
+Reads the FILEs (or stdin) line by line and writes the tokens of each line.
+With bench-modes, tokenizes the lines in each segmentation mode (greedy and
+backtracking by default) instead and compares their speed and tokens. With
+--version, prints the version instead, and with --verbose also the features
+and (given a VOCAB) the size and fingerprint of the vocabulary.";
+
+// the segmentation modes by their names on the command line
+const MODES: &[(&str, SegmentationMode)] = &[
+    ("greedy", SegmentationMode::Greedy),
+    ("backtracking", SegmentationMode::Backtracking),
+];
+
+// the number of lines tokenized in parallel before writing them
+const CHUNK_LINES: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ids,
+    Tokens,
+    Json,
+}
+
+enum Command {
+    Tokenize {
+        vocab: String,
+    },
+    BenchModes {
+        vocab: String,
+        modes: Vec<SegmentationMode>,
+    },
+    Version {
+        vocab: Option<String>,
+        verbose: bool,
+    },
+}
+
+struct Args {
+    command: Command,
+    output: Option<String>,
+    special_tokens: bool,
+    format: Format,
+    threads: usize,
+    inputs: Vec<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1).peekable();
+    let bench_modes = args.next_if(|arg| arg == "bench-modes").is_some();
+    let mut modes: Vec<_> = MODES.iter().map(|&(_, mode)| mode).collect();
+    let (mut vocab, mut output, mut inputs) = (None, None, Vec::new());
+    let (mut special_tokens, mut format, mut threads) = (true, Format::Ids, 1);
+    let (mut version, mut verbose) = (false, false);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--vocab" => vocab = Some(value()?),
+            "--output" => output = Some(value()?),
+            "--no-special-tokens" => special_tokens = false,
+            "--output-format" => {
+                format = match value()?.as_str() {
+                    "ids" => Format::Ids,
+                    "tokens" => Format::Tokens,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown output format {:?}", other)),
+                }
+            }
+            "--threads" => {
+                threads = value()?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("--threads needs a positive number")?
+            }
+            "--modes" if bench_modes => {
+                modes = value()?
+                    .split(',')
+                    .map(|name| match MODES.iter().find(|&&(n, _)| n == name) {
+                        Some(&(_, mode)) => Ok(mode),
+                        None => Err(format!("unknown segmentation mode {:?}", name)),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "--version" => version = true,
+            "--verbose" => verbose = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => inputs.push(arg),
+        }
+    }
+    let command = if version {
+        Command::Version { vocab, verbose }
+    } else if bench_modes {
+        Command::BenchModes {
+            vocab: vocab.ok_or("--vocab is required")?,
+            modes,
+        }
+    } else {
+        Command::Tokenize {
+            vocab: vocab.ok_or("--vocab is required")?,
+        }
+    };
+    Ok(Args {
+        command,
+        output,
+        special_tokens,
+        format,
+        threads,
+        inputs,
+    })
+}
+
+// what we count over all inputs
+#[derive(Default)]
+struct Totals {
+    tokens: usize,
+    unk_tokens: usize,
+}
+
+// tokenizes one line into its output line and the numbers of tokens and
+// of `[UNK]` tokens
+fn tokenize_line(
+    tokenizer: &AlephAlphaTokenizer,
+    format: Format,
+    line: &str,
+) -> (String, usize, usize) {
+    let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    tokenizer.tokens_into(line, &mut ids, &mut ranges, None);
+    let unk_id = tokenizer.unk_id();
+    let unk_tokens = ids.iter().filter(|&&id| id == unk_id).count();
+    let output = match format {
+        Format::Ids => ids.iter().map(u64::to_string).collect::<Vec<_>>().join(" "),
+        Format::Tokens => tokenizer.texts_of(&ids).join(" "),
+        Format::Json => {
+            let ranges: Vec<[usize; 2]> = ranges.iter().map(|r| [r.start, r.end]).collect();
+            serde_json::json!({ "ids": ids, "ranges": ranges }).to_string()
+        }
+    };
+    (output, ids.len(), unk_tokens)
+}
+
+fn tokenize_input<R: BufRead, W: Write>(
+    tokenizer: &AlephAlphaTokenizer,
+    format: Format,
+    input: R,
+    output: &mut W,
+    totals: &mut Totals,
+) -> io::Result<(usize, usize)> {
+    let (mut lines, mut bytes) = (0, 0);
+    let mut chunk = Vec::with_capacity(CHUNK_LINES);
+    let mut input_lines = input.lines();
+    loop {
+        chunk.clear();
+        for line in input_lines.by_ref().take(CHUNK_LINES) {
+            chunk.push(line?);
+        }
+        if chunk.is_empty() {
+            return Ok((lines, bytes));
+        }
+        let tokenized: Vec<_> = chunk
+            .par_iter()
+            .map(|line| tokenize_line(tokenizer, format, line))
+            .collect();
+        for (line, (out, tokens, unk_tokens)) in chunk.iter().zip(tokenized) {
+            writeln!(output, "{}", out)?;
+            lines += 1;
+            bytes += line.len() + 1;
+            totals.tokens += tokens;
+            totals.unk_tokens += unk_tokens;
+        }
+    }
+}
+
+// prints the version, and verbosely the features and the vocabulary
+fn print_version(vocab: Option<&str>, verbose: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
+    println!("aleph-alpha-tokenize {}", env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return Ok(());
+    }
+    println!("features: {}", aleph_alpha_tokenizer::features().join(" "));
+    if let Some(vocab) = vocab {
+        let info = AlephAlphaTokenizer::from_vocab(vocab)?.version_info();
+        println!(
+            "vocabulary: {} tokens, fingerprint {:016x}",
+            info.vocab_size, info.vocab_fingerprint
+        );
+    }
+    Ok(())
+}
+
+// the name of a segmentation mode on the command line
+fn mode_name(mode: SegmentationMode) -> &'static str {
+    MODES
+        .iter()
+        .find(|&&(_, m)| m == mode)
+        .map_or("?", |&(name, _)| name)
+}
+
+// tokenizes the lines of the inputs in each of the modes and prints how
+// they compare
+fn bench(
+    vocab: &str,
+    modes: &[SegmentationMode],
+    inputs: &[String],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let tokenizer = AlephAlphaTokenizer::from_vocab(vocab)?;
+    let mut corpus = Vec::new();
+    for name in inputs {
+        if name == "-" {
+            let stdin = io::stdin();
+            let lock = stdin.lock();
+            corpus.extend(lock.lines().collect::<io::Result<Vec<_>>>()?);
+        } else {
+            let file = BufReader::new(File::open(name)?);
+            corpus.extend(file.lines().collect::<io::Result<Vec<_>>>()?);
+        }
+    }
+    for report in tokenizer.bench_modes(corpus, modes) {
+        print!(
+            "{}: {} words, {} tokens ({:.3} per word), {:.3}% [UNK], {:.0} tokens/s",
+            mode_name(report.mode),
+            report.words,
+            report.tokens,
+            report.tokens_per_word(),
+            100.0 * report.unknown_rate(),
+            report.tokens_per_sec()
+        );
+        for (index, &other) in modes.iter().enumerate() {
+            if other != report.mode {
+                print!(
+                    ", {:.3}% of words differ from {}",
+                    100.0 * report.disagreement_rate(index),
+                    mode_name(other)
+                );
+            }
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let inputs = if args.inputs.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.inputs
+    };
+    let vocab = match &args.command {
+        Command::Tokenize { vocab } => vocab,
+        Command::BenchModes { vocab, modes } => return bench(vocab, modes, &inputs),
+        Command::Version { vocab, verbose } => return print_version(vocab.as_deref(), *verbose),
+    };
+    let tokenizer = AlephAlphaTokenizer::builder()
+        .vocab_path(vocab)
+        .add_special_tokens(args.special_tokens)
+        .build()?;
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()?;
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    let mut totals = Totals::default();
+    for name in &inputs {
+        let start = Instant::now();
+        let (lines, bytes) = if name == "-" {
+            let stdin = io::stdin();
+            let lock = stdin.lock();
+            tokenize_input(&tokenizer, args.format, lock, &mut output, &mut totals)?
+        } else {
+            let file = BufReader::new(File::open(name)?);
+            tokenize_input(&tokenizer, args.format, file, &mut output, &mut totals)?
+        };
+        let secs = start.elapsed().as_secs_f64();
+        eprintln!(
+            "{}: {} lines, {} bytes in {:.3}s ({:.2} MB/s)",
+            if name == "-" { "stdin" } else { name },
+            lines,
+            bytes,
+            secs,
+            bytes as f64 / 1e6 / secs.max(1e-9)
+        );
+    }
+    output.flush()?;
+    eprintln!(
+        "{} tokens, {} [UNK] tokens",
+        totals.tokens, totals.unk_tokens
+    );
+    Ok(())
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+    if let Err(e) = run(args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,561 @@
+//! A binary format containing the prebuilt FSTs, so loading a tokenizer
+//! doesn't need to sort the vocabulary and build the FSTs again.
+//!
+//! All numbers are little endian. The layout is:
+//!
+//! * the magic bytes `AATK` and a version byte, whose high bit is set if
+//!   there is an end-of-word suffix
+//! * the `[UNK]` id (`u32`), then the `[CLS]`, `[SEP]`, `[PAD]` and (since
+//!   version 2) `[MASK]` ids (each a presence byte and a `u32`)
+//! * the continuing subword prefix as length (`u32`) and UTF-8 bytes, then
+//!   the end-of-word suffix (if any) the same way
+//! * the number of special tokens (`u64`) and their ids (`u64` each)
+//! * the number of tokens (`u64`) and each token as length (`u32`) and
+//!   UTF-8 bytes, or (since version 3) the end of each token (`u32`) within
+//!   the text of all tokens, followed by that text as length (`u64`) and
+//!   UTF-8 bytes
+//! * the starter and follower FSTs, each as length (`u64`) and bytes
+//!
+//! [`save_binary`](AlephAlphaTokenizer::save_binary) writes version 2, and
+//! [`to_static_bytes`](AlephAlphaTokenizer::to_static_bytes) version 3,
+//! whose token texts and FSTs can be used where they are, see
+//! [`from_static`](AlephAlphaTokenizer::from_static).
+
+use crate::estimate::{Calibration, BUILTIN_SAMPLE};
+use crate::texts::TokenTexts;
+use crate::{AlephAlphaTokenizer, ConsistencyError, FstData, MatcherBackend, TokenizerOptions};
+use fst::raw::Fst;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::fs::{self, File};
+#[cfg(feature = "std-fs")]
+use std::io::BufWriter;
+use std::io::Write;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+use std::sync::Arc;
+
+const MAGIC: &[u8; 4] = b"AATK";
+// the version `save_binary` writes
+#[cfg(feature = "std-fs")]
+const VERSION: u8 = 2;
+// the version with the token texts in one piece
+const STATIC_VERSION: u8 = 3;
+// set in the version byte if the end-of-word suffix follows the prefix, so
+// readers that don't know about it reject the data
+const SUFFIX_FLAG: u8 = 0x80;
+
+/// The errors that can occur when reading the binary format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BinaryFormatError {
+    /// The data doesn't start with the magic bytes
+    BadMagic,
+    /// The data was written in a version of the format we cannot read
+    UnsupportedVersion(u8),
+    /// The data ended early
+    Truncated,
+    /// A token is not valid UTF-8
+    InvalidToken(usize),
+    /// The continuing subword prefix or end-of-word suffix is not valid UTF-8
+    InvalidPrefix,
+    /// The contents don't agree with each other
+    Inconsistent(ConsistencyError),
+}
+
+impl fmt::Display for BinaryFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryFormatError::BadMagic => f.write_str("not a binary tokenizer file"),
+            BinaryFormatError::UnsupportedVersion(v) => write!(
+                f,
+                "unsupported binary tokenizer version {} (expected at most {})",
+                v, STATIC_VERSION
+            ),
+            BinaryFormatError::Truncated => f.write_str("binary tokenizer file is truncated"),
+            BinaryFormatError::InvalidToken(id) => write!(f, "token {} is not valid UTF-8", id),
+            BinaryFormatError::InvalidPrefix => {
+                f.write_str("continuing subword prefix is not valid UTF-8")
+            }
+            BinaryFormatError::Inconsistent(e) => write!(f, "inconsistent tokenizer: {}", e),
+        }
+    }
+}
+
+impl Error for BinaryFormatError {}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, len: usize) -> Result<&'b [u8], BinaryFormatError> {
+        if self.bytes.len() < len {
+            return Err(BinaryFormatError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, BinaryFormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, BinaryFormatError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, BinaryFormatError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize, BinaryFormatError> {
+        let len = self.u64()?;
+        // a length larger than the remaining data cannot be valid
+        if len > self.bytes.len() as u64 {
+            return Err(BinaryFormatError::Truncated);
+        }
+        Ok(len as usize)
+    }
+
+    fn opt_u32(&mut self) -> Result<Option<u32>, BinaryFormatError> {
+        let present = self.u8()? != 0;
+        let value = self.u32()?;
+        Ok(if present { Some(value) } else { None })
+    }
+}
+
+fn write_opt_u32<W: Write>(w: &mut W, value: Option<u32>) -> std::io::Result<()> {
+    w.write_all(&[value.is_some() as u8])?;
+    w.write_all(&value.unwrap_or(0).to_le_bytes())
+}
+
+// everything but the token texts and FSTs
+struct Header {
+    unk_id: u32,
+    prefix: Option<u32>,
+    suffix: Option<u32>,
+    pad_id: Option<u32>,
+    mask_id: Option<u32>,
+    continuing_subword_prefix: String,
+    end_of_word_suffix: String,
+    special_tokens: Vec<u64>,
+}
+
+impl Header {
+    fn into_tokenizer(
+        self,
+        tokens: TokenTexts,
+        starters: FstData,
+        followers: FstData,
+    ) -> Result<AlephAlphaTokenizer, Box<dyn Error + Send + Sync>> {
+        let tokenizer = AlephAlphaTokenizer {
+            tokens: Arc::new(tokens),
+            starters: Arc::new(Fst::new(starters)?),
+            followers: Arc::new(Fst::new(followers)?),
+            special_tokens: Arc::new(self.special_tokens),
+            unk_id: self.unk_id,
+            prefix: self.prefix,
+            suffix: self.suffix,
+            pad_id: self.pad_id,
+            mask_id: self.mask_id,
+            byte_ids: Vec::new(),
+            suppressed_ids: Vec::new(),
+            continuing_subword_prefix: self.continuing_subword_prefix,
+            end_of_word_suffix: self.end_of_word_suffix,
+            options: TokenizerOptions::default(),
+            calibration: Calibration::default(),
+            indexes: Arc::default(),
+            ascii_fast_path: true,
+            backend: MatcherBackend::Fst,
+        };
+        tokenizer.calibrate(BUILTIN_SAMPLE);
+        Ok(tokenizer)
+    }
+}
+
+// the token texts as read: copied from their separate pieces (before
+// version 3), or as the text of all tokens and their ends
+enum Texts<'b> {
+    Parsed(TokenTexts),
+    Joined(&'b str, Vec<u32>),
+}
+
+// the parts of the binary format, borrowing what needn't be copied
+struct Parts<'b> {
+    header: Header,
+    tokens: Texts<'b>,
+    starters: &'b [u8],
+    followers: &'b [u8],
+}
+
+fn read_binary(bytes: &[u8]) -> Result<Parts<'_>, BinaryFormatError> {
+    let mut r = Reader { bytes };
+    if r.take(MAGIC.len())
+        .map_err(|_| BinaryFormatError::BadMagic)?
+        != MAGIC
+    {
+        return Err(BinaryFormatError::BadMagic);
+    }
+    let flagged = r.u8()?;
+    let version = flagged & !SUFFIX_FLAG;
+    // version 1 only lacks the `[MASK]` id
+    if !(1..=STATIC_VERSION).contains(&version) {
+        return Err(BinaryFormatError::UnsupportedVersion(flagged));
+    }
+    let unk_id = r.u32()?;
+    let prefix = r.opt_u32()?;
+    let suffix = r.opt_u32()?;
+    let pad_id = r.opt_u32()?;
+    let mask_id = if version == 1 { None } else { r.opt_u32()? };
+    let len = r.u32()? as usize;
+    let continuing_subword_prefix = std::str::from_utf8(r.take(len)?)
+        .map_err(|_| BinaryFormatError::InvalidPrefix)?
+        .to_string();
+    let end_of_word_suffix = if flagged & SUFFIX_FLAG == 0 {
+        String::new()
+    } else {
+        let len = r.u32()? as usize;
+        std::str::from_utf8(r.take(len)?)
+            .map_err(|_| BinaryFormatError::InvalidPrefix)?
+            .to_string()
+    };
+    let special_len = r.len()?;
+    let special_tokens = (0..special_len)
+        .map(|_| r.u64())
+        .collect::<Result<Vec<_>, _>>()?;
+    let token_len = r.len()?;
+    let tokens = if version == STATIC_VERSION {
+        let ends = (0..token_len)
+            .map(|_| r.u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        let text_len = r.len()?;
+        let text = r.take(text_len)?;
+        let text = std::str::from_utf8(text).map_err(|e| {
+            // the first token that isn't valid UTF-8
+            let id = ends.partition_point(|&end| end as usize <= e.valid_up_to());
+            BinaryFormatError::InvalidToken(id)
+        })?;
+        Texts::Joined(text, ends)
+    } else {
+        let mut tokens = TokenTexts::default();
+        for i in 0..token_len {
+            let len = r.u32()? as usize;
+            let token = std::str::from_utf8(r.take(len)?)
+                .map_err(|_| BinaryFormatError::InvalidToken(i))?;
+            tokens.push(token);
+        }
+        Texts::Parsed(tokens)
+    };
+    let starters_len = r.len()?;
+    let starters = r.take(starters_len)?;
+    let followers_len = r.len()?;
+    let followers = r.take(followers_len)?;
+    Ok(Parts {
+        header: Header {
+            unk_id,
+            prefix,
+            suffix,
+            pad_id,
+            mask_id,
+            continuing_subword_prefix,
+            end_of_word_suffix,
+            special_tokens,
+        },
+        tokens,
+        starters,
+        followers,
+    })
+}
+
+impl AlephAlphaTokenizer {
+    /// Saves the tokenizer in a binary format that includes the prebuilt
+    /// FSTs, so [`from_binary`](AlephAlphaTokenizer::from_binary) can load it
+    /// faster than [`from_vocab`](AlephAlphaTokenizer::from_vocab).
+    ///
+    /// The [`TokenizerOptions`] are not saved.
+    #[cfg(feature = "std-fs")]
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_binary(&mut w, VERSION)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Gets the tokenizer in the binary format of
+    /// [`from_static`](AlephAlphaTokenizer::from_static), e.g. to write it
+    /// from a build script and embed it with `include_bytes!`.
+    ///
+    /// Like [`save_binary`](AlephAlphaTokenizer::save_binary), this doesn't
+    /// include the [`TokenizerOptions`].
+    pub fn to_static_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_binary(&mut bytes, STATIC_VERSION)
+            .expect("writing to a `Vec` cannot fail");
+        bytes
+    }
+
+    fn write_binary<W: Write>(&self, w: &mut W, version: u8) -> std::io::Result<()> {
+        let suffix = self.end_of_word_suffix.as_bytes();
+        w.write_all(MAGIC)?;
+        if suffix.is_empty() {
+            w.write_all(&[version])?;
+        } else {
+            w.write_all(&[version | SUFFIX_FLAG])?;
+        }
+        w.write_all(&self.unk_id.to_le_bytes())?;
+        write_opt_u32(w, self.prefix)?;
+        write_opt_u32(w, self.suffix)?;
+        write_opt_u32(w, self.pad_id)?;
+        write_opt_u32(w, self.mask_id)?;
+        w.write_all(&(self.continuing_subword_prefix.len() as u32).to_le_bytes())?;
+        w.write_all(self.continuing_subword_prefix.as_bytes())?;
+        if !suffix.is_empty() {
+            w.write_all(&(suffix.len() as u32).to_le_bytes())?;
+            w.write_all(suffix)?;
+        }
+        w.write_all(&(self.special_tokens.len() as u64).to_le_bytes())?;
+        for id in self.special_tokens.iter() {
+            w.write_all(&id.to_le_bytes())?;
+        }
+        w.write_all(&(self.tokens.len() as u64).to_le_bytes())?;
+        if version == STATIC_VERSION {
+            let (text, ends) = self.tokens.parts();
+            for end in ends {
+                w.write_all(&end.to_le_bytes())?;
+            }
+            w.write_all(&(text.len() as u64).to_le_bytes())?;
+            w.write_all(text.as_bytes())?;
+        } else {
+            for token in self.tokens.iter() {
+                w.write_all(&(token.len() as u32).to_le_bytes())?;
+                w.write_all(token.as_bytes())?;
+            }
+        }
+        for fst in &[&self.starters, &self.followers] {
+            let bytes = fst.as_bytes();
+            w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            w.write_all(bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Loads a tokenizer saved with
+    /// [`save_binary`](AlephAlphaTokenizer::save_binary), using the default
+    /// [`TokenizerOptions`].
+    ///
+    /// This checks the header and verifies the internal consistency (see
+    /// [`verify_internal_consistency`]) of the loaded tokenizer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let path = std::env::temp_dir().join("aat-vocab.bin");
+    /// let from_vocab = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// from_vocab.save_binary(&path).unwrap();
+    /// let from_binary = AlephAlphaTokenizer::from_binary(&path).unwrap();
+    ///
+    /// let text = "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend \
+    ///     Impfstoff gegen FSME zur Verfügung?";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut binary_ids, mut binary_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// from_vocab.tokens_into(text, &mut ids, &mut ranges, None);
+    /// from_binary.tokens_into(text, &mut binary_ids, &mut binary_ranges, None);
+    /// assert_eq!(ids, binary_ids);
+    /// assert_eq!(ranges, binary_ranges);
+    /// assert_eq!(Some(5), from_binary.mask_id());
+    ///
+    /// // files of version 1 have no `[MASK]` id, but still load
+    /// let mut bytes = std::fs::read(&path).unwrap();
+    /// bytes[4] = 1;
+    /// bytes.drain(24..29);
+    /// let version_1 = AlephAlphaTokenizer::from_binary_bytes(&bytes).unwrap();
+    /// assert_eq!(None, version_1.mask_id());
+    /// assert_eq!(Some(0), version_1.pad_id());
+    /// ```
+    ///
+    /// Corrupted files are rejected:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let path = std::env::temp_dir().join("aat-corrupt-vocab.bin");
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// tokenizer.save_binary(&path).unwrap();
+    /// let mut bytes = std::fs::read(&path).unwrap();
+    ///
+    /// // the token text no longer agrees with the FSTs
+    /// let cls = bytes.windows(5).position(|w| w == b"[CLS]").unwrap();
+    /// bytes[cls + 1] = b'X';
+    /// let error = AlephAlphaTokenizer::from_binary_bytes(&bytes).err().unwrap();
+    /// assert!(error.to_string().starts_with("inconsistent tokenizer"));
+    /// assert!(AlephAlphaTokenizer::from_binary_bytes_unchecked(&bytes).is_ok());
+    ///
+    /// let error = AlephAlphaTokenizer::from_binary_bytes(&bytes[..1000]).err().unwrap();
+    /// assert_eq!("binary tokenizer file is truncated", error.to_string());
+    ///
+    /// bytes[4] = 99;
+    /// let error = AlephAlphaTokenizer::from_binary_bytes(&bytes).err().unwrap();
+    /// assert_eq!("unsupported binary tokenizer version 99 (expected at most 3)", error.to_string());
+    /// ```
+    ///
+    /// [`verify_internal_consistency`]: AlephAlphaTokenizer::verify_internal_consistency
+    #[cfg(feature = "std-fs")]
+    pub fn from_binary<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::from_binary_bytes(&fs::read(path)?)
+    }
+
+    /// Like [`from_binary`](AlephAlphaTokenizer::from_binary), but skips the
+    /// consistency check.
+    ///
+    /// Only use this for files you trust, e.g. because you wrote them
+    /// yourself on startup-critical paths.
+    #[cfg(feature = "std-fs")]
+    pub fn from_binary_unchecked<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Self::from_binary_bytes_unchecked(&fs::read(path)?)
+    }
+
+    /// Loads a tokenizer in binary format from memory, see
+    /// [`from_binary`](AlephAlphaTokenizer::from_binary).
+    pub fn from_binary_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let tokenizer = Self::from_binary_bytes_unchecked(bytes)?;
+        tokenizer
+            .verify_internal_consistency()
+            .map_err(BinaryFormatError::Inconsistent)?;
+        Ok(tokenizer)
+    }
+
+    /// Loads a tokenizer in binary format from memory without checking its
+    /// consistency, see
+    /// [`from_binary_unchecked`](AlephAlphaTokenizer::from_binary_unchecked).
+    ///
+    /// Even with FST keys that end within a char, the token ranges stay on
+    /// char boundaries:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let fst = |keys: &[(&[u8], u64)]| fst::Map::from_iter(keys.iter().cloned()).unwrap();
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nx\n##y\n").unwrap();
+    /// let path = std::env::temp_dir().join("aat-split-vocab.bin");
+    /// tokenizer.save_binary(&path).unwrap();
+    /// let mut bytes = std::fs::read(&path).unwrap();
+    ///
+    /// // replace the FSTs by ones that split `é` (`\xc3\xa9`) in the middle
+    /// let fsts = |starters: &[(&[u8], u64)], followers: &[(&[u8], u64)]| {
+    ///     let mut bytes = Vec::new();
+    ///     for fst in &[fst(starters), fst(followers)] {
+    ///         let fst = fst.as_fst().as_bytes();
+    ///         bytes.extend_from_slice(&(fst.len() as u64).to_le_bytes());
+    ///         bytes.extend_from_slice(fst);
+    ///     }
+    ///     bytes
+    /// };
+    /// let valid = fsts(&[(b"[UNK]", 0), (b"x", 1)], &[(b"y", 2)]);
+    /// assert!(bytes.ends_with(&valid));
+    /// bytes.truncate(bytes.len() - valid.len());
+    /// bytes.extend(fsts(&[(b"[UNK]", 0), (b"\xc3", 1)], &[(b"\xa9", 2)]));
+    ///
+    /// let split = AlephAlphaTokenizer::from_binary_bytes_unchecked(&bytes).unwrap();
+    /// let text = "é";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// split.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!((vec![0], vec![0..2]), (ids, ranges.clone()));
+    /// assert!(ranges.iter().all(|range| text.get(range.clone()).is_some()));
+    ///
+    /// // the checked loader rejects such keys
+    /// let error = AlephAlphaTokenizer::from_binary_bytes(&bytes).err().unwrap();
+    /// assert_eq!(
+    ///     "inconsistent tokenizer: token \"\u{fffd}\" with id 1 is not valid UTF-8",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn from_binary_bytes_unchecked(bytes: &[u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let parts = read_binary(bytes)?;
+        let tokens = match parts.tokens {
+            Texts::Parsed(tokens) => tokens,
+            Texts::Joined(text, ends) => TokenTexts::from_parts(text.to_string().into(), ends)
+                .map_err(BinaryFormatError::InvalidToken)?,
+        };
+        let starters = FstData::Owned(parts.starters.to_vec());
+        let followers = FstData::Owned(parts.followers.to_vec());
+        parts.header.into_tokenizer(tokens, starters, followers)
+    }
+
+    /// Loads a tokenizer embedded in the program, as written by
+    /// [`to_static_bytes`](AlephAlphaTokenizer::to_static_bytes), using the
+    /// default [`TokenizerOptions`].
+    ///
+    /// The tokenizer uses the token texts and FSTs where they are instead of
+    /// copying them, so this only reads the header and the ends of the
+    /// tokens, and is nearly instant. Like
+    /// [`from_binary_bytes_unchecked`](AlephAlphaTokenizer::from_binary_bytes_unchecked),
+    /// this doesn't check the consistency of the tokenizer, which is best
+    /// done once in a test with
+    /// [`verify_internal_consistency`](AlephAlphaTokenizer::verify_internal_consistency).
+    /// Bytes written by [`save_binary`](AlephAlphaTokenizer::save_binary)
+    /// load, too, but their token texts are copied.
+    ///
+    /// # Examples
+    ///
+    /// A build script can prepare the bytes, e.g.
+    ///
+    /// ```no_run
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use std::path::Path;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let out_dir = std::env::var("OUT_DIR").unwrap();
+    /// std::fs::write(Path::new(&out_dir).join("vocab.aatk"), tokenizer.to_static_bytes()).unwrap();
+    /// ```
+    ///
+    /// and the program embeds them with
+    /// `include_bytes!(concat!(env!("OUT_DIR"), "/vocab.aatk"))`. Here, the
+    /// bytes of the [example vocabulary](crate::examples) are embedded:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// static EMBEDDED: &[u8] = include_bytes!("../examples.aatk");
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_static(EMBEDDED).unwrap();
+    /// assert_eq!(Ok(()), tokenizer.verify_internal_consistency());
+    /// let built = examples::tokenizer();
+    /// assert_eq!(built.to_static_bytes(), EMBEDDED);
+    /// assert!(tokenizer.is_compatible_with(&built));
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let (mut built_ids, mut built_ranges) = (Vec::<u32>::new(), Vec::new());
+    /// for text in &["Ein interessantes Beispiel", "Ich esse Steak. Batman Robin", "☃", ""] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     built.tokens_into(text, &mut built_ids, &mut built_ranges, None);
+    ///     assert_eq!((&built_ids, &built_ranges), (&ids, &ranges), "{:?}", text);
+    /// }
+    /// assert_eq!(built.texts_of(&ids), tokenizer.texts_of(&ids));
+    /// assert_eq!(Some(17), tokenizer.id_of("##man"));
+    ///
+    /// // the same tokenizer loads from memory, too
+    /// let copied = AlephAlphaTokenizer::from_binary_bytes(EMBEDDED).unwrap();
+    /// assert_eq!(tokenizer.fingerprint(), copied.fingerprint());
+    ///
+    /// let error = AlephAlphaTokenizer::from_static(&EMBEDDED[..100]).err().unwrap();
+    /// assert_eq!("binary tokenizer file is truncated", error.to_string());
+    /// ```
+    pub fn from_static(bytes: &'static [u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let parts = read_binary(bytes)?;
+        let tokens = match parts.tokens {
+            Texts::Parsed(tokens) => tokens,
+            Texts::Joined(text, ends) => TokenTexts::from_parts(text.into(), ends)
+                .map_err(BinaryFormatError::InvalidToken)?,
+        };
+        let starters = FstData::Static(parts.starters);
+        let followers = FstData::Static(parts.followers);
+        parts.header.into_tokenizer(tokens, starters, followers)
+    }
+}
@@ -0,0 +1,2056 @@
+use crate::estimate::BUILTIN_SAMPLE;
+use crate::texts::TokenTexts;
+use crate::{
+    AlephAlphaTokenizer, CleanAction, LiteralMode, MarkerScheme, MatcherBackend, SegmentationMode,
+    SpecialTokens, TokenizerError, UnicodeNormalization, UnkMode, UnknownSeparator, WhitespaceMode,
+};
+#[cfg(feature = "std-fs")]
+use std::fs::File;
+use std::io::BufRead;
+#[cfg(feature = "std-fs")]
+use std::io::BufReader;
+
+/// The options that influence how an [`AlephAlphaTokenizer`] tokenizes text.
+///
+/// Use [`AlephAlphaTokenizerBuilder`] to set them, and
+/// [`AlephAlphaTokenizer::options`] to inspect them.
+///
+/// With the `serde` feature, these can be read from configuration files;
+/// missing fields take their default value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct TokenizerOptions {
+    /// Whether to put the `[CLS]` token (if present) in front of each text
+    pub add_prefix: bool,
+    /// Whether to put the `[SEP]` token (if present) after each text
+    pub add_suffix: bool,
+    /// Whether to rejoin words that were hyphenated at a line break or
+    /// contain soft hyphens before matching them
+    pub rejoin_hyphenation: bool,
+    /// Words with more characters than this become a single `[UNK]` token
+    pub max_chars_per_word: Option<usize>,
+    /// Words with more tokens than this become a single `[UNK]` token
+    pub max_tokens_per_word: Option<usize>,
+    /// Whether to lowercase words before matching them
+    pub lowercase: bool,
+    /// Whether a word starting with the continuing subword prefix may start
+    /// with a follower token, as in huggingface's WordPiece
+    pub leading_prefix_as_follower: bool,
+    /// Whether to split words at punctuation and around CJK ideographs, as
+    /// BERT's basic tokenizer does
+    pub split_punctuation: bool,
+    /// Whether words the vocabulary doesn't cover become one byte token
+    /// (`<0x00>` to `<0xFF>`) per byte instead of a single `[UNK]`
+    pub byte_fallback: bool,
+    /// How words are split into tokens
+    pub segmentation_mode: SegmentationMode,
+    /// What becomes of the words the vocabulary doesn't cover
+    pub unk_mode: UnkMode,
+    /// Whether to remove accents (but not the diaeresis of umlauts) from
+    /// words before matching them; this needs the `unicode-normalization`
+    /// feature
+    pub strip_accents: bool,
+    /// The Unicode normalization form to bring words into before matching
+    /// them, if any; this needs the `unicode-normalization` feature
+    pub normalize: Option<UnicodeNormalization>,
+    /// Whether to remove invisible format characters from words and split
+    /// words at control characters
+    pub clean_text: bool,
+    /// The characters to clean in addition to (or instead of) the default
+    /// set, where later ones override earlier ones
+    pub clean_chars: Vec<(char, CleanAction)>,
+    /// Whether a word that is the text of a special token (e.g. `[SEP]`)
+    /// becomes that token, whatever the other options do to words
+    pub parse_special_tokens: bool,
+    /// The characters that separate words
+    pub whitespace: WhitespaceMode,
+    /// What becomes of the words that start with the continuing subword
+    /// prefix
+    pub literal_hash_hash: LiteralMode,
+    /// The characters at which a word the vocabulary doesn't cover is split
+    /// into parts that are matched as words of their own, before the word
+    /// becomes `[UNK]`
+    pub fallback_separators: Vec<char>,
+    /// What becomes of the fallback separators without a token
+    pub unknown_separator: UnknownSeparator,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        TokenizerOptions {
+            add_prefix: true,
+            add_suffix: true,
+            rejoin_hyphenation: false,
+            max_chars_per_word: None,
+            max_tokens_per_word: None,
+            lowercase: false,
+            leading_prefix_as_follower: false,
+            split_punctuation: false,
+            byte_fallback: false,
+            segmentation_mode: SegmentationMode::Greedy,
+            unk_mode: UnkMode::WholeWord,
+            strip_accents: false,
+            normalize: None,
+            clean_text: false,
+            clean_chars: Vec::new(),
+            parse_special_tokens: false,
+            whitespace: WhitespaceMode::Unicode,
+            literal_hash_hash: LiteralMode::Text,
+            fallback_separators: Vec::new(),
+            unknown_separator: UnknownSeparator::Unk,
+        }
+    }
+}
+
+/// Option presets for common model families.
+///
+/// A preset only sets the builder's options, so they can still be overridden
+/// afterwards. The BERT presets split punctuation and CJK ideographs off
+/// words like BERT's basic tokenizer, which also strips accents for uncased
+/// models (with the `unicode-normalization` feature), and keep the special
+/// tokens in texts whole like transformers' `BertTokenizer`. The causal
+/// preset leaves the words as they are, and a builder without a preset keeps
+/// the plain [`TokenizerOptions::default`].
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizerBuilder, Preset, TokenizerOptions};
+///
+/// let cases: &[(Preset, TokenizerOptions, &[u64])] = &[
+///     (
+///         Preset::BertCased,
+///         TokenizerOptions {
+///             add_prefix: true,
+///             add_suffix: true,
+///             split_punctuation: true,
+///             parse_special_tokens: true,
+///             ..TokenizerOptions::default()
+///         },
+///         &[3, 1671, 229, 145, 601, 464, 4, 4],
+///     ),
+///     (
+///         Preset::BertUncased,
+///         TokenizerOptions {
+///             add_prefix: true,
+///             add_suffix: true,
+///             lowercase: true,
+///             split_punctuation: true,
+///             strip_accents: cfg!(feature = "unicode-normalization"),
+///             parse_special_tokens: true,
+///             ..TokenizerOptions::default()
+///         },
+///         &[3, 1169, 229, 145, 3809, 464, 4, 4],
+///     ),
+///     (
+///         Preset::CausalLm,
+///         TokenizerOptions {
+///             add_prefix: false,
+///             add_suffix: true,
+///             parse_special_tokens: false,
+///             ..TokenizerOptions::default()
+///         },
+///         &[1671, 229, 145, 601, 464, 4, 4],
+///     ),
+/// ];
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// for (preset, options, expected) in cases {
+///     let builder = AlephAlphaTokenizerBuilder::from_preset(*preset).vocab_path("vocab.txt");
+///     assert_eq!(builder.options(), options);
+///     let tokenizer = builder.build().unwrap();
+///     assert_eq!(tokenizer.options(), options);
+///     tokenizer.tokens_into("Ich esse Steak [SEP]", &mut ids, &mut ranges, None);
+///     assert_eq!(&ids[..], *expected, "{:?}", preset);
+/// }
+///
+/// // the BERT presets split punctuation off words, but not off special tokens
+/// let texts = |builder: AlephAlphaTokenizerBuilder| {
+///     let tokenizer = builder.vocab_path("vocab.txt").build().unwrap();
+///     let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+///     tokenizer.tokens_into("Steak. [SEP]", &mut ids, &mut ranges, None);
+///     tokenizer.texts_of(&ids).join(" ")
+/// };
+/// let bert = || AlephAlphaTokenizerBuilder::from_preset(Preset::BertCased);
+/// assert_eq!("[CLS] Ste ##ak . [SEP] [SEP]", texts(bert()));
+/// assert_eq!("[CLS] Ste ##ak . [ S ##EP ] [SEP]", texts(bert().parse_special_tokens(false)));
+/// assert_eq!("[CLS] Ste ##ak ##. [SEP] [SEP]", texts(AlephAlphaTokenizerBuilder::new()));
+/// assert_eq!(&TokenizerOptions::default(), AlephAlphaTokenizerBuilder::default().options());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// A cased BERT model: wrap each text in `[CLS]` … `[SEP]` and split
+    /// punctuation off words other than special tokens
+    BertCased,
+    /// An uncased BERT model: wrap each text in `[CLS]` … `[SEP]`, split
+    /// punctuation off words other than special tokens, lowercase them and
+    /// strip accents
+    BertUncased,
+    /// A generation-style model: no `[CLS]`, but end each text with `[SEP]`
+    CausalLm,
+}
+
+impl Preset {
+    /// Gets the options this preset stands for.
+    pub fn options(self) -> TokenizerOptions {
+        match self {
+            Preset::BertCased => TokenizerOptions {
+                add_prefix: true,
+                add_suffix: true,
+                split_punctuation: true,
+                parse_special_tokens: true,
+                ..TokenizerOptions::default()
+            },
+            Preset::BertUncased => TokenizerOptions {
+                add_prefix: true,
+                add_suffix: true,
+                lowercase: true,
+                split_punctuation: true,
+                strip_accents: cfg!(feature = "unicode-normalization"),
+                parse_special_tokens: true,
+                ..TokenizerOptions::default()
+            },
+            Preset::CausalLm => TokenizerOptions {
+                add_prefix: false,
+                add_suffix: true,
+                parse_special_tokens: false,
+                ..TokenizerOptions::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Vocab {
+    #[cfg(feature = "std-fs")]
+    Path(String),
+    Lines(Result<TokenTexts, TokenizerError>),
+    #[cfg(feature = "std-fs")]
+    IdPath(String),
+    IdLines(Result<IdLines, TokenizerError>),
+}
+
+// the bytes per line of typical vocabularies (about 8.5 for BERT's), to
+// guess the number of tokens from the size of a file
+const AVERAGE_LINE_BYTES: usize = 8;
+
+// the tokens at their ids, and the line of each id (sorted by id)
+type IdLines = (TokenTexts, Vec<(u64, usize)>);
+
+// read the trimmed lines like `BufRead::lines`, but straight into the
+// token texts, report invalid UTF-8 with its line number, and drop a byte
+// order mark; `size` is the expected size of the vocabulary in bytes
+fn read_lines<R: BufRead>(mut reader: R, size: usize) -> Result<TokenTexts, TokenizerError> {
+    // a reader of bytes in memory has them all in its buffer
+    let size = size.max(reader.fill_buf()?.len());
+    let mut lines = TokenTexts::with_capacity(size, size / AVERAGE_LINE_BYTES);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let line_no = lines.len() + 1;
+        let mut bytes = &line[..];
+        if line_no == 1 && bytes.starts_with(b"\xef\xbb\xbf") {
+            bytes = &bytes[3..];
+        }
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| TokenizerError::InvalidVocabLine { line_no })?
+            .trim();
+        if !lines.fits(text) {
+            return Err(TokenizerError::InvalidVocab(
+                "the tokens take more than 4 GiB".to_string(),
+            ));
+        }
+        lines.push(text);
+        line.clear();
+    }
+    Ok(lines)
+}
+
+// opens a vocabulary file, together with its size in bytes
+#[cfg(feature = "std-fs")]
+fn open_vocab(path: &str) -> Result<(BufReader<File>, usize), TokenizerError> {
+    let file = File::open(path)?;
+    let size = file
+        .metadata()
+        .map_or(0, |metadata| metadata.len() as usize);
+    Ok((BufReader::new(file), size))
+}
+
+// read `token<TAB>id` lines into the token texts at their ids, leaving the
+// ids in between empty like blank lines
+fn read_id_lines<R: BufRead>(reader: R, size: usize) -> Result<IdLines, TokenizerError> {
+    let lines = read_lines(reader, size)?;
+    let mut entries = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            TokenizerError::InvalidVocab(format!(
+                "line {} is not a token and its id separated by a tab",
+                i + 1
+            ))
+        };
+        let (token, id) = line.rsplit_once('\t').ok_or_else(invalid)?;
+        let token = token.trim();
+        let id = id.trim().parse::<u32>().map_err(|_| invalid())?;
+        if token.is_empty() {
+            return Err(invalid());
+        }
+        entries.push((u64::from(id), token, i + 1));
+    }
+    // the sort is stable, so duplicates stay in line order
+    entries.sort_by_key(|&(id, _, _)| id);
+    if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(TokenizerError::DuplicateId {
+            id: pair[0].0,
+            first_line: pair[0].2,
+            second_line: pair[1].2,
+        });
+    }
+    let mut texts = TokenTexts::default();
+    for &(id, token, _) in &entries {
+        while (texts.len() as u64) < id {
+            texts.push("");
+        }
+        texts.push(token);
+    }
+    let line_nos = entries
+        .iter()
+        .map(|&(id, _, line_no)| (id, line_no))
+        .collect();
+    Ok((texts, line_nos))
+}
+
+// make the lines of duplicate tokens those of the file with ids, rather than
+// the ids counted from 1
+fn with_id_lines(error: TokenizerError, line_nos: &[(u64, usize)]) -> TokenizerError {
+    let line_of = |line: usize| {
+        let id = line as u64 - 1;
+        line_nos
+            .binary_search_by_key(&id, |&(id, _)| id)
+            .map_or(line, |i| line_nos[i].1)
+    };
+    match error {
+        TokenizerError::DuplicateToken {
+            token,
+            first_line,
+            second_line,
+        } => {
+            let (first, second) = (line_of(first_line), line_of(second_line));
+            TokenizerError::DuplicateToken {
+                token,
+                first_line: first.min(second),
+                second_line: first.max(second),
+            }
+        }
+        error => error,
+    }
+}
+
+/// A builder to configure and create an [`AlephAlphaTokenizer`].
+///
+/// The constructors of [`AlephAlphaTokenizer`] are shortcuts for this
+/// builder with the default options.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizerBuilder, Preset};
+///
+/// let tokenizer = AlephAlphaTokenizerBuilder::from_preset(Preset::CausalLm)
+///     .vocab_path("vocab.txt")
+///     .add_prefix(true)
+///     .build()
+///     .unwrap();
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// tokenizer.tokens_into("Ich esse Steak", &mut ids, &mut ranges, None);
+/// assert_eq!(&[3, 1671, 229, 145, 601, 464, 4], &ids[..]);
+/// ```
+///
+/// Some other configurations:
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, AlephAlphaTokenizerBuilder};
+///
+/// let vocab = "<unk>\n<s>\n</s>\nSuper\n##man\nBat\n";
+/// let builder = || {
+///     AlephAlphaTokenizer::builder()
+///         .vocab_reader(vocab.as_bytes())
+///         .unk_token("<unk>")
+///         .cls_token("<s>")
+///         .sep_token("</s>")
+/// };
+/// let cases: Vec<(AlephAlphaTokenizerBuilder, &[u64])> = vec![
+///     (builder(), &[1, 3, 4, 5, 4, 0, 2]),
+///     (builder().add_special_tokens(false), &[3, 4, 5, 4, 0]),
+///     (builder().max_chars_per_word(Some(6)), &[1, 0, 5, 4, 0, 2]),
+///     (builder().cls_token("Bat"), &[5, 3, 4, 5, 4, 0, 2]),
+/// ];
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// for (builder, expected) in cases {
+///     let tokenizer = builder.build().unwrap();
+///     tokenizer.tokens_into("Superman Batman Robin", &mut ids, &mut ranges, None);
+///     assert_eq!(&ids[..], expected);
+/// }
+///
+/// // without a vocabulary or `[UNK]` token, we cannot build a tokenizer
+/// assert!(AlephAlphaTokenizer::builder().build().is_err());
+/// assert!(AlephAlphaTokenizer::builder().vocab_reader(vocab.as_bytes()).build().is_err());
+/// ```
+#[derive(Debug)]
+pub struct AlephAlphaTokenizerBuilder {
+    vocab: Option<Vocab>,
+    unk_token: String,
+    cls_token: Option<String>,
+    sep_token: Option<String>,
+    pad_token: Option<String>,
+    mask_token: Option<String>,
+    bracketed_special_tokens: bool,
+    additional_special_tokens: Vec<String>,
+    markers: MarkerScheme,
+    eager_indexes: bool,
+    parallel_build: bool,
+    suppressed_ids: Vec<u64>,
+    ascii_fast_path: bool,
+    backend: MatcherBackend,
+    #[cfg(feature = "mmap")]
+    pub(crate) fst_files: Option<(String, String)>,
+    options: TokenizerOptions,
+}
+
+impl Default for AlephAlphaTokenizerBuilder {
+    fn default() -> Self {
+        AlephAlphaTokenizerBuilder {
+            vocab: None,
+            unk_token: String::new(),
+            cls_token: None,
+            sep_token: None,
+            pad_token: None,
+            mask_token: None,
+            bracketed_special_tokens: false,
+            additional_special_tokens: Vec::new(),
+            markers: MarkerScheme::default(),
+            eager_indexes: false,
+            parallel_build: false,
+            suppressed_ids: Vec::new(),
+            ascii_fast_path: true,
+            backend: MatcherBackend::Fst,
+            #[cfg(feature = "mmap")]
+            fst_files: None,
+            options: TokenizerOptions::default(),
+        }
+        .special_tokens(&SpecialTokens::default())
+    }
+}
+
+impl AlephAlphaTokenizerBuilder {
+    /// Creates a builder with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder with the options of the given [`Preset`].
+    pub fn from_preset(preset: Preset) -> Self {
+        AlephAlphaTokenizerBuilder {
+            options: preset.options(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the path of the vocabulary file to load.
+    #[cfg(feature = "std-fs")]
+    pub fn vocab_path(mut self, path: &str) -> Self {
+        self.vocab = Some(Vocab::Path(path.to_string()));
+        self
+    }
+
+    /// Reads the vocabulary, one token per line, from the given reader.
+    ///
+    /// The vocabulary is read right away, but errors are only returned by
+    /// [`build`](AlephAlphaTokenizerBuilder::build).
+    pub fn vocab_reader<R: BufRead>(mut self, reader: R) -> Self {
+        self.vocab = Some(Vocab::Lines(read_lines(reader, 0)));
+        self
+    }
+
+    /// Sets the path of a vocabulary file with a token and its id, separated
+    /// by a tab, on each line, see
+    /// [`from_vocab_with_ids`](AlephAlphaTokenizer::from_vocab_with_ids).
+    #[cfg(feature = "std-fs")]
+    pub fn vocab_path_with_ids(mut self, path: &str) -> Self {
+        self.vocab = Some(Vocab::IdPath(path.to_string()));
+        self
+    }
+
+    /// Reads a vocabulary with a token and its id, separated by a tab, on
+    /// each line from the given reader, see
+    /// [`from_vocab_with_ids`](AlephAlphaTokenizer::from_vocab_with_ids).
+    ///
+    /// The vocabulary is read right away, but errors are only returned by
+    /// [`build`](AlephAlphaTokenizerBuilder::build).
+    pub fn vocab_reader_with_ids<R: BufRead>(mut self, reader: R) -> Self {
+        self.vocab = Some(Vocab::IdLines(read_id_lines(reader, 0)));
+        self
+    }
+
+    // sets the tokens in the order of their ids
+    pub(crate) fn vocab_tokens(mut self, tokens: Result<Vec<String>, TokenizerError>) -> Self {
+        let texts = tokens.map(|tokens| tokens.iter().map(|token| token.trim()).collect());
+        self.vocab = Some(Vocab::Lines(texts));
+        self
+    }
+
+    /// Sets the texts of all special tokens at once.
+    pub fn special_tokens(mut self, special: &SpecialTokens) -> Self {
+        self.unk_token = special.unk.to_string();
+        self.cls_token = special.cls.map(str::to_string);
+        self.sep_token = special.sep.map(str::to_string);
+        self.pad_token = special.pad.map(str::to_string);
+        self.mask_token = special.mask.map(str::to_string);
+        self.bracketed_special_tokens = special.bracketed;
+        self.additional_special_tokens = special.additional.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Sets the text of the token for unknown words (`[UNK]` by default).
+    pub fn unk_token(mut self, token: &str) -> Self {
+        self.unk_token = token.to_string();
+        self
+    }
+
+    /// Sets the text of the classification token, which is used as prefix
+    /// (`[CLS]` by default).
+    pub fn cls_token(mut self, token: &str) -> Self {
+        self.cls_token = Some(token.to_string());
+        self
+    }
+
+    /// Sets the text of the separator token, which is used as suffix
+    /// (`[SEP]` by default).
+    pub fn sep_token(mut self, token: &str) -> Self {
+        self.sep_token = Some(token.to_string());
+        self
+    }
+
+    /// Sets the text of the padding token (`[PAD]` by default).
+    pub fn pad_token(mut self, token: &str) -> Self {
+        self.pad_token = Some(token.to_string());
+        self
+    }
+
+    /// Sets the text of the mask token (`[MASK]` by default).
+    pub fn mask_token(mut self, token: &str) -> Self {
+        self.mask_token = Some(token.to_string());
+        self
+    }
+
+    /// Marks other tokens of the vocabulary as special, e.g. domain markers
+    /// like `<|endofprompt|>` that don't follow the `[...]` convention.
+    ///
+    /// These are special like the others, e.g. for
+    /// [`is_special`](AlephAlphaTokenizer::is_special) and decoding, but no
+    /// word can match them; they only become tokens with
+    /// [`parse_special_tokens`](Self::parse_special_tokens). Building fails
+    /// if the vocabulary lacks one of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\n<|endofprompt|>\n▶USER\n▶\n##USER\nSuper\n##man\n";
+    /// let builder = || {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .additional_special_tokens(&["<|endofprompt|>", "▶USER"])
+    /// };
+    /// let tokenizer = builder().build().unwrap();
+    /// let parsing = builder().parse_special_tokens(true).build().unwrap();
+    /// let texts = |tokenizer: &AlephAlphaTokenizer, text| {
+    ///     let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// };
+    ///
+    /// // only parsing special tokens gives them
+    /// assert_eq!("[CLS] ▶ ##USER Super ##man [UNK] [SEP]", texts(&tokenizer, "▶USER Superman <|endofprompt|>"));
+    /// assert_eq!(
+    ///     "[CLS] ▶USER Super ##man <|endofprompt|> [SEP]",
+    ///     texts(&parsing, "▶USER Superman <|endofprompt|>")
+    /// );
+    /// assert_eq!(Some((3, 5)), tokenizer.match_starter("▶USER"));
+    ///
+    /// // and they are special like the others
+    /// assert!(tokenizer.is_special(3u64) && tokenizer.is_special(4u64));
+    /// assert!(!tokenizer.is_special(5u64));
+    /// assert_eq!(Some(4), tokenizer.id_of("▶USER"));
+    /// let special: Vec<_> = tokenizer.special_tokens().collect();
+    /// assert_eq!(vec![(0, "[UNK]"), (1, "[CLS]"), (2, "[SEP]"), (3, "<|endofprompt|>"), (4, "▶USER")], special);
+    ///
+    /// let error = builder().additional_special_tokens(&["<|im_end|>"]).build().err().unwrap();
+    /// assert!(matches!(error, TokenizerError::MissingSpecialToken(ref token) if token == "<|im_end|>"));
+    /// assert_eq!("the vocabulary has no special token \"<|im_end|>\"", error.to_string());
+    /// ```
+    pub fn additional_special_tokens(mut self, tokens: &[&str]) -> Self {
+        self.additional_special_tokens = tokens.iter().map(|token| token.to_string()).collect();
+        self
+    }
+
+    /// Sets the prefix that marks tokens continuing a word (`##` by default),
+    /// e.g. `@@` for subword-nmt style vocabularies.
+    ///
+    /// With an empty prefix, every token (except the special ones) may both
+    /// start and continue a word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = |vocab: &str, prefix: &str| {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .continuing_subword_prefix(prefix)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let hashes = tokenizer("[UNK]\n[CLS]\n[SEP]\nSuper\n##man\nman\nBat\n", "##");
+    /// let ats = tokenizer("[UNK]\n[CLS]\n[SEP]\nSuper\n@@man\nman\nBat\n", "@@");
+    /// let empty = tokenizer("[UNK]\n[CLS]\n[SEP]\nSuper\nman\nBat\n", "");
+    ///
+    /// let text = "Superman Batman man Robin";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut at_ids, mut at_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// hashes.tokens_into(text, &mut ids, &mut ranges, None);
+    /// ats.tokens_into(text, &mut at_ids, &mut at_ranges, None);
+    /// assert_eq!(&[1, 3, 4, 6, 4, 5, 0, 2], &ids[..]);
+    /// assert_eq!(ids, at_ids);
+    /// assert_eq!(ranges, at_ranges);
+    /// assert_eq!(Some(4), ats.id_of("@@man"));
+    /// assert_eq!(Some(5), ats.id_of("man"));
+    /// assert_eq!(Some(5), hashes.id_of("man"));
+    ///
+    /// // "man" both continues "Super" and "Bat" and is a word of its own
+    /// empty.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 3, 4, 5, 4, 4, 0, 2], &ids[..]);
+    /// assert_eq!(&[0..0, 0..5, 5..8, 9..12, 12..15, 16..19, 20..25, 25..25], &ranges[..]);
+    /// assert_eq!("", empty.continuing_subword_prefix());
+    /// ```
+    pub fn continuing_subword_prefix(mut self, prefix: &str) -> Self {
+        self.markers = MarkerScheme::ContinuationPrefix(prefix.to_string());
+        self
+    }
+
+    /// Sets how the vocabulary marks the tokens within a word: a prefix on
+    /// the tokens that continue a word (the default, see
+    /// [`continuing_subword_prefix`](Self::continuing_subword_prefix)), or a
+    /// suffix on those that end one, e.g. `</w>` as in some BPE vocabularies.
+    ///
+    /// With an end-of-word suffix, each word is matched as tokens without
+    /// the suffix followed by one with it that reaches the end of the word,
+    /// e.g. `low` `er</w>` for "lower", and a word that cannot be covered
+    /// this way becomes a single `[UNK]`, whatever the
+    /// [`unk_mode`](Self::unk_mode). [`id_of`](AlephAlphaTokenizer::id_of)
+    /// and [`decode`](AlephAlphaTokenizer::decode) take the suffix into
+    /// account. Such tokenizers cannot be saved as huggingface JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, MarkerScheme, SegmentationMode};
+    ///
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\nlow\nlow</w>\ner</w>\nest</w>\nn\ne\new</w>\n";
+    /// let scheme = MarkerScheme::EndOfWordSuffix("</w>".to_string());
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .marker_scheme(scheme.clone())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(scheme, tokenizer.marker_scheme());
+    /// assert_eq!("", tokenizer.continuing_subword_prefix());
+    ///
+    /// let text = "lower lowest low new newer";
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] low er</w> low est</w> low</w> n ew</w> [UNK] [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(
+    ///     &[0..0, 0..3, 3..5, 6..9, 9..12, 13..16, 17..18, 18..20, 21..26, 26..26],
+    ///     &ranges[..]
+    /// );
+    /// assert_eq!("[CLS] lower lowest low new [UNK] [SEP]", tokenizer.decode(&ids, false));
+    /// assert_eq!("lower lowest low new", tokenizer.decode(&ids[..8], true));
+    /// // so does huggingface's decoding, with the `huggingface` feature
+    ///# #[cfg(feature = "huggingface")] {
+    /// use tokenizers::tokenizer::Decoder;
+    /// let tokens = ids.iter().map(|&id| tokenizer.text_of(id).to_string()).collect();
+    /// assert_eq!(tokenizer.decode(&ids, false), tokenizer.decoder().decode(tokens).unwrap());
+    ///# }
+    ///
+    /// // the suffix tells both kinds of tokens apart
+    /// assert_eq!(Some(3), tokenizer.id_of("low"));
+    /// assert_eq!(Some(4), tokenizer.id_of("low</w>"));
+    /// assert_eq!(Some(9), tokenizer.id_of("ew</w>"));
+    /// assert_eq!(None, tokenizer.id_of("ew"));
+    /// assert_eq!(Some(0), tokenizer.id_of("[UNK]"));
+    ///
+    /// // the special tokens are words of their own
+    /// tokenizer.tokens_into("low [SEP]", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] low</w> [SEP] [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// // the binary format keeps the suffix
+    /// let loaded = AlephAlphaTokenizer::from_binary_bytes(&tokenizer.to_static_bytes()).unwrap();
+    /// assert_eq!(scheme, loaded.marker_scheme());
+    /// assert_eq!(Some(4), loaded.id_of("low</w>"));
+    ///
+    /// // a word needs a token to end it, even if it has no other tokens
+    /// tokenizer.tokens_into("n", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 0, 2], &ids[..]);
+    ///
+    /// // backtracking finds segmentations the longest matches miss
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\nlo\nlow\nwer</w>\n";
+    /// let mut ids_of = |mode| {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .marker_scheme(MarkerScheme::EndOfWordSuffix("</w>".to_string()))
+    ///         .segmentation_mode(mode)
+    ///         .build()
+    ///         .unwrap()
+    ///         .tokens_into("lower", &mut ids, &mut ranges, None);
+    ///     ids.clone()
+    /// };
+    /// assert_eq!(vec![1, 0, 2], ids_of(SegmentationMode::Greedy));
+    /// assert_eq!(vec![1, 3, 5, 2], ids_of(SegmentationMode::Backtracking));
+    /// ```
+    pub fn marker_scheme(mut self, scheme: MarkerScheme) -> Self {
+        self.markers = scheme;
+        self
+    }
+
+    /// Sets whether the prefix and suffix tokens are added around each text.
+    pub fn add_special_tokens(self, add: bool) -> Self {
+        self.add_prefix(add).add_suffix(add)
+    }
+
+    /// Sets whether the `[CLS]` token (if present) is put in front of each
+    /// text.
+    pub fn add_prefix(mut self, add_prefix: bool) -> Self {
+        self.options.add_prefix = add_prefix;
+        self
+    }
+
+    /// Sets whether the `[SEP]` token (if present) is put after each text.
+    pub fn add_suffix(mut self, add_suffix: bool) -> Self {
+        self.options.add_suffix = add_suffix;
+        self
+    }
+
+    /// Sets whether words hyphenated at a line break (as in "Kranken-\nhaus")
+    /// are rejoined and soft hyphens are ignored before matching.
+    ///
+    /// The token ranges still refer to the original text, so the tokens of
+    /// a rejoined word span both lines including the hyphen and line break.
+    /// A hyphen at a line break is kept for matching if the next line starts
+    /// with an uppercase letter, as it is likely part of a compound then. A
+    /// word of nothing but soft hyphens becomes a single `[UNK]`. This is
+    /// off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizerBuilder;
+    ///
+    /// let tokenizer = AlephAlphaTokenizerBuilder::new()
+    ///     .vocab_path("vocab.txt")
+    ///     .rejoin_hyphenation(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut joined_ids, mut joined_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("im Bundes-\nregierungsbezirk", &mut ids, &mut ranges, None);
+    /// tokenizer.tokens_into(
+    ///     "im Bundesregierungsbezirk",
+    ///     &mut joined_ids,
+    ///     &mut joined_ranges,
+    ///     None,
+    /// );
+    /// assert_eq!(joined_ids, ids);
+    /// assert_eq!(&ranges[2..], &[3..20, 20..27, 27..27]);
+    ///
+    /// tokenizer.tokens_into("Kranken\u{AD}haus", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[3, 4870, 4], &ids[..]);
+    /// assert_eq!(&[0..0, 0..13, 13..13], &ranges[..]);
+    ///
+    /// // a word of nothing but soft hyphens has no token to cover it
+    /// tokenizer.tokens_into("\u{AD}", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..2, 2..2], &ranges[..]);
+    /// tokenizer.tokens_into("a \u{AD} b", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] a [UNK] b [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..1, 2..4, 5..6, 6..6], &ranges[..]);
+    /// ```
+    pub fn rejoin_hyphenation(mut self, rejoin_hyphenation: bool) -> Self {
+        self.options.rejoin_hyphenation = rejoin_hyphenation;
+        self
+    }
+
+    /// Sets the maximum number of characters of a word. Longer words become a
+    /// single `[UNK]` token. There is no limit by default.
+    ///
+    /// The limit is checked before matching any tokens, so a long word only
+    /// costs counting its characters (or not even that if it has more than
+    /// four bytes per allowed character).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let vocab = "[PAD]\n[UNK]\n[CLS]\n[SEP]\n[MASK]\n🦀\n🦀🦀\nKrabbe\n##n\n";
+    /// let texts = |max, text| {
+    ///     let tokenizer = AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .max_chars_per_word(Some(max))
+    ///         .build()
+    ///         .unwrap();
+    ///     let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// };
+    /// assert_eq!("[CLS] Krabbe ##n [SEP]", texts(7, "Krabben"));
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(6, "Krabben"));
+    /// // a word of exactly `max` chars is within the limit, whatever their bytes
+    /// assert_eq!("[CLS] 🦀 [SEP]", texts(1, "🦀"));
+    /// assert_eq!("[CLS] 🦀🦀 [SEP]", texts(2, "🦀🦀"));
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(1, "🦀🦀"));
+    /// ```
+    pub fn max_chars_per_word(mut self, max: Option<usize>) -> Self {
+        self.options.max_chars_per_word = max;
+        self
+    }
+
+    /// Sets the maximum number of tokens of a word. A word that would have
+    /// more tokens becomes a single `[UNK]` token (or its bytes with
+    /// [`byte_fallback`](Self::byte_fallback)), whatever the
+    /// [`UnkMode`]. There is no limit by default.
+    ///
+    /// This bounds the work on long words the vocabulary doesn't cover, e.g.
+    /// base64 blobs: greedy matching finds each token by walking the
+    /// vocabulary at most as many bytes as the longest token has, and stops
+    /// after `max + 1` tokens instead of walking the whole word only to throw
+    /// the tokens away for a `[UNK]`. With
+    /// [`SegmentationMode::Backtracking`] (or [`UnkMode::PerChar`] once a
+    /// word isn't covered), the tokens are only counted when the whole word
+    /// is matched, which still takes time linear in the length of the word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode, UnkMode};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let bounded = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .max_tokens_per_word(Some(8))
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut bounded_ids, mut bounded_ranges) = (Vec::<u64>::new(), Vec::new());
+    ///
+    /// // a blob of 100 kB is a single `[UNK]`
+    /// let blob = "QmFzZTY0IGlzIG5vIEdlcm1hbg".repeat(4_000);
+    /// let text = format!("Steak {} Steak", blob);
+    /// bounded.tokens_into(&text, &mut bounded_ids, &mut bounded_ranges, None);
+    /// assert_eq!("[CLS] Ste ##ak [UNK] Ste ##ak [SEP]", bounded.texts_of(&bounded_ids).join(" "));
+    /// assert_eq!(6..6 + blob.len(), bounded_ranges[3]);
+    ///
+    /// // which would have been many tokens
+    /// tokenizer.tokens_into(&blob, &mut ids, &mut ranges, None);
+    /// assert!(ids.len() > 10_000);
+    ///
+    /// // whatever the modes
+    /// for (segmentation, unk) in &[
+    ///     (SegmentationMode::Backtracking, UnkMode::WholeWord),
+    ///     (SegmentationMode::Greedy, UnkMode::PerChar),
+    /// ] {
+    ///     let bounded = AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .max_tokens_per_word(Some(8))
+    ///         .segmentation_mode(*segmentation)
+    ///         .unk_mode(*unk)
+    ///         .build()
+    ///         .unwrap();
+    ///     bounded.tokens_into(&blob, &mut bounded_ids, &mut bounded_ranges, None);
+    ///     assert_eq!("[CLS] [UNK] [SEP]", bounded.texts_of(&bounded_ids).join(" "));
+    /// }
+    ///
+    /// // the words within the limit keep their tokens
+    /// for text in &[
+    ///     "Der Hund spielt im Garten.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Grüße aus dem Krankenhaus ☃ Supercalifragilistic",
+    /// ] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     bounded.tokens_into(text, &mut bounded_ids, &mut bounded_ranges, None);
+    ///     assert_eq!((&ids, &ranges), (&bounded_ids, &bounded_ranges), "{:?}", text);
+    /// }
+    /// ```
+    pub fn max_tokens_per_word(mut self, max: Option<usize>) -> Self {
+        self.options.max_tokens_per_word = max;
+        self
+    }
+
+    /// Sets whether words are lowercased before matching them against the
+    /// vocabulary, e.g. for uncased models.
+    ///
+    /// The token ranges still refer to the original text, even where the
+    /// lowercase form has a different length (as for `ẞ` or `İ`). This only
+    /// applies to [`tokens_into`](AlephAlphaTokenizer::tokens_into) and
+    /// friends; within huggingface's `Tokenizer`, use a normalizer. This is
+    /// off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut lower_ids, mut lower_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("STEAK", &mut ids, &mut ranges, None);
+    /// tokenizer.tokens_into("steak", &mut lower_ids, &mut lower_ranges, None);
+    /// assert_eq!(lower_ids, ids);
+    /// assert_eq!(lower_ranges, ranges);
+    ///
+    /// // "ẞ" has three bytes, but "ß" only two
+    /// let text = "GROẞE STRAẞE";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// tokenizer.tokens_into("große straße", &mut lower_ids, &mut lower_ranges, None);
+    /// assert_eq!(lower_ids, ids);
+    /// assert_eq!(
+    ///     vec!["", "GROẞE", "STRA", "ẞE", ""],
+    ///     ranges.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>()
+    /// );
+    /// ```
+    pub fn lowercase(mut self, lowercase: bool) -> Self {
+        self.options.lowercase = lowercase;
+        self
+    }
+
+    /// Sets whether a word starting with the continuing subword prefix may
+    /// start with a follower token that includes the prefix, as huggingface's
+    /// WordPiece does.
+    ///
+    /// By default, the prefix is matched like any other characters, which
+    /// keeps e.g. markdown headings intact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let wordpiece = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .leading_prefix_as_follower(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let texts = |tokenizer: &AlephAlphaTokenizer, text| {
+    ///     let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// };
+    /// for tokenizer in &[&tokenizer, &wordpiece] {
+    ///     assert_eq!("[CLS] # ### Head ##ing [SEP]", texts(tokenizer, "## Heading"));
+    /// }
+    /// assert_eq!("[CLS] # ### ### Abschnitt [SEP]", texts(&tokenizer, "### Abschnitt"));
+    /// assert_eq!("[CLS] ### Abschnitt [SEP]", texts(&wordpiece, "### Abschnitt"));
+    /// assert_eq!("[CLS] # ### ##He ##adin ##g [SEP]", texts(&tokenizer, "##Heading"));
+    /// assert_eq!("[CLS] ##He ##adin ##g [SEP]", texts(&wordpiece, "##Heading"));
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// wordpiece.tokens_into("##Heading", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[0..0, 0..4, 4..8, 8..9, 9..9], &ranges[..]);
+    /// ```
+    pub fn leading_prefix_as_follower(mut self, leading_prefix_as_follower: bool) -> Self {
+        self.options.leading_prefix_as_follower = leading_prefix_as_follower;
+        self
+    }
+
+    /// Sets what becomes of the words that literally start with the
+    /// continuing subword prefix, e.g. markdown headings like `## Heading` or
+    /// identifiers like `##define`.
+    ///
+    /// By default ([`LiteralMode::Text`]), the prefix is text like any other
+    /// (see [`leading_prefix_as_follower`](Self::leading_prefix_as_follower)).
+    /// With [`LiteralMode::Unk`], such words become unknown, so their tokens
+    /// can't be mistaken for a continuation of the word before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, LiteralMode};
+    ///
+    /// let texts = |mode, leading_prefix_as_follower, text| {
+    ///     let tokenizer = AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .literal_hash_hash(mode)
+    ///         .leading_prefix_as_follower(leading_prefix_as_follower)
+    ///         .build()
+    ///         .unwrap();
+    ///     let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// };
+    /// let text = "## Überschrift";
+    /// assert_eq!("[CLS] # ### Überschrift [SEP]", texts(LiteralMode::Text, false, text));
+    /// assert_eq!("[CLS] # ### Überschrift [SEP]", texts(LiteralMode::Text, true, text));
+    /// assert_eq!("[CLS] [UNK] Überschrift [SEP]", texts(LiteralMode::Unk, false, text));
+    /// assert_eq!("[CLS] [UNK] Überschrift [SEP]", texts(LiteralMode::Unk, true, text));
+    /// let text = "##foo";
+    /// assert_eq!("[CLS] # ### ##fo ##o [SEP]", texts(LiteralMode::Text, false, text));
+    /// assert_eq!("[CLS] ##fo ##o [SEP]", texts(LiteralMode::Text, true, text));
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(LiteralMode::Unk, false, text));
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(LiteralMode::Unk, true, text));
+    /// // only a prefix at the start of a word counts
+    /// assert_eq!("[CLS] f ##oo ### ### [SEP]", texts(LiteralMode::Unk, false, "foo##"));
+    /// ```
+    pub fn literal_hash_hash(mut self, mode: LiteralMode) -> Self {
+        self.options.literal_hash_hash = mode;
+        self
+    }
+
+    /// Sets whether words are split at punctuation and around CJK
+    /// ideographs, as BERT's basic tokenizer does. Each punctuation character
+    /// and each ideograph becomes a word of its own. By default, words are
+    /// only split at whitespace.
+    ///
+    /// A hyphen before a line break stays with its word, so
+    /// [`rejoin_hyphenation`] still applies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .split_punctuation(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let text = "Ich esse Steak.";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 4813, 4], &ids[..]);
+    /// assert_eq!("[CLS] Ich es ##se Ste ##ak . [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..3, 4..6, 6..8, 9..12, 12..14, 14..15, 15..15], &ranges[..]);
+    ///
+    /// let mut words = Vec::new();
+    /// let text = "(Wer?) 東京,ja";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    /// let words: Vec<_> = words
+    ///     .iter()
+    ///     .map(|w| &text[ranges[w.start].start..ranges[w.end - 1].end])
+    ///     .collect();
+    /// assert_eq!(&["(", "Wer", "?", ")", "東", "京", ",", "ja"], &words[..]);
+    /// ```
+    ///
+    /// [`rejoin_hyphenation`]: AlephAlphaTokenizerBuilder::rejoin_hyphenation
+    pub fn split_punctuation(mut self, split_punctuation: bool) -> Self {
+        self.options.split_punctuation = split_punctuation;
+        self
+    }
+
+    /// Sets whether words the vocabulary doesn't cover become one byte token
+    /// per byte instead of a single `[UNK]`, as in Llama-style vocabularies.
+    ///
+    /// The byte tokens are the vocabulary's `<0x00>` to `<0xFF>` tokens;
+    /// [`build`] fails with [`TokenizerError::MissingByteToken`] if any of
+    /// them is missing. Each byte token has the range of its byte, so the
+    /// ranges of non-ASCII characters are split, too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let mut vocab = "[UNK]\n[CLS]\n[SEP]\nSuper\n##man\n".to_string();
+    /// for byte in 0..=255u8 {
+    ///     vocab += &format!("<0x{:02X}>\n", byte);
+    /// }
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .byte_fallback(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(tokenizer.options().byte_fallback);
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Superman Jö", &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] Super ##man <0x4A> <0xC3> <0xB6> [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(&[0..0, 0..5, 5..8, 9..10, 10..11, 11..12, 12..12], &ranges[..]);
+    /// assert_eq!(5 + 0x4A, ids[3]);
+    ///
+    /// // counted in chars, the second byte of `ö` is empty
+    /// tokenizer.tokens_into_chars("Superman Jö", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[0..0, 0..5, 5..8, 9..10, 10..11, 11..11, 11..11], &ranges[..]);
+    ///
+    /// // without byte fallback, the word is unknown
+    /// let unk = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// unk.tokens_into("Superman Jö", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 3, 4, 0, 2], &ids[..]);
+    ///
+    /// // vocabularies without byte tokens are rejected right away
+    /// let error = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .byte_fallback(true)
+    ///     .build()
+    ///     .err()
+    ///     .unwrap();
+    /// assert!(matches!(error, TokenizerError::MissingByteToken(ref token) if token == "<0x00>"));
+    /// ```
+    ///
+    /// [`build`]: AlephAlphaTokenizerBuilder::build
+    pub fn byte_fallback(mut self, byte_fallback: bool) -> Self {
+        self.options.byte_fallback = byte_fallback;
+        self
+    }
+
+    /// Sets how words are split into tokens. The default,
+    /// [`SegmentationMode::Greedy`], matches WordPiece, which takes the
+    /// longest matching token at each step and gives up on the word if the
+    /// rest then cannot be matched, even if shorter tokens would have covered
+    /// it. [`SegmentationMode::Backtracking`] tries those, too, so a word
+    /// only becomes `[UNK]` if no combination of tokens covers it. Words
+    /// that greedy matching covers keep their tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode};
+    ///
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\na\nab\nabc\n##b\n##bc\n##cd\n";
+    /// let backtracking = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .segmentation_mode(SegmentationMode::Backtracking)
+    ///     .build()
+    ///     .unwrap();
+    /// let greedy = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// assert_eq!(SegmentationMode::Greedy, greedy.options().segmentation_mode);
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// // after `abc`, nothing matches `d`
+    /// greedy.tokens_into("abcd abc", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] abc [SEP]", greedy.texts_of(&ids).join(" "));
+    /// // both `a ##b ##cd` and `ab ##cd` cover it, and the latter is shorter
+    /// backtracking.tokens_into("abcd abc", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] ab ##cd abc [SEP]", backtracking.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..2, 2..4, 5..8, 8..8], &ranges[..]);
+    /// // nothing covers `e`
+    /// backtracking.tokens_into("abce", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", backtracking.texts_of(&ids).join(" "));
+    ///
+    /// // a real vocabulary covers more words, and those it covered already
+    /// // keep their tokens
+    /// let greedy = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let backtracking = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .segmentation_mode(SegmentationMode::Backtracking)
+    ///     .build()
+    ///     .unwrap();
+    /// let text = "Ein Junge im Kindergarten spielt mit dem Ball. Grüße ☃ Supercalifragilistic";
+    /// let (mut greedy_ids, mut greedy_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// greedy.tokens_into(text, &mut greedy_ids, &mut greedy_ranges, None);
+    /// backtracking.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!((&greedy_ids, &greedy_ranges), (&ids, &ranges));
+    /// ```
+    pub fn segmentation_mode(mut self, segmentation_mode: SegmentationMode) -> Self {
+        self.options.segmentation_mode = segmentation_mode;
+        self
+    }
+
+    /// Sets what becomes of the words the vocabulary doesn't cover. By
+    /// default, [`UnkMode::WholeWord`], such a word becomes a single `[UNK]`,
+    /// so a stray emoji loses the rest of its word, too. With
+    /// [`UnkMode::PerChar`], the word keeps the tokens that match, and each
+    /// character where none matches becomes an `[UNK]` with the range of just
+    /// that character (or its byte tokens with [`byte_fallback`]). The tokens
+    /// after such a character continue the word, so they are followers.
+    ///
+    /// Words that are longer than [`max_chars_per_word`], and words in a
+    /// vocabulary without followers, still become a single `[UNK]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, UnkMode};
+    ///
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\nGarten\n##haus\nhaus\n##!\n";
+    /// let per_char = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .unk_mode(UnkMode::PerChar)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// per_char.tokens_into("Garten🦀haus", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Garten [UNK] ##haus [SEP]", per_char.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..6, 6..10, 10..14, 14..14], &ranges[..]);
+    ///
+    /// // each character that matches nothing is unknown, even at the start
+    /// per_char.tokens_into("🦀🦀haus! Gartenx", &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] [UNK] [UNK] ##haus ##! Garten [UNK] [SEP]",
+    ///     per_char.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(&[0..0, 0..4, 4..8, 8..12, 12..13, 14..20, 20..21, 21..21], &ranges[..]);
+    ///
+    /// // by default, the whole word is unknown
+    /// let whole_word = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// assert_eq!(UnkMode::WholeWord, whole_word.options().unk_mode);
+    /// whole_word.tokens_into("Garten🦀haus haus", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] haus [SEP]", whole_word.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..14, 15..19, 19..19], &ranges[..]);
+    ///
+    /// // the words the vocabulary covers don't change
+    /// let per_char = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .unk_mode(UnkMode::PerChar)
+    ///     .build()
+    ///     .unwrap();
+    /// let whole_word = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let text = "Ein Junge im Kindergarten spielt mit dem Ball.";
+    /// let (mut expected_ids, mut expected_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// whole_word.tokens_into(text, &mut expected_ids, &mut expected_ranges, None);
+    /// per_char.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!((&expected_ids, &expected_ranges), (&ids, &ranges));
+    /// ```
+    ///
+    /// [`byte_fallback`]: AlephAlphaTokenizerBuilder::byte_fallback
+    /// [`max_chars_per_word`]: AlephAlphaTokenizerBuilder::max_chars_per_word
+    pub fn unk_mode(mut self, unk_mode: UnkMode) -> Self {
+        self.options.unk_mode = unk_mode;
+        self
+    }
+
+    /// Sets whether a word the vocabulary doesn't cover is split at hyphens
+    /// and slashes before it becomes unknown (`false` by default), so
+    /// compounds like `FSME-Impfstoff` or `und/oder` keep the tokens of
+    /// their parts. This is
+    /// [`fallback_separators`](Self::fallback_separators) with `-` and `/`.
+    pub fn split_fallback(self, split: bool) -> Self {
+        let separators: &[char] = if split { &['-', '/'] } else { &[] };
+        self.fallback_separators(separators)
+    }
+
+    /// Sets the characters at which a word the vocabulary doesn't cover is
+    /// split (none by default).
+    ///
+    /// Each part between the separators is then matched as a word of its
+    /// own, and each separator as a token that starts a word, or as set by
+    /// [`unknown_separator`](Self::unknown_separator) if there is no such
+    /// token. Only if a part isn't covered either does the whole word become
+    /// unknown, as set by [`unk_mode`](Self::unk_mode). The words the
+    /// vocabulary covers are tokenized as they are, separators and all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, UnknownSeparator};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// // without `##-`, hyphenated words aren't covered
+    /// let hyphen = tokenizer.id_of("##-").unwrap();
+    /// let builder = || {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .suppressed_ids(&[hyphen])
+    /// };
+    /// let texts = |tokenizer: &AlephAlphaTokenizer, text| {
+    ///     let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     let texts = tokenizer.texts_of(&ids).join(" ");
+    ///     (texts, ranges)
+    /// };
+    /// let text = "die Folgen der Borreliose-Erkrankung";
+    /// let unsplit = builder().build().unwrap();
+    /// assert_eq!("[CLS] die Folgen der [UNK] [SEP]", texts(&unsplit, text).0);
+    ///
+    /// let split = builder().split_fallback(true).build().unwrap();
+    /// let (tokens, ranges) = texts(&split, text);
+    /// assert_eq!("[CLS] die Folgen der Bor ##rel ##iose - Erkrankung [SEP]", tokens);
+    /// let parts: Vec<_> = ranges[4..9].iter().map(|range| &text[range.clone()]).collect();
+    /// assert_eq!(vec!["Bor", "rel", "iose", "-", "Erkrankung"], parts);
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// split.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(Ok(()), split.validate_ranges(text, &ids, &ranges));
+    /// // the words the vocabulary covers stay the same
+    /// let text = "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?";
+    /// let tokenizer_split = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .split_fallback(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(texts(&tokenizer, text), texts(&tokenizer_split, text));
+    ///
+    /// // a part that isn't covered makes the whole word unknown
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(&split, "Borreliose-☃").0);
+    /// assert_eq!("[CLS] [UNK] [SEP]", texts(&split, "Borreliose-Super☃").0);
+    ///
+    /// // separators without a token become `[UNK]`, or are dropped
+    /// let slash = tokenizer.id_of("/").unwrap();
+    /// let texts_without_slash = |unknown| {
+    ///     let tokenizer = AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .suppressed_ids(&[slash, tokenizer.id_of("##/").unwrap()])
+    ///         .fallback_separators(&['/'])
+    ///         .unknown_separator(unknown)
+    ///         .build()
+    ///         .unwrap();
+    ///     texts(&tokenizer, "Clans/Clankriminalität")
+    /// };
+    /// let (tokens, ranges) = texts_without_slash(UnknownSeparator::Unk);
+    /// assert_eq!("[CLS] Cla ##ns [UNK] Cla ##n ##k ##riminal ##ität [SEP]", tokens);
+    /// assert_eq!(&[0..0, 0..3, 3..5, 5..6, 6..9, 9..10, 10..11, 11..18, 18..23, 23..23], &ranges[..]);
+    /// let (tokens, ranges) = texts_without_slash(UnknownSeparator::Drop);
+    /// assert_eq!("[CLS] Cla ##ns Cla ##n ##k ##riminal ##ität [SEP]", tokens);
+    /// assert_eq!(&[0..0, 0..3, 3..5, 6..9, 9..10, 10..11, 11..18, 18..23, 23..23], &ranges[..]);
+    /// ```
+    pub fn fallback_separators(mut self, separators: &[char]) -> Self {
+        self.options.fallback_separators = separators.to_vec();
+        self
+    }
+
+    /// Sets what becomes of the
+    /// [`fallback_separators`](Self::fallback_separators) without a token
+    /// ([`UnknownSeparator::Unk`] by default).
+    pub fn unknown_separator(mut self, unknown: UnknownSeparator) -> Self {
+        self.options.unknown_separator = unknown;
+        self
+    }
+
+    /// Sets whether accents are removed from words before matching them
+    /// against the vocabulary, as BERT's `strip_accents` does, e.g. for
+    /// vocabularies built from text without them. Unlike there, the
+    /// diaeresis stays, so `é` becomes `e`, but `ü` stays `ü`.
+    ///
+    /// The token ranges still refer to the original text, both for composed
+    /// characters and for those followed by combining accents. This is off
+    /// by default, and only available with the `unicode-normalization`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .strip_accents(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut plain_ids, mut plain_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Cafe", &mut plain_ids, &mut plain_ranges, None);
+    /// assert_eq!("[CLS] Caf ##e [SEP]", tokenizer.texts_of(&plain_ids).join(" "));
+    ///
+    /// // `é` takes two bytes
+    /// tokenizer.tokens_into("Café", &mut ids, &mut ranges, None);
+    /// assert_eq!(plain_ids, ids);
+    /// assert_eq!(&[0..0, 0..3, 3..5, 5..5], &ranges[..]);
+    /// // and so does the combining acute accent after the `e`
+    /// tokenizer.tokens_into("Cafe\u{301} Café", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Caf ##e Caf ##e [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..3, 3..6, 7..10, 10..12, 12..12], &ranges[..]);
+    ///
+    /// // umlauts stay, and without stripping, the vocabulary lacks `##é`
+    /// tokenizer.tokens_into("Über", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Über [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// let plain = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// plain.tokens_into("Café", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", plain.texts_of(&ids).join(" "));
+    /// ```
+    #[cfg(feature = "unicode-normalization")]
+    pub fn strip_accents(mut self, strip_accents: bool) -> Self {
+        self.options.strip_accents = strip_accents;
+        self
+    }
+
+    /// Sets the Unicode normalization form words are brought into before
+    /// matching them against the vocabulary, so e.g. an `e` followed by a
+    /// combining acute accent matches the same tokens as `é`.
+    ///
+    /// The token ranges still refer to the original text. There is no
+    /// normalization by default, and this is only available with the
+    /// `unicode-normalization` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, UnicodeNormalization};
+    ///
+    /// let vocab = "[UNK]\n[CLS]\n[SEP]\nCafé\nfinden\n##s\n";
+    /// let nfc = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .normalize(Some(UnicodeNormalization::Nfc))
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let decomposed = "Cafe\u{301}s Café";
+    /// nfc.tokens_into(decomposed, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Café ##s Café [SEP]", nfc.texts_of(&ids).join(" "));
+    /// assert_eq!(
+    ///     vec!["", "Cafe\u{301}", "s", "Café", ""],
+    ///     ranges.iter().map(|r| &decomposed[r.clone()]).collect::<Vec<_>>()
+    /// );
+    /// // NFC keeps the `ﬁ` ligature, NFKC splits it
+    /// nfc.tokens_into("ﬁnden", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", nfc.texts_of(&ids).join(" "));
+    /// let nfkc = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader(vocab.as_bytes())
+    ///     .normalize(Some(UnicodeNormalization::Nfkc))
+    ///     .build()
+    ///     .unwrap();
+    /// nfkc.tokens_into("ﬁnden", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] finden [SEP]", nfkc.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..7, 7..7], &ranges[..]);
+    /// ```
+    #[cfg(feature = "unicode-normalization")]
+    pub fn normalize(mut self, normalize: Option<UnicodeNormalization>) -> Self {
+        self.options.normalize = normalize;
+        self
+    }
+
+    /// Sets whether invisible characters are cleaned from the text, as text
+    /// scraped from the web often contains them between the letters of a
+    /// word, which then becomes `[UNK]`.
+    ///
+    /// By default, these are removed from the words before matching them:
+    ///
+    /// * the soft hyphen (U+00AD), the zero-width space (U+200B), the
+    ///   zero-width (non-)joiner (U+200C, U+200D), the word joiner
+    ///   (U+2060), the byte order mark (U+FEFF) and the Mongolian vowel
+    ///   separator (U+180E)
+    /// * the BiDi controls (U+061C, U+200E, U+200F, U+202A to U+202E and
+    ///   U+2066 to U+2069)
+    ///
+    /// and words are split at the control characters that aren't
+    /// whitespace (U+0000 to U+0008, U+000E to U+001F and U+007F to U+009F
+    /// except U+0085), like at whitespace. [`clean_char`] changes this set.
+    ///
+    /// This happens while splitting the text into words, so the token
+    /// ranges refer to the original text: removed characters at the start
+    /// or end of a token are in no token range, like whitespace. This is
+    /// off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .clean_text(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut clean_ids, mut clean_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Beispiel", &mut clean_ids, &mut clean_ranges, None);
+    /// // the zero-width space takes three bytes
+    /// let text = "Bei\u{200B}spiel";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(clean_ids, ids);
+    /// assert_eq!(&[0..0, 0..11, 11..11], &ranges[..]);
+    ///
+    /// // at the ends of words and tokens, removed characters are in no range
+    /// let text = "\u{FEFF}\u{200E}Beispiel\u{200B} Super\u{200B}\u{200B}man\u{200B}";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Beispiel Super ##man [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(
+    ///     vec!["Beispiel", "Super", "man"],
+    ///     ranges[1..4].iter().map(|r| &text[r.clone()]).collect::<Vec<_>>()
+    /// );
+    /// assert_eq!(Ok(()), tokenizer.validate_ranges(text, &ids, &ranges));
+    ///
+    /// // control characters split words
+    /// tokenizer.tokens_into("Bei\u{1}spiel", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Bei spiel [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..3, 4..9, 9..9], &ranges[..]);
+    ///
+    /// // without cleaning, the zero-width space defeats the match
+    /// let plain = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// plain.tokens_into("Bei\u{200B}spiel", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", plain.texts_of(&ids).join(" "));
+    /// ```
+    ///
+    /// [`clean_char`]: AlephAlphaTokenizerBuilder::clean_char
+    pub fn clean_text(mut self, clean_text: bool) -> Self {
+        self.options.clean_text = clean_text;
+        self
+    }
+
+    /// Sets what [`clean_text`] does with a character, overriding the
+    /// default set and earlier calls for it. This has no effect unless
+    /// `clean_text` is on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, CleanAction};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .clean_text(true)
+    ///     .clean_char('_', CleanAction::Remove)
+    ///     .clean_char('|', CleanAction::Separate)
+    ///     .clean_char('\u{200B}', CleanAction::Keep)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Bei_spiel|Bei|spiel", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Beispiel Bei spiel [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..9, 10..13, 14..19, 19..19], &ranges[..]);
+    /// tokenizer.tokens_into("Bei\u{200B}spiel", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [UNK] [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// ```
+    ///
+    /// [`clean_text`]: AlephAlphaTokenizerBuilder::clean_text
+    pub fn clean_char(mut self, c: char, action: CleanAction) -> Self {
+        self.options.clean_chars.push((c, action));
+        self
+    }
+
+    /// Sets whether a whitespace-separated word that is the text of a special
+    /// token (including those added with
+    /// [`add_special_tokens`](AlephAlphaTokenizer::add_special_tokens))
+    /// becomes that token. This is off by default.
+    ///
+    /// The special tokens are among the tokens that start a word anyway, so
+    /// even without this, `[SEP]` in a text usually becomes the `[SEP]`
+    /// token. But the options that change words before matching them don't
+    /// spare the special tokens: [`split_punctuation`] splits off the
+    /// brackets, and [`lowercase`] turns `[MASK]` into `[mask]`. With this
+    /// on, such words are matched as they are. Only whole words count, so
+    /// `x[SEP]` is tokenized as before.
+    ///
+    /// Texts from users can then contain special tokens that mean something
+    /// to the model, so only turn this on for trusted texts like templates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let builder = || AlephAlphaTokenizer::builder().vocab_path("vocab.txt").split_punctuation(true);
+    /// let tokenizer = builder().parse_special_tokens(true).build().unwrap();
+    /// let text = "Frage: Wer? [SEP] Antwort: [MASK] x[SEP]";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] Frage : Wer [UNK] [SEP] Antwort : [MASK] x [ S ##EP ] [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!((4, 12..17), (ids[5], ranges[5].clone()));
+    /// assert_eq!((5, 27..33), (ids[8], ranges[8].clone()));
+    ///
+    /// // by default, the brackets are split off
+    /// let plain = builder().build().unwrap();
+    /// let (mut plain_ids, mut plain_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// plain.tokens_into(text, &mut plain_ids, &mut plain_ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] Frage : Wer [UNK] [ S ##EP ] Antwort : [ M ##AS ##K ] x [ S ##EP ] [SEP]",
+    ///     plain.texts_of(&plain_ids).join(" ")
+    /// );
+    /// assert_ne!(plain.fingerprint(), tokenizer.fingerprint());
+    ///
+    /// // the special tokens aren't lowercased
+    /// let lowercase = builder().lowercase(true).parse_special_tokens(true).build().unwrap();
+    /// lowercase.tokens_into("[MASK]", &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] [MASK] [SEP]", lowercase.texts_of(&ids).join(" "));
+    ///
+    /// // without options that change words, the tokens stay the same
+    /// let plain = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let parsing = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .parse_special_tokens(true)
+    ///     .build()
+    ///     .unwrap();
+    /// plain.tokens_into(text, &mut plain_ids, &mut plain_ranges, None);
+    /// parsing.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!((&plain_ids, &plain_ranges), (&ids, &ranges));
+    /// ```
+    ///
+    /// [`split_punctuation`]: AlephAlphaTokenizerBuilder::split_punctuation
+    /// [`lowercase`]: AlephAlphaTokenizerBuilder::lowercase
+    pub fn parse_special_tokens(mut self, parse_special_tokens: bool) -> Self {
+        self.options.parse_special_tokens = parse_special_tokens;
+        self
+    }
+
+    /// Sets which characters separate words, by default those of
+    /// `char::is_whitespace` (see [`WhitespaceMode`]).
+    ///
+    /// With [`WhitespaceMode::AsciiOnly`], e.g. a no-break space is part of
+    /// its word, as in other tokenizers that only split at ASCII whitespace.
+    /// The ranges of words then cover such characters, and rejoining
+    /// hyphenated words, incremental and streaming tokenization respect the
+    /// mode, too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizerBuilder, WhitespaceMode};
+    ///
+    /// let tokenizer = |whitespace| {
+    ///     AlephAlphaTokenizerBuilder::new()
+    ///         .vocab_path("vocab.txt")
+    ///         .whitespace(whitespace)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// let word_ranges = |whitespace, text| {
+    ///     let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    ///     tokenizer(whitespace).tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    ///     let words: Vec<_> = words
+    ///         .iter()
+    ///         .map(|w| ranges[w.start].start..ranges[w.end - 1].end)
+    ///         .collect();
+    ///     words
+    /// };
+    /// let text = "100\u{a0}km  Weg";
+    /// assert_eq!(vec![0..3, 5..7, 9..12], word_ranges(WhitespaceMode::Unicode, text));
+    /// assert_eq!(vec![0..7, 9..12], word_ranges(WhitespaceMode::AsciiOnly, text));
+    /// let no_break = WhitespaceMode::Custom(vec!['\u{a0}']);
+    /// assert_eq!(vec![0..3, 5..12], word_ranges(no_break, text));
+    /// let zero_width = WhitespaceMode::Custom(vec![' ', '\u{200b}']);
+    /// assert_eq!(vec![0..3, 6..10], word_ranges(zero_width, "Ich\u{200b}esse"));
+    ///
+    /// // the default is as before
+    /// let default = AlephAlphaTokenizerBuilder::new().vocab_path("vocab.txt").build().unwrap();
+    /// assert!(default.is_compatible_with(&tokenizer(WhitespaceMode::Unicode)));
+    /// assert!(!default.is_compatible_with(&tokenizer(WhitespaceMode::AsciiOnly)));
+    /// ```
+    pub fn whitespace(mut self, whitespace: WhitespaceMode) -> Self {
+        self.options.whitespace = whitespace;
+        self
+    }
+
+    /// Sets the options to match huggingface's WordPiece on any input: words
+    /// of more than 100 characters become a single `[UNK]` token (see
+    /// [`max_chars_per_word`]), and [`leading_prefix_as_follower`] is on.
+    /// Turning it off resets both to their defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .wordpiece_compatibility(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(Some(100), tokenizer.options().max_chars_per_word);
+    ///
+    /// // 100 characters are fine
+    /// let text = format!("Ich {} Steak", "esse".repeat(25));
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(&text, &mut ids, &mut ranges, None);
+    /// assert_eq!(31, ids.len());
+    ///
+    /// let text = format!("Ich {} Steak", "Steak".repeat(30));
+    /// tokenizer.tokens_into(&text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[3, 1671, 2, 601, 464, 4], &ids[..]);
+    /// assert_eq!(4..154, ranges[2]);
+    /// ```
+    ///
+    /// [`max_chars_per_word`]: AlephAlphaTokenizerBuilder::max_chars_per_word
+    /// [`leading_prefix_as_follower`]: AlephAlphaTokenizerBuilder::leading_prefix_as_follower
+    pub fn wordpiece_compatibility(self, compatible: bool) -> Self {
+        self.max_chars_per_word(if compatible { Some(100) } else { None })
+            .leading_prefix_as_follower(compatible)
+    }
+
+    /// Sets whether to build the indexes behind
+    /// [`id_of`](AlephAlphaTokenizer::id_of),
+    /// [`is_special`](AlephAlphaTokenizer::is_special),
+    /// [`is_starter`](AlephAlphaTokenizer::is_starter) and
+    /// [`is_follower`](AlephAlphaTokenizer::is_follower) right away instead of
+    /// on first use (`false` by default), so latency-sensitive services
+    /// don't pay for them on their first request.
+    pub fn eager_indexes(mut self, eager: bool) -> Self {
+        self.eager_indexes = eager;
+        self
+    }
+
+    /// Sets whether to build the FSTs of the starters and followers on two
+    /// threads (`false` by default), which loads large vocabularies faster.
+    /// The tokenizer is the same either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let serial = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let parallel = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .parallel_build(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(serial.fingerprint(), parallel.fingerprint());
+    /// for text in &["Ich esse Steak.", "Wie definiert die Bundesregierung Clans und Clankriminalität?"] {
+    ///     assert_eq!(serial.encode::<u32>(text), parallel.encode::<u32>(text));
+    /// }
+    ///
+    /// // and so are the errors
+    /// let vocab = "[UNK]\nfoo\n##bar\nfoo\n##bar\n";
+    /// let error = |parallel| {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .parallel_build(parallel)
+    ///         .build()
+    ///         .err()
+    ///         .unwrap()
+    ///         .to_string()
+    /// };
+    /// assert_eq!(error(false), error(true));
+    /// ```
+    pub fn parallel_build(mut self, parallel: bool) -> Self {
+        self.parallel_build = parallel;
+        self
+    }
+
+    /// Sets the ids of tokens that are never emitted (none by default), e.g.
+    /// tokens removed from a pruned embedding matrix or blocked for privacy.
+    ///
+    /// The suppressed tokens are left out of the FSTs, so each word is
+    /// segmented as if the vocabulary lacked them, falling back to other
+    /// tokens or `[UNK]`, and the ranges stay those of the tokens emitted.
+    /// [`id_of`](AlephAlphaTokenizer::id_of) doesn't find them either, but
+    /// their ids keep their texts. Special tokens cannot be suppressed, and
+    /// neither can tokens from the FSTs given to `fst_files`, which are used
+    /// as they are, so building fails in both cases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let suppressing = |tokens: &[&str]| {
+    ///     let ids: Vec<u64> = tokens.iter().map(|token| tokenizer.id_of(token).unwrap()).collect();
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .suppressed_ids(&ids)
+    ///         .build()
+    /// };
+    /// let texts = |tokenizer: &AlephAlphaTokenizer, text| {
+    ///     let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     let texts = tokenizer.texts_of(&ids).join(" ");
+    ///     (texts, ranges)
+    /// };
+    /// let text = "Der Hund spielt im Garten.";
+    /// assert_eq!("[CLS] Der Hund spielt im Garten ##. [SEP]", texts(&tokenizer, text).0);
+    ///
+    /// // without `Garten`, the word is segmented with other tokens …
+    /// let suppressed = suppressing(&["Garten"]).unwrap();
+    /// let (tokens, ranges) = texts(&suppressed, text);
+    /// assert_eq!("[CLS] Der Hund spielt im Gar ##ten ##. [SEP]", tokens);
+    /// assert_eq!(&[0..0, 0..3, 4..8, 9..15, 16..18, 19..22, 22..25, 25..26, 26..26], &ranges[..]);
+    /// assert_eq!(None, suppressed.id_of("Garten"));
+    /// assert_eq!("Garten", suppressed.text_of(5383u64));
+    /// assert_eq!(vec![5383], suppressed.suppressed_ids());
+    /// assert_ne!(tokenizer.fingerprint(), suppressed.fingerprint());
+    /// assert!(tokenizer.suppressed_ids().is_empty());
+    ///
+    /// // … or, if nothing else ends it, becomes `[UNK]`
+    /// let suppressed = suppressing(&["##.", "Hund", "##."]).unwrap();
+    /// let (tokens, ranges) = texts(&suppressed, text);
+    /// assert_eq!("[CLS] Der Hu ##nd spielt im [UNK] [SEP]", tokens);
+    /// assert_eq!(&[0..0, 0..3, 4..6, 6..8, 9..15, 16..18, 19..26, 26..26], &ranges[..]);
+    /// // the ids are kept sorted, without repetitions
+    /// let mut ids = vec![tokenizer.id_of("Hund").unwrap(), tokenizer.id_of("##.").unwrap()];
+    /// ids.sort_unstable();
+    /// assert_eq!(ids, suppressed.suppressed_ids());
+    ///
+    /// let error = suppressing(&["[CLS]"]).err().unwrap();
+    /// assert!(matches!(error, TokenizerError::SuppressedSpecialToken(ref token) if token == "[CLS]"));
+    /// ```
+    pub fn suppressed_ids(mut self, ids: &[u64]) -> Self {
+        self.suppressed_ids = ids.to_vec();
+        self
+    }
+
+    /// Sets what words are matched against ([`MatcherBackend::Fst`] by
+    /// default). With the `datrie` feature, [`MatcherBackend::DoubleArray`]
+    /// trades memory for throughput: its tries are built from the FSTs when
+    /// the tokenizer is built (and again after adding tokens), and find each
+    /// byte of a token in constant time instead of searching the
+    /// transitions of an FST node. For `vocab.txt`, the tries take about
+    /// 0.9 MB (the FSTs 0.2 MB), and words are tokenized about three times as
+    /// fast, see the `Tokenizer` benchmark.
+    ///
+    /// The tokens are the same with every backend. Only tokenizing words
+    /// uses the backend; e.g. [`match_starter`](AlephAlphaTokenizer::match_starter)
+    /// always matches against the FSTs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, MatcherBackend};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(MatcherBackend::Fst, tokenizer.matcher_backend());
+    ///# #[cfg(feature = "datrie")] {
+    /// use aleph_alpha_tokenizer::{SegmentationMode, UnkMode};
+    ///
+    /// let build = |backend, segmentation, unk| {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .matcher_backend(backend)
+    ///         .segmentation_mode(segmentation)
+    ///         .unk_mode(unk)
+    ///         .wordpiece_compatibility(true)
+    ///         .build()
+    ///         .unwrap()
+    /// };
+    /// // the sentences of the benchmark, and random ASCII words
+    /// let mut texts: Vec<String> = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "## Überschrift ☃ [CLS] Supercalifragilistic",
+    /// ]
+    /// .iter()
+    /// .map(|text| text.to_string())
+    /// .collect();
+    /// let mut seed = 0x2545_f491_4f6c_dd1du64;
+    /// for _ in 0..200 {
+    ///     let text = (0..40)
+    ///         .map(|_| {
+    ///             seed ^= seed << 13;
+    ///             seed ^= seed >> 7;
+    ///             seed ^= seed << 17;
+    ///             (b' ' + (seed % 95) as u8) as char
+    ///         })
+    ///         .collect();
+    ///     texts.push(text);
+    /// }
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut trie_ids, mut trie_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for &segmentation in &[SegmentationMode::Greedy, SegmentationMode::Backtracking] {
+    ///     for &unk in &[UnkMode::WholeWord, UnkMode::PerChar] {
+    ///         let fst = build(MatcherBackend::Fst, segmentation, unk);
+    ///         let trie = build(MatcherBackend::DoubleArray, segmentation, unk);
+    ///         assert_eq!(MatcherBackend::DoubleArray, trie.matcher_backend());
+    ///         for text in &texts {
+    ///             fst.tokens_into(text, &mut ids, &mut ranges, None);
+    ///             trie.tokens_into(text, &mut trie_ids, &mut trie_ranges, None);
+    ///             assert_eq!((&ids, &ranges), (&trie_ids, &trie_ranges), "{:?}", text);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // the tries follow the added tokens
+    /// let mut trie = build(MatcherBackend::DoubleArray, SegmentationMode::Greedy, UnkMode::WholeWord);
+    /// trie.add_tokens(&["Supercalifragilistic", "##expialigetisch"]).unwrap();
+    /// trie.tokens_into("Supercalifragilisticexpialigetisch", &mut trie_ids, &mut trie_ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] Supercalifragilistic ##expialigetisch [SEP]",
+    ///     trie.texts_of(&trie_ids).join(" ")
+    /// );
+    ///# }
+    /// ```
+    pub fn matcher_backend(mut self, backend: MatcherBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Sets whether to split ASCII texts into words byte by byte (`true` by
+    /// default) instead of char by char. Both give the same tokens, so this
+    /// is only for testing and benchmarking the general path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// // a simple pseudo-random generator to build the texts
+    /// let mut seed = 42u64;
+    /// let mut random = |n: usize| {
+    ///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     (seed >> 33) as usize % n
+    /// };
+    /// let pieces = [
+    ///     " ", "  ", "\t", "\n", "\x0B", "\x0C", "\r", "\x1C", "-", "-\n", ".", ",", "?", "$",
+    ///     "'", "a", "Z", "Ich", "esse", "Steak", "Super", "man", "Kranken", "haus", "FSME",
+    ///     "Supercalifragilistic", "##", "[UNK]", "[CLS]",
+    /// ];
+    /// for builder in vec![
+    ///     AlephAlphaTokenizer::builder(),
+    ///     AlephAlphaTokenizer::builder().split_punctuation(true),
+    ///     AlephAlphaTokenizer::builder().rejoin_hyphenation(true).lowercase(true),
+    ///     AlephAlphaTokenizer::builder()
+    ///         .split_punctuation(true)
+    ///         .rejoin_hyphenation(true)
+    ///         .max_chars_per_word(Some(6)),
+    /// ] {
+    ///     let fast = builder.vocab_path("vocab.txt").build().unwrap();
+    ///     let slow = AlephAlphaTokenizer::builder()
+    ///         .with_options(fast.options().clone())
+    ///         .vocab_path("vocab.txt")
+    ///         .ascii_fast_path(false)
+    ///         .build()
+    ///         .unwrap();
+    ///     let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    ///     let (mut slow_ids, mut slow_ranges, mut slow_words) = (Vec::new(), Vec::new(), Vec::new());
+    ///     for _ in 0..300 {
+    ///         let text: String = (0..random(40)).map(|_| pieces[random(pieces.len())]).collect();
+    ///         assert!(text.is_ascii());
+    ///         fast.tokens_into(&text, &mut ids, &mut ranges, Some(&mut words));
+    ///         slow.tokens_into(&text, &mut slow_ids, &mut slow_ranges, Some(&mut slow_words));
+    ///         assert_eq!((&slow_ids, &slow_ranges, &slow_words), (&ids, &ranges, &words), "{:?}", text);
+    ///         fast.tokens_into_chars(&text, &mut ids, &mut ranges, None);
+    ///         slow.tokens_into_chars(&text, &mut slow_ids, &mut slow_ranges, None);
+    ///         assert_eq!((&slow_ids, &slow_ranges), (&ids, &ranges), "{:?}", text);
+    ///     }
+    /// }
+    /// ```
+    #[doc(hidden)]
+    pub fn ascii_fast_path(mut self, enabled: bool) -> Self {
+        self.ascii_fast_path = enabled;
+        self
+    }
+
+    /// Sets all [`TokenizerOptions`] at once, e.g. as read from a
+    /// configuration file.
+    pub fn with_options(mut self, options: TokenizerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Gets the options configured so far.
+    pub fn options(&self) -> &TokenizerOptions {
+        &self.options
+    }
+
+    // parse the tokens with the special tokens and prefix configured so far
+    fn tokenizer_from(&self, tokens: TokenTexts) -> Result<AlephAlphaTokenizer, TokenizerError> {
+        let additional: Vec<&str> = self
+            .additional_special_tokens
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let special = SpecialTokens {
+            unk: &self.unk_token,
+            cls: self.cls_token.as_deref(),
+            sep: self.sep_token.as_deref(),
+            pad: self.pad_token.as_deref(),
+            mask: self.mask_token.as_deref(),
+            bracketed: self.bracketed_special_tokens,
+            additional: &additional,
+        };
+        #[cfg(feature = "mmap")]
+        if let Some((starters, followers)) = &self.fst_files {
+            if !self.suppressed_ids.is_empty() {
+                return Err(TokenizerError::SuppressedWithFstFiles);
+            }
+            // safety: the caller of `fst_files` guarantees the files stay unchanged
+            let tokenizer = AlephAlphaTokenizer::from_token_lines_with(
+                tokens,
+                &special,
+                &self.markers,
+                |_, _| unsafe {
+                    Ok((
+                        crate::mmap::map_fst(starters)?,
+                        crate::mmap::map_fst(followers)?,
+                    ))
+                },
+            )?;
+            tokenizer.verify_internal_consistency()?;
+            return Ok(tokenizer);
+        }
+        AlephAlphaTokenizer::from_token_lines(
+            tokens,
+            &special,
+            &self.markers,
+            self.parallel_build,
+            &self.suppressed_ids,
+        )
+    }
+
+    /// Loads the vocabulary and creates the tokenizer.
+    pub fn build(mut self) -> Result<AlephAlphaTokenizer, TokenizerError> {
+        let (tokens, line_nos) = match self.vocab.take() {
+            #[cfg(feature = "std-fs")]
+            Some(Vocab::Path(path)) => {
+                let (reader, size) = open_vocab(&path)?;
+                (read_lines(reader, size)?, None)
+            }
+            Some(Vocab::Lines(lines)) => (lines?, None),
+            #[cfg(feature = "std-fs")]
+            Some(Vocab::IdPath(path)) => {
+                let (reader, size) = open_vocab(&path)?;
+                let (tokens, line_nos) = read_id_lines(reader, size)?;
+                (tokens, Some(line_nos))
+            }
+            Some(Vocab::IdLines(lines)) => {
+                let (tokens, line_nos) = lines?;
+                (tokens, Some(line_nos))
+            }
+            None => return Err(TokenizerError::MissingVocab),
+        };
+        let mut tokenizer = self.tokenizer_from(tokens).map_err(|e| match &line_nos {
+            Some(line_nos) => with_id_lines(e, line_nos),
+            None => e,
+        })?;
+        if let Some(&id) = self
+            .suppressed_ids
+            .iter()
+            .find(|&&id| tokenizer.is_special(id))
+        {
+            let token = tokenizer.text_of(id).to_string();
+            return Err(TokenizerError::SuppressedSpecialToken(token));
+        }
+        // ids beyond the vocabulary have no token to suppress
+        let mut suppressed = self.suppressed_ids;
+        suppressed.retain(|&id| id < tokenizer.tokens.len() as u64);
+        suppressed.sort_unstable();
+        suppressed.dedup();
+        tokenizer.suppressed_ids = suppressed;
+        tokenizer.options = self.options;
+        tokenizer.ascii_fast_path = self.ascii_fast_path;
+        tokenizer.backend = self.backend;
+        if tokenizer.options.byte_fallback {
+            tokenizer.byte_ids = tokenizer.byte_token_ids()?;
+        }
+        tokenizer.calibrate(BUILTIN_SAMPLE);
+        if self.eager_indexes {
+            tokenizer.build_indexes();
+        }
+        // the tries are needed for the first word anyway
+        #[cfg(feature = "datrie")]
+        tokenizer.tries();
+        Ok(tokenizer)
+    }
+}
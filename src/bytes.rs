@@ -0,0 +1,55 @@
+use crate::{AlephAlphaTokenizer, IdSink, RangeSink, TokenID, TokenizerError};
+use std::ops::Range;
+
+// the text of the token for a byte, as in Llama-style vocabularies
+fn byte_token(byte: u8) -> String {
+    format!("<0x{:02X}>", byte)
+}
+
+impl AlephAlphaTokenizer {
+    // looks up the ids of the byte tokens `<0x00>` to `<0xFF>`, in order
+    pub(crate) fn byte_token_ids(&self) -> Result<Vec<u32>, TokenizerError> {
+        (0..=255u8)
+            .map(|byte| {
+                let token = byte_token(byte);
+                self.id_of(&token)
+                    .map(|id| id as u32)
+                    .ok_or(TokenizerError::MissingByteToken(token))
+            })
+            .collect()
+    }
+
+    // the byte of a byte token with byte fallback
+    pub(crate) fn byte_of(&self, id: u64) -> Option<u8> {
+        let token = self.text_of(id);
+        let hex = token.strip_prefix("<0x")?.strip_suffix('>')?;
+        let byte = u8::from_str_radix(hex, 16).ok()?;
+        (self
+            .byte_ids
+            .get(usize::from(byte))
+            .map(|&id| u64::from(id))
+            == Some(id))
+        .then_some(byte)
+    }
+
+    // pushes the tokens of a word that the vocabulary doesn't cover: one
+    // byte token per byte with byte fallback, a single `[UNK]` otherwise
+    pub(crate) fn push_unknown<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) {
+        if self.byte_ids.is_empty() {
+            token_ids.push(T::coerce(u64::from(self.unk_id)));
+            token_ranges.push(range);
+            return;
+        }
+        for (i, &byte) in text.as_bytes()[range.clone()].iter().enumerate() {
+            let pos = range.start + i;
+            token_ids.push(T::coerce(u64::from(self.byte_ids[usize::from(byte)])));
+            token_ranges.push(pos..pos + 1);
+        }
+    }
+}
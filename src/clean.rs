@@ -0,0 +1,63 @@
+//! Removing invisible characters from words, or splitting words at control
+//! characters, see
+//! [`clean_text`](crate::AlephAlphaTokenizerBuilder::clean_text)
+
+use std::ops::Range;
+
+/// What [`clean_text`](crate::AlephAlphaTokenizerBuilder::clean_text) does
+/// with a character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum CleanAction {
+    /// Leave the character out when matching the word, so it is in no token
+    /// range if it is at the start or end of a token
+    Remove,
+    /// Split words at the character, like at whitespace
+    Separate,
+    /// Keep the character as it is, e.g. to exempt one of the default set
+    Keep,
+}
+
+// the default set of characters that are cleaned
+fn default_action(c: char) -> CleanAction {
+    match c {
+        // soft hyphen, Mongolian vowel separator, zero-width space,
+        // (non-)joiner, word joiner and the byte order mark
+        '\u{AD}' | '\u{180E}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' => {
+            CleanAction::Remove
+        }
+        // the BiDi controls
+        '\u{61C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => {
+            CleanAction::Remove
+        }
+        // the control characters that aren't whitespace
+        '\u{0}'..='\u{8}' | '\u{E}'..='\u{1F}' | '\u{7F}'..='\u{84}' | '\u{86}'..='\u{9F}' => {
+            CleanAction::Separate
+        }
+        _ => CleanAction::Keep,
+    }
+}
+
+/// Gets what becomes of the character, where the later of the given
+/// characters override the earlier ones and the default set.
+pub(crate) fn action(chars: &[(char, CleanAction)], c: char) -> CleanAction {
+    chars
+        .iter()
+        .rev()
+        .find(|&&(clean, _)| clean == c)
+        .map_or_else(|| default_action(c), |&(_, action)| action)
+}
+
+/// Narrows the range to leave out the characters to remove at either end.
+pub(crate) fn trim_removed(
+    text: &str,
+    range: Range<usize>,
+    chars: &[(char, CleanAction)],
+) -> Range<usize> {
+    let removed = |c| action(chars, c) == CleanAction::Remove;
+    let span = &text[range.clone()];
+    let start = range.start + (span.len() - span.trim_start_matches(removed).len());
+    let end = range.start + span.trim_end_matches(removed).len();
+    start..end.max(start)
+}
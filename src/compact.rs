@@ -0,0 +1,138 @@
+use crate::{AlephAlphaTokenizer, RangeSink, TokenID};
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The error of
+/// [`tokens_into_offsets`](AlephAlphaTokenizer::tokens_into_offsets) if the
+/// offset type cannot hold the length of the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffsetOverflow {
+    /// the length of the text in bytes
+    pub len: usize,
+}
+
+impl fmt::Display for OffsetOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a text of {} bytes is too long for the offsets",
+            self.len
+        )
+    }
+}
+
+impl Error for OffsetOverflow {}
+
+// collects the ranges with another offset type, which can hold every
+// offset once it holds the length of the text
+struct Offsets<'a, O>(&'a mut Vec<Range<O>>);
+
+fn narrow<O: TryFrom<usize>>(offset: usize) -> O {
+    O::try_from(offset).unwrap_or_else(|_| unreachable!("the offsets fit"))
+}
+
+fn widen<O: TryInto<usize>>(offset: O) -> usize {
+    offset
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("the offsets came from usize"))
+}
+
+impl<O: Copy + TryFrom<usize> + TryInto<usize>> RangeSink for Offsets<'_, O> {
+    #[inline]
+    fn push(&mut self, range: Range<usize>) {
+        self.0.push(narrow(range.start)..narrow(range.end));
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    #[inline]
+    fn last_end(&self) -> usize {
+        self.0.last().map_or(0, |range| widen(range.end))
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but with
+    /// another offset type for the token ranges, e.g. `u32` to halve their
+    /// memory.
+    ///
+    /// If the offset type cannot hold the length of the text (and thus all
+    /// offsets), this returns an [`OffsetOverflow`] and leaves the outputs
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, OffsetOverflow};
+    /// use std::ops::Range;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .rejoin_hyphenation(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let (mut offset_ids, mut offsets) = (Vec::<u32>::new(), Vec::<Range<u32>>::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+    ///         FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "Grüße aus dem Kranken-\nhaus ☃",
+    /// ] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     tokenizer.tokens_into_offsets(text, &mut offset_ids, &mut offsets, None).unwrap();
+    ///     assert_eq!(ids, offset_ids);
+    ///     let widened: Vec<Range<usize>> =
+    ///         offsets.iter().map(|r| r.start as usize..r.end as usize).collect();
+    ///     assert_eq!(ranges, widened, "{:?}", text);
+    /// }
+    ///
+    /// // `u16` offsets only hold texts of up to 64 KiB
+    /// let text = "Ich esse Steak. ".repeat(5000);
+    /// let mut short = Vec::<Range<u16>>::new();
+    /// assert_eq!(
+    ///     Err(OffsetOverflow { len: 80000 }),
+    ///     tokenizer.tokens_into_offsets(&text, &mut offset_ids, &mut short, None)
+    /// );
+    /// assert!(offset_ids.is_empty() && short.is_empty());
+    /// assert!(tokenizer.tokens_into_offsets(&text[..65535], &mut offset_ids, &mut short, None).is_ok());
+    /// assert_eq!(65535, short.last().unwrap().end);
+    /// ```
+    pub fn tokens_into_offsets<T: TokenID, O: Copy + TryFrom<usize> + TryInto<usize>>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<O>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) -> Result<(), OffsetOverflow> {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut words = words;
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        if O::try_from(text.len()).is_err() {
+            return Err(OffsetOverflow { len: text.len() });
+        }
+        let mut offsets = Offsets(token_ranges);
+        self.add_prefix(token_ids, &mut offsets);
+        self.words_into(text, 0, token_ids, &mut offsets, words);
+        self.add_suffix(token_ids, &mut offsets);
+        Ok(())
+    }
+}
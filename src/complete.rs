@@ -0,0 +1,105 @@
+use crate::{AlephAlphaTokenizer, FstData};
+use fst::automaton::{Automaton, Str};
+use fst::raw::Fst;
+use fst::{IntoStreamer, Streamer};
+
+// the keys starting with the prefix and their ids, in order
+fn complete(fst: &Fst<FstData>, prefix: &str, limit: usize) -> Vec<(String, u64)> {
+    let mut completions = Vec::new();
+    let mut stream = fst.search(Str::new(prefix).starts_with()).into_stream();
+    while completions.len() < limit {
+        match stream.next() {
+            Some((key, output)) => {
+                completions.push((String::from_utf8_lossy(key).into_owned(), output.value()))
+            }
+            None => break,
+        }
+    }
+    completions
+}
+
+impl AlephAlphaTokenizer {
+    /// Gets the tokens that can start a word and start with the given text,
+    /// with their ids, in lexicographic (byte) order.
+    ///
+    /// This includes the special tokens, which are matched like starters.
+    /// For an empty prefix, this gets all of them, so see
+    /// [`complete_starters_limited`] to get only the first ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let completions = tokenizer.complete_starters("Kinder");
+    /// assert!(completions.contains(&("Kinder".to_string(), tokenizer.id_of("Kinder").unwrap())));
+    /// assert!(completions.iter().any(|(token, _)| token == "Kindergarten"));
+    /// assert!(completions.iter().all(|(token, _)| token.starts_with("Kinder")));
+    /// assert!(completions.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    /// for (token, id) in &completions {
+    ///     assert_eq!(token, tokenizer.text_of(*id));
+    /// }
+    ///
+    /// // `Straße` has a multi-byte char
+    /// assert!(tokenizer.complete_starters("Stra").iter().any(|(token, _)| token == "Straße"));
+    /// assert!(tokenizer.complete_starters("Supercalifragilistic").is_empty());
+    /// ```
+    ///
+    /// [`complete_starters_limited`]: AlephAlphaTokenizer::complete_starters_limited
+    pub fn complete_starters(&self, prefix: &str) -> Vec<(String, u64)> {
+        complete(&self.starters, prefix, usize::MAX)
+    }
+
+    /// Like [`complete_starters`](AlephAlphaTokenizer::complete_starters),
+    /// but gets at most `limit` tokens, the first ones in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let all = tokenizer.complete_starters("Ge");
+    /// assert!(all.len() > 10);
+    /// assert_eq!(&all[..10], &tokenizer.complete_starters_limited("Ge", 10)[..]);
+    /// assert_eq!(3, tokenizer.complete_starters_limited("", 3).len());
+    /// assert!(tokenizer.complete_starters_limited("Ge", 0).is_empty());
+    /// ```
+    pub fn complete_starters_limited(&self, prefix: &str, limit: usize) -> Vec<(String, u64)> {
+        complete(&self.starters, prefix, limit)
+    }
+
+    /// Gets the tokens that can continue a word and whose text (without the
+    /// continuing subword prefix, as they match the word) starts with the
+    /// given text, with their ids, in lexicographic (byte) order.
+    ///
+    /// For an empty prefix, this gets all of them, so see
+    /// [`complete_followers_limited`] to get only the first ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let completions = tokenizer.complete_followers("ung");
+    /// assert!(completions.contains(&("ung".to_string(), tokenizer.id_of("##ung").unwrap())));
+    /// assert!(completions.iter().any(|(token, _)| token == "ungen"));
+    /// for (token, id) in &completions {
+    ///     assert_eq!(format!("##{}", token), tokenizer.text_of(*id));
+    /// }
+    /// assert_eq!(5, tokenizer.complete_followers_limited("", 5).len());
+    /// ```
+    ///
+    /// [`complete_followers_limited`]: AlephAlphaTokenizer::complete_followers_limited
+    pub fn complete_followers(&self, prefix: &str) -> Vec<(String, u64)> {
+        complete(&self.followers, prefix, usize::MAX)
+    }
+
+    /// Like [`complete_followers`](AlephAlphaTokenizer::complete_followers),
+    /// but gets at most `limit` tokens, the first ones in order.
+    pub fn complete_followers_limited(&self, prefix: &str, limit: usize) -> Vec<(String, u64)> {
+        complete(&self.followers, prefix, limit)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{EncodeOptions, TokenizerOptions};
+use std::error::Error;
+use std::fmt;
+
+/// The errors that can occur when reading or validating options.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionsError {
+    /// The configuration could not be parsed
+    Parse(String),
+    /// An option has a value that makes no sense
+    Invalid {
+        /// the name of the option
+        option: &'static str,
+        /// why the value is rejected
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::Parse(e) => write!(f, "cannot parse options: {}", e),
+            OptionsError::Invalid { option, reason } => {
+                write!(f, "invalid option `{}`: {}", option, reason)
+            }
+        }
+    }
+}
+
+impl Error for OptionsError {}
+
+#[cfg(feature = "json")]
+fn from_json_str<'d, T: serde::Deserialize<'d>>(json: &'d str) -> Result<T, OptionsError> {
+    serde_json::from_str(json).map_err(|e| OptionsError::Parse(e.to_string()))
+}
+
+impl EncodeOptions {
+    /// Checks that the options can be used together.
+    ///
+    /// A `max_input_bytes` of zero is rejected, as it would reject every
+    /// text that isn't empty, and so is a `max_length` of zero, which would
+    /// leave no tokens.
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        if self.max_input_bytes == Some(0) {
+            return Err(OptionsError::Invalid {
+                option: "max_input_bytes",
+                reason: "a limit of 0 bytes rejects every text",
+            });
+        }
+        if self.max_length == Some(0) {
+            return Err(OptionsError::Invalid {
+                option: "max_length",
+                reason: "a limit of 0 tokens leaves no tokens",
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads and validates the options from JSON. Missing fields take their
+    /// default value, unknown fields are rejected.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{EncodeOptions, OptionsError, TruncationStrategy};
+    ///
+    /// assert_eq!(EncodeOptions::default(), EncodeOptions::from_json_str("{}").unwrap());
+    /// assert_eq!(
+    ///     EncodeOptions { max_input_bytes: Some(1 << 20), ..EncodeOptions::default() },
+    ///     EncodeOptions::from_json_str(r#"{"max_input_bytes": 1048576}"#).unwrap()
+    /// );
+    /// assert_eq!(
+    ///     EncodeOptions::default(),
+    ///     EncodeOptions::from_json_str(r#"{"max_input_bytes": null}"#).unwrap()
+    /// );
+    ///
+    /// let options = EncodeOptions {
+    ///     max_input_bytes: Some(4096),
+    ///     max_length: Some(512),
+    ///     truncation: TruncationStrategy::OnlySecond,
+    ///     truncate_whole_words: true,
+    ///     skip_special_tokens: false,
+    /// };
+    /// let json = serde_json::to_string(&options).unwrap();
+    /// assert_eq!(
+    ///     concat!(
+    ///         r#"{"max_input_bytes":4096,"max_length":512,"truncation":"OnlySecond","#,
+    ///         r#""truncate_whole_words":true,"skip_special_tokens":false}"#,
+    ///     ),
+    ///     json
+    /// );
+    /// assert_eq!(options, EncodeOptions::from_json_str(&json).unwrap());
+    ///
+    /// assert_eq!(
+    ///     "invalid option `max_input_bytes`: a limit of 0 bytes rejects every text",
+    ///     EncodeOptions::from_json_str(r#"{"max_input_bytes": 0}"#)
+    ///         .unwrap_err()
+    ///         .to_string()
+    /// );
+    /// assert_eq!(
+    ///     "invalid option `max_length`: a limit of 0 tokens leaves no tokens",
+    ///     EncodeOptions::from_json_str(r#"{"max_length": 0}"#)
+    ///         .unwrap_err()
+    ///         .to_string()
+    /// );
+    /// match EncodeOptions::from_json_str(r#"{"max_input_byte": 10}"#) {
+    ///     Err(OptionsError::Parse(e)) => assert!(e.contains("unknown field `max_input_byte`")),
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<Self, OptionsError> {
+        let options: Self = from_json_str(json)?;
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+impl TokenizerOptions {
+    /// Checks that the options can be used together.
+    ///
+    /// A `max_chars_per_word` or `max_tokens_per_word` of zero is rejected,
+    /// as it would turn every word into `[UNK]`, and so are `strip_accents` and `normalize` without
+    /// the `unicode-normalization` feature.
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        if self.max_chars_per_word == Some(0) {
+            return Err(OptionsError::Invalid {
+                option: "max_chars_per_word",
+                reason: "a limit of 0 characters turns every word into [UNK]",
+            });
+        }
+        if self.max_tokens_per_word == Some(0) {
+            return Err(OptionsError::Invalid {
+                option: "max_tokens_per_word",
+                reason: "a limit of 0 tokens turns every word into [UNK]",
+            });
+        }
+        if cfg!(not(feature = "unicode-normalization")) {
+            if self.strip_accents {
+                return Err(OptionsError::Invalid {
+                    option: "strip_accents",
+                    reason: "stripping accents needs the `unicode-normalization` feature",
+                });
+            }
+            if self.normalize.is_some() {
+                return Err(OptionsError::Invalid {
+                    option: "normalize",
+                    reason: "normalizing needs the `unicode-normalization` feature",
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads and validates the options from JSON. Missing fields take their
+    /// default value, unknown fields are rejected.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, Preset, TokenizerOptions};
+    ///
+    /// assert_eq!(TokenizerOptions::default(), TokenizerOptions::from_json_str("{}").unwrap());
+    /// let cased = r#"{"split_punctuation": true, "parse_special_tokens": true}"#;
+    /// assert_eq!(Preset::BertCased.options(), TokenizerOptions::from_json_str(cased).unwrap());
+    /// let wordpiece = r#"{
+    ///     "add_prefix": false,
+    ///     "max_chars_per_word": 100,
+    ///     "leading_prefix_as_follower": true
+    /// }"#;
+    /// let options = TokenizerOptions::from_json_str(wordpiece).unwrap();
+    /// assert_eq!(
+    ///     TokenizerOptions {
+    ///         add_prefix: false,
+    ///         max_chars_per_word: Some(100),
+    ///         leading_prefix_as_follower: true,
+    ///         ..TokenizerOptions::default()
+    ///     },
+    ///     options
+    /// );
+    ///
+    /// // the options survive a round trip and configure the builder
+    /// let json = serde_json::to_string(&options).unwrap();
+    /// assert_eq!(options, TokenizerOptions::from_json_str(&json).unwrap());
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .with_options(options.clone())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(&options, tokenizer.options());
+    ///
+    /// assert!(TokenizerOptions::from_json_str(r#"{"max_chars_per_word": 0}"#).is_err());
+    /// assert!(TokenizerOptions::from_json_str(r#"{"lowercase": "yes"}"#).is_err());
+    /// let stripped = TokenizerOptions::from_json_str(r#"{"strip_accents": true}"#);
+    /// assert_eq!(cfg!(feature = "unicode-normalization"), stripped.is_ok());
+    /// let nfkc = TokenizerOptions::from_json_str(r#"{"normalize": "Nfkc"}"#);
+    /// assert_eq!(cfg!(feature = "unicode-normalization"), nfkc.is_ok());
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> Result<Self, OptionsError> {
+        let options: Self = from_json_str(json)?;
+        options.validate()?;
+        Ok(options)
+    }
+}
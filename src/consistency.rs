@@ -0,0 +1,329 @@
+use crate::{AlephAlphaTokenizer, FstData};
+use fst::raw::Fst;
+use fst::Streamer;
+use std::error::Error;
+use std::fmt;
+
+/// An inconsistency between the token table and the FSTs of a tokenizer.
+///
+/// See [`AlephAlphaTokenizer::verify_internal_consistency`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsistencyError {
+    /// An FST key maps to an id outside of the token table
+    IdOutOfRange {
+        /// the FST key (without the prefix for followers)
+        key: String,
+        /// the id it maps to
+        id: u64,
+    },
+    /// An FST key doesn't match the text of the token it maps to
+    KeyMismatch {
+        /// the FST key (without the prefix for followers)
+        key: String,
+        /// the id it maps to
+        id: u64,
+    },
+    /// An id is larger than `u32::MAX`, which huggingface's tokenizers
+    /// cannot represent
+    IdTooLarge {
+        /// the token text or FST key
+        key: String,
+        /// the id
+        id: u64,
+    },
+    /// A special token id is outside of the token table
+    SpecialOutOfRange {
+        /// the special token id
+        id: u64,
+    },
+    /// The bitset behind [`is_special`](AlephAlphaTokenizer::is_special)
+    /// disagrees with the special tokens
+    SpecialBitMismatch {
+        /// the token id
+        id: u64,
+        /// whether it is the id of a special token, whose bit isn't set, or
+        /// of another one, whose bit is
+        special: bool,
+    },
+    /// An FST key isn't valid UTF-8, so it could end within a char
+    KeyNotUtf8 {
+        /// the FST key (without the prefix for followers), with the invalid
+        /// bytes replaced
+        key: String,
+        /// the id it maps to
+        id: u64,
+    },
+}
+
+impl fmt::Display for ConsistencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyError::IdOutOfRange { key, id } => {
+                write!(f, "token {:?} has id {}, which is out of range", key, id)
+            }
+            ConsistencyError::KeyMismatch { key, id } => {
+                write!(f, "token {:?} maps to id {} of a different token", key, id)
+            }
+            ConsistencyError::IdTooLarge { key, id } => {
+                write!(f, "token {:?} has id {}, which exceeds u32", key, id)
+            }
+            ConsistencyError::SpecialOutOfRange { id } => {
+                write!(f, "special token id {} is out of range", id)
+            }
+            ConsistencyError::SpecialBitMismatch { id, special: true } => {
+                write!(f, "special token id {} is not marked as special", id)
+            }
+            ConsistencyError::SpecialBitMismatch { id, special: false } => {
+                write!(f, "token id {} is marked as special, but isn't", id)
+            }
+            ConsistencyError::KeyNotUtf8 { key, id } => {
+                write!(f, "token {:?} with id {} is not valid UTF-8", key, id)
+            }
+        }
+    }
+}
+
+impl Error for ConsistencyError {}
+
+/// A problem with a vocabulary that still loads, as returned by
+/// [`AlephAlphaTokenizer::vocab_warnings`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VocabWarning {
+    /// A token contains whitespace, so no word can ever match it
+    ContainsWhitespace {
+        /// the token
+        token: String,
+        /// its line, starting at 1
+        line: usize,
+    },
+    /// A line is blank, so its id is never used
+    BlankLine {
+        /// the line, starting at 1
+        line: usize,
+    },
+}
+
+impl fmt::Display for VocabWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VocabWarning::ContainsWhitespace { token, line } => write!(
+                f,
+                "token {:?} in line {} contains whitespace, so it never matches; \
+                 remove the whitespace or the token",
+                token, line
+            ),
+            VocabWarning::BlankLine { line } => write!(
+                f,
+                "line {} is blank, so its id is never used; \
+                 removing it would change the ids of the tokens after it",
+                line
+            ),
+        }
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Finds the problems of the vocabulary that don't keep it from loading,
+    /// e.g. to report them after building the tokenizer. Duplicate tokens
+    /// and a missing `[UNK]` token are errors instead, see
+    /// [`TokenizerError`](crate::TokenizerError).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError, VocabWarning};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(vec![VocabWarning::BlankLine { line: 26897 }], tokenizer.vocab_warnings());
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\n\nice cream\n").unwrap();
+    /// let warnings = tokenizer.vocab_warnings();
+    /// assert_eq!(
+    ///     vec![
+    ///         VocabWarning::BlankLine { line: 3 },
+    ///         VocabWarning::ContainsWhitespace { token: "ice cream".to_string(), line: 4 },
+    ///     ],
+    ///     warnings
+    /// );
+    /// assert_eq!(
+    ///     "line 3 is blank, so its id is never used; \
+    ///      removing it would change the ids of the tokens after it",
+    ///     warnings[0].to_string()
+    /// );
+    /// assert_eq!(
+    ///     "token \"ice cream\" in line 4 contains whitespace, so it never matches; \
+    ///      remove the whitespace or the token",
+    ///     warnings[1].to_string()
+    /// );
+    ///
+    /// // special tokens must not occur twice either
+    /// let error = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\n[CLS]\nfoo\n[CLS]\n").err().unwrap();
+    /// assert!(matches!(
+    ///     error,
+    ///     TokenizerError::DuplicateToken { ref token, first_line: 2, second_line: 4 } if token == "[CLS]"
+    /// ));
+    /// assert_eq!("token \"[CLS]\" occurs in lines 2 and 4", error.to_string());
+    /// ```
+    pub fn vocab_warnings(&self) -> Vec<VocabWarning> {
+        let mut warnings = Vec::new();
+        for (i, token) in self.tokens.iter().enumerate() {
+            let line = i + 1;
+            if token.is_empty() {
+                warnings.push(VocabWarning::BlankLine { line });
+            } else if token.contains(char::is_whitespace) {
+                warnings.push(VocabWarning::ContainsWhitespace {
+                    token: token.to_string(),
+                    line,
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Checks that the FSTs and the token table agree with each other.
+    ///
+    /// Every FST output must be a valid id that fits into a `u32` and whose
+    /// token text is the key (with the continuing subword prefix for
+    /// followers), and all special token ids, including `[UNK]`, `[CLS]`,
+    /// `[SEP]` and `[PAD]`, must be valid ids. The bitset behind
+    /// [`is_special`](AlephAlphaTokenizer::is_special) must mark exactly the
+    /// special tokens, so this builds it if it isn't yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(Ok(()), tokenizer.verify_internal_consistency());
+    /// let special: Vec<u64> = tokenizer.special_tokens().map(|(id, _)| id).collect();
+    /// assert!((0..tokenizer.vocab_size() as u64)
+    ///     .all(|id| tokenizer.is_special(id) == special.contains(&id)));
+    /// ```
+    ///
+    /// A tokenizer with sparse ids, where "big" has the id 2<sup>33</sup>, is
+    /// rejected when loading it, and fails within huggingface's tokenizers if
+    /// loaded unchecked:
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, BinaryFormatError, ConsistencyError};
+    /// use fst::raw::Fst;
+    ///
+    /// let big = 1u64 << 33;
+    /// let mut bytes = b"AATK\x01".to_vec();
+    /// bytes.extend_from_slice(&0u32.to_le_bytes()); // [UNK]
+    /// bytes.extend_from_slice(&[0; 15]); // no [CLS], [SEP] or [PAD]
+    /// bytes.extend_from_slice(&2u32.to_le_bytes());
+    /// bytes.extend_from_slice(b"##");
+    /// bytes.extend_from_slice(&1u64.to_le_bytes()); // one special token: [UNK]
+    /// bytes.extend_from_slice(&0u64.to_le_bytes());
+    /// bytes.extend_from_slice(&2u64.to_le_bytes()); // two tokens
+    /// for token in &["[UNK]", "big"] {
+    ///     bytes.extend_from_slice(&(token.len() as u32).to_le_bytes());
+    ///     bytes.extend_from_slice(token.as_bytes());
+    /// }
+    /// let starters = Fst::from_iter_map(vec![("[UNK]", 0), ("big", big)])?.to_vec();
+    /// let followers = Fst::from_iter_map(Vec::<(&str, u64)>::new())?.to_vec();
+    /// for fst in &[starters, followers] {
+    ///     bytes.extend_from_slice(&(fst.len() as u64).to_le_bytes());
+    ///     bytes.extend_from_slice(fst);
+    /// }
+    ///
+    /// let error = AlephAlphaTokenizer::from_binary_bytes(&bytes).err().unwrap();
+    /// assert_eq!(
+    ///     Some(&BinaryFormatError::Inconsistent(ConsistencyError::IdTooLarge {
+    ///         key: "big".to_string(),
+    ///         id: big,
+    ///     })),
+    ///     error.downcast_ref::<BinaryFormatError>()
+    /// );
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_binary_bytes_unchecked(&bytes)?;
+    /// assert_eq!(Some(big), tokenizer.id_of("big"));
+    ///# #[cfg(feature = "huggingface")] {
+    /// use tokenizers::tokenizer::Model;
+    ///
+    /// let error = tokenizer.tokenize("big").err().unwrap();
+    /// assert_eq!(
+    ///     Some(&ConsistencyError::IdTooLarge { key: "big".to_string(), id: big }),
+    ///     error.downcast_ref::<ConsistencyError>()
+    /// );
+    /// assert_eq!(None, tokenizer.token_to_id("big"));
+    ///# }
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn verify_internal_consistency(&self) -> Result<(), ConsistencyError> {
+        self.verify_fst(&self.starters, "", "")?;
+        self.verify_fst(
+            &self.followers,
+            &self.continuing_subword_prefix,
+            &self.end_of_word_suffix,
+        )?;
+        let len = self.tokens.len() as u64;
+        let fixed = [
+            Some(self.unk_id),
+            self.prefix,
+            self.suffix,
+            self.pad_id,
+            self.mask_id,
+        ];
+        let specials = self.special_tokens.iter().cloned();
+        if let Some(id) = specials
+            .chain(fixed.iter().flatten().map(|&id| u64::from(id)))
+            .find(|&id| id >= len)
+        {
+            return Err(ConsistencyError::SpecialOutOfRange { id });
+        }
+        if let Some((id, special)) = self.special_bit_mismatch() {
+            return Err(ConsistencyError::SpecialBitMismatch { id, special });
+        }
+        Ok(())
+    }
+
+    fn verify_fst(
+        &self,
+        fst: &Fst<FstData>,
+        prefix: &str,
+        suffix: &str,
+    ) -> Result<(), ConsistencyError> {
+        let mut stream = fst.stream();
+        while let Some((key, output)) = stream.next() {
+            let id = output.value();
+            let describe = || String::from_utf8_lossy(key).into_owned();
+            if std::str::from_utf8(key).is_err() {
+                return Err(ConsistencyError::KeyNotUtf8 {
+                    key: describe(),
+                    id,
+                });
+            }
+            if id > u64::from(u32::MAX) {
+                return Err(ConsistencyError::IdTooLarge {
+                    key: describe(),
+                    id,
+                });
+            }
+            let token = match self.tokens.get(id as usize) {
+                Some(token) => token.trim(),
+                None => {
+                    return Err(ConsistencyError::IdOutOfRange {
+                        key: describe(),
+                        id,
+                    })
+                }
+            };
+            if token.len() != prefix.len() + key.len() + suffix.len()
+                || !token.starts_with(prefix)
+                || !token.ends_with(suffix)
+                || &token.as_bytes()[prefix.len()..token.len() - suffix.len()] != key
+            {
+                return Err(ConsistencyError::KeyMismatch {
+                    key: describe(),
+                    id,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,456 @@
+//! Tokenizes a corpus of text files into shards of token ids for
+//! pretraining, which can be memory-mapped as flat arrays.
+//!
+//! [`tokenize_corpus`] treats each input file as one document. It
+//! concatenates the tokens of all documents (each optionally followed by an
+//! end-of-document token) and splits them into shards of
+//! [`shard_tokens`](CorpusOptions::shard_tokens) tokens each; only the last
+//! shard may be shorter. The output directory then contains:
+//!
+//! * `shard-00000.bin`, `shard-00001.bin`, …: the token ids as little-endian
+//!   `u16`s if the vocabulary has at most 65536 tokens, else as `u32`s
+//! * `index.tsv`: a text file of tab-separated lines, which are
+//!   * `aleph-alpha-tokenizer-corpus` and the format version (`1`)
+//!   * `settings`, the id width in bytes, the tokens per shard, the
+//!     end-of-document id (or `-`), whether `[CLS]` and `[SEP]` are included
+//!     (`0` or `1`) and a fingerprint of the tokenizer
+//!   * one `document` line per document with the index of its first token
+//!     in the corpus, its number of tokens, the checksum of its file and its
+//!     path
+//!   * one `shard` line per shard with its file name, its number of tokens
+//!     and its checksum
+//!
+//! The checksums are hexadecimal FNV-1a hashes of the file contents. The
+//! `document` and `shard` lines are appended as the work progresses, so an
+//! interrupted run can be resumed: running again with the same settings
+//! keeps the shards whose documents and contents still match the index, and
+//! only tokenizes the documents from the first shard that doesn't.
+
+use crate::version::{fnv1a, FNV_OFFSET};
+use crate::AlephAlphaTokenizer;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+const MAGIC: &str = "aleph-alpha-tokenizer-corpus";
+const VERSION: u32 = 1;
+const INDEX: &str = "index.tsv";
+// the number of documents to read and tokenize at once
+const BATCH: usize = 64;
+
+/// Options for [`tokenize_corpus`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorpusOptions {
+    /// The number of tokens per shard (`16 * 1024 * 1024` by default)
+    pub shard_tokens: usize,
+    /// The id of a token to append to each document, if any
+    pub end_of_document: Option<u64>,
+    /// Whether to start each document with `[CLS]` and end it with `[SEP]`
+    /// (if the vocabulary has them, `false` by default)
+    pub add_special_tokens: bool,
+    /// Whether to tokenize documents in parallel (`true` by default); this
+    /// needs the `rayon` feature and is ignored without it
+    pub parallel: bool,
+}
+
+impl Default for CorpusOptions {
+    fn default() -> Self {
+        CorpusOptions {
+            shard_tokens: 16 * 1024 * 1024,
+            end_of_document: None,
+            add_special_tokens: false,
+            parallel: true,
+        }
+    }
+}
+
+/// What [`tokenize_corpus`] did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorpusSummary {
+    /// The number of documents
+    pub documents: usize,
+    /// The number of tokens of all documents
+    pub tokens: u64,
+    /// The number of shards
+    pub shards: usize,
+    /// The number of shards kept from an earlier run
+    pub reused_shards: usize,
+    /// The size of each token id in the shards in bytes (2 or 4)
+    pub id_width: usize,
+}
+
+/// Gets the name of the shard file with the given index.
+pub fn shard_name(index: usize) -> String {
+    format!("shard-{:05}.bin", index)
+}
+
+fn checksum(bytes: &[u8]) -> String {
+    format!("{:016x}", fnv1a(FNV_OFFSET, bytes))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+// a `document` line of the index
+struct Document {
+    first: u64,
+    tokens: u64,
+    checksum: String,
+    path: String,
+}
+
+// a `shard` line of the index
+struct Shard {
+    name: String,
+    checksum: String,
+}
+
+// the parts of an earlier index that still apply
+#[derive(Default)]
+struct Resumed {
+    documents: Vec<Document>,
+    shards: Vec<Shard>,
+}
+
+// reads the documents and shards of an index with the same settings
+fn read_index(path: &Path, header: &str) -> Resumed {
+    let index = match fs::read_to_string(path) {
+        Ok(index) if index.starts_with(header) => index,
+        _ => return Resumed::default(),
+    };
+    let mut resumed = Resumed::default();
+    for line in index[header.len()..].lines() {
+        let fields: Vec<&str> = line.splitn(5, '\t').collect();
+        match fields[..] {
+            ["document", first, tokens, checksum, path] => {
+                if let (Ok(first), Ok(tokens)) = (first.parse(), tokens.parse()) {
+                    resumed.documents.push(Document {
+                        first,
+                        tokens,
+                        checksum: checksum.to_string(),
+                        path: path.to_string(),
+                    });
+                }
+            }
+            ["shard", name, _, checksum] => resumed.shards.push(Shard {
+                name: name.to_string(),
+                checksum: checksum.to_string(),
+            }),
+            // an interrupted run may leave a partial line
+            _ => {}
+        }
+    }
+    resumed
+}
+
+// collects the token ids into shards
+struct Shards<'a> {
+    dir: &'a Path,
+    index: BufWriter<File>,
+    shard_tokens: usize,
+    id_width: usize,
+    buf: Vec<u8>,
+    tokens: usize,
+    next: usize,
+}
+
+impl Shards<'_> {
+    fn push(&mut self, ids: &[u32]) -> io::Result<()> {
+        for &id in ids {
+            let bytes = id.to_le_bytes();
+            self.buf.extend_from_slice(&bytes[..self.id_width]);
+            self.tokens += 1;
+            if self.tokens == self.shard_tokens {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    // writes the current shard, unless an identical file is already there
+    fn flush(&mut self) -> io::Result<()> {
+        let name = shard_name(self.next);
+        let path = self.dir.join(&name);
+        let sum = checksum(&self.buf);
+        if !fs::read(&path).is_ok_and(|old| checksum(&old) == sum) {
+            let tmp = self.dir.join(format!("{}.tmp", name));
+            fs::write(&tmp, &self.buf)?;
+            fs::rename(&tmp, &path)?;
+        }
+        writeln!(self.index, "shard\t{}\t{}\t{}", name, self.tokens, sum)?;
+        self.index.flush()?;
+        self.buf.clear();
+        self.tokens = 0;
+        self.next += 1;
+        Ok(())
+    }
+}
+
+/// Tokenizes the text files into shards of token ids in `out_dir`, as
+/// described in the [module documentation](self), e.g. to prepare
+/// pretraining data.
+///
+/// The directory is created if needed. If it holds the index of an earlier
+/// run with the same options and tokenizer, the shards that still match are
+/// kept, and shards with unchanged contents are not written again.
+///
+/// # Errors
+///
+/// Fails if reading or writing a file fails, a document is not valid UTF-8,
+/// `shard_tokens` is `0`, or the `end_of_document` id is not in the
+/// vocabulary.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::corpus::{shard_name, tokenize_corpus, CorpusOptions};
+/// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+/// use std::convert::TryInto;
+/// use std::fs;
+///
+/// let dir = std::env::temp_dir().join("aat-corpus");
+/// let _ = fs::remove_dir_all(&dir);
+/// let docs = dir.join("docs");
+/// fs::create_dir_all(&docs).unwrap();
+/// let texts = [
+///     "Ich esse Steak.",
+///     "Der Hund spielt im Garten.",
+///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+/// ];
+/// let inputs: Vec<_> = texts
+///     .iter()
+///     .enumerate()
+///     .map(|(i, text)| {
+///         let path = docs.join(format!("{}.txt", i));
+///         fs::write(&path, text).unwrap();
+///         path
+///     })
+///     .collect();
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let options = CorpusOptions {
+///     shard_tokens: 8,
+///     end_of_document: tokenizer.sep_id(),
+///     ..CorpusOptions::default()
+/// };
+/// let out = dir.join("out");
+/// let summary = tokenize_corpus(&tokenizer, &inputs, &out, &options).unwrap();
+///
+/// // each document without [CLS] and [SEP], followed by the end of document
+/// let (mut expected, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// let mut lengths = Vec::new();
+/// for text in &texts {
+///     let start = expected.len();
+///     tokenizer.extend_tokens_into(text, &mut expected, &mut ranges, None, false, false);
+///     expected.push(tokenizer.sep_id().unwrap());
+///     lengths.push(expected.len() - start);
+/// }
+/// assert_eq!(2, summary.id_width);
+/// assert_eq!((3, expected.len() as u64), (summary.documents, summary.tokens));
+/// assert_eq!((expected.len() + 7) / 8, summary.shards);
+///
+/// let read_shards = |shards| {
+///     let mut ids = Vec::new();
+///     for shard in 0..shards {
+///         let bytes = fs::read(out.join(shard_name(shard))).unwrap();
+///         assert!(bytes.len() == 16 || shard == shards - 1);
+///         ids.extend(bytes.chunks(2).map(|id| u16::from_le_bytes(id.try_into().unwrap()) as u64));
+///     }
+///     ids
+/// };
+/// assert_eq!(expected, read_shards(summary.shards));
+///
+/// // the index gives the document boundaries
+/// let index = fs::read_to_string(out.join("index.tsv")).unwrap();
+/// let documents: Vec<Vec<&str>> = index
+///     .lines()
+///     .filter(|line| line.starts_with("document\t"))
+///     .map(|line| line.split('\t').collect())
+///     .collect();
+/// let mut first = 0;
+/// for (document, (length, input)) in documents.iter().zip(lengths.iter().zip(&inputs)) {
+///     assert_eq!(first.to_string(), document[1]);
+///     assert_eq!(length.to_string(), document[2]);
+///     assert_eq!(input.display().to_string(), document[4]);
+///     first += length;
+/// }
+///
+/// // running again keeps the full shards
+/// let again = tokenize_corpus(&tokenizer, &inputs, &out, &options).unwrap();
+/// assert_eq!(expected.len() / 8, again.reused_shards);
+/// assert_eq!(expected, read_shards(again.shards));
+///
+/// // changing the last document keeps the shards before it
+/// fs::write(&inputs[2], "Ein Junge spielt.").unwrap();
+/// let changed = tokenize_corpus(&tokenizer, &inputs, &out, &options).unwrap();
+/// assert_eq!((lengths[0] + lengths[1]) / 8, changed.reused_shards);
+/// expected.truncate(lengths[0] + lengths[1]);
+/// tokenizer.extend_tokens_into("Ein Junge spielt.", &mut expected, &mut ranges, None, false, false);
+/// expected.push(tokenizer.sep_id().unwrap());
+/// assert_eq!(expected, read_shards(changed.shards));
+///
+/// let options = CorpusOptions { shard_tokens: 0, ..CorpusOptions::default() };
+/// assert!(tokenize_corpus(&tokenizer, &inputs, &out, &options).is_err());
+/// ```
+pub fn tokenize_corpus(
+    tokenizer: &AlephAlphaTokenizer,
+    inputs: &[PathBuf],
+    out_dir: &Path,
+    options: &CorpusOptions,
+) -> io::Result<CorpusSummary> {
+    if options.shard_tokens == 0 {
+        return Err(invalid_input(
+            "a shard needs at least one token".to_string(),
+        ));
+    }
+    let vocab_size = tokenizer.vocab_size() as u64;
+    if let Some(id) = options.end_of_document.filter(|&id| id >= vocab_size) {
+        return Err(invalid_input(format!(
+            "the end of document id {} is not in the vocabulary",
+            id
+        )));
+    }
+    let id_width = if vocab_size <= 1 << 16 { 2 } else { 4 };
+    // the options change the tokens, too
+    let info = tokenizer.version_info();
+    let options_hash = fnv1a(FNV_OFFSET, format!("{:?}", tokenizer.options()).as_bytes());
+    let header = format!(
+        "{}\t{}\nsettings\t{}\t{}\t{}\t{}\t{:016x}{:016x}\n",
+        MAGIC,
+        VERSION,
+        id_width,
+        options.shard_tokens,
+        options
+            .end_of_document
+            .map_or_else(|| "-".to_string(), |id| id.to_string()),
+        options.add_special_tokens as u8,
+        info.vocab_fingerprint,
+        options_hash,
+    );
+    fs::create_dir_all(out_dir)?;
+    let index_path = out_dir.join(INDEX);
+    let resumed = read_index(&index_path, &header);
+
+    // the documents that are unchanged, and the full shards of them that are
+    let mut documents = 0;
+    let mut verified_tokens = 0;
+    for (document, input) in resumed.documents.iter().zip(inputs) {
+        if document.path != input.display().to_string()
+            || document.first != verified_tokens
+            || !fs::read(input).is_ok_and(|text| checksum(&text) == document.checksum)
+        {
+            break;
+        }
+        documents += 1;
+        verified_tokens += document.tokens;
+    }
+    let shard_tokens = options.shard_tokens as u64;
+    let reused_shards = resumed
+        .shards
+        .iter()
+        .enumerate()
+        .take_while(|&(i, shard)| {
+            (i as u64 + 1) * shard_tokens <= verified_tokens
+                && shard.name == shard_name(i)
+                && fs::read(out_dir.join(&shard.name))
+                    .is_ok_and(|bytes| checksum(&bytes) == shard.checksum)
+        })
+        .count();
+    let start = reused_shards as u64 * shard_tokens;
+    let first_document = resumed.documents[..documents]
+        .iter()
+        .position(|document| document.first + document.tokens > start)
+        .unwrap_or(documents);
+
+    // keep the index lines of what is reused
+    let mut index = String::from(&header);
+    for document in &resumed.documents[..first_document] {
+        index.push_str(&format!(
+            "document\t{}\t{}\t{}\t{}\n",
+            document.first, document.tokens, document.checksum, document.path
+        ));
+    }
+    for shard in &resumed.shards[..reused_shards] {
+        index.push_str(&format!(
+            "shard\t{}\t{}\t{}\n",
+            shard.name, shard_tokens, shard.checksum
+        ));
+    }
+    let tmp = out_dir.join(format!("{}.tmp", INDEX));
+    fs::write(&tmp, index)?;
+    fs::rename(&tmp, &index_path)?;
+    let index = BufWriter::new(OpenOptions::new().append(true).open(&index_path)?);
+
+    let mut shards = Shards {
+        dir: out_dir,
+        index,
+        shard_tokens: options.shard_tokens,
+        id_width,
+        buf: Vec::new(),
+        tokens: 0,
+        next: reused_shards,
+    };
+    let mut first = resumed.documents[..first_document]
+        .last()
+        .map_or(0, |document| document.first + document.tokens);
+    // the tokens of the first document that are in the reused shards
+    let mut skip = (start - first) as usize;
+    let tokenize = |path: &PathBuf| -> io::Result<(String, Vec<u32>)> {
+        let text = fs::read(path)?;
+        let sum = checksum(&text);
+        let text = String::from_utf8(text).map_err(|e| {
+            let message = format!("{} is not valid UTF-8: {}", path.display(), e);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+        let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+        let special = options.add_special_tokens;
+        tokenizer.extend_tokens_into(&text, &mut ids, &mut ranges, None, special, special);
+        if let Some(id) = options.end_of_document {
+            ids.push(id as u32);
+        }
+        Ok((sum, ids))
+    };
+    for batch in inputs[first_document..].chunks(BATCH) {
+        #[cfg(feature = "rayon")]
+        let tokenized: Vec<_> = if options.parallel {
+            batch.par_iter().map(tokenize).collect()
+        } else {
+            batch.iter().map(tokenize).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let tokenized: Vec<_> = batch.iter().map(tokenize).collect();
+        for (path, tokenized) in batch.iter().zip(tokenized) {
+            let (sum, ids) = tokenized?;
+            writeln!(
+                shards.index,
+                "document\t{}\t{}\t{}\t{}",
+                first,
+                ids.len(),
+                sum,
+                path.display()
+            )?;
+            first += ids.len() as u64;
+            shards.push(&ids[skip.min(ids.len())..])?;
+            skip = skip.saturating_sub(ids.len());
+        }
+    }
+    if shards.tokens > 0 {
+        shards.flush()?;
+    }
+    shards.index.flush()?;
+    // remove the shards of an earlier run of a larger corpus
+    for i in shards.next..resumed.shards.len() {
+        let _ = fs::remove_file(out_dir.join(shard_name(i)));
+    }
+    Ok(CorpusSummary {
+        documents: inputs.len(),
+        tokens: first,
+        shards: shards.next,
+        reused_shards,
+        id_width,
+    })
+}
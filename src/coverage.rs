@@ -0,0 +1,236 @@
+use crate::clean::{self, CleanAction};
+use crate::{AlephAlphaTokenizer, TokenID, UnknownSeparator, WhitespaceMode};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// A violation of the invariants of token ranges, as found by
+/// [`AlephAlphaTokenizer::validate_ranges`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RangeError {
+    /// There are not as many ranges as ids
+    LengthMismatch {
+        /// the number of ids
+        ids: usize,
+        /// the number of ranges
+        ranges: usize,
+    },
+    /// A range ends before it starts, or after the end of the text
+    OutOfBounds {
+        /// the index of the token
+        index: usize,
+    },
+    /// A range starts or ends within a char, and isn't that of a byte token
+    NotOnCharBoundary {
+        /// the index of the token
+        index: usize,
+    },
+    /// A range starts before the previous one ends
+    Overlapping {
+        /// the index of the token
+        index: usize,
+    },
+    /// The range of a token that isn't special is empty
+    EmptyToken {
+        /// the index of the token
+        index: usize,
+    },
+    /// A part of the text that isn't whitespace is in no range
+    Uncovered {
+        /// the index of the first token after the part, or the number of
+        /// tokens if the part is at the end
+        index: usize,
+        /// the byte offset of the first char in no range
+        offset: usize,
+    },
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::LengthMismatch { ids, ranges } => {
+                write!(f, "there are {} ranges for {} ids", ranges, ids)
+            }
+            RangeError::OutOfBounds { index } => {
+                write!(f, "the range of token {} is out of bounds", index)
+            }
+            RangeError::NotOnCharBoundary { index } => {
+                write!(f, "the range of token {} splits a char", index)
+            }
+            RangeError::Overlapping { index } => write!(
+                f,
+                "the range of token {} starts before the previous one ends",
+                index
+            ),
+            RangeError::EmptyToken { index } => {
+                write!(
+                    f,
+                    "the range of token {} is empty, but it isn't special",
+                    index
+                )
+            }
+            RangeError::Uncovered { index, offset } => write!(
+                f,
+                "the text at byte {} (before token {}) is in no range",
+                offset, index
+            ),
+        }
+    }
+}
+
+impl Error for RangeError {}
+
+// the byte offset of the first char in the part of the text that isn't
+// whitespace (or cleaned away, or a dropped separator)
+fn uncovered(
+    text: &str,
+    gap: Range<usize>,
+    whitespace: &WhitespaceMode,
+    clean: Option<&[(char, CleanAction)]>,
+    dropped: &[char],
+) -> Option<usize> {
+    let part = text.get(gap.clone())?;
+    part.char_indices()
+        .find(|&(_, c)| {
+            !whitespace.is_whitespace(c)
+                && clean.is_none_or(|chars| clean::action(chars, c) == CleanAction::Keep)
+                && !dropped.contains(&c)
+        })
+        .map(|(i, _)| gap.start + i)
+}
+
+impl AlephAlphaTokenizer {
+    /// Checks that the byte ranges of the tokens of a text, e.g. from
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into), make sense: each
+    /// range is within the text and, except for byte tokens, on char
+    /// boundaries, the ranges are in order without overlapping, only
+    /// special tokens (like the added `[CLS]` and `[SEP]`) have empty
+    /// ranges, and everything but whitespace (and the characters that
+    /// [`clean_text`](crate::AlephAlphaTokenizerBuilder::clean_text) cleans
+    /// or the dropped
+    /// [`fallback_separators`](crate::AlephAlphaTokenizerBuilder::fallback_separators))
+    /// is in a range.
+    ///
+    /// This takes one pass over the text and the ranges. In debug builds,
+    /// `tokens_into` checks its output with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, RangeError};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let text = "Grüße, Superman! ☃";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     vec!["[CLS]", "Grü", "##ße", "##,", "Super", "##man", "##!", "[UNK]", "[SEP]"],
+    ///     tokenizer.texts_of(&ids)
+    /// );
+    /// assert_eq!(&[0..0, 0..4, 4..7, 7..8], &ranges[..4]);
+    /// assert_eq!(Ok(()), tokenizer.validate_ranges(text, &ids, &ranges));
+    ///
+    /// let corrupt = |index: usize, range: std::ops::Range<usize>| {
+    ///     let mut ranges = ranges.clone();
+    ///     ranges[index] = range;
+    ///     tokenizer.validate_ranges(text, &ids, &ranges)
+    /// };
+    /// assert_eq!(
+    ///     Err(RangeError::LengthMismatch { ids: ids.len(), ranges: 1 }),
+    ///     tokenizer.validate_ranges(text, &ids, &ranges[..1])
+    /// );
+    /// let last = ranges.len() - 1;
+    /// assert_eq!(Err(RangeError::OutOfBounds { index: last }), corrupt(last, 50..50));
+    /// assert_eq!(Err(RangeError::OutOfBounds { index: 1 }), corrupt(1, 3..2));
+    /// // `ü` takes the bytes 2 and 3
+    /// assert_eq!(Err(RangeError::NotOnCharBoundary { index: 1 }), corrupt(1, 0..3));
+    /// assert_eq!(Err(RangeError::Overlapping { index: 2 }), corrupt(1, 0..7));
+    /// assert_eq!(Err(RangeError::EmptyToken { index: 1 }), corrupt(1, 0..0));
+    /// let error = corrupt(1, 1..4).unwrap_err();
+    /// assert_eq!(RangeError::Uncovered { index: 1, offset: 0 }, error);
+    /// assert_eq!("the text at byte 0 (before token 1) is in no range", error.to_string());
+    ///
+    /// // a word of soft hyphens, which rejoining hyphenated words ignores,
+    /// // is still covered, by `[UNK]`
+    /// let rejoining = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .rejoin_hyphenation(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let text = "a \u{AD} b";
+    /// rejoining.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] a [UNK] b [SEP]", rejoining.texts_of(&ids).join(" "));
+    /// assert_eq!(Ok(()), rejoining.validate_ranges(text, &ids, &ranges));
+    /// ids.remove(2);
+    /// ranges.remove(2);
+    /// assert_eq!(
+    ///     Err(RangeError::Uncovered { index: 2, offset: 2 }),
+    ///     rejoining.validate_ranges(text, &ids, &ranges)
+    /// );
+    /// ```
+    pub fn validate_ranges<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &[T],
+        token_ranges: &[Range<usize>],
+    ) -> Result<(), RangeError> {
+        if token_ids.len() != token_ranges.len() {
+            return Err(RangeError::LengthMismatch {
+                ids: token_ids.len(),
+                ranges: token_ranges.len(),
+            });
+        }
+        let whitespace = &self.options.whitespace;
+        let dropped = match self.options.unknown_separator {
+            UnknownSeparator::Unk => &[][..],
+            UnknownSeparator::Drop => &self.options.fallback_separators[..],
+        };
+        let mut covered = 0;
+        for (index, (id, range)) in token_ids.iter().zip(token_ranges).enumerate() {
+            if range.start > range.end || range.end > text.len() {
+                return Err(RangeError::OutOfBounds { index });
+            }
+            let id = id.clone().restore();
+            let on_boundary =
+                text.is_char_boundary(range.start) && text.is_char_boundary(range.end);
+            if !on_boundary
+                && !self
+                    .byte_ids
+                    .iter()
+                    .any(|&byte_id| u64::from(byte_id) == id)
+            {
+                return Err(RangeError::NotOnCharBoundary { index });
+            }
+            if range.start < covered {
+                return Err(RangeError::Overlapping { index });
+            }
+            // the lazy index isn't worth it for the few empty ranges
+            if range.is_empty() && !self.special_tokens.contains(&id) {
+                return Err(RangeError::EmptyToken { index });
+            }
+            if let Some(offset) = uncovered(
+                text,
+                covered..range.start,
+                whitespace,
+                self.clean_chars(),
+                dropped,
+            ) {
+                return Err(RangeError::Uncovered { index, offset });
+            }
+            covered = range.end;
+        }
+        match uncovered(
+            text,
+            covered..text.len(),
+            whitespace,
+            self.clean_chars(),
+            dropped,
+        ) {
+            Some(offset) => Err(RangeError::Uncovered {
+                index: token_ranges.len(),
+                offset,
+            }),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::matcher::{ends_on_char, PrefixMatcher};
+use fst::raw::Fst;
+use fst::Streamer;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+// the check of a cell that belongs to no node
+const FREE: u32 = u32::MAX;
+// the value of a node that ends no key (token ids are far smaller)
+const NO_VALUE: u64 = u64::MAX;
+
+/// A double-array trie of the keys of an FST, which finds the child of a
+/// node by an addition and a comparison instead of searching the
+/// transitions of the node, at the cost of more memory.
+///
+/// The root is in cell `0`. The child for byte `b` of the node in cell `n`
+/// is in cell `base[n] + b` if `check` of that cell is `n`.
+#[derive(Debug, Default)]
+pub(crate) struct DoubleArray {
+    base: Vec<u32>,
+    // the cell of the parent of each node, or `FREE`
+    check: Vec<u32>,
+    // the value of the key each node ends, or `NO_VALUE`
+    values: Vec<u64>,
+}
+
+impl DoubleArray {
+    // builds the trie of the (sorted) keys of the FST
+    pub(crate) fn from_fst<D: AsRef<[u8]>>(fst: &Fst<D>) -> Self {
+        let (mut keys, mut stream) = (Vec::with_capacity(fst.len()), fst.stream());
+        while let Some((key, output)) = stream.next() {
+            keys.push((key.to_vec(), output.value()));
+        }
+        DoubleArray::from_sorted(&keys)
+    }
+
+    fn from_sorted(keys: &[(Vec<u8>, u64)]) -> Self {
+        let mut trie = DoubleArray {
+            base: vec![0],
+            check: vec![FREE],
+            values: vec![NO_VALUE],
+        };
+        // the nodes whose children are yet to be placed, as their cell, their
+        // depth and the keys below them
+        let mut queue = VecDeque::new();
+        queue.push_back((0, 0, 0..keys.len()));
+        let (mut children, mut free) = (Vec::new(), FreeCells::default());
+        free.take(0);
+        while let Some((cell, depth, mut below)) = queue.pop_front() {
+            // the key ending here (if any) sorts before the longer ones
+            if below.start < below.end && keys[below.start].0.len() == depth {
+                trie.values[cell] = keys[below.start].1;
+                below.start += 1;
+            }
+            // the keys are grouped by their byte at `depth`
+            children.clear();
+            while below.start < below.end {
+                let b = keys[below.start].0[depth];
+                let end = below.start
+                    + keys[below.clone()]
+                        .iter()
+                        .take_while(|(key, _)| key[depth] == b)
+                        .count();
+                children.push((b, below.start..end));
+                below.start = end;
+            }
+            if children.is_empty() {
+                continue;
+            }
+            let base = trie.find_base(&children, &mut free);
+            trie.base[cell] = base as u32;
+            for (b, keys) in children.drain(..) {
+                let child = base + usize::from(b);
+                trie.check[child] = cell as u32;
+                queue.push_back((child, depth + 1, keys));
+            }
+        }
+        trie
+    }
+
+    // finds a base where all children fit into free cells, and makes room
+    // for them
+    fn find_base(&mut self, children: &[(u8, Range<usize>)], free: &mut FreeCells) -> usize {
+        let first = usize::from(children[0].0);
+        // only the bases that put the first child into a free cell can fit
+        let mut cell = free.next(first + 1);
+        let base = loop {
+            let base = cell - first;
+            if children[1..].iter().all(|&(b, _)| {
+                self.check
+                    .get(base + usize::from(b))
+                    .is_none_or(|&c| c == FREE)
+            }) {
+                break base;
+            }
+            cell = free.next(cell + 1);
+        };
+        let end = base + usize::from(children[children.len() - 1].0) + 1;
+        if self.check.len() < end {
+            self.base.resize(end, 0);
+            self.check.resize(end, FREE);
+            self.values.resize(end, NO_VALUE);
+        }
+        for &(b, _) in children {
+            free.take(base + usize::from(b));
+        }
+        base
+    }
+
+    #[inline]
+    fn child(&self, cell: usize, b: u8) -> Option<usize> {
+        let child = self.base[cell] as usize + usize::from(b);
+        // a leaf has a base of `0`, but no cell has it as its parent
+        (self.check.get(child) == Some(&(cell as u32))).then_some(child)
+    }
+}
+
+// the free cells, where each taken cell points to a later cell that may be
+// free, so finding the next free cell skips the runs of taken cells (the
+// cells beyond the end are all free)
+#[derive(Default)]
+struct FreeCells {
+    next: Vec<usize>,
+}
+
+impl FreeCells {
+    // finds the first free cell from `cell` on
+    fn next(&mut self, cell: usize) -> usize {
+        let mut free = cell;
+        while free < self.next.len() && self.next[free] != free {
+            free = self.next[free];
+        }
+        // point the cells on the way to the free one
+        let mut cell = cell;
+        while cell < free.min(self.next.len()) {
+            let next = self.next[cell];
+            self.next[cell] = free;
+            cell = next;
+        }
+        free
+    }
+
+    fn take(&mut self, cell: usize) {
+        if self.next.len() <= cell {
+            self.next.extend(self.next.len()..=cell);
+        }
+        self.next[cell] = cell + 1;
+    }
+}
+
+impl PrefixMatcher for DoubleArray {
+    #[inline]
+    fn longest_prefix(&self, input: &[u8]) -> Option<(usize, u64)> {
+        let (mut cell, mut last_match) = (0, None);
+        for (i, &b) in input.iter().enumerate() {
+            match self.child(cell, b) {
+                Some(child) => cell = child,
+                None => break,
+            }
+            let value = self.values[cell];
+            if value != NO_VALUE && ends_on_char(input, i + 1) {
+                last_match = Some((i + 1, value));
+            }
+        }
+        last_match
+    }
+
+    fn find_prefixes(&self, input: &[u8], matches: &mut Vec<(usize, u64)>) {
+        matches.clear();
+        let mut cell = 0;
+        for (i, &b) in input.iter().enumerate() {
+            match self.child(cell, b) {
+                Some(child) => cell = child,
+                None => break,
+            }
+            let value = self.values[cell];
+            if value != NO_VALUE && ends_on_char(input, i + 1) {
+                matches.push((i + 1, value));
+            }
+        }
+    }
+}
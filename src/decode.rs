@@ -0,0 +1,316 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/// The error of decoding an id that is not in the vocabulary, e.g. one
+/// generated by a model with a larger vocabulary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownIdError {
+    /// the position of the id among the decoded ones (since the last
+    /// [`finish`](StreamingDecoder::finish))
+    pub index: usize,
+}
+
+impl fmt::Display for UnknownIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "id {} to decode is not in the vocabulary", self.index)
+    }
+}
+
+impl Error for UnknownIdError {}
+
+/// Decodes the tokens of generated text one at a time, see
+/// [`AlephAlphaTokenizer::streaming_decoder`].
+///
+/// The text of each token is printable right away: a token that starts a
+/// word gets a space before it (unless it's the first one), and a follower
+/// token is appended without its continuing subword prefix, so the pieces
+/// add up to what [`AlephAlphaTokenizer::decode`] gives for all ids at once.
+/// With an [end-of-word suffix](crate::MarkerScheme::EndOfWordSuffix), the
+/// token after one that ends a word (or a special token) starts a word, and
+/// the suffix is left out.
+///
+/// With [byte fallback](crate::AlephAlphaTokenizerBuilder::byte_fallback),
+/// the byte tokens (`<0x00>` to `<0xFF>`) become their bytes, which are
+/// held back until they form chars, and a run of them is a word of its own.
+/// [`finish`](StreamingDecoder::finish) returns the bytes left over, with
+/// replacement characters for those that form no char.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+/// for text in &[
+///     "Ich esse Steak.",
+///     "Der Hund spielt im Garten. ☃ [MASK]",
+///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+///     "Liegen der Bundesregierung statistische Daten zu Todesfällen vor?",
+///     "",
+/// ] {
+///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+///     for &skip_special_tokens in &[true, false] {
+///         let mut decoder = tokenizer.streaming_decoder(skip_special_tokens);
+///         let mut streamed = String::new();
+///         for &id in &ids {
+///             streamed.push_str(decoder.push(id).unwrap());
+///         }
+///         streamed.push_str(decoder.finish());
+///         assert_eq!(tokenizer.decode(&ids, skip_special_tokens), streamed);
+///     }
+/// }
+///
+/// let mut decoder = tokenizer.streaming_decoder(true);
+/// let id = |token| tokenizer.id_of(token).unwrap();
+/// assert_eq!(Ok(""), decoder.push(id("[CLS]")));
+/// assert_eq!(Ok("Super"), decoder.push(id("Super")));
+/// assert_eq!(Ok("man"), decoder.push(id("##man")));
+/// assert_eq!(Ok(" Ball"), decoder.push(id("Ball")));
+/// assert_eq!("", decoder.finish());
+///
+/// // after `finish`, the next token starts a new text
+/// assert_eq!(Ok("Ball"), decoder.push(id("Ball")));
+/// // ids beyond the vocabulary are errors, not panics
+/// let error = decoder.push(tokenizer.vocab_size() as u64).unwrap_err();
+/// assert_eq!(1, error.index);
+/// ```
+///
+/// With byte fallback:
+///
+/// ```
+/// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+///
+/// let mut vocab = "[UNK]\n[CLS]\n[SEP]\nIch\ness\n##e\n".to_string();
+/// for byte in 0..=255u8 {
+///     vocab += &format!("<0x{:02X}>\n", byte);
+/// }
+/// let tokenizer = AlephAlphaTokenizer::builder()
+///     .vocab_reader(vocab.as_bytes())
+///     .byte_fallback(true)
+///     .build()
+///     .unwrap();
+/// let text = "Ich esse ☃";
+/// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+/// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+/// assert_eq!(
+///     "[CLS] Ich ess ##e <0xE2> <0x98> <0x83> [SEP]",
+///     tokenizer.texts_of(&ids).join(" ")
+/// );
+/// assert_eq!(text, tokenizer.decode(&ids, true));
+///
+/// // the bytes wait until they form a char
+/// let mut decoder = tokenizer.streaming_decoder(true);
+/// let pieces: Vec<_> = ids
+///     .iter()
+///     .map(|&id| decoder.push(id).unwrap().to_string())
+///     .collect();
+/// assert_eq!(vec!["", "Ich", " ess", "e", " ", "", "☃", ""], pieces);
+///
+/// // and those left over are replaced
+/// decoder.push(ids[1]).unwrap();
+/// decoder.push(ids[4]).unwrap();
+/// assert_eq!("\u{FFFD}", decoder.finish());
+/// ```
+#[derive(Clone)]
+pub struct StreamingDecoder<'t> {
+    tokenizer: &'t AlephAlphaTokenizer,
+    skip_special_tokens: bool,
+    // whether a token was output since the start or `finish`
+    started: bool,
+    // whether the last token ended a word, for an end-of-word suffix
+    ended: bool,
+    // whether the last token was a byte token
+    in_bytes: bool,
+    // the bytes of byte tokens that don't form a char yet
+    bytes: Vec<u8>,
+    // the number of ids pushed since the start or `finish`
+    pushed: usize,
+    // the text of the last `push`
+    piece: String,
+}
+
+impl<'t> StreamingDecoder<'t> {
+    /// Decodes the next token, returning the text to append to the text so
+    /// far, which is empty for a skipped special token or a byte that
+    /// doesn't complete a char.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the id is not in the vocabulary, leaving the text so far as
+    /// it is.
+    pub fn push<T: TokenID>(&mut self, id: T) -> Result<&str, UnknownIdError> {
+        self.piece.clear();
+        let tokenizer = self.tokenizer;
+        let index = self.pushed;
+        let id = id
+            .try_restore()
+            .filter(|&id| usize::try_from(id).is_ok_and(|id| id < tokenizer.tokens.len()))
+            .ok_or(UnknownIdError { index })?;
+        self.pushed += 1;
+        let token = tokenizer.text_of(id).trim();
+        let suffix = tokenizer.end_of_word_suffix.as_str();
+        let prefix = tokenizer.continuing_subword_prefix();
+        if let Some(byte) = tokenizer.byte_of(id) {
+            // a run of bytes is a word of its own
+            if !self.in_bytes && self.started && (suffix.is_empty() || self.ended) {
+                self.piece.push(' ');
+            }
+            self.bytes.push(byte);
+            self.flush_bytes(false);
+            self.started = true;
+            self.ended = true;
+            self.in_bytes = true;
+            return Ok(&self.piece);
+        }
+        self.flush_bytes(true);
+        self.in_bytes = false;
+        if self.skip_special_tokens && tokenizer.is_special(id) {
+            return Ok(&self.piece);
+        }
+        if !suffix.is_empty() {
+            if self.started && self.ended {
+                self.piece.push(' ');
+            }
+            let word = token
+                .strip_suffix(suffix)
+                .filter(|_| !tokenizer.is_special(id));
+            self.piece.push_str(word.unwrap_or(token));
+            self.ended = word.is_some() || tokenizer.is_special(id);
+            self.started = self.started || !self.piece.is_empty();
+            return Ok(&self.piece);
+        }
+        match token.strip_prefix(prefix) {
+            Some(follower) if !prefix.is_empty() && self.started => self.piece.push_str(follower),
+            _ => {
+                if self.started {
+                    self.piece.push(' ');
+                }
+                self.piece.push_str(token);
+            }
+        }
+        self.started = self.started || !self.piece.is_empty();
+        Ok(&self.piece)
+    }
+
+    /// Ends the text, returning what is left of it, so the next token starts
+    /// a new text.
+    ///
+    /// As the space between two words is only output with the second one,
+    /// only the bytes of byte tokens that don't form a char are held back,
+    /// which this returns as replacement characters.
+    pub fn finish(&mut self) -> &str {
+        self.piece.clear();
+        self.flush_bytes(true);
+        self.started = false;
+        self.ended = true;
+        self.in_bytes = false;
+        self.pushed = 0;
+        &self.piece
+    }
+
+    // moves the bytes that form chars to the piece, and with `lossy` the
+    // others, too, as replacement characters; a byte that cannot start or
+    // continue a char is replaced right away
+    fn flush_bytes(&mut self, lossy: bool) {
+        loop {
+            match std::str::from_utf8(&self.bytes) {
+                Ok(text) => {
+                    self.piece.push_str(text);
+                    self.bytes.clear();
+                    return;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    let end = match e.error_len() {
+                        Some(len) => valid + len,
+                        None if lossy => self.bytes.len(),
+                        None => valid,
+                    };
+                    self.piece
+                        .push_str(&String::from_utf8_lossy(&self.bytes[..end]));
+                    self.bytes.drain(..end);
+                    if e.error_len().is_none() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Joins the tokens of the ids, with a space before each token that
+    /// starts a word, merging follower tokens onto the token before them
+    /// without their continuing subword prefix, and leaving out the special
+    /// tokens if `skip_special_tokens` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ich esse Steak.", &mut ids, &mut ranges, None);
+    /// assert_eq!("Ich esse Steak.", tokenizer.decode(&ids, true));
+    /// assert_eq!("[CLS] Ich esse Steak. [SEP]", tokenizer.decode(&ids, false));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an id is not in the vocabulary, like
+    /// [`text_of`](AlephAlphaTokenizer::text_of); use
+    /// [`try_decode`](AlephAlphaTokenizer::try_decode) for ids that may be
+    /// invalid.
+    pub fn decode<T: TokenID>(&self, ids: &[T], skip_special_tokens: bool) -> String {
+        self.try_decode(ids, skip_special_tokens)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`decode`](AlephAlphaTokenizer::decode), but fails with the
+    /// position of the first id that is not in the vocabulary instead of
+    /// panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, UnknownIdError};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let ids = [1671, 229, 145, u32::MAX];
+    /// assert_eq!(Ok("Ich esse".to_string()), tokenizer.try_decode(&ids[..3], true));
+    /// assert_eq!(Err(UnknownIdError { index: 3 }), tokenizer.try_decode(&ids, true));
+    /// assert_eq!(Err(UnknownIdError { index: 0 }), tokenizer.try_decode(&[-1i64], true));
+    /// ```
+    pub fn try_decode<T: TokenID>(
+        &self,
+        ids: &[T],
+        skip_special_tokens: bool,
+    ) -> Result<String, UnknownIdError> {
+        let mut decoder = self.streaming_decoder(skip_special_tokens);
+        let mut text = String::new();
+        for id in ids {
+            text.push_str(decoder.push(id.clone())?);
+        }
+        text.push_str(decoder.finish());
+        Ok(text)
+    }
+
+    /// Creates a decoder to decode the tokens of a text one at a time, e.g.
+    /// to show generated text as it comes, see [`StreamingDecoder`].
+    pub fn streaming_decoder(&self, skip_special_tokens: bool) -> StreamingDecoder<'_> {
+        StreamingDecoder {
+            tokenizer: self,
+            skip_special_tokens,
+            started: false,
+            ended: true,
+            in_bytes: false,
+            bytes: Vec::new(),
+            pushed: 0,
+            piece: String::new(),
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Comparing the standalone tokenization with the huggingface integration
+
+use crate::AlephAlphaTokenizer;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use tokenizers::pre_tokenizers::bert::BertPreTokenizer;
+use tokenizers::pre_tokenizers::whitespace::WhitespaceSplit;
+use tokenizers::tokenizer::{PreTokenizedString, PreTokenizer};
+
+/// A token as produced by one of the paths compared by [`compare_paths`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathToken {
+    /// The token id
+    pub id: u64,
+    /// The text of the token in the vocabulary
+    pub text: String,
+    /// The range of the token in `char`s
+    pub offsets: Range<usize>,
+}
+
+impl fmt::Display for PathToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?} at {:?}", self.id, self.text, self.offsets)
+    }
+}
+
+/// How the standalone tokenization and the huggingface integration differ,
+/// as returned by [`compare_paths`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// The paths disagree on a token, or one has more tokens than the other
+    Token {
+        /// The index of the first differing token (without `[CLS]`)
+        index: usize,
+        /// The token of the standalone tokenization, if any
+        standalone: Option<PathToken>,
+        /// The token of the huggingface integration, if any
+        huggingface: Option<PathToken>,
+    },
+    /// The huggingface integration returned an error
+    Failed(String),
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |token: &Option<PathToken>| {
+            token
+                .as_ref()
+                .map_or_else(|| "no token".to_string(), PathToken::to_string)
+        };
+        match self {
+            Divergence::Token {
+                index,
+                standalone,
+                huggingface,
+            } => write!(
+                f,
+                "token {} differs: standalone has {}, huggingface has {}",
+                index,
+                side(standalone),
+                side(huggingface)
+            ),
+            Divergence::Failed(e) => write!(f, "huggingface tokenization failed: {}", e),
+        }
+    }
+}
+
+impl Error for Divergence {}
+
+/// Tokenizes the text with [`tokens_into_chars`] and with the [`Model`]
+/// implementation after a `WhitespaceSplit` pre-tokenizer (or a
+/// `BertPreTokenizer` if the tokenizer [splits punctuation]), and reports the
+/// first token where the ids or offsets differ.
+///
+/// The `[CLS]` and `[SEP]` tokens are left out, as the `Model` doesn't add
+/// them. This requires the `huggingface` feature; the `paths` fuzz targets
+/// use it to keep both paths in sync.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{compare_paths, AlephAlphaTokenizer, Divergence, Preset};
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// for text in &[
+///     "Ich esse Steak.",
+///     "  Grüße ☃ aus\u{a0}Köln\n",
+///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+///     "",
+/// ] {
+///     assert_eq!(Ok(()), compare_paths(&tokenizer, text), "{:?}", text);
+/// }
+///
+/// // the huggingface integration leaves lowercasing to the normalizer
+/// let uncased = AlephAlphaTokenizer::builder()
+///     .vocab_path("vocab.txt")
+///     .with_options(Preset::BertUncased.options())
+///     .build()
+///     .unwrap();
+/// let divergence = compare_paths(&uncased, "Ich esse").unwrap_err();
+/// match &divergence {
+///     Divergence::Token { index, standalone, huggingface } => {
+///         assert_eq!(0, *index);
+///         assert_eq!("ich", standalone.as_ref().unwrap().text);
+///         assert_eq!("Ich", huggingface.as_ref().unwrap().text);
+///     }
+///     other => panic!("{:?}", other),
+/// }
+/// assert_eq!(
+///     r#"token 0 differs: standalone has 1169 "ich" at 0..3, huggingface has 1671 "Ich" at 0..3"#,
+///     divergence.to_string()
+/// );
+///
+/// // splitting punctuation matches huggingface's `BertPreTokenizer`
+/// let splitting = AlephAlphaTokenizer::builder()
+///     .vocab_path("vocab.txt")
+///     .split_punctuation(true)
+///     .build()
+///     .unwrap();
+/// for text in &[
+///     "Ich esse Steak.",
+///     "Der Hund spielt im Garten.",
+///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+///         Borreliose vor und wenn ja, wie lauten diese?",
+///     "„Wirklich?!“ – (ja…)",
+///     "",
+/// ] {
+///     assert_eq!(Ok(()), compare_paths(&splitting, text), "{:?}", text);
+/// }
+///
+/// // and so does byte fallback
+/// let mut vocab = "[UNK]\n[CLS]\n[SEP]\nSuper\n##man\n".to_string();
+/// for byte in 0..=255u8 {
+///     vocab += &format!("<0x{:02X}>\n", byte);
+/// }
+/// let bytes = AlephAlphaTokenizer::builder()
+///     .vocab_reader(vocab.as_bytes())
+///     .byte_fallback(true)
+///     .build()
+///     .unwrap();
+/// assert_eq!(Ok(()), compare_paths(&bytes, "Superman Jö ☃ Supermän"));
+/// ```
+///
+/// [`tokens_into_chars`]: AlephAlphaTokenizer::tokens_into_chars
+/// [splits punctuation]: crate::AlephAlphaTokenizerBuilder::split_punctuation
+pub fn compare_paths(tokenizer: &AlephAlphaTokenizer, text: &str) -> Result<(), Divergence> {
+    let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    tokenizer.tokens_into_chars(text, &mut ids, &mut ranges, None);
+    let skip_prefix = tokenizer.options.add_prefix && tokenizer.prefix.is_some();
+    let skip_suffix = tokenizer.options.add_suffix && tokenizer.suffix.is_some();
+    let end = ids.len() - usize::from(skip_suffix);
+    let standalone: Vec<PathToken> = ids[..end]
+        .iter()
+        .zip(&ranges[..end])
+        .skip(usize::from(skip_prefix))
+        .map(|(&id, range)| PathToken {
+            id,
+            text: tokenizer.text_of(id).to_string(),
+            offsets: range.clone(),
+        })
+        .collect();
+
+    let failed = |e: Box<dyn Error + Send + Sync>| Divergence::Failed(e.to_string());
+    let mut words = PreTokenizedString::from(text);
+    if tokenizer.options.split_punctuation {
+        BertPreTokenizer.pre_tokenize(&mut words)
+    } else {
+        WhitespaceSplit.pre_tokenize(&mut words)
+    }
+    .map_err(failed)?;
+    let encoding = tokenizer.tokenize_into_encoding(words, 0).map_err(failed)?;
+    let huggingface: Vec<PathToken> = encoding
+        .get_ids()
+        .iter()
+        .zip(encoding.get_offsets())
+        .map(|(&id, &(start, end))| PathToken {
+            id: u64::from(id),
+            text: tokenizer.text_of(id).to_string(),
+            offsets: start..end,
+        })
+        .collect();
+
+    let differs = |(s, h): (&PathToken, &PathToken)| s.id != h.id || s.offsets != h.offsets;
+    let index = standalone
+        .iter()
+        .zip(&huggingface)
+        .position(differs)
+        .or_else(|| {
+            if standalone.len() == huggingface.len() {
+                None
+            } else {
+                Some(standalone.len().min(huggingface.len()))
+            }
+        });
+    match index {
+        Some(index) => Err(Divergence::Token {
+            index,
+            standalone: standalone.get(index).cloned(),
+            huggingface: huggingface.get(index).cloned(),
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,588 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// Options for a single call to
+/// [`tokens_into_with`](AlephAlphaTokenizer::tokens_into_with) or
+/// [`tokens_into_pair_with`](AlephAlphaTokenizer::tokens_into_pair_with).
+///
+/// Unlike the [`TokenizerOptions`](crate::TokenizerOptions), these can differ
+/// between calls on the same tokenizer.
+///
+/// With the `serde` feature, these can be read from configuration files;
+/// missing fields take their default value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct EncodeOptions {
+    /// Reject texts longer than this many bytes, e.g. to guard a service
+    /// against huge requests. By default, there is no limit.
+    pub max_input_bytes: Option<usize>,
+    /// Truncate the tokens, including `[CLS]` and `[SEP]`, to at most this
+    /// many, e.g. the maximum sequence length of a model. By default,
+    /// nothing is truncated.
+    pub max_length: Option<usize>,
+    /// Which text loses tokens when truncating to `max_length`
+    pub truncation: TruncationStrategy,
+    /// When truncating, remove all tokens of a word instead of only some of
+    /// them, so no word is cut in half. This may leave fewer than
+    /// `max_length` tokens.
+    pub truncate_whole_words: bool,
+    /// Leave out the `[CLS]` and `[SEP]` tokens even if the tokenizer adds
+    /// them, e.g. to build a prompt format from several texts.
+    pub skip_special_tokens: bool,
+}
+
+/// Which text loses tokens when truncating to
+/// [`EncodeOptions::max_length`].
+///
+/// Tokens are always removed from the end of a text; `[CLS]` and `[SEP]`
+/// are kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TruncationStrategy {
+    /// Remove tokens from the text that currently has more of them (the
+    /// second one on a tie), one at a time, until the tokens fit
+    #[default]
+    LongestFirst,
+    /// Only remove tokens from the first text
+    OnlyFirst,
+    /// Only remove tokens from the second text, so single texts cannot be
+    /// truncated
+    OnlySecond,
+}
+
+/// The errors that can occur when tokenizing with [`EncodeOptions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The text is longer than [`EncodeOptions::max_input_bytes`]
+    InputTooLarge {
+        /// the length of the text in bytes
+        len: usize,
+        /// the configured maximum
+        max: usize,
+    },
+    /// A token id cannot be represented by the requested id type
+    IdOverflow {
+        /// the token id
+        id: u64,
+    },
+    /// The tokens cannot be truncated to [`EncodeOptions::max_length`] with
+    /// the [`TruncationStrategy`], e.g. because `[CLS]` and `[SEP]` alone
+    /// don't fit
+    CannotTruncate {
+        /// the number of tokens before truncating
+        len: usize,
+        /// the configured maximum
+        max_length: usize,
+    },
+    /// A window of
+    /// [`try_tokens_into_windows`](AlephAlphaTokenizer::try_tokens_into_windows)
+    /// has no room for any token besides `[CLS]` and `[SEP]`
+    WindowTooSmall {
+        /// the maximum number of tokens per window
+        max_len: usize,
+    },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::InputTooLarge { len, max } => write!(
+                f,
+                "input of {} bytes exceeds the maximum of {} bytes",
+                len, max
+            ),
+            EncodeError::IdOverflow { id } => {
+                write!(f, "token id {} does not fit the requested id type", id)
+            }
+            EncodeError::CannotTruncate { len, max_length } => write!(
+                f,
+                "cannot truncate {} tokens to the maximum of {} tokens",
+                len, max_length
+            ),
+            EncodeError::WindowTooSmall { max_len } => write!(
+                f,
+                "a window of {} tokens has no room besides the special tokens",
+                max_len
+            ),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+// the tokens of one text without `[CLS]` and `[SEP]`, with the token index
+// ranges of its words
+struct Segment<T> {
+    ids: Vec<T>,
+    ranges: Vec<Range<usize>>,
+    words: Vec<Range<usize>>,
+}
+
+impl<T> Segment<T> {
+    // the length after removing the last token, or the last word
+    fn shorter(&self, len: usize, whole_words: bool) -> usize {
+        if !whole_words {
+            return len - 1;
+        }
+        self.words
+            .iter()
+            .rev()
+            .map(|word| word.start)
+            .find(|&start| start < len)
+            .unwrap_or(0)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.ids.truncate(len);
+        self.ranges.truncate(len);
+        self.words.retain(|word| word.start < len);
+        if let Some(word) = self.words.last_mut() {
+            word.end = word.end.min(len);
+        }
+    }
+}
+
+// checks a text against `max_input_bytes`
+fn check_input(text: &str, options: &EncodeOptions) -> Result<(), EncodeError> {
+    match options.max_input_bytes {
+        Some(max) if text.len() > max => Err(EncodeError::InputTooLarge {
+            len: text.len(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// truncates the segments to `max_length` minus the number of special tokens
+fn truncate<T>(
+    first: &mut Segment<T>,
+    mut second: Option<&mut Segment<T>>,
+    special: usize,
+    options: &EncodeOptions,
+    max_length: usize,
+) -> Result<(), EncodeError> {
+    let (mut first_len, mut second_len) =
+        (first.ids.len(), second.as_ref().map_or(0, |s| s.ids.len()));
+    let len = first_len + second_len + special;
+    let error = EncodeError::CannotTruncate { len, max_length };
+    let budget = max_length
+        .checked_sub(special)
+        .ok_or_else(|| error.clone())?;
+    let whole_words = options.truncate_whole_words;
+    while first_len + second_len > budget {
+        let truncate_first = match options.truncation {
+            TruncationStrategy::LongestFirst => first_len > second_len,
+            TruncationStrategy::OnlyFirst => true,
+            TruncationStrategy::OnlySecond => false,
+        };
+        match (truncate_first, second.as_deref()) {
+            (true, _) if first_len > 0 => first_len = first.shorter(first_len, whole_words),
+            (false, Some(second)) if second_len > 0 => {
+                second_len = second.shorter(second_len, whole_words)
+            }
+            _ => return Err(error),
+        }
+    }
+    first.truncate(first_len);
+    if let Some(second) = second.as_mut() {
+        second.truncate(second_len);
+    }
+    Ok(())
+}
+
+impl AlephAlphaTokenizer {
+    // tokenizes a text without `[CLS]` and `[SEP]`
+    fn segment<T: TokenID>(&self, text: &str) -> Segment<T> {
+        let mut segment = Segment {
+            ids: Vec::new(),
+            ranges: Vec::new(),
+            words: Vec::new(),
+        };
+        self.words_into(
+            text,
+            0,
+            &mut segment.ids,
+            &mut segment.ranges,
+            Some(&mut segment.words),
+        );
+        segment
+    }
+
+    // appends the `[CLS]` token if asked to
+    fn push_prefix<T: TokenID>(
+        &self,
+        add_prefix: bool,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+    ) {
+        if let (true, Some(id)) = (add_prefix, self.prefix) {
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(0..0);
+        }
+    }
+
+    // appends the segment and (if asked to) a `[SEP]` after it
+    fn push_segment<T: TokenID>(
+        &self,
+        segment: Segment<T>,
+        add_suffix: bool,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: &mut Option<&mut Vec<Range<usize>>>,
+    ) {
+        let start = token_ids.len();
+        if let Some(w) = words.as_mut() {
+            w.extend(
+                segment
+                    .words
+                    .iter()
+                    .map(|word| word.start + start..word.end + start),
+            );
+        }
+        let pos = segment.ranges.last().map_or(0, |range| range.end);
+        token_ids.extend(segment.ids);
+        token_ranges.extend(segment.ranges);
+        if let (true, Some(id)) = (add_suffix, self.suffix) {
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(pos..pos);
+        }
+    }
+
+    // whether to add the `[CLS]` and `[SEP]` tokens (if present) with the
+    // options
+    fn specials_with(&self, options: &EncodeOptions) -> (bool, bool) {
+        let add = !options.skip_special_tokens;
+        (
+            add && self.options.add_prefix,
+            add && self.options.add_suffix,
+        )
+    }
+
+    // the number of `[CLS]` and `[SEP]` tokens around a single text
+    fn special_count(&self, (add_prefix, add_suffix): (bool, bool)) -> (usize, usize) {
+        let prefix = (add_prefix && self.prefix.is_some()) as usize;
+        let suffix = (add_suffix && self.suffix.is_some()) as usize;
+        (prefix, suffix)
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but checks
+    /// the text against the given [`EncodeOptions`] first, and truncates the
+    /// tokens to their `max_length`.
+    ///
+    /// If the text is rejected, the outputs are left empty. The `words`
+    /// (if given) only include the remaining tokens of a truncated word.
+    ///
+    /// # Limits
+    ///
+    /// Token ranges are `usize` byte offsets, so any text that fits in
+    /// memory can be tokenized without overflow. The time is linear in the
+    /// length of the text, but the tokens of a word are kept until the whole
+    /// word is matched, so very long "words" (e.g. base64 blobs) use a lot of
+    /// memory and often end up as a single `[UNK]` token anyway. Runs of
+    /// whitespace, however long, are skipped in one go and produce no tokens.
+    /// Set `max_input_bytes` when the texts come from untrusted sources.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeError, EncodeOptions};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let options = EncodeOptions { max_input_bytes: Some(14), ..EncodeOptions::default() };
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer
+    ///     .tokens_into_with("Ich esse Steak", &mut ids, &mut ranges, None, &options)
+    ///     .unwrap();
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 4], &ids[..]);
+    ///
+    /// let text = "Ich esse Steak ".repeat(1000);
+    /// assert_eq!(
+    ///     Err(EncodeError::InputTooLarge { len: 15000, max: 14 }),
+    ///     tokenizer.tokens_into_with(&text, &mut ids, &mut ranges, None, &options)
+    /// );
+    /// assert!(ids.is_empty() && ranges.is_empty());
+    ///
+    /// // megabytes of blank padding
+    /// let padding = " \n\t\u{3000}".repeat(1 << 18);
+    /// let text = format!("{}Ich{}esse Steak{}", padding, padding, padding);
+    /// let unlimited = EncodeOptions::default();
+    /// tokenizer.tokens_into_with(&text, &mut ids, &mut ranges, None, &unlimited).unwrap();
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 4], &ids[..]);
+    /// assert_eq!(ranges[1].start, padding.len());
+    ///
+    /// // truncating keeps [CLS] and [SEP]
+    /// let options = EncodeOptions { max_length: Some(5), ..EncodeOptions::default() };
+    /// let mut words = Vec::new();
+    /// let text = "Ich esse Steak";
+    /// tokenizer.tokens_into_with(text, &mut ids, &mut ranges, Some(&mut words), &options).unwrap();
+    /// assert_eq!(&[3, 1671, 229, 145, 4], &ids[..]);
+    /// assert_eq!(&[0..0, 0..3, 4..6, 6..8, 8..8], &ranges[..]);
+    /// assert_eq!(&[1..2, 2..4], &words[..]);
+    ///
+    /// let options = EncodeOptions { max_length: Some(4), ..options };
+    /// tokenizer.tokens_into_with(text, &mut ids, &mut ranges, Some(&mut words), &options).unwrap();
+    /// assert_eq!(&[3, 1671, 229, 4], &ids[..]);
+    /// assert_eq!(&[1..2, 2..3], &words[..]);
+    /// // or remove all tokens of the words that don't fit
+    /// let options = EncodeOptions { truncate_whole_words: true, ..options };
+    /// tokenizer.tokens_into_with(text, &mut ids, &mut ranges, Some(&mut words), &options).unwrap();
+    /// assert_eq!(&[3, 1671, 4], &ids[..]);
+    /// assert_eq!(&[0..0, 0..3, 3..3], &ranges[..]);
+    ///
+    /// let options = EncodeOptions { max_length: Some(1), ..options };
+    /// assert_eq!(
+    ///     Err(EncodeError::CannotTruncate { len: 7, max_length: 1 }),
+    ///     tokenizer.tokens_into_with(text, &mut ids, &mut ranges, None, &options)
+    /// );
+    /// ```
+    ///
+    /// Without `[CLS]` and `[SEP]`, the tokens are those between them:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeOptions};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let skip = EncodeOptions { skip_special_tokens: true, ..EncodeOptions::default() };
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// let (mut inner_ids, mut inner_ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let mut inner_words = Vec::new();
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Grüße ☃ Supercalifragilistic",
+    ///     "",
+    /// ] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    ///     tokenizer
+    ///         .tokens_into_with(text, &mut inner_ids, &mut inner_ranges, Some(&mut inner_words), &skip)
+    ///         .unwrap();
+    ///     let middle = 1..ids.len() - 1;
+    ///     assert_eq!(&ids[middle.clone()], &inner_ids[..]);
+    ///     assert_eq!(&ranges[middle], &inner_ranges[..]);
+    ///     let shifted: Vec<_> = words.iter().map(|word| word.start - 1..word.end - 1).collect();
+    ///     assert_eq!(shifted, inner_words);
+    /// }
+    ///
+    /// // the `max_length` counts no [CLS] and [SEP] either
+    /// let options = EncodeOptions { max_length: Some(3), ..skip };
+    /// tokenizer
+    ///     .tokens_into_with("Ich esse Steak", &mut ids, &mut ranges, Some(&mut words), &options)
+    ///     .unwrap();
+    /// assert_eq!(&[1671, 229, 145], &ids[..]);
+    /// assert_eq!(&[0..3, 4..6, 6..8], &ranges[..]);
+    /// assert_eq!(&[0..1, 1..3], &words[..]);
+    /// ```
+    pub fn tokens_into_with<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+        options: &EncodeOptions,
+    ) -> Result<(), EncodeError> {
+        let mut words = words;
+        token_ids.clear();
+        token_ranges.clear();
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        check_input(text, options)?;
+        let specials = self.specials_with(options);
+        let max_length = match options.max_length {
+            Some(max_length) => max_length,
+            None => {
+                let (add_prefix, add_suffix) = specials;
+                self.extend_tokens_into(
+                    text,
+                    token_ids,
+                    token_ranges,
+                    words,
+                    add_prefix,
+                    add_suffix,
+                );
+                return Ok(());
+            }
+        };
+        let mut segment = self.segment(text);
+        let (prefix, suffix) = self.special_count(specials);
+        truncate(&mut segment, None, prefix + suffix, options, max_length)?;
+        self.push_prefix(specials.0, token_ids, token_ranges);
+        self.push_segment(segment, specials.1, token_ids, token_ranges, &mut words);
+        Ok(())
+    }
+
+    /// Like [`tokens_into_pair`](AlephAlphaTokenizer::tokens_into_pair), but
+    /// checks both texts against the given [`EncodeOptions`] first, and
+    /// truncates the tokens to their `max_length`.
+    ///
+    /// If a text is rejected or cannot be truncated, the outputs are left
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, EncodeError, EncodeOptions, TruncationStrategy};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let (a, b) = ("Ein interessantes Beispiel Ich esse Steak.", "Superman");
+    /// let (mut ids, mut ranges, mut type_ids, mut words) =
+    ///     (Vec::<u64>::new(), Vec::new(), Vec::new(), Vec::new());
+    ///
+    /// // the much longer first text loses tokens, the second keeps all
+    /// let options = EncodeOptions { max_length: Some(8), ..EncodeOptions::default() };
+    /// tokenizer
+    ///     .tokens_into_pair_with(a, b, &mut ids, &mut ranges, &mut type_ids, Some(&mut words), &options)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     "[CLS] Ein interess ##antes [SEP] Super ##man [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(&[0..0, 0..3, 4..12, 12..17, 17..17, 0..5, 5..8, 8..8], &ranges[..]);
+    /// assert_eq!(&[0, 0, 0, 0, 0, 1, 1, 1], &type_ids[..]);
+    /// assert_eq!(&[1..2, 2..4, 5..7], &words[..]);
+    ///
+    /// let mut encode = |options: &EncodeOptions| {
+    ///     tokenizer
+    ///         .tokens_into_pair_with(a, b, &mut ids, &mut ranges, &mut type_ids, None, options)
+    ///         .map(|()| tokenizer.texts_of(&ids).join(" "))
+    /// };
+    ///
+    /// // once both have the same length, they lose tokens in turn
+    /// let options = EncodeOptions { max_length: Some(4), ..options };
+    /// assert_eq!(Ok("[CLS] Ein [SEP] [SEP]".to_string()), encode(&options));
+    ///
+    /// let options = EncodeOptions { max_length: Some(9), ..options };
+    /// assert_eq!(
+    ///     Ok("[CLS] Ein interess ##antes Bei [SEP] Super ##man [SEP]".to_string()),
+    ///     encode(&options)
+    /// );
+    /// let whole_words = EncodeOptions { truncate_whole_words: true, ..options };
+    /// assert_eq!(
+    ///     Ok("[CLS] Ein interess ##antes [SEP] Super ##man [SEP]".to_string()),
+    ///     encode(&whole_words)
+    /// );
+    ///
+    /// // or without [CLS] and [SEP]
+    /// let skip = EncodeOptions { skip_special_tokens: true, ..EncodeOptions::default() };
+    /// assert_eq!(
+    ///     Ok("Ein interess ##antes Bei ##spiel Ich es ##se Ste ##ak ##. Super ##man".to_string()),
+    ///     encode(&skip)
+    /// );
+    /// let skip = EncodeOptions { max_length: Some(5), ..skip };
+    /// assert_eq!(Ok("Ein interess ##antes Super ##man".to_string()), encode(&skip));
+    ///
+    /// let only_second = EncodeOptions { truncation: TruncationStrategy::OnlySecond, ..options };
+    /// assert_eq!(
+    ///     Err(EncodeError::CannotTruncate { len: 16, max_length: 9 }),
+    ///     encode(&only_second)
+    /// );
+    /// assert!(ids.is_empty() && ranges.is_empty() && type_ids.is_empty());
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn tokens_into_pair_with<T: TokenID>(
+        &self,
+        text_a: &str,
+        text_b: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        type_ids: &mut Vec<T>,
+        words: Option<&mut Vec<Range<usize>>>,
+        options: &EncodeOptions,
+    ) -> Result<(), EncodeError> {
+        let mut words = words;
+        token_ids.clear();
+        token_ranges.clear();
+        type_ids.clear();
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        check_input(text_a, options)?;
+        check_input(text_b, options)?;
+        let specials = self.specials_with(options);
+        if options.max_length.is_none() && !options.skip_special_tokens {
+            self.tokens_into_pair(text_a, text_b, token_ids, token_ranges, type_ids, words);
+            return Ok(());
+        }
+        let (mut first, mut second) = (self.segment(text_a), self.segment(text_b));
+        if let Some(max_length) = options.max_length {
+            let (prefix, suffix) = self.special_count(specials);
+            let special = prefix + 2 * suffix;
+            truncate(&mut first, Some(&mut second), special, options, max_length)?;
+        }
+        self.push_prefix(specials.0, token_ids, token_ranges);
+        self.push_segment(first, specials.1, token_ids, token_ranges, &mut words);
+        let b_start = token_ids.len();
+        self.push_segment(second, specials.1, token_ids, token_ranges, &mut words);
+        type_ids.resize(b_start, T::zero());
+        type_ids.resize(token_ids.len(), T::coerce(1));
+        Ok(())
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but fails
+    /// instead of silently wrapping if a token id doesn't fit into `T`.
+    ///
+    /// If a token id doesn't fit, the outputs are left empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeError};
+    ///
+    /// let mut vocab = String::from("[PAD]\n[UNK]\n");
+    /// for i in 0..70000 {
+    ///     vocab.push_str(&format!("t{}\n", i));
+    /// }
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u16>::new(), Vec::new());
+    /// tokenizer.try_tokens_into("t0 t65533", &mut ids, &mut ranges, None).unwrap();
+    /// assert_eq!(&[2, 65535], &ids[..]);
+    ///
+    /// assert_eq!(
+    ///     Err(EncodeError::IdOverflow { id: 65536 }),
+    ///     tokenizer.try_tokens_into("t0 t65534", &mut ids, &mut ranges, None)
+    /// );
+    /// assert!(ids.is_empty() && ranges.is_empty());
+    ///
+    /// // the unchecked variant wraps around
+    /// tokenizer.tokens_into("t0 t65534", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 0], &ids[..]);
+    /// ```
+    pub fn try_tokens_into<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        mut words: Option<&mut Vec<Range<usize>>>,
+    ) -> Result<(), EncodeError> {
+        // if the largest id fits, all others do, too
+        let max_id = self.tokens.len().saturating_sub(1) as u64;
+        if T::try_coerce(max_id).is_some() {
+            self.tokens_into(text, token_ids, token_ranges, words);
+            return Ok(());
+        }
+        let mut ids = Vec::<u64>::new();
+        self.tokens_into(text, &mut ids, token_ranges, words.as_deref_mut());
+        token_ids.clear();
+        for id in ids {
+            match T::try_coerce(id) {
+                Some(id) => token_ids.push(id),
+                None => {
+                    token_ids.clear();
+                    token_ranges.clear();
+                    if let Some(w) = words {
+                        w.clear();
+                    }
+                    return Err(EncodeError::IdOverflow { id });
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::ops::Range;
+
+/// The tokens of a text, as filled by
+/// [`encode_into`](AlephAlphaTokenizer::encode_into): their ids and ranges
+/// along with the other inputs a model needs, one value per token each.
+///
+/// The `Vec`s are re-used when encoding into the same `Encoding` again.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, Encoding};
+///
+/// // all fields describe the same tokens
+/// fn check(tokenizer: &AlephAlphaTokenizer, encoding: &Encoding<u32>, text: &str) {
+///     let len = encoding.len();
+///     assert_eq!(len, encoding.ranges.len(), "{:?}", text);
+///     assert_eq!(len, encoding.type_ids.len(), "{:?}", text);
+///     assert!(encoding.type_ids.iter().all(|&type_id| type_id == 0), "{:?}", text);
+///     let mut expected = Vec::<u32>::new();
+///     tokenizer.attentions_into_for(&encoding.ids, &mut expected);
+///     assert_eq!(expected, encoding.attention, "{:?}", text);
+///     tokenizer.special_tokens_mask_into(&encoding.ids, &mut expected);
+///     assert_eq!(expected, encoding.special_tokens_mask, "{:?}", text);
+///     for (i, range) in encoding.ranges.iter().enumerate() {
+///         assert!(range.end <= text.len(), "{:?}", text);
+///         let word = encoding.words.iter().position(|word| word.contains(&i));
+///         // the special tokens belong to no word
+///         assert_eq!(word.is_none(), tokenizer.is_special(encoding.ids[i]), "{:?}", text);
+///     }
+///     for pair in encoding.words.windows(2) {
+///         assert_eq!(pair[0].end, pair[1].start, "{:?}", text);
+///     }
+/// }
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let mut encoding = Encoding::default();
+/// for text in &[
+///     "Ich esse Steak.",
+///     "Der Hund spielt im Garten.",
+///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+///         FSME zur Verfügung?",
+///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+///         Borreliose vor und wenn ja, wie lauten diese?",
+///     "",
+/// ] {
+///     tokenizer.encode_into(text, &mut encoding);
+///     let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+///     tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+///     assert_eq!((&ids, &ranges, &words), (&encoding.ids, &encoding.ranges, &encoding.words));
+///     check(&tokenizer, &encoding, text);
+///
+///     let len = encoding.len();
+///     encoding.pad_to(len + 3, 0);
+///     assert_eq!(len + 3, encoding.len());
+///     assert_eq!(&[0, 0, 0], &encoding.ids[len..]);
+///     assert_eq!(&[0, 0, 0], &encoding.attention[len..]);
+///     check(&tokenizer, &encoding, text);
+///
+///     encoding.truncate(len / 2);
+///     assert_eq!(len / 2, encoding.len());
+///     assert_eq!(&ids[..len / 2], &encoding.ids[..]);
+///     check(&tokenizer, &encoding, text);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Encoding<T> {
+    /// The token ids
+    pub ids: Vec<T>,
+    /// The byte range of each token in the text
+    pub ranges: Vec<Range<usize>>,
+    /// The indices of the tokens of each word, as the `words` of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into)
+    pub words: Vec<Range<usize>>,
+    /// `0` for each padding token and `1` for the others
+    pub attention: Vec<T>,
+    /// The type id of each token, `0` for a single text
+    pub type_ids: Vec<T>,
+    /// `1` for each [special](AlephAlphaTokenizer::is_special) token and `0`
+    /// for the others
+    pub special_tokens_mask: Vec<T>,
+}
+
+impl<T> Default for Encoding<T> {
+    fn default() -> Self {
+        Encoding {
+            ids: Vec::new(),
+            ranges: Vec::new(),
+            words: Vec::new(),
+            attention: Vec::new(),
+            type_ids: Vec::new(),
+            special_tokens_mask: Vec::new(),
+        }
+    }
+}
+
+impl<T> Encoding<T> {
+    /// Gets the number of tokens.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Determines whether there are no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Keeps only the first `len` tokens, cutting off the words after them.
+    ///
+    /// Unlike [`EncodeOptions::max_length`](crate::EncodeOptions::max_length),
+    /// this doesn't keep the `[SEP]` token.
+    pub fn truncate(&mut self, len: usize) {
+        self.ids.truncate(len);
+        self.ranges.truncate(len);
+        self.attention.truncate(len);
+        self.type_ids.truncate(len);
+        self.special_tokens_mask.truncate(len);
+        self.words.retain(|word| word.start < len);
+        if let Some(word) = self.words.last_mut() {
+            word.end = word.end.min(len);
+        }
+    }
+
+    /// Finds the token whose range contains the byte position, like
+    /// [`AlephAlphaTokenizer::char_to_token`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ein interessantes Beispiel";
+    /// let encoding = tokenizer.encode::<u32>(text);
+    /// let answer = text.find("Beispiel").unwrap()..text.len();
+    /// let first = encoding.char_to_token(answer.start).unwrap();
+    /// let last = encoding.char_to_token(answer.end - 1).unwrap();
+    /// assert_eq!(Some(answer.start), encoding.token_to_chars(first).map(|r| r.start));
+    /// assert_eq!(Some(answer.end), encoding.token_to_chars(last).map(|r| r.end));
+    /// assert_eq!(None, encoding.char_to_token(answer.start - 1));
+    /// assert_eq!(None, encoding.token_to_chars(0));
+    /// assert_eq!(None, encoding.token_to_chars(encoding.len()));
+    /// ```
+    pub fn char_to_token(&self, byte_pos: usize) -> Option<usize> {
+        AlephAlphaTokenizer::char_to_token(&self.ranges, byte_pos)
+    }
+
+    /// Gets the range of the token in the text, like
+    /// [`AlephAlphaTokenizer::token_to_chars`].
+    pub fn token_to_chars(&self, token_index: usize) -> Option<Range<usize>> {
+        AlephAlphaTokenizer::token_to_chars(&self.ranges, token_index)
+    }
+
+    // keeps the tokens whose index is set in `keep`, remapping the words
+    fn retain_tokens(&mut self, keep: &[bool]) {
+        fn retain<U>(values: &mut Vec<U>, keep: &[bool]) {
+            let mut index = 0..;
+            values.retain(|_| keep[index.next().unwrap()]);
+        }
+        // the number of tokens kept before each index
+        let mut kept = Vec::with_capacity(keep.len() + 1);
+        kept.push(0);
+        for &k in keep {
+            kept.push(kept[kept.len() - 1] + usize::from(k));
+        }
+        for word in &mut self.words {
+            *word = kept[word.start]..kept[word.end];
+        }
+        retain(&mut self.ids, keep);
+        retain(&mut self.ranges, keep);
+        retain(&mut self.attention, keep);
+        retain(&mut self.type_ids, keep);
+        retain(&mut self.special_tokens_mask, keep);
+    }
+}
+
+impl<T: TokenID> Encoding<T> {
+    /// Appends `pad_id` tokens up to `len` tokens, without attention, as
+    /// special tokens and with an empty range at the end of the last token.
+    /// Longer encodings are kept as they are.
+    pub fn pad_to(&mut self, len: usize, pad_id: T) {
+        if len <= self.len() {
+            return;
+        }
+        let pos = self.ranges.last().map_or(0, |range| range.end);
+        self.ids.resize(len, pad_id);
+        self.ranges.resize(len, pos..pos);
+        self.attention.resize(len, T::zero());
+        self.type_ids.resize(len, T::zero());
+        self.special_tokens_mask.resize(len, T::coerce(1));
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes the text into the given [`Encoding`], like
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into) does, and fills its
+    /// other fields for the tokens.
+    pub fn encode_into<T: TokenID>(&self, text: &str, encoding: &mut Encoding<T>) {
+        let Encoding {
+            ids,
+            ranges,
+            words,
+            attention,
+            type_ids,
+            special_tokens_mask,
+        } = encoding;
+        self.tokens_into(text, ids, ranges, Some(words));
+        self.attentions_into_for(ids, attention);
+        type_ids.clear();
+        type_ids.resize(ids.len(), T::zero());
+        self.special_tokens_mask_into(ids, special_tokens_mask);
+    }
+
+    /// Tokenizes the text into a new [`Encoding`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let encoding = tokenizer.encode::<u64>("Super");
+    /// assert_eq!(&[3, 4285, 4], &encoding.ids[..]);
+    /// assert_eq!(&[0..0, 0..5, 5..5], &encoding.ranges[..]);
+    /// ```
+    pub fn encode<T: TokenID>(&self, text: &str) -> Encoding<T> {
+        let mut encoding = Encoding::default();
+        self.encode_into(text, &mut encoding);
+        encoding
+    }
+
+    /// Replaces the ids of all tokens that overlap any of the byte `spans`
+    /// by `replacement_id`, returning the indices of those tokens.
+    ///
+    /// Special tokens (like `[CLS]` and `[SEP]`) are never replaced. If
+    /// `merge` is set, each run of adjacent replaced tokens is merged into a
+    /// single token covering the whole run; the returned indices still refer
+    /// to the tokens before merging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let unk = tokenizer.id_of("[UNK]").unwrap();
+    /// let text = "Ich esse Steak";
+    /// let encoding = tokenizer.encode::<u64>(text);
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 4], &encoding.ids[..]);
+    ///
+    /// // the span ends in the middle of "es" + "##se"
+    /// let mut redacted = encoding.clone();
+    /// assert_eq!(vec![2, 3], tokenizer.redact(&mut redacted, &[5..7], unk, false));
+    /// assert_eq!(&[3, 1671, 2, 2, 601, 464, 4], &redacted.ids[..]);
+    /// assert_eq!(encoding.ranges, redacted.ranges);
+    ///
+    /// // [CLS] and [SEP] are kept
+    /// let mut redacted = encoding.clone();
+    /// let affected = tokenizer.redact(&mut redacted, &[0..text.len()], unk, false);
+    /// assert_eq!(vec![1, 2, 3, 4, 5], affected);
+    /// assert_eq!(&[3, 2, 2, 2, 2, 2, 4], &redacted.ids[..]);
+    ///
+    /// let mut redacted = encoding.clone();
+    /// assert_eq!(vec![2, 3, 4], tokenizer.redact(&mut redacted, &[5..10], unk, true));
+    /// assert_eq!(&[3, 1671, 2, 464, 4], &redacted.ids[..]);
+    /// assert_eq!(&[0..0, 0..3, 4..12, 12..14, 14..14], &redacted.ranges[..]);
+    /// // a merged token belongs to the word it starts in
+    /// assert_eq!(&[1..2, 2..3, 3..4], &redacted.words[..]);
+    /// // [UNK] is special, too
+    /// assert_eq!(&[1, 0, 1, 0, 1], &redacted.special_tokens_mask[..]);
+    /// ```
+    pub fn redact<T: TokenID>(
+        &self,
+        encoding: &mut Encoding<T>,
+        spans: &[Range<usize>],
+        replacement_id: u64,
+        merge: bool,
+    ) -> Vec<usize> {
+        let ranges = &encoding.ranges;
+        let mut affected = Vec::new();
+        for span in spans {
+            // the ranges are sorted, so we can skip those ending before the span
+            let first = ranges.partition_point(|r| r.end <= span.start);
+            affected.extend(
+                (first..ranges.len())
+                    .take_while(|&i| ranges[i].start < span.end)
+                    .filter(|&i| {
+                        let range = &ranges[i];
+                        range.start < range.end && !self.is_special(encoding.ids[i].clone())
+                    }),
+            );
+        }
+        affected.sort_unstable();
+        affected.dedup();
+        let special = if self.is_special(replacement_id) {
+            T::coerce(1)
+        } else {
+            T::zero()
+        };
+        for &i in &affected {
+            encoding.ids[i] = T::coerce(replacement_id);
+            encoding.special_tokens_mask[i] = special.clone();
+        }
+        if merge {
+            merge_runs(encoding, &affected);
+        }
+        affected
+    }
+}
+
+// merge each run of consecutive indices into its first token
+fn merge_runs<T>(encoding: &mut Encoding<T>, indices: &[usize]) {
+    let mut keep = vec![true; encoding.len()];
+    let mut run_start = 0;
+    for (n, &i) in indices.iter().enumerate() {
+        if n > 0 && indices[n - 1] + 1 == i {
+            keep[i] = false;
+            encoding.ranges[run_start].end = encoding.ranges[i].end;
+        } else {
+            run_start = i;
+        }
+    }
+    encoding.retain_tokens(&keep);
+}
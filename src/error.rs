@@ -0,0 +1,182 @@
+use crate::ConsistencyError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// The errors that can occur when loading or saving a vocabulary.
+///
+/// This converts into a `Box<dyn Error + Send + Sync>` like any other
+/// error, so code using `?` on such results keeps working.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+///
+/// let error = AlephAlphaTokenizer::from_vocab_bytes(b"[CLS]\nfoo\n").err().unwrap();
+/// assert!(matches!(error, TokenizerError::MissingUnkToken(ref unk) if unk == "[UNK]"));
+/// assert_eq!("the vocabulary has no [UNK] token", error.to_string());
+///
+/// let error = AlephAlphaTokenizer::from_vocab("no/such/vocab.txt").err().unwrap();
+/// assert!(matches!(error, TokenizerError::Io(_)));
+///
+/// let error = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\nbar\nfoo\n").err().unwrap();
+/// assert!(matches!(
+///     error,
+///     TokenizerError::DuplicateToken { ref token, first_line: 2, second_line: 4 } if token == "foo"
+/// ));
+///
+/// let error = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\n\xff\n").err().unwrap();
+/// assert!(matches!(error, TokenizerError::InvalidVocabLine { line_no: 3 }));
+///
+/// let error = AlephAlphaTokenizer::builder().build().err().unwrap();
+/// assert!(matches!(error, TokenizerError::MissingVocab));
+///
+/// // existing code returning boxed errors still compiles
+/// fn load() -> Result<AlephAlphaTokenizer, Box<dyn std::error::Error + Send + Sync>> {
+///     Ok(AlephAlphaTokenizer::from_vocab("vocab.txt")?)
+/// }
+/// assert!(load().is_ok());
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TokenizerError {
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// Building or reading an FST failed
+    Fst(fst::Error),
+    /// The builder was not given a vocabulary
+    MissingVocab,
+    /// The vocabulary has no token for unknown words (with the given text)
+    MissingUnkToken(String),
+    /// The vocabulary lacks a token that was to be marked as special
+    MissingSpecialToken(String),
+    /// A token occurs twice in the vocabulary
+    DuplicateToken {
+        /// the token
+        token: String,
+        /// the line of the first occurrence, starting at 1
+        first_line: usize,
+        /// the line of the second occurrence, starting at 1
+        second_line: usize,
+    },
+    /// Two lines of a vocabulary with ids give the same id
+    DuplicateId {
+        /// the id
+        id: u64,
+        /// the line of the first occurrence, starting at 1
+        first_line: usize,
+        /// the line of the second occurrence, starting at 1
+        second_line: usize,
+    },
+    /// A line of the vocabulary is not valid UTF-8
+    InvalidVocabLine {
+        /// the line, starting at 1
+        line_no: usize,
+    },
+    /// A vocabulary in another format (e.g. JSON) is malformed
+    InvalidVocab(String),
+    /// The loaded data don't agree with each other
+    Inconsistent(ConsistencyError),
+    /// Byte fallback is on, but the vocabulary lacks the token (e.g. `<0x00>`)
+    MissingByteToken(String),
+    /// Two vocabularies spell the same special token differently, e.g.
+    /// `[UNK]` and `<unk>`
+    SpecialTokenConflict {
+        /// the text in the base vocabulary
+        base: String,
+        /// the text in the other vocabulary
+        other: String,
+    },
+    /// A special token (with the given text) was to be suppressed
+    SuppressedSpecialToken(String),
+    /// Tokens were to be suppressed from FSTs mapped from files, which are
+    /// used as they are
+    SuppressedWithFstFiles,
+}
+
+impl fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenizerError::Io(e) => write!(f, "I/O error: {}", e),
+            TokenizerError::Fst(e) => write!(f, "FST error: {}", e),
+            TokenizerError::MissingVocab => f.write_str("no vocabulary given"),
+            TokenizerError::MissingUnkToken(unk) => {
+                write!(f, "the vocabulary has no {} token", unk)
+            }
+            TokenizerError::MissingSpecialToken(token) => {
+                write!(f, "the vocabulary has no special token {:?}", token)
+            }
+            TokenizerError::DuplicateToken {
+                token,
+                first_line,
+                second_line,
+            } => write!(
+                f,
+                "token {:?} occurs in lines {} and {}",
+                token, first_line, second_line
+            ),
+            TokenizerError::DuplicateId {
+                id,
+                first_line,
+                second_line,
+            } => write!(
+                f,
+                "id {} occurs in lines {} and {}",
+                id, first_line, second_line
+            ),
+            TokenizerError::InvalidVocabLine { line_no } => {
+                write!(f, "line {} of the vocabulary is not valid UTF-8", line_no)
+            }
+            TokenizerError::InvalidVocab(e) => write!(f, "invalid vocabulary: {}", e),
+            TokenizerError::Inconsistent(e) => write!(f, "inconsistent tokenizer: {}", e),
+            TokenizerError::MissingByteToken(token) => {
+                write!(
+                    f,
+                    "the vocabulary has no byte token {} for byte fallback",
+                    token
+                )
+            }
+            TokenizerError::SpecialTokenConflict { base, other } => write!(
+                f,
+                "special token {:?} is {:?} in the other vocabulary",
+                base, other
+            ),
+            TokenizerError::SuppressedSpecialToken(token) => {
+                write!(f, "the special token {:?} cannot be suppressed", token)
+            }
+            TokenizerError::SuppressedWithFstFiles => {
+                f.write_str("tokens cannot be suppressed from prebuilt FST files")
+            }
+        }
+    }
+}
+
+impl Error for TokenizerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TokenizerError::Io(e) => Some(e),
+            TokenizerError::Fst(e) => Some(e),
+            TokenizerError::Inconsistent(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TokenizerError {
+    fn from(e: io::Error) -> Self {
+        TokenizerError::Io(e)
+    }
+}
+
+impl From<fst::Error> for TokenizerError {
+    fn from(e: fst::Error) -> Self {
+        TokenizerError::Fst(e)
+    }
+}
+
+impl From<ConsistencyError> for TokenizerError {
+    fn from(e: ConsistencyError) -> Self {
+        TokenizerError::Inconsistent(e)
+    }
+}
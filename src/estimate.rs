@@ -0,0 +1,118 @@
+use crate::AlephAlphaTokenizer;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// a few sentences to measure the token density of a vocabulary on loading
+pub(crate) const BUILTIN_SAMPLE: &[&str] = &[
+    "Die Stadtverwaltung hat am Montag neue Regeln für den Verkehr in der Innenstadt vorgestellt.",
+    "Nach Angaben des Wetterdienstes wird es in den kommenden Tagen deutlich kühler.",
+    "Im Jahresbericht wurden die wichtigsten Ergebnisse der Untersuchung zusammengefasst.",
+    "Viele Studierende suchen zu Beginn des Semesters noch eine bezahlbare Wohnung.",
+];
+
+/// The token statistics of a tokenizer, stored as bits of `f64`s so
+/// [`AlephAlphaTokenizer::calibrate`] can update them through `&self`.
+#[derive(Debug)]
+pub(crate) struct Calibration {
+    tokens_per_byte: AtomicU64,
+    subwords_per_word: AtomicU64,
+}
+
+// a clone starts from the statistics so far
+impl Clone for Calibration {
+    fn clone(&self) -> Self {
+        Calibration {
+            tokens_per_byte: AtomicU64::new(self.tokens_per_byte.load(Ordering::Relaxed)),
+            subwords_per_word: AtomicU64::new(self.subwords_per_word.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration {
+            tokens_per_byte: AtomicU64::new(0.25f64.to_bits()),
+            subwords_per_word: AtomicU64::new(1.5f64.to_bits()),
+        }
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Measures the number of tokens per byte and subword tokens per word on
+    /// the given sample texts, to be used by
+    /// [`estimated_tokens`](AlephAlphaTokenizer::estimated_tokens).
+    ///
+    /// On loading, the tokenizer is calibrated on a small built-in sample of
+    /// German text. Call this with texts from your domain for a better
+    /// estimate. Samples without any text are ignored.
+    pub fn calibrate(&self, sample: &[&str]) {
+        let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+        let (mut bytes, mut tokens, mut words) = (0, 0, 0);
+        for text in sample {
+            self.tokens_into(text, &mut ids, &mut ranges, None);
+            bytes += text.len();
+            tokens += ids.len() - self.affix_len();
+            words += text.split_whitespace().count();
+        }
+        if bytes == 0 || words == 0 {
+            return;
+        }
+        let tokens_per_byte = tokens as f64 / bytes as f64;
+        let subwords_per_word = tokens as f64 / words as f64;
+        let calibration = &self.calibration;
+        calibration
+            .tokens_per_byte
+            .store(tokens_per_byte.to_bits(), Ordering::Relaxed);
+        calibration
+            .subwords_per_word
+            .store(subwords_per_word.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the calibrated average number of tokens per byte of text.
+    pub fn tokens_per_byte(&self) -> f64 {
+        f64::from_bits(self.calibration.tokens_per_byte.load(Ordering::Relaxed))
+    }
+
+    /// Gets the calibrated average number of tokens per whitespace-separated
+    /// word.
+    pub fn subwords_per_word(&self) -> f64 {
+        f64::from_bits(self.calibration.subwords_per_word.load(Ordering::Relaxed))
+    }
+
+    /// Estimates the number of tokens for a text of `byte_len` bytes,
+    /// including the `[CLS]` and `[SEP]` tokens.
+    ///
+    /// This is meant for pre-allocating buffers. For texts similar to the
+    /// [calibration](AlephAlphaTokenizer::calibrate) sample, the estimate
+    /// is usually within a factor of 2 of the actual number for single
+    /// sentences, and much closer for longer texts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let sample = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    /// ];
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// tokenizer.calibrate(&sample);
+    /// assert!(tokenizer.subwords_per_word() >= 1.0);
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for text in &sample {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     let estimate = tokenizer.estimated_tokens(text.len()) as f64;
+    ///     let actual = ids.len() as f64;
+    ///     assert!(estimate / 2.0 <= actual && actual <= estimate * 2.0, "{}", text);
+    /// }
+    /// assert_eq!(2, tokenizer.estimated_tokens(0));
+    /// ```
+    pub fn estimated_tokens(&self, byte_len: usize) -> usize {
+        (byte_len as f64 * self.tokens_per_byte()).ceil() as usize + self.affix_len()
+    }
+}
@@ -0,0 +1,56 @@
+//! A tiny vocabulary with stable ids, so examples work without a vocabulary
+//! file and keep their ids when a real vocabulary changes.
+//!
+//! | id | token      | id | token   |
+//! |----|------------|----|---------|
+//! |  0 | `[PAD]`    | 10 | `Ich`   |
+//! |  1 | `[UNK]`    | 11 | `es`    |
+//! |  2 | `[CLS]`    | 12 | `##se`  |
+//! |  3 | `[SEP]`    | 13 | `Ste`   |
+//! |  4 | `[MASK]`   | 14 | `##ak`  |
+//! |  5 | `Ein`      | 15 | `##.`   |
+//! |  6 | `interess` | 16 | `Super` |
+//! |  7 | `##antes`  | 17 | `##man` |
+//! |  8 | `Bei`      | 18 | `Bat`   |
+//! |  9 | `##spiel`  |    |         |
+//!
+//! # Examples
+//!
+//! ```
+//! use aleph_alpha_tokenizer::examples::{self, CLS, SEP, UNK};
+//!
+//! let tokenizer = examples::tokenizer();
+//! let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+//! tokenizer.tokens_into("Ein interessantes Beispiel", &mut ids, &mut ranges, None);
+//! assert_eq!(&[CLS, 5, 6, 7, 8, 9, SEP], &ids[..]);
+//! assert_eq!(&[0..0, 0..3, 4..12, 12..17, 18..21, 21..26, 26..26], &ranges[..]);
+//!
+//! tokenizer.tokens_into("Ich esse Steak. Batman Robin", &mut ids, &mut ranges, None);
+//! assert_eq!(&[CLS, 10, 11, 12, 13, 14, 15, 18, 17, UNK, SEP], &ids[..]);
+//! assert_eq!(examples::VOCAB.lines().count(), tokenizer.version_info().vocab_size);
+//! ```
+
+use crate::AlephAlphaTokenizer;
+
+/// The example vocabulary, one token per line.
+pub const VOCAB: &str = "[PAD]\n[UNK]\n[CLS]\n[SEP]\n[MASK]\n\
+    Ein\ninteress\n##antes\nBei\n##spiel\n\
+    Ich\nes\n##se\nSte\n##ak\n##.\n\
+    Super\n##man\nBat\n";
+
+/// The id of the `[PAD]` token
+pub const PAD: u64 = 0;
+/// The id of the `[UNK]` token
+pub const UNK: u64 = 1;
+/// The id of the `[CLS]` token
+pub const CLS: u64 = 2;
+/// The id of the `[SEP]` token
+pub const SEP: u64 = 3;
+/// The id of the `[MASK]` token
+pub const MASK: u64 = 4;
+
+/// Creates a tokenizer with the default options from the [`VOCAB`].
+pub fn tokenizer() -> AlephAlphaTokenizer {
+    AlephAlphaTokenizer::from_vocab_bytes(VOCAB.as_bytes())
+        .expect("the example vocabulary is valid")
+}
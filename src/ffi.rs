@@ -0,0 +1,230 @@
+//! A C interface, built with the `ffi` feature, to embed the tokenizer in
+//! services written in other languages.
+//!
+//! `include/aleph_alpha_tokenizer.h` declares these functions for C and
+//! C++; it can be regenerated with `cbindgen --config cbindgen.toml`. Build
+//! a library to link against with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or
+//! `staticlib`).
+//!
+//! The tokenizer is an opaque pointer, which must be freed with
+//! [`aa_tokenizer_free`]. All other memory belongs to the caller: the
+//! functions only write into the buffers they are given and report how much
+//! of them they used. Functions that return an `isize` return one of the
+//! negative `AA_ERROR_*` codes on failure. Panics are caught and reported as
+//! [`AA_ERROR_PANIC`] (or a null pointer) instead of unwinding into the
+//! caller.
+//!
+//! # Examples
+//!
+//! ```
+//! use aleph_alpha_tokenizer::ffi::*;
+//! use std::{ffi::CString, ptr};
+//!
+//! let path = CString::new("vocab.txt").unwrap();
+//! let tokenizer = unsafe { aa_tokenizer_new(path.as_ptr()) };
+//! assert!(!tokenizer.is_null());
+//!
+//! let text = "Ich esse Steak.";
+//! // no text has more tokens than bytes, besides `[CLS]` and `[SEP]`
+//! let capacity = text.len() + 2;
+//! let (mut ids, mut offsets) = (vec![0i64; capacity], vec![0usize; 2 * capacity]);
+//! let len = unsafe {
+//!     aa_tokenize(
+//!         tokenizer,
+//!         text.as_ptr(),
+//!         text.len(),
+//!         ids.as_mut_ptr(),
+//!         offsets.as_mut_ptr(),
+//!         capacity,
+//!     )
+//! };
+//! assert_eq!(8, len);
+//! assert_eq!(&[3, 1671, 229, 145, 601, 464, 26914, 4], &ids[..8]);
+//! assert_eq!(&[0, 0, 0, 3, 4, 6], &offsets[..6]);
+//!
+//! let mut short = [0i64; 4];
+//! let len = unsafe {
+//!     aa_tokenize(tokenizer, text.as_ptr(), text.len(), short.as_mut_ptr(), ptr::null_mut(), 4)
+//! };
+//! assert_eq!(AA_ERROR_BUFFER_TOO_SMALL, len);
+//! let invalid = b"\xff";
+//! let len = unsafe {
+//!     aa_tokenize(tokenizer, invalid.as_ptr(), 1, short.as_mut_ptr(), ptr::null_mut(), 4)
+//! };
+//! assert_eq!(AA_ERROR_INVALID_UTF8, len);
+//!
+//! let mut buf = [0u8; 8];
+//! let id_to_token = |id, buf: &mut [u8]| unsafe {
+//!     aa_id_to_token(tokenizer, id, buf.as_mut_ptr().cast(), buf.len())
+//! };
+//! assert_eq!(5, id_to_token(4285, &mut buf));
+//! assert_eq!(b"Super\0", &buf[..6]);
+//! assert_eq!(AA_ERROR_BUFFER_TOO_SMALL, id_to_token(4285, &mut buf[..5]));
+//! assert_eq!(AA_ERROR_UNKNOWN_ID, id_to_token(-1, &mut buf));
+//!
+//! unsafe { aa_tokenizer_free(tokenizer) };
+//!
+//! let missing = CString::new("no/such/vocab.txt").unwrap();
+//! assert!(unsafe { aa_tokenizer_new(missing.as_ptr()) }.is_null());
+//! assert!(unsafe { aa_tokenizer_new(ptr::null()) }.is_null());
+//! ```
+
+use crate::AlephAlphaTokenizer;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice, str};
+
+/// A required pointer argument was null.
+pub const AA_ERROR_NULL_POINTER: isize = -1;
+/// The text was not valid UTF-8.
+pub const AA_ERROR_INVALID_UTF8: isize = -2;
+/// The output buffer was too small; nothing useful was written to it.
+pub const AA_ERROR_BUFFER_TOO_SMALL: isize = -3;
+/// The token id is not in the vocabulary.
+pub const AA_ERROR_UNKNOWN_ID: isize = -4;
+/// The tokenizer panicked.
+pub const AA_ERROR_PANIC: isize = -5;
+
+/// The opaque tokenizer handle of the C interface.
+pub struct AaTokenizer {
+    tokenizer: AlephAlphaTokenizer,
+}
+
+// runs `f`, turning a panic into `AA_ERROR_PANIC`
+fn guard(f: impl FnOnce() -> isize) -> isize {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(AA_ERROR_PANIC)
+}
+
+/// Loads a tokenizer from a vocabulary file with one token per line, or
+/// returns null if the path is null or not UTF-8, or the file cannot be
+/// loaded.
+///
+/// # Safety
+///
+/// `vocab_path` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn aa_tokenizer_new(vocab_path: *const c_char) -> *mut AaTokenizer {
+    if vocab_path.is_null() {
+        return ptr::null_mut();
+    }
+    let path = CStr::from_ptr(vocab_path);
+    catch_unwind(|| {
+        let path = path.to_str().ok()?;
+        let tokenizer = AlephAlphaTokenizer::from_vocab(path).ok()?;
+        Some(Box::into_raw(Box::new(AaTokenizer { tokenizer })))
+    })
+    .ok()
+    .flatten()
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Frees a tokenizer from [`aa_tokenizer_new`]; null is ignored.
+///
+/// # Safety
+///
+/// `tokenizer` must be null or come from [`aa_tokenizer_new`], and must not
+/// be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn aa_tokenizer_free(tokenizer: *mut AaTokenizer) {
+    if !tokenizer.is_null() {
+        // dropping a tokenizer doesn't panic
+        drop(Box::from_raw(tokenizer));
+    }
+}
+
+/// Tokenizes the `text_len` bytes of UTF-8 text at `text`, writing up to
+/// `capacity` token ids to `out_ids` and their byte ranges to `out_offsets`
+/// as `start, end` pairs (so it must hold `2 * capacity` values).
+///
+/// Returns the number of tokens, or [`AA_ERROR_BUFFER_TOO_SMALL`] if there
+/// are more than `capacity`. A `capacity` of `text_len + 2` always suffices.
+/// `out_offsets` may be null if the offsets aren't needed.
+///
+/// # Safety
+///
+/// `tokenizer` must come from [`aa_tokenizer_new`], `text` must point to
+/// `text_len` readable bytes, `out_ids` to `capacity` and `out_offsets` (if
+/// not null) to `2 * capacity` writable values.
+#[no_mangle]
+pub unsafe extern "C" fn aa_tokenize(
+    tokenizer: *const AaTokenizer,
+    text: *const u8,
+    text_len: usize,
+    out_ids: *mut i64,
+    out_offsets: *mut usize,
+    capacity: usize,
+) -> isize {
+    if tokenizer.is_null() || (text.is_null() && text_len > 0) || out_ids.is_null() {
+        return AA_ERROR_NULL_POINTER;
+    }
+    let tokenizer = &(*tokenizer).tokenizer;
+    let bytes = if text_len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(text, text_len)
+    };
+    let out_ids = slice::from_raw_parts_mut(out_ids, capacity);
+    let mut out_offsets = if out_offsets.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts_mut(out_offsets, 2 * capacity))
+    };
+    guard(|| {
+        let text = match str::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(_) => return AA_ERROR_INVALID_UTF8,
+        };
+        let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+        tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+        if ids.len() > capacity {
+            return AA_ERROR_BUFFER_TOO_SMALL;
+        }
+        out_ids[..ids.len()].copy_from_slice(&ids);
+        if let Some(offsets) = out_offsets.as_mut() {
+            for (pair, range) in offsets.chunks_exact_mut(2).zip(&ranges) {
+                pair[0] = range.start;
+                pair[1] = range.end;
+            }
+        }
+        ids.len() as isize
+    })
+}
+
+/// Copies the text of the token with the given id to `buf` as a
+/// NUL-terminated string of at most `buf_len` bytes.
+///
+/// Returns the length of the text without the NUL,
+/// [`AA_ERROR_UNKNOWN_ID`] if the id is not in the vocabulary, or
+/// [`AA_ERROR_BUFFER_TOO_SMALL`] if the text and NUL don't fit.
+///
+/// # Safety
+///
+/// `tokenizer` must come from [`aa_tokenizer_new`] and `buf` must point to
+/// `buf_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aa_id_to_token(
+    tokenizer: *const AaTokenizer,
+    id: i64,
+    buf: *mut c_char,
+    buf_len: usize,
+) -> isize {
+    if tokenizer.is_null() || buf.is_null() {
+        return AA_ERROR_NULL_POINTER;
+    }
+    let tokenizer = &(*tokenizer).tokenizer;
+    let buf = slice::from_raw_parts_mut(buf.cast::<u8>(), buf_len);
+    guard(|| {
+        if id < 0 || id as u64 >= tokenizer.vocab_size() as u64 {
+            return AA_ERROR_UNKNOWN_ID;
+        }
+        let text = tokenizer.text_of(id as u64).as_bytes();
+        if text.len() >= buf.len() {
+            return AA_ERROR_BUFFER_TOO_SMALL;
+        }
+        buf[..text.len()].copy_from_slice(text);
+        buf[text.len()] = 0;
+        text.len() as isize
+    })
+}
@@ -0,0 +1,332 @@
+use crate::{AlephAlphaTokenizer, TokenizerError};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+const ENDPOINT: &str = "https://huggingface.co";
+
+/// The errors that can occur when loading a tokenizer from the Hugging Face
+/// Hub with [`AlephAlphaTokenizer::from_pretrained`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HubError {
+    /// The hub could not be reached, and the file is not cached
+    Network(String),
+    /// Neither the repository, the revision nor the file exist (or the
+    /// repository is private, which the hub doesn't tell apart)
+    NotFound {
+        /// the URL of the file
+        url: String,
+    },
+    /// The repository is private or gated, and there is no `HF_TOKEN` that
+    /// gives access to it
+    AuthRequired {
+        /// the repository
+        repo: String,
+    },
+    /// The hub answered with another error status
+    Status {
+        /// the URL of the file
+        url: String,
+        /// the HTTP status
+        status: u16,
+    },
+    /// `HF_HUB_OFFLINE` is set, and the file is not cached
+    Offline {
+        /// the file in the cache
+        path: PathBuf,
+    },
+    /// Reading or writing the cache failed
+    Io(io::Error),
+    /// The downloaded vocabulary is invalid
+    Vocab(TokenizerError),
+}
+
+impl fmt::Display for HubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HubError::Network(e) => write!(f, "cannot reach the hub: {}", e),
+            HubError::NotFound { url } => write!(
+                f,
+                "nothing found at {}; check the repository, revision and access",
+                url
+            ),
+            HubError::AuthRequired { repo } => write!(
+                f,
+                "the repository {} needs a token with access to it in HF_TOKEN",
+                repo
+            ),
+            HubError::Status { url, status } => {
+                write!(f, "the hub answered {} for {}", status, url)
+            }
+            HubError::Offline { path } => write!(
+                f,
+                "HF_HUB_OFFLINE is set, but {} is not cached",
+                path.display()
+            ),
+            HubError::Io(e) => write!(f, "cannot use the cache: {}", e),
+            HubError::Vocab(e) => write!(f, "invalid vocabulary: {}", e),
+        }
+    }
+}
+
+impl Error for HubError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HubError::Io(e) => Some(e),
+            HubError::Vocab(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HubError {
+    fn from(e: io::Error) -> Self {
+        HubError::Io(e)
+    }
+}
+
+impl From<TokenizerError> for HubError {
+    fn from(e: TokenizerError) -> Self {
+        HubError::Vocab(e)
+    }
+}
+
+// the settings of the huggingface environment variables
+struct Hub {
+    endpoint: String,
+    cache: PathBuf,
+    token: Option<String>,
+    offline: bool,
+}
+
+impl Hub {
+    fn from_env() -> Self {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        let cache = var("HF_HUB_CACHE").map(PathBuf::from).unwrap_or_else(|| {
+            let home = var("HF_HOME").map(PathBuf::from).unwrap_or_else(|| {
+                let cache = var("XDG_CACHE_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".cache")))
+                    .unwrap_or_else(env::temp_dir);
+                cache.join("huggingface")
+            });
+            home.join("hub")
+        });
+        let offline = var("HF_HUB_OFFLINE").is_some_and(|value| value != "0");
+        Hub {
+            endpoint: var("HF_ENDPOINT").unwrap_or_else(|| ENDPOINT.to_string()),
+            cache,
+            token: var("HF_TOKEN"),
+            offline,
+        }
+    }
+
+    // get the path of the file in the cache, downloading it unless the
+    // cached file has the same etag
+    fn fetch(&self, repo: &str, revision: &str, file: &str) -> Result<PathBuf, HubError> {
+        let dir = self
+            .cache
+            .join(format!("models--{}", repo.replace('/', "--")))
+            .join(revision.replace('/', "--"));
+        let path = dir.join(file);
+        let etag_path = dir.join(format!("{}.etag", file));
+        let cached = path.is_file();
+        if self.offline {
+            return if cached {
+                Ok(path)
+            } else {
+                Err(HubError::Offline { path })
+            };
+        }
+        let url = format!(
+            "{}/{}/resolve/{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            repo,
+            revision,
+            file
+        );
+        let mut request = ureq::get(&url);
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        if cached {
+            if let Ok(etag) = fs::read_to_string(&etag_path) {
+                request = request.set("If-None-Match", etag.trim());
+            }
+        }
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(401, _)) | Err(ureq::Error::Status(403, _)) => {
+                return Err(HubError::AuthRequired {
+                    repo: repo.to_string(),
+                })
+            }
+            Err(ureq::Error::Status(404, _)) => return Err(HubError::NotFound { url }),
+            Err(ureq::Error::Status(status, _)) => return Err(HubError::Status { url, status }),
+            // without a connection, the cached file will do
+            Err(ureq::Error::Transport(_)) if cached => return Ok(path),
+            Err(ureq::Error::Transport(e)) => return Err(HubError::Network(e.to_string())),
+        };
+        if response.status() == 304 {
+            return Ok(path);
+        }
+        // large files have the etag of their content in another header
+        let etag = response
+            .header("X-Linked-Etag")
+            .or_else(|| response.header("ETag"))
+            .map(str::to_string);
+        let mut content = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut content)
+            .map_err(|e| HubError::Network(e.to_string()))?;
+        fs::create_dir_all(&dir)?;
+        // write the file first, so an interrupted download is never cached
+        let partial = dir.join(format!("{}.partial", file));
+        fs::write(&partial, content)?;
+        fs::rename(&partial, &path)?;
+        match etag {
+            Some(etag) => fs::write(&etag_path, etag)?,
+            None if etag_path.is_file() => fs::remove_file(&etag_path)?,
+            None => {}
+        }
+        Ok(path)
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Creates a tokenizer from the `vocab.txt` of a repository on the
+    /// Hugging Face Hub, e.g. `"bert-base-german-cased"`, or its
+    /// `tokenizer.json` if it has no `vocab.txt`.
+    ///
+    /// A revision (a branch, tag or commit) can follow the repository after
+    /// an `@`, as in `"bert-base-german-cased@v1.0"`; the default is `main`.
+    /// The files are cached in `models--<owner>--<name>` within the hub cache
+    /// of huggingface's libraries (`HF_HUB_CACHE`, or the `hub` directory in
+    /// `HF_HOME`, which defaults to `~/.cache/huggingface`), and downloaded
+    /// again only if the hub has another version. `HF_TOKEN` is sent to
+    /// access private or gated repositories, `HF_ENDPOINT` replaces
+    /// `https://huggingface.co`, and with `HF_HUB_OFFLINE=1` only cached
+    /// files are used. If the hub cannot be reached, a cached file is used,
+    /// too.
+    ///
+    /// This is only available with the `hub` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, HubError};
+    /// use std::io::{BufRead, BufReader, Write};
+    /// use std::net::TcpListener;
+    /// use std::sync::mpsc;
+    ///
+    /// // a hub that serves two revisions of a `vocab.txt`, a `tokenizer.json` and
+    /// // a private repository, and tells which files it sent
+    /// let tokenizer_json = r###"{
+    ///   "model": {"unk_token": "<unk>", "vocab": {"<unk>": 0, "Bat": 1, "##man": 2}}
+    /// }"###;
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let endpoint = format!("http://{}", listener.local_addr()?);
+    /// let (sent, downloads) = mpsc::channel();
+    /// std::thread::spawn(move || {
+    ///     for stream in listener.incoming() {
+    ///         let mut stream = stream.unwrap();
+    ///         let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+    ///         let path = lines.next().unwrap().split(' ').nth(1).unwrap().to_string();
+    ///         let headers: Vec<String> = lines.take_while(|line| !line.is_empty()).collect();
+    ///         let cached = headers.iter().any(|h| h.to_lowercase() == "if-none-match: \"v1\"");
+    ///         let (status, etag, body) = match path.as_str() {
+    ///             "/aleph/batman/resolve/main/vocab.txt" if cached => ("304 Not Modified", "v1", ""),
+    ///             "/aleph/batman/resolve/main/vocab.txt" => ("200 OK", "v1", "[UNK]\nSuper\n##man\n"),
+    ///             "/aleph/batman/resolve/v0/vocab.txt" => ("200 OK", "v0", "[UNK]\nBat\n"),
+    ///             "/aleph/json/resolve/main/tokenizer.json" => ("200 OK", "j1", tokenizer_json),
+    ///             "/aleph/private/resolve/main/vocab.txt" => ("401 Unauthorized", "", ""),
+    ///             _ => ("404 Not Found", "", ""),
+    ///         };
+    ///         if status.starts_with("200") {
+    ///             sent.send(path).unwrap();
+    ///         }
+    ///         write!(
+    ///             stream,
+    ///             "HTTP/1.1 {}\r\nETag: \"{}\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+    ///             status,
+    ///             etag,
+    ///             body.len(),
+    ///             body
+    ///         )
+    ///         .unwrap();
+    ///     }
+    /// });
+    /// let cache = std::env::temp_dir().join(format!("aat-hub-{}", std::process::id()));
+    /// std::env::set_var("HF_HOME", &cache);
+    /// std::env::set_var("HF_ENDPOINT", &endpoint);
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let tokenizer = AlephAlphaTokenizer::from_pretrained("aleph/batman")?;
+    /// tokenizer.tokens_into("Superman", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 2], &ids[..]);
+    /// assert!(cache.join("hub/models--aleph--batman/main/vocab.txt").is_file());
+    /// assert_eq!("/aleph/batman/resolve/main/vocab.txt", downloads.try_recv()?);
+    ///
+    /// // the second time, the file is cached
+    /// AlephAlphaTokenizer::from_pretrained("aleph/batman")?;
+    /// assert!(downloads.try_recv().is_err());
+    ///
+    /// // a pinned revision, and a repository with only a `tokenizer.json`
+    /// let v0 = AlephAlphaTokenizer::from_pretrained("aleph/batman@v0")?;
+    /// assert_eq!(Some(1), v0.id_of("Bat"));
+    /// let json = AlephAlphaTokenizer::from_pretrained("aleph/json")?;
+    /// json.tokens_into("Batman", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 2], &ids[..]);
+    /// assert_eq!(2, downloads.try_iter().count());
+    ///
+    /// let error = AlephAlphaTokenizer::from_pretrained("aleph/private").err().unwrap();
+    /// assert!(matches!(error, HubError::AuthRequired { ref repo } if repo == "aleph/private"));
+    /// let error = AlephAlphaTokenizer::from_pretrained("aleph/nothing").err().unwrap();
+    /// assert!(matches!(error, HubError::NotFound { ref url } if url.ends_with("/tokenizer.json")));
+    ///
+    /// // offline, only the cached files can be loaded
+    /// std::env::set_var("HF_HUB_OFFLINE", "1");
+    /// AlephAlphaTokenizer::from_pretrained("aleph/batman@v0")?;
+    /// let error = AlephAlphaTokenizer::from_pretrained("aleph/batman@v1").err().unwrap();
+    /// assert!(matches!(error, HubError::Offline { .. }));
+    ///
+    /// // without a hub to reach, too
+    /// std::env::remove_var("HF_HUB_OFFLINE");
+    /// let closed = TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+    /// std::env::set_var("HF_ENDPOINT", format!("http://{}", closed));
+    /// AlephAlphaTokenizer::from_pretrained("aleph/batman")?;
+    /// let error = AlephAlphaTokenizer::from_pretrained("aleph/batman@v1").err().unwrap();
+    /// assert!(matches!(error, HubError::Network(_)));
+    /// assert!(downloads.try_recv().is_err());
+    /// std::fs::remove_dir_all(&cache)?;
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn from_pretrained(repo_id: &str) -> Result<Self, HubError> {
+        let (repo, revision) = match repo_id.find('@') {
+            Some(at) => (&repo_id[..at], &repo_id[at + 1..]),
+            None => (repo_id, "main"),
+        };
+        let hub = Hub::from_env();
+        match hub.fetch(repo, revision, "vocab.txt") {
+            Ok(path) => Ok(Self::from_vocab_bytes(&fs::read(path)?)?),
+            Err(HubError::NotFound { .. }) => {
+                let path = hub.fetch(repo, revision, "tokenizer.json")?;
+                let path = path.to_str().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "the cache path is not UTF-8")
+                })?;
+                Ok(Self::from_tokenizer_json(path)?)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,551 @@
+//! The integration with huggingface's tokenizers
+
+use crate::{AlephAlphaTokenizer, ConsistencyError};
+#[cfg(feature = "std-fs")]
+#[cfg(all(feature = "std-fs", not(feature = "json")))]
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tokenizers::decoders::DecoderWrapper;
+use tokenizers::normalizers::NormalizerWrapper;
+use tokenizers::pre_tokenizers::PreTokenizerWrapper;
+use tokenizers::processors::PostProcessorWrapper;
+use tokenizers::tokenizer::{
+    AddedToken, Decoder, Encoding, Model, OffsetType, Offsets, PreTokenizedString,
+    Token as HfToken, TokenizerImpl, Trainer,
+};
+
+/// A huggingface tokenizer with an [`AlephAlphaTokenizer`] as its model.
+///
+/// `Tokenizer::new` only takes huggingface's own models, so create the
+/// tokenizer with `HfTokenizer::new` instead. The normalizer, pre-tokenizer,
+/// post-processor and decoder are huggingface's; to decode with an
+/// [`AlephAlphaDecoder`], name it as the decoder type of the `TokenizerImpl`.
+pub type HfTokenizer = TokenizerImpl<
+    AlephAlphaTokenizer,
+    NormalizerWrapper,
+    PreTokenizerWrapper,
+    PostProcessorWrapper,
+    DecoderWrapper,
+>;
+
+// huggingface's tokenizers use `u32` ids
+fn hf_id(id: u64, key: &str) -> Result<u32, ConsistencyError> {
+    u32::try_from(id).map_err(|_| ConsistencyError::IdTooLarge {
+        key: key.to_string(),
+        id,
+    })
+}
+
+// like in `tokens_into_chars`, the first byte token of a char covers it, so
+// the other byte tokens of a char get an empty range after it
+fn char_aligned(word: &str, range: Range<usize>) -> Offsets {
+    let boundary = |mut i: usize| {
+        while !word.is_char_boundary(i) {
+            i += 1;
+        }
+        i
+    };
+    let start = boundary(range.start);
+    (start, boundary(range.end).max(start))
+}
+
+impl AlephAlphaTokenizer {
+    // tokenizes the word like `tokens_into` does, giving the tokens their
+    // vocabulary text as value and byte offsets within the word
+    fn tokenize_hf(&self, word: &str) -> Result<Vec<HfToken>, ConsistencyError> {
+        let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+        self.tokenize_word(
+            self.options.segmentation_mode,
+            word,
+            0..word.len(),
+            &mut ids,
+            &mut ranges,
+        );
+        ids.iter()
+            .zip(ranges)
+            .map(|(&id, range)| {
+                let id = hf_id(id, word)?;
+                let value = self.text_of(id).to_string();
+                Ok(HfToken::new(id, value, char_aligned(word, range)))
+            })
+            .collect()
+    }
+
+    /// Tokenizes the words of a pre-tokenized string into an [`Encoding`]
+    /// with the given type id.
+    ///
+    /// The offsets are in characters of the original text and each token
+    /// gets the index of its word, just like `Tokenizer::encode_char_offsets` gives them
+    /// without special tokens. The words are what a huggingface
+    /// `PreTokenizer` leaves in the `PreTokenizedString`. This requires the
+    /// `huggingface` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, HfTokenizer};
+    /// use tokenizers::{
+    ///     pre_tokenizers::bert::BertPreTokenizer,
+    ///     tokenizer::{PreTokenizedString, PreTokenizer},
+    /// };
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt")?;
+    /// let mut hf = HfTokenizer::new(AlephAlphaTokenizer::from_vocab("vocab.txt")?);
+    /// hf.with_pre_tokenizer(Some(BertPreTokenizer));
+    /// for text in &[
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend \
+    ///      Impfstoff gegen FSME zur Verfügung?",
+    ///     "",
+    /// ] {
+    ///     let expected = hf.encode_char_offsets(*text, false)?;
+    ///     for &type_id in &[0, 1] {
+    ///         let mut words = PreTokenizedString::from(*text);
+    ///         BertPreTokenizer.pre_tokenize(&mut words)?;
+    ///         let encoding = tokenizer.tokenize_into_encoding(words, type_id)?;
+    ///         assert_eq!(expected.get_ids(), encoding.get_ids());
+    ///         assert_eq!(expected.get_offsets(), encoding.get_offsets());
+    ///         assert_eq!(expected.get_word_ids(), encoding.get_word_ids());
+    ///         assert!(encoding.get_type_ids().iter().all(|&t| t == type_id));
+    ///     }
+    /// }
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn tokenize_into_encoding(
+        &self,
+        mut words: PreTokenizedString,
+        type_id: u32,
+    ) -> Result<Encoding, Box<dyn Error + Send + Sync>> {
+        words.tokenize(|word| Ok(self.tokenize_hf(word.get())?))?;
+        words.into_encoding(None, type_id, OffsetType::Char)
+    }
+}
+
+/// This type implements the [`Model`] trait so you can use it within
+/// huggingface's tokenizers framework, see [`HfTokenizer`].
+///
+/// The offsets are given in bytes, relative to the word as given by the
+/// pre-tokenizer, so the `Tokenizer` can map them back through the
+/// normalizer to the original text.
+///
+/// Tokenizing fails with [`ConsistencyError::IdTooLarge`] if a token's id
+/// doesn't fit into a `u32`. There is nothing to train, so the trainer is an
+/// [`AlephAlphaTrainer`].
+///
+/// # Examples
+///
+/// Each token's offsets cover the part of the original text it stems from:
+///
+/// ```
+///# use std::error::Error;
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, HfTokenizer};
+/// use tokenizers::{
+///     normalizers::bert::BertNormalizer,
+///     pre_tokenizers::bert::BertPreTokenizer,
+///     tokenizer::{NormalizedString, Normalizer},
+/// };
+///
+/// for &(strip_accents, lowercase) in &[(false, false), (true, false), (true, true)] {
+///     let normalizer = || BertNormalizer::new(true, true, Some(strip_accents), lowercase);
+///     let mut tokenizer = HfTokenizer::new(AlephAlphaTokenizer::from_vocab("vocab.txt")?);
+///     tokenizer.with_normalizer(Some(normalizer()))?;
+///     tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
+///     for text in &[
+///         "Ich  esse   Steak.",
+///         "Grüße aus Köln, München!",
+///         "  führende\tLeerzeichen?!",
+///         "Maßstäbe (für) Äpfel",
+///         "Kaf\u{7}fee und 中文 Tee",
+///     ] {
+///         let encoding = tokenizer.encode_char_offsets(*text, false)?;
+///         let chars: Vec<char> = text.chars().collect();
+///         for (token, &(start, end)) in encoding.get_tokens().iter().zip(encoding.get_offsets()) {
+///             let mut source = NormalizedString::from(chars[start..end].iter().collect::<String>());
+///             normalizer().normalize(&mut source)?;
+///             if token != "[UNK]" {
+///                 assert_eq!(source.get().trim(), token.trim_start_matches("##"), "{}", text);
+///             }
+///         }
+///     }
+/// }
+///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// The tokens are those of
+/// [`tokens_into`](AlephAlphaTokenizer::tokens_into), with their text in the
+/// vocabulary as value. A word the vocabulary doesn't fully cover becomes a
+/// single `[UNK]` token:
+///
+/// ```
+///# use std::error::Error;
+/// use aleph_alpha_tokenizer::examples;
+/// use tokenizers::tokenizer::Model;
+///
+/// let tokenizer = examples::tokenizer();
+/// let tokenize = |word| -> Result<Vec<_>, Box<dyn Error + Send + Sync>> {
+///     let tokens = tokenizer.tokenize(word)?;
+///     Ok(tokens.into_iter().map(|t| (t.id, t.value, t.offsets)).collect())
+/// };
+/// let token = |id, value: &str, offsets| (id, value.to_string(), offsets);
+/// assert_eq!(vec![token(16, "Super", (0, 5))], tokenize("Super")?);
+/// assert_eq!(
+///     vec![token(13, "Ste", (0, 3)), token(14, "##ak", (3, 5)), token(15, "##.", (5, 6))],
+///     tokenize("Steak.")?
+/// );
+/// assert_eq!(vec![token(1, "[UNK]", (0, 9))], tokenize("Supermän")?);
+/// assert_eq!(vec![token(1, "[UNK]", (0, 5))], tokenize("Über")?);
+///
+/// // saving writes the vocabulary (and with the `json` feature, the files
+/// // of `save_pretrained`)
+/// let dir = std::env::temp_dir();
+/// let files = tokenizer.save(&dir, Some("aat-hf-example"))?;
+/// assert_eq!(dir.join("aat-hf-example-vocab.txt"), files[0]);
+/// assert_eq!(if cfg!(feature = "json") { 3 } else { 1 }, files.len());
+/// let saved = aleph_alpha_tokenizer::AlephAlphaTokenizer::from_vocab(files[0].to_str().unwrap())?;
+/// assert_eq!(tokenizer.get_vocab(), saved.get_vocab());
+///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+impl Model for AlephAlphaTokenizer {
+    type Trainer = AlephAlphaTrainer;
+
+    fn tokenize(&self, sequence: &str) -> Result<Vec<HfToken>, Box<dyn Error + Send + Sync>> {
+        Ok(self.tokenize_hf(sequence)?)
+    }
+
+    fn token_to_id(&self, token: &str) -> Option<u32> {
+        self.id_of(token).and_then(|id| u32::try_from(id).ok())
+    }
+
+    fn id_to_token(&self, id: u32) -> Option<String> {
+        self.tokens.get(id as usize).map(str::to_string)
+    }
+
+    fn get_vocab(&self) -> HashMap<String, u32> {
+        AlephAlphaTokenizer::get_vocab(self)
+    }
+
+    fn get_vocab_size(&self) -> usize {
+        self.vocab_size()
+    }
+
+    #[cfg(not(feature = "std-fs"))]
+    fn save(
+        &self,
+        _folder: &Path,
+        _name: Option<&str>,
+    ) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+        Err("saving the vocabulary needs the `std-fs` feature".into())
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn save(
+        &self,
+        folder: &Path,
+        name: Option<&str>,
+    ) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+        #[cfg(feature = "json")]
+        return self.save_pretrained(folder, name);
+        #[cfg(not(feature = "json"))]
+        {
+            let vocab_name = name.map_or(Cow::Borrowed("vocab.txt"), |n| {
+                Cow::Borrowed(n) + "-vocab.txt"
+            });
+            let mut vocab_path = folder.to_path_buf();
+            vocab_path.push(Path::new(vocab_name.as_ref()));
+            Ok(vec![self.save_vocab(vocab_path)?])
+        }
+    }
+
+    fn get_trainer(&self) -> AlephAlphaTrainer {
+        AlephAlphaTrainer
+    }
+}
+
+/// The [`Trainer`] of an [`AlephAlphaTokenizer`] within huggingface's
+/// tokenizers.
+///
+/// The vocabulary is given when building the tokenizer, so feeding it
+/// ignores the texts and training fails.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::examples;
+/// use tokenizers::tokenizer::{Model, Trainer};
+///
+/// let mut tokenizer = examples::tokenizer();
+/// let mut trainer = tokenizer.get_trainer();
+/// trainer.feed(["Ich esse Steak."].iter(), |text| Ok(vec![text.to_string()])).unwrap();
+/// assert_eq!(
+///     "the vocabulary of an AlephAlphaTokenizer cannot be trained",
+///     trainer.train(&mut tokenizer).unwrap_err().to_string()
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AlephAlphaTrainer;
+
+impl Trainer for AlephAlphaTrainer {
+    type Model = AlephAlphaTokenizer;
+
+    fn should_show_progress(&self) -> bool {
+        false
+    }
+
+    fn train(
+        &self,
+        _model: &mut AlephAlphaTokenizer,
+    ) -> Result<Vec<AddedToken>, Box<dyn Error + Send + Sync>> {
+        Err("the vocabulary of an AlephAlphaTokenizer cannot be trained".into())
+    }
+
+    fn feed<I, S, F>(
+        &mut self,
+        _iterator: I,
+        _process: F,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>
+    where
+        I: Iterator<Item = S> + Send,
+        S: AsRef<str> + Send,
+        F: Fn(&str) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> + Sync,
+    {
+        Ok(())
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Gets the special tokens as [`AddedToken`]s.
+    ///
+    /// Register them with `Tokenizer::add_special_tokens`, so that
+    /// `Tokenizer::decode` can skip them if asked to.
+    pub fn special_added_tokens(&self) -> Vec<AddedToken> {
+        self.special_tokens
+            .iter()
+            .map(|&id| AddedToken::from(self.text_of(id).to_string(), true))
+            .collect()
+    }
+
+    /// Creates an [`AlephAlphaDecoder`] for this tokenizer's
+    /// [marker scheme](AlephAlphaTokenizer::marker_scheme), which decodes
+    /// like [`decode`](AlephAlphaTokenizer::decode).
+    ///
+    /// With an empty prefix, every token is a follower, so the decoder joins
+    /// all tokens without spaces. With an end-of-word suffix, the decoder
+    /// leaves out the suffix and puts a space after the tokens that have it
+    /// and after the special tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use tokenizers::tokenizer::{Decoder, Model};
+    ///
+    /// let tokenizer = |vocab: &str, prefix: &str| {
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_reader(vocab.as_bytes())
+    ///         .continuing_subword_prefix(prefix)
+    ///         .build()
+    /// };
+    /// let hashes = tokenizer("[UNK]\nSuper\n##man\nBat\n", "##")?;
+    /// let ats = tokenizer("[UNK]\nSuper\n@@man\nBat\n", "@@")?;
+    ///
+    /// let mut at_tokens = Vec::new();
+    /// for word in &["Superman", "Batman"] {
+    ///     let hash_tokens = hashes.tokenize(word)?;
+    ///     let tokens = ats.tokenize(word)?;
+    ///     for (hash, at) in hash_tokens.iter().zip(&tokens) {
+    ///         assert_eq!((hash.id, hash.offsets), (at.id, at.offsets));
+    ///         assert_eq!(hash.value.replace("##", "@@"), at.value);
+    ///         assert_eq!(Some(at.id), ats.token_to_id(&at.value));
+    ///     }
+    ///     at_tokens.extend(tokens);
+    /// }
+    ///
+    /// let values = at_tokens.into_iter().map(|t| t.value).collect();
+    /// assert_eq!("Superman Batman", ats.decoder().decode(values)?);
+    ///
+    /// // with an end-of-word suffix
+    /// use aleph_alpha_tokenizer::MarkerScheme;
+    ///
+    /// let suffixes = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader("[UNK]\n[CLS]\n[SEP]\nlow\nlow</w>\ner</w>\nest</w>\n".as_bytes())
+    ///     .marker_scheme(MarkerScheme::EndOfWordSuffix("</w>".to_string()))
+    ///     .build()?;
+    /// let values = ["[CLS]", "low", "er</w>", "low", "est</w>", "low</w>", "[SEP]"];
+    /// let values: Vec<_> = values.iter().map(|value| value.to_string()).collect();
+    /// assert_eq!("[CLS] lower lowest low [SEP]", suffixes.decoder().decode(values.clone())?);
+    /// let ids: Vec<_> = values.iter().map(|value| suffixes.token_to_id(value).unwrap()).collect();
+    /// assert_eq!(suffixes.decode(&ids, false), suffixes.decoder().decode(values)?);
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn decoder(&self) -> AlephAlphaDecoder {
+        if self.end_of_word_suffix.is_empty() {
+            return AlephAlphaDecoder::new(self.continuing_subword_prefix.clone(), true);
+        }
+        AlephAlphaDecoder {
+            special_tokens: self
+                .special_tokens()
+                .map(|(_, token)| token.to_string())
+                .collect(),
+            ..AlephAlphaDecoder::with_end_of_word_suffix(self.end_of_word_suffix.clone(), true)
+        }
+    }
+}
+
+/// A [`Decoder`] that undoes the splitting into starter and follower tokens.
+///
+/// Follower tokens are merged onto the previous token with their prefix
+/// (`##` by default) dropped, while all other tokens are separated by a space. Afterwards, the
+/// spaces before punctuation are cleaned up, exactly like huggingface's
+/// `WordPiece` decoder does. With an end-of-word suffix instead (see
+/// [`with_end_of_word_suffix`](AlephAlphaDecoder::with_end_of_word_suffix)),
+/// the tokens are merged unless the one before has the suffix, which is
+/// dropped.
+///
+/// Special tokens are skipped by the `Tokenizer` when `skip_special_tokens`
+/// is set, provided they were registered using
+/// [`AlephAlphaTokenizer::special_added_tokens`].
+///
+/// # Examples
+///
+/// ```
+///# use std::error::Error;
+/// use tokenizers::{
+///     decoders::wordpiece::WordPiece as WordPieceDecoder,
+///     models::wordpiece::WordPiece,
+///     normalizers::NormalizerWrapper,
+///     pre_tokenizers::{bert::BertPreTokenizer, PreTokenizerWrapper},
+///     processors::{bert::BertProcessing, PostProcessorWrapper},
+///     tokenizer::{Tokenizer, TokenizerImpl},
+/// };
+/// use aleph_alpha_tokenizer::{AlephAlphaDecoder, AlephAlphaTokenizer};
+///
+/// let aleph_alpha = AlephAlphaTokenizer::from_vocab("vocab.txt")?;
+/// let specials = aleph_alpha.special_added_tokens();
+/// let mut tokenizer: TokenizerImpl<
+///     AlephAlphaTokenizer,
+///     NormalizerWrapper,
+///     PreTokenizerWrapper,
+///     PostProcessorWrapper,
+///     AlephAlphaDecoder,
+/// > = TokenizerImpl::new(aleph_alpha);
+/// tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
+/// tokenizer.with_post_processor(Some(BertProcessing::new(
+///     ("[SEP]".to_string(), 4),
+///     ("[CLS]".to_string(), 3),
+/// )));
+/// tokenizer.with_decoder(Some(AlephAlphaDecoder::default()));
+/// tokenizer.add_special_tokens(specials.clone())?;
+///
+/// let mut wordpiece = Tokenizer::new(WordPiece::from_file("vocab.txt").build()?);
+/// wordpiece.with_pre_tokenizer(Some(BertPreTokenizer));
+/// wordpiece.with_post_processor(Some(BertProcessing::new(
+///     ("[SEP]".to_string(), 4),
+///     ("[CLS]".to_string(), 3),
+/// )));
+/// wordpiece.with_decoder(Some(WordPieceDecoder::default()));
+/// wordpiece.add_special_tokens(specials)?;
+///
+/// let text = "Ein Junge im Kindergarten spielt mit dem Ball.";
+/// let ids = tokenizer.encode(text, true)?.get_ids().to_vec();
+/// assert_eq!(text, tokenizer.decode(&ids, true)?);
+/// assert_eq!(format!("[CLS] {} [SEP]", text), tokenizer.decode(&ids, false)?);
+/// for &skip_special_tokens in &[true, false] {
+///     assert_eq!(
+///         wordpiece.decode(&ids, skip_special_tokens)?,
+///         tokenizer.decode(&ids, skip_special_tokens)?
+///     );
+/// }
+///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct AlephAlphaDecoder {
+    prefix: String,
+    suffix: String,
+    // the tokens that are words of their own with a suffix
+    special_tokens: Vec<String>,
+    cleanup: bool,
+}
+
+impl AlephAlphaDecoder {
+    /// Creates a decoder for the given follower prefix, optionally cleaning
+    /// up spaces before punctuation.
+    pub fn new(prefix: String, cleanup: bool) -> Self {
+        AlephAlphaDecoder {
+            prefix,
+            suffix: String::new(),
+            special_tokens: Vec::new(),
+            cleanup,
+        }
+    }
+
+    /// Creates a decoder for the given end-of-word suffix (e.g. `</w>`),
+    /// optionally cleaning up spaces before punctuation.
+    ///
+    /// Unlike the decoder of [`AlephAlphaTokenizer::decoder`], this doesn't
+    /// know the special tokens, so a token after one is merged onto it.
+    pub fn with_end_of_word_suffix(suffix: String, cleanup: bool) -> Self {
+        AlephAlphaDecoder {
+            suffix,
+            ..AlephAlphaDecoder::new(String::new(), cleanup)
+        }
+    }
+}
+
+impl Default for AlephAlphaDecoder {
+    fn default() -> Self {
+        AlephAlphaDecoder::new("##".to_string(), true)
+    }
+}
+
+// the same cleanup as huggingface's `WordPiece` decoder
+fn cleanup(token: &str) -> String {
+    token
+        .replace(" .", ".")
+        .replace(" ?", "?")
+        .replace(" !", "!")
+        .replace(" ,", ",")
+        .replace(" ' ", "'")
+        .replace(" n't", "n't")
+        .replace(" 'm", "'m")
+        .replace(" do not", " don't")
+        .replace(" 's", "'s")
+        .replace(" 've", "'ve")
+        .replace(" 're", "'re")
+}
+
+impl Decoder for AlephAlphaDecoder {
+    fn decode_chain(
+        &self,
+        mut tokens: Vec<String>,
+    ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        // whether the token before ended a word, for an end-of-word suffix
+        let mut ended = false;
+        for (i, token) in tokens.iter_mut().enumerate() {
+            if !self.suffix.is_empty() {
+                let special = self.special_tokens.contains(token);
+                let space = if ended { " " } else { "" };
+                let word = token
+                    .strip_suffix(self.suffix.as_str())
+                    .filter(|_| !special)
+                    .map(|word| format!("{}{}", space, word));
+                ended = word.is_some() || special;
+                *token = word.unwrap_or_else(|| format!("{}{}", space, token));
+            } else if i != 0 {
+                *token = match token.strip_prefix(self.prefix.as_str()) {
+                    Some(follower) => follower.to_string(),
+                    None => format!(" {}", token),
+                };
+            }
+            if self.cleanup {
+                *token = cleanup(token);
+            }
+        }
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{AlephAlphaTokenizer, Encoding, TokenID};
+use std::iter;
+use std::ops::Range;
+
+/// The changes to an [`Encoding`] after its text was edited, as returned by
+/// [`retokenize_span`](AlephAlphaTokenizer::retokenize_span).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodingPatch<T> {
+    /// The indices of the old tokens to replace
+    pub tokens: Range<usize>,
+    /// The ids of the new tokens
+    pub ids: Vec<T>,
+    /// The ranges of the new tokens in the edited text
+    pub ranges: Vec<Range<usize>>,
+    /// The indices of the new tokens of each new word, counting from the
+    /// first new token
+    pub words: Vec<Range<usize>>,
+    /// How far the ranges of the tokens after the replaced ones move
+    pub shift: isize,
+    // whether each new token is special
+    special_tokens_mask: Vec<T>,
+    // the range of the suffix token depends on the last word
+    suffix: bool,
+}
+
+impl<T: TokenID> EncodingPatch<T> {
+    /// Applies the patch to the old encoding, so it becomes the encoding of
+    /// the edited text.
+    pub fn apply(self, encoding: &mut Encoding<T>) {
+        let (first, len) = (self.tokens.start, self.ids.len());
+        let end = first + len;
+        // the replaced tokens are whole words, so are the replaced words
+        let words = &encoding.words;
+        let old_words = words.partition_point(|word| word.start < first)
+            ..words.partition_point(|word| word.start < self.tokens.end);
+        let moved = len as isize - self.tokens.len() as isize;
+        for word in &mut encoding.words[old_words.end..] {
+            *word = (word.start as isize + moved) as usize..(word.end as isize + moved) as usize;
+        }
+        let new_words = self.words.into_iter();
+        encoding.words.splice(
+            old_words,
+            new_words.map(|word| first + word.start..first + word.end),
+        );
+        encoding.ids.splice(self.tokens.clone(), self.ids);
+        encoding.ranges.splice(self.tokens.clone(), self.ranges);
+        let attention = iter::repeat_n(T::coerce(1), len);
+        encoding.attention.splice(self.tokens.clone(), attention);
+        let type_ids = iter::repeat_n(T::zero(), len);
+        encoding.type_ids.splice(self.tokens.clone(), type_ids);
+        encoding
+            .special_tokens_mask
+            .splice(self.tokens, self.special_tokens_mask);
+        let offset = self.shift;
+        let shift = |pos: usize| (pos as isize + offset) as usize;
+        for range in &mut encoding.ranges[end..] {
+            *range = shift(range.start)..shift(range.end);
+        }
+        if self.suffix {
+            let len = encoding.ranges.len();
+            let pos = encoding.ranges[..len - 1].last().map_or(0, |r| r.end);
+            encoding.ranges[len - 1] = pos..pos;
+        }
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Re-tokenizes only the words touched by an edit of the text of an
+    /// [`Encoding`].
+    ///
+    /// The `edit` is the byte range of the old text that was replaced by
+    /// `inserted_len` bytes, and `new_text` is the whole text after the edit.
+    /// The old encoding must have been created by this tokenizer. The edit
+    /// is extended to the surrounding whitespace, and only the words within
+    /// are tokenized again. With the `rejoin_hyphenation` option, words may
+    /// span lines, so the whole text is tokenized again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let mut text = "Ein Junge im Kindergarten spielt mit dem Ball.\nDer Hund spielt im Garten."
+    ///     .to_string();
+    /// let mut encoding = tokenizer.encode::<u64>(&text);
+    ///
+    /// // a simple pseudo-random generator to pick the edits
+    /// let mut seed = 42u64;
+    /// let mut random = |n: usize| {
+    ///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     (seed >> 33) as usize % n
+    /// };
+    /// let insertions = ["", " ", "  ", "\n", "e", "Haus", " und ", "ä", "Kranken", "."];
+    /// for _ in 0..300 {
+    ///     let boundaries: Vec<usize> = text
+    ///         .char_indices()
+    ///         .map(|(i, _)| i)
+    ///         .chain(Some(text.len()))
+    ///         .collect();
+    ///     let first = random(boundaries.len());
+    ///     let last = (first + random(4)).min(boundaries.len() - 1);
+    ///     let (start, end) = (boundaries[first], boundaries[last]);
+    ///     let inserted = insertions[random(insertions.len())];
+    ///     text.replace_range(start..end, inserted);
+    ///
+    ///     let patch = tokenizer.retokenize_span(&encoding, start..end, &text, inserted.len());
+    ///     patch.apply(&mut encoding);
+    ///     assert_eq!(tokenizer.encode::<u64>(&text), encoding, "{:?}", text);
+    /// }
+    /// ```
+    pub fn retokenize_span<T: TokenID>(
+        &self,
+        old: &Encoding<T>,
+        edit: Range<usize>,
+        new_text: &str,
+        inserted_len: usize,
+    ) -> EncodingPatch<T> {
+        let shift = inserted_len as isize - edit.len() as isize;
+        let (start, end) = if self.options.rejoin_hyphenation {
+            (0, new_text.len())
+        } else {
+            let whitespace = &self.options.whitespace;
+            let inserted_end = edit.start + inserted_len;
+            let start = new_text[..edit.start]
+                .char_indices()
+                .rev()
+                .find(|&(_, c)| whitespace.is_whitespace(c))
+                .map_or(0, |(i, c)| i + c.len_utf8());
+            let end = new_text[inserted_end..]
+                .find(|c: char| whitespace.is_whitespace(c))
+                .map_or(new_text.len(), |i| inserted_end + i);
+            (start, end)
+        };
+        let old_end = (end as isize - shift) as usize;
+        // the prefix and suffix tokens are never replaced
+        let has_prefix = self.options.add_prefix && self.prefix.is_some();
+        let has_suffix = self.options.add_suffix && self.suffix.is_some();
+        let body_start = has_prefix as usize;
+        let body = &old.ranges[body_start..old.len() - has_suffix as usize];
+        let first = body_start + body.partition_point(|r| r.end <= start);
+        let last = body_start + body.partition_point(|r| r.start < old_end);
+        let (mut ids, mut ranges, mut words) = (Vec::new(), Vec::new(), Vec::new());
+        self.words_into(
+            &new_text[..end],
+            start,
+            &mut ids,
+            &mut ranges,
+            Some(&mut words),
+        );
+        let mut special_tokens_mask = Vec::new();
+        self.special_tokens_mask_into(&ids, &mut special_tokens_mask);
+        EncodingPatch {
+            tokens: first..last,
+            ids,
+            ranges,
+            words,
+            shift,
+            special_tokens_mask,
+            suffix: has_suffix,
+        }
+    }
+
+    /// Tokenizes text that continues a text that was already tokenized, e.g.
+    /// what a user types after a prompt.
+    ///
+    /// The last word of `previous_text` may continue in `new_text`, so its
+    /// tokens may change, e.g. merge into one longer token. This tokenizes
+    /// from the start of that word, filling the tokens after it with their
+    /// ranges within the concatenated `previous_text` and `new_text`, and
+    /// returns how many of the tokens of `previous_text` they replace.
+    /// These are the trailing tokens of
+    /// [`extend_tokens_into`](AlephAlphaTokenizer::extend_tokens_into) with
+    /// neither `[CLS]` nor `[SEP]`, and no `[CLS]` or `[SEP]` tokens are
+    /// added here either. With the `rejoin_hyphenation` option, the
+    /// hyphenated words before the last word are tokenized again, too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let previous = "Der Hund spielt im Gar";
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.extend_tokens_into(previous, &mut ids, &mut ranges, None, false, false);
+    /// assert_eq!("Gar", tokenizer.text_of(ids[ids.len() - 1]));
+    ///
+    /// // "Gar" and "ten" merge into "Garten"
+    /// let (mut new_ids, mut new_ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let discard = tokenizer.tokens_into_continuation(previous, "ten.", &mut new_ids, &mut new_ranges);
+    /// assert_eq!(1, discard);
+    /// assert_eq!(&["Garten", "##."], &tokenizer.texts_of(&new_ids)[..]);
+    /// assert_eq!(&[19..25, 25..26], &new_ranges[..]);
+    ///
+    /// // "Hund" stays the same, and a complete word needs no tokens again
+    /// let discard = tokenizer.tokens_into_continuation("Der Hund", " spielt", &mut new_ids, &mut new_ranges);
+    /// assert_eq!(1, discard);
+    /// assert_eq!(&["Hund", "spielt"], &tokenizer.texts_of(&new_ids)[..]);
+    /// let discard = tokenizer.tokens_into_continuation("Der Hund ", "spielt", &mut new_ids, &mut new_ranges);
+    /// assert_eq!(0, discard);
+    /// assert_eq!(&["spielt"], &tokenizer.texts_of(&new_ids)[..]);
+    /// assert_eq!(&[9..15], &new_ranges[..]);
+    ///
+    /// // the same as tokenizing the concatenated text
+    /// let text = "Ein Junge im Kindergarten spielt mit dem Ball. Grüße ☃ Supercalifragilistic";
+    /// let (mut expected_ids, mut expected_ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.extend_tokens_into(text, &mut expected_ids, &mut expected_ranges, None, false, false);
+    /// for split in (0..=text.len()).filter(|&i| text.is_char_boundary(i)) {
+    ///     let (previous, new) = text.split_at(split);
+    ///     ids.clear();
+    ///     ranges.clear();
+    ///     tokenizer.extend_tokens_into(previous, &mut ids, &mut ranges, None, false, false);
+    ///     let discard = tokenizer.tokens_into_continuation(previous, new, &mut new_ids, &mut new_ranges);
+    ///     ids.truncate(ids.len() - discard);
+    ///     ids.extend_from_slice(&new_ids);
+    ///     ranges.truncate(ranges.len() - discard);
+    ///     ranges.extend_from_slice(&new_ranges);
+    ///     assert_eq!((&expected_ids, &expected_ranges), (&ids, &ranges), "{:?}", split);
+    /// }
+    /// ```
+    pub fn tokens_into_continuation<T: TokenID>(
+        &self,
+        previous_text: &str,
+        new_text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+    ) -> usize {
+        let start = self.complete_words_end(previous_text);
+        let (mut old_ids, mut old_ranges) = (Vec::<T>::new(), Vec::new());
+        self.words_into(previous_text, start, &mut old_ids, &mut old_ranges, None);
+        // only the incomplete words need to be joined with the new text
+        let text = [&previous_text[start..], new_text].concat();
+        token_ids.clear();
+        token_ranges.clear();
+        self.words_into(&text, 0, token_ids, token_ranges, None);
+        for range in token_ranges.iter_mut() {
+            *range = range.start + start..range.end + start;
+        }
+        old_ids.len()
+    }
+}
@@ -0,0 +1,388 @@
+use crate::clean::{self, CleanAction};
+use crate::{
+    is_ascii_whitespace, normalize, skip_whitespace, AlephAlphaTokenizer, TokenID, WhitespaceMode,
+};
+use std::ops::Range;
+use unicode_categories::UnicodeCategories;
+
+// is the character a CJK ideograph, as in BERT's basic tokenizer?
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{4E00}'..='\u{9FFF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{20000}'..='\u{2A6DF}'
+            | '\u{2A700}'..='\u{2B73F}'
+            | '\u{2B740}'..='\u{2B81F}'
+            | '\u{2B820}'..='\u{2CEAF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{2F800}'..='\u{2FA1F}'
+    )
+}
+
+// does the character at `pos` become a word of its own? A hyphen before a
+// line break stays with its word, so it can still be rejoined.
+fn splits_off(word: &str, pos: usize, c: char) -> bool {
+    if c.is_ascii_punctuation() || c.is_punctuation() {
+        c != '-' || !word[pos + 1..].starts_with(char::is_whitespace)
+    } else {
+        is_cjk(c)
+    }
+}
+
+// `splits_off` for an ASCII word, where all punctuation is ASCII
+fn splits_off_ascii(word: &[u8], pos: usize) -> bool {
+    let byte = word[pos];
+    byte.is_ascii_punctuation()
+        && (byte != b'-' || !word.get(pos + 1).copied().is_some_and(is_ascii_whitespace))
+}
+
+// splits a text into the words `tokens_into` tokenizes
+pub(crate) struct Spans<'t> {
+    text: &'t str,
+    pos: usize,
+    rejoin_hyphenation: bool,
+    split_punctuation: bool,
+    // the rest of the current word while splitting off punctuation
+    word: Range<usize>,
+    // whether the text is ASCII, so it can be split byte by byte
+    ascii: bool,
+    // the characters to clean, if cleaning
+    clean: Option<&'t [(char, CleanAction)]>,
+    // the characters between words instead of whitespace, if given
+    is_separator: Option<&'t dyn Fn(char) -> bool>,
+    // the whitespace between words, unless there are separators
+    whitespace: &'t WhitespaceMode,
+    // the tokenizer whose special tokens aren't split, if parsing them
+    specials: Option<&'t AlephAlphaTokenizer>,
+}
+
+impl<'t> Spans<'t> {
+    // does the character split words when cleaning?
+    fn separates(&self, c: char) -> bool {
+        self.clean
+            .is_some_and(|chars| clean::action(chars, c) == CleanAction::Separate)
+    }
+
+    // the offset of the next whitespace (or separator) after `pos`
+    fn find_whitespace(&self) -> Option<usize> {
+        let rest = &self.text[self.pos..];
+        if let Some(is_separator) = self.is_separator {
+            return rest.find(|c: char| is_separator(c) || self.separates(c));
+        }
+        // the bytes of a non-ASCII char are never ASCII whitespace
+        match (self.whitespace, self.ascii, self.clean) {
+            (WhitespaceMode::Unicode, true, None) | (WhitespaceMode::AsciiOnly, _, None) => {
+                rest.bytes().position(is_ascii_whitespace)
+            }
+            (WhitespaceMode::Unicode, true, Some(_))
+            | (WhitespaceMode::AsciiOnly, true, Some(_)) => rest
+                .bytes()
+                .position(|byte| is_ascii_whitespace(byte) || self.separates(char::from(byte))),
+            (WhitespaceMode::Unicode, false, None) => rest.find(char::is_whitespace),
+            _ => rest.find(|c: char| self.is_space(c) || self.separates(c)),
+        }
+    }
+
+    // is the character whitespace between words?
+    fn is_space(&self, c: char) -> bool {
+        self.whitespace.is_whitespace(c)
+    }
+
+    // the offset of the next word at or after `pos`
+    fn skip_whitespace(&self, mut pos: usize) -> usize {
+        if let Some(is_separator) = self.is_separator {
+            let rest = &self.text[pos..];
+            let skipped = rest.find(|c: char| !(is_separator(c) || self.separates(c)));
+            return pos + skipped.unwrap_or(rest.len());
+        }
+        if *self.whitespace != WhitespaceMode::Unicode {
+            let rest = &self.text[pos..];
+            let skipped = rest.find(|c: char| !(self.is_space(c) || self.separates(c)));
+            return pos + skipped.unwrap_or(rest.len());
+        }
+        loop {
+            pos = skip_whitespace(self.text, pos);
+            match self.text[pos..].chars().next() {
+                Some(c) if self.separates(c) => pos += c.len_utf8(),
+                _ => return pos,
+            }
+        }
+    }
+
+    // the next whitespace-separated word
+    fn next_word(&mut self) -> Option<Range<usize>> {
+        let text = self.text;
+        while let Some(next_ws) = self.find_whitespace() {
+            let start = self.pos;
+            let mut word_end = start + next_ws;
+            if self.rejoin_hyphenation && next_ws != 0 {
+                word_end = normalize::rejoined_word_end(text, start, word_end, self.whitespace);
+                if word_end == text.len() {
+                    break;
+                }
+            }
+            self.pos = self.skip_whitespace(word_end);
+            if word_end != start {
+                return Some(start..word_end);
+            }
+        }
+        if self.pos < text.len() {
+            let start = self.pos;
+            self.pos = text.len();
+            return Some(start..text.len());
+        }
+        None
+    }
+
+    // splits the next punctuation or CJK character or the text before it off
+    // the current word
+    fn next_piece(&mut self) -> Range<usize> {
+        let (word, start) = (&self.text[..self.word.end], self.word.start);
+        if self.ascii {
+            let bytes = word.as_bytes();
+            let end = if splits_off_ascii(bytes, start) {
+                start + 1
+            } else {
+                (start + 1..bytes.len())
+                    .find(|&pos| splits_off_ascii(bytes, pos))
+                    .unwrap_or(bytes.len())
+            };
+            self.word.start = end;
+            return start..end;
+        }
+        let mut chars = word[start..].char_indices();
+        let (_, first) = chars.next().expect("the word is not empty");
+        let end = if splits_off(word, start, first) {
+            start + first.len_utf8()
+        } else {
+            chars
+                .find(|&(i, c)| splits_off(word, start + i, c))
+                .map_or(word.len(), |(i, _)| start + i)
+        };
+        self.word.start = end;
+        start..end
+    }
+}
+
+impl<'t> Iterator for Spans<'t> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        loop {
+            let span = if self.split_punctuation {
+                if self.word.is_empty() {
+                    let word = self.next_word()?;
+                    let text = &self.text[word.clone()];
+                    if self.specials.is_some_and(|t| t.special_id(text).is_some()) {
+                        return Some(word);
+                    }
+                    self.word = word;
+                }
+                self.next_piece()
+            } else {
+                self.next_word()?
+            };
+            let span = match self.clean {
+                Some(chars) => clean::trim_removed(self.text, span, chars),
+                None => return Some(span),
+            };
+            // a span of only removed characters has no tokens
+            if !span.is_empty() {
+                return Some(span);
+            }
+        }
+    }
+}
+
+struct Tokens<'a> {
+    tokenizer: &'a AlephAlphaTokenizer,
+    text: &'a str,
+    spans: Spans<'a>,
+    // the tokens of the current word, which may still become an `[UNK]`
+    ids: Vec<u64>,
+    ranges: Vec<Range<usize>>,
+    next: usize,
+    // the end of the last token, where the suffix goes
+    end: usize,
+    suffix: bool,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (u64, Range<usize>);
+
+    fn next(&mut self) -> Option<(u64, Range<usize>)> {
+        while self.next == self.ids.len() {
+            self.ids.clear();
+            self.ranges.clear();
+            self.next = 0;
+            let tokenizer = self.tokenizer;
+            if let Some(span) = self.spans.next() {
+                tokenizer.tokenize_span(self.text, span, &mut self.ids, &mut self.ranges);
+            } else if self.suffix {
+                self.suffix = false;
+                if let (true, Some(id)) = (tokenizer.options.add_suffix, tokenizer.suffix) {
+                    self.ids.push(u64::from(id));
+                    self.ranges.push(self.end..self.end);
+                }
+            } else {
+                return None;
+            }
+        }
+        let (id, range) = (self.ids[self.next], self.ranges[self.next].clone());
+        self.next += 1;
+        self.end = range.end;
+        Some((id, range))
+    }
+}
+
+impl AlephAlphaTokenizer {
+    pub(crate) fn spans<'t>(&'t self, text: &'t str) -> Spans<'t> {
+        self.spans_from(text, 0)
+    }
+
+    // the word spans of `text[start..]`
+    pub(crate) fn spans_from<'t>(&'t self, text: &'t str, start: usize) -> Spans<'t> {
+        Spans {
+            text,
+            pos: start,
+            rejoin_hyphenation: self.options.rejoin_hyphenation,
+            split_punctuation: self.options.split_punctuation,
+            word: start..start,
+            ascii: self.ascii_fast_path && text.as_bytes()[start..].is_ascii(),
+            clean: self.clean_chars(),
+            is_separator: None,
+            whitespace: &self.options.whitespace,
+            specials: Some(self).filter(|t| t.options.parse_special_tokens),
+        }
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but splits
+    /// the text into words at the characters the predicate holds for
+    /// instead of at whitespace, e.g. to also split log lines at `/` and
+    /// `_`. The separators are in no token range, like whitespace there.
+    ///
+    /// With `char::is_whitespace` as the predicate, this gives the same
+    /// tokens as `tokens_into`, which is faster, though.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let is_separator = |c: char| c.is_whitespace() || matches!(c, '/' | '-' | '_');
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u64>::new(), Vec::new(), Vec::new());
+    /// let text = "foo/bar-baz";
+    /// tokenizer.tokens_into_split_by(text, is_separator, &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!(3, words.len());
+    /// let word_texts: Vec<&str> = words
+    ///     .iter()
+    ///     .map(|w| &text[ranges[w.start].start..ranges[w.end - 1].end])
+    ///     .collect();
+    /// assert_eq!(vec!["foo", "bar", "baz"], word_texts);
+    /// assert_eq!(&[0..0, 11..11], &[ranges[0].clone(), ranges.last().unwrap().clone()]);
+    ///
+    /// // at whitespace, the words and ranges are those of `tokens_into`
+    /// let (mut expected_ids, mut expected_ranges, mut expected_words) =
+    ///     (Vec::<u64>::new(), Vec::new(), Vec::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "  Der Hund\tspielt\u{a0}im Garten. ☃ ",
+    ///     "foo/bar-baz",
+    ///     "",
+    /// ] {
+    ///     tokenizer.tokens_into_split_by(
+    ///         text,
+    ///         char::is_whitespace,
+    ///         &mut ids,
+    ///         &mut ranges,
+    ///         Some(&mut words),
+    ///     );
+    ///     tokenizer.tokens_into(text, &mut expected_ids, &mut expected_ranges, Some(&mut expected_words));
+    ///     assert_eq!((&expected_ids, &expected_ranges), (&ids, &ranges));
+    ///     assert_eq!(expected_words, words);
+    /// }
+    /// ```
+    pub fn tokens_into_split_by<T: TokenID, F: Fn(char) -> bool>(
+        &self,
+        text: &str,
+        is_separator: F,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut words = words;
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        self.add_prefix(token_ids, token_ranges);
+        let spans = Spans {
+            is_separator: Some(&is_separator),
+            ..self.spans(text)
+        };
+        self.spans_into(text, spans, token_ids, token_ranges, words);
+        self.add_suffix(token_ids, token_ranges);
+    }
+
+    /// Lazily tokenizes the text, yielding the id and byte range of each
+    /// token, including the `[CLS]` and `[SEP]` tokens.
+    ///
+    /// This gives the same tokens as
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into), but only keeps the
+    /// tokens of one word at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let hyphenating = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .rejoin_hyphenation(true)
+    ///     .add_prefix(false)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff \
+    ///         gegen FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "  Kranken-\nhaus ☃  ",
+    ///     "",
+    /// ] {
+    ///     for tokenizer in &[&tokenizer, &hyphenating] {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         let (lazy_ids, lazy_ranges): (Vec<_>, Vec<_>) = tokenizer.tokens(text).unzip();
+    ///         assert_eq!(ids, lazy_ids, "{:?}", text);
+    ///         assert_eq!(ranges, lazy_ranges, "{:?}", text);
+    ///     }
+    /// }
+    /// ```
+    pub fn tokens<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (u64, Range<usize>)> + 'a {
+        let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+        self.add_prefix(&mut ids, &mut ranges);
+        Tokens {
+            tokenizer: self,
+            text,
+            spans: self.spans(text),
+            ids,
+            ranges,
+            next: 0,
+            end: 0,
+            suffix: true,
+        }
+    }
+}
@@ -0,0 +1,565 @@
+use crate::{AlephAlphaTokenizer, AlephAlphaTokenizerBuilder, TokenizerError, TokenizerOptions};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+fn invalid(message: String) -> TokenizerError {
+    TokenizerError::InvalidVocab(message)
+}
+
+fn read_json(path: &str) -> Result<Value, TokenizerError> {
+    serde_json::from_reader(BufReader::new(File::open(path)?)).map_err(|e| invalid(e.to_string()))
+}
+
+// puts the tokens of a token → id map in the order of their ids
+fn dense_tokens<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a Value)>,
+) -> Result<Vec<String>, TokenizerError> {
+    let mut slots: Vec<Option<String>> = Vec::new();
+    for (token, id) in entries {
+        let id = id
+            .as_u64()
+            .ok_or_else(|| invalid(format!("the id of {:?} is not an integer: {}", token, id)))?;
+        let index = id as usize;
+        if index >= slots.len() {
+            slots.resize(index + 1, None);
+        }
+        match &slots[index] {
+            Some(other) if other != token => {
+                return Err(invalid(format!(
+                    "both {:?} and {:?} have the id {}",
+                    other, token, id
+                )))
+            }
+            _ => slots[index] = Some(token.to_string()),
+        }
+    }
+    slots
+        .into_iter()
+        .enumerate()
+        .map(|(id, token)| token.ok_or_else(|| invalid(format!("no token has the id {}", id))))
+        .collect()
+}
+
+fn vocab_map(value: &Value) -> Result<&Map<String, Value>, TokenizerError> {
+    value
+        .as_object()
+        .ok_or_else(|| invalid("the vocabulary is not a JSON object".to_string()))
+}
+
+// the tokens of the model vocabulary and the added tokens
+fn tokenizer_tokens(json: &Value) -> Result<Vec<String>, TokenizerError> {
+    let vocab = vocab_map(&json["model"]["vocab"])?;
+    let added = json["added_tokens"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+    let added = added
+        .iter()
+        .filter_map(|token| Some((token["content"].as_str()?, &token["id"])));
+    dense_tokens(vocab.iter().map(|(k, v)| (k.as_str(), v)).chain(added))
+}
+
+// the first option that changes the tokens in a way a tokenizer.json cannot
+// express, if any
+fn unsaved_option(options: &TokenizerOptions) -> Option<&'static str> {
+    let default = TokenizerOptions::default();
+    [
+        ("rejoin_hyphenation", options.rejoin_hyphenation),
+        ("max_tokens_per_word", options.max_tokens_per_word.is_some()),
+        ("byte_fallback", options.byte_fallback),
+        (
+            "segmentation_mode",
+            options.segmentation_mode != default.segmentation_mode,
+        ),
+        ("unk_mode", options.unk_mode != default.unk_mode),
+        ("strip_accents", options.strip_accents),
+        ("normalize", options.normalize.is_some()),
+        ("clean_text", options.clean_text),
+        ("whitespace", options.whitespace != default.whitespace),
+        (
+            "fallback_separators",
+            !options.fallback_separators.is_empty(),
+        ),
+    ]
+    .iter()
+    .find(|&&(_, set)| set)
+    .map(|&(option, _)| option)
+}
+
+impl AlephAlphaTokenizerBuilder {
+    /// Reads the vocabulary from a huggingface `vocab.json` file, which maps
+    /// each token to its id.
+    ///
+    /// The ids must be dense, i.e. each id from `0` to the largest id must
+    /// belong to exactly one token. The vocabulary is read right away, but
+    /// errors are only returned by [`build`](AlephAlphaTokenizerBuilder::build).
+    ///
+    /// This is only available with the `json` feature.
+    pub fn vocab_json(self, path: &str) -> Self {
+        let tokens = read_json(path).and_then(|json| {
+            let vocab = vocab_map(&json)?;
+            dense_tokens(vocab.iter().map(|(k, v)| (k.as_str(), v)))
+        });
+        self.vocab_tokens(tokens)
+    }
+
+    /// Reads the vocabulary from a huggingface `tokenizer.json` file.
+    ///
+    /// Besides the vocabulary of the model (plus the `added_tokens`), this
+    /// sets the `unk_token`, `cls_token`, `sep_token`, `pad_token` and
+    /// `continuing_subword_prefix` of the model section when present. If
+    /// the model has no `cls_token` or `sep_token`, those of a `BertProcessing`
+    /// post processor are used, and without a `pad_token` the one of the
+    /// padding configuration. A `BertNormalizer` sets whether to
+    /// [`lowercase`](AlephAlphaTokenizerBuilder::lowercase), and a
+    /// `BertPreTokenizer` turns on
+    /// [`split_punctuation`](AlephAlphaTokenizerBuilder::split_punctuation).
+    /// Like [`vocab_json`], errors are only returned by
+    /// [`build`](AlephAlphaTokenizerBuilder::build).
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// [`vocab_json`]: AlephAlphaTokenizerBuilder::vocab_json
+    pub fn tokenizer_json(self, path: &str) -> Self {
+        let json = match read_json(path) {
+            Ok(json) => json,
+            Err(e) => return self.vocab_tokens(Err(e)),
+        };
+        let mut builder = self.vocab_tokens(tokenizer_tokens(&json));
+        let model = &json["model"];
+        let processor = &json["post_processor"];
+        if let Some(unk) = model["unk_token"].as_str() {
+            builder = builder.unk_token(unk);
+        }
+        if let Some(cls) = model["cls_token"]
+            .as_str()
+            .or_else(|| processor["cls"][0].as_str())
+        {
+            builder = builder.cls_token(cls);
+        }
+        if let Some(sep) = model["sep_token"]
+            .as_str()
+            .or_else(|| processor["sep"][0].as_str())
+        {
+            builder = builder.sep_token(sep);
+        }
+        let pad = model["pad_token"].as_str();
+        if let Some(pad) = pad.or_else(|| json["padding"]["pad_token"].as_str()) {
+            builder = builder.pad_token(pad);
+        }
+        if let Some(prefix) = model["continuing_subword_prefix"].as_str() {
+            builder = builder.continuing_subword_prefix(prefix);
+        }
+        let normalizer = &json["normalizer"];
+        if normalizer["type"] == "BertNormalizer" {
+            builder = builder.lowercase(normalizer["lowercase"] == true);
+        }
+        if json["pre_tokenizer"]["type"] == "BertPreTokenizer" {
+            builder = builder.split_punctuation(true);
+        }
+        builder
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Creates a tokenizer from a huggingface `vocab.json` file, see
+    /// [`AlephAlphaTokenizerBuilder::vocab_json`].
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let vocab = std::fs::read_to_string("vocab.txt")?;
+    /// let map: serde_json::Map<_, _> = vocab
+    ///     .lines()
+    ///     .enumerate()
+    ///     .map(|(id, token)| (token.to_string(), id.into()))
+    ///     .collect();
+    /// let path = std::env::temp_dir().join("aat-vocab.json");
+    /// std::fs::write(&path, serde_json::to_string(&map)?)?;
+    ///
+    /// let from_txt = AlephAlphaTokenizer::from_vocab("vocab.txt")?;
+    /// let from_json = AlephAlphaTokenizer::from_vocab_json(path.to_str().unwrap())?;
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut json_ids, mut json_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    /// ] {
+    ///     from_txt.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     from_json.tokens_into(text, &mut json_ids, &mut json_ranges, None);
+    ///     assert_eq!(ids, json_ids);
+    ///     assert_eq!(ranges, json_ranges);
+    /// }
+    ///
+    /// // ids must neither be missing nor be given twice
+    /// let path = std::env::temp_dir().join("aat-bad-vocab.json");
+    /// for bad in &[r#"{"[UNK]": 0, "foo": 2}"#, r#"{"[UNK]": 0, "foo": 1, "bar": 1}"#] {
+    ///     std::fs::write(&path, bad)?;
+    ///     assert!(AlephAlphaTokenizer::from_vocab_json(path.to_str().unwrap()).is_err());
+    /// }
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn from_vocab_json(path: &str) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_json(path).build()
+    }
+
+    /// Creates a tokenizer from a huggingface `tokenizer.json` file, see
+    /// [`AlephAlphaTokenizerBuilder::tokenizer_json`].
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer_json = r#"{
+    ///   "version": "1.0",
+    ///   "added_tokens": [
+    ///     {"id": 0, "content": "<pad>", "special": true},
+    ///     {"id": 1, "content": "<unk>", "special": true},
+    ///     {"id": 2, "content": "<s>", "special": true},
+    ///     {"id": 3, "content": "</s>", "special": true}
+    ///   ],
+    ///   "post_processor": {"type": "BertProcessing", "sep": ["</s>", 3], "cls": ["<s>", 2]},
+    ///   "padding": {"pad_id": 0, "pad_token": "<pad>"},
+    ///   "model": {
+    ///     "type": "WordPiece",
+    ///     "unk_token": "<unk>",
+    ///     "continuing_subword_prefix": "@@",
+    ///     "max_input_chars_per_word": 100,
+    ///     "vocab": {"<pad>": 0, "<unk>": 1, "<s>": 2, "</s>": 3, "Super": 4, "@@man": 5, "Bat": 6}
+    ///   }
+    /// }"#;
+    /// let path = std::env::temp_dir().join("aat-tokenizer.json");
+    /// std::fs::write(&path, tokenizer_json)?;
+    /// let from_json = AlephAlphaTokenizer::from_tokenizer_json(path.to_str().unwrap())?;
+    ///
+    /// let from_txt = AlephAlphaTokenizer::builder()
+    ///     .vocab_reader("<pad>\n<unk>\n<s>\n</s>\nSuper\n@@man\nBat\n".as_bytes())
+    ///     .unk_token("<unk>")
+    ///     .cls_token("<s>")
+    ///     .sep_token("</s>")
+    ///     .pad_token("<pad>")
+    ///     .continuing_subword_prefix("@@")
+    ///     .build()?;
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut json_ids, mut json_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// from_txt.tokens_into("Superman Batman Robin", &mut ids, &mut ranges, None);
+    /// from_json.tokens_into("Superman Batman Robin", &mut json_ids, &mut json_ranges, None);
+    /// assert_eq!(&[2, 4, 5, 6, 5, 1, 3], &ids[..]);
+    /// assert_eq!(ids, json_ids);
+    /// assert_eq!(ranges, json_ranges);
+    /// assert!(from_json.is_special(0u64));
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn from_tokenizer_json(path: &str) -> Result<Self, TokenizerError> {
+        Self::builder().tokenizer_json(path).build()
+    }
+
+    /// Saves the tokenizer as a huggingface `tokenizer.json` file with a
+    /// `WordPiece` model, so e.g. Python code using `transformers` can load
+    /// the same vocabulary.
+    ///
+    /// The file contains the vocabulary, the `[UNK]` token, the continuing
+    /// subword prefix and [`max_chars_per_word`] (or an unlimited number of
+    /// characters), the special tokens as `added_tokens`, a `WhitespaceSplit`
+    /// pre-tokenizer like the whitespace splitting of this crate as well as a
+    /// `BertProcessing` post-processor if `[CLS]` and `[SEP]` are added.
+    /// Lowercasing becomes a `BertNormalizer`, and splitting punctuation a
+    /// `BertNormalizer` that handles CJK ideographs plus a `BertPreTokenizer`.
+    ///
+    /// The other [`TokenizerOptions`] that change the tokens (e.g.
+    /// [`strip_accents`](TokenizerOptions::strip_accents), which keeps the
+    /// diaeresis of umlauts unlike huggingface's) have no counterpart, so
+    /// saving a tokenizer that sets them is an error, as is saving one with
+    /// [suppressed tokens](AlephAlphaTokenizer::suppressed_ids), only one of
+    /// `[CLS]` and `[SEP]` added, or an
+    /// [end-of-word suffix](crate::MarkerScheme::EndOfWordSuffix).
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, AlephAlphaTokenizerBuilder, Preset};
+    ///
+    /// let texts = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    /// ];
+    /// let path = std::env::temp_dir().join("aat-exported-tokenizer.json");
+    /// let path = path.to_str().unwrap();
+    /// // the uncased preset also strips accents with `unicode-normalization`
+    /// let uncased = AlephAlphaTokenizerBuilder::from_preset(Preset::BertUncased);
+    ///# #[cfg(feature = "unicode-normalization")]
+    /// let uncased = uncased.strip_accents(false);
+    /// for builder in vec![
+    ///     AlephAlphaTokenizer::builder(),
+    ///     AlephAlphaTokenizerBuilder::from_preset(Preset::BertCased),
+    ///     uncased,
+    /// ] {
+    ///     let tokenizer = builder.vocab_path("vocab.txt").build()?;
+    ///     tokenizer.save_tokenizer_json(path)?;
+    ///
+    ///     // loading the file again gives the same tokens
+    ///     let reloaded = AlephAlphaTokenizer::from_tokenizer_json(path)?;
+    ///     let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    ///     let (mut reloaded_ids, mut reloaded_ranges) = (Vec::<u64>::new(), Vec::new());
+    ///     for text in &texts {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         reloaded.tokens_into(text, &mut reloaded_ids, &mut reloaded_ranges, None);
+    ///         assert_eq!(ids, reloaded_ids);
+    ///         assert_eq!(ranges, reloaded_ranges);
+    ///     }
+    ///     let (options, reloaded) = (tokenizer.options(), reloaded.options());
+    ///     assert_eq!(options.lowercase, reloaded.lowercase);
+    ///     assert_eq!(options.split_punctuation, reloaded.split_punctuation);
+    ///
+    ///     // and so does huggingface's `Tokenizer` loading the file
+    ///#    #[cfg(feature = "huggingface")] {
+    ///     use tokenizers::tokenizer::Tokenizer;
+    ///
+    ///     let hf = Tokenizer::from_file(path)?;
+    ///     for text in &texts {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         let hf_ids: Vec<u64> = hf
+    ///             .encode(*text, true)?
+    ///             .get_ids()
+    ///             .iter()
+    ///             .map(|&id| u64::from(id))
+    ///             .collect();
+    ///         assert_eq!(ids, hf_ids, "{:?}", text);
+    ///     }
+    ///#    }
+    /// }
+    ///
+    /// // what a tokenizer.json cannot express is an error
+    /// for builder in vec![
+    ///     AlephAlphaTokenizer::builder().rejoin_hyphenation(true),
+    ///     AlephAlphaTokenizer::builder().suppressed_ids(&[464]),
+    ///     AlephAlphaTokenizerBuilder::from_preset(Preset::CausalLm),
+    /// ] {
+    ///     let tokenizer = builder.vocab_path("vocab.txt").build()?;
+    ///     assert!(tokenizer.save_tokenizer_json(path).is_err());
+    /// }
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    ///
+    /// [`max_chars_per_word`]: crate::AlephAlphaTokenizerBuilder::max_chars_per_word
+    pub fn save_tokenizer_json(&self, path: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !self.end_of_word_suffix.is_empty() {
+            return Err("a WordPiece model has no end-of-word suffix".into());
+        }
+        if !self.suppressed_ids.is_empty() {
+            return Err("a WordPiece model cannot suppress tokens".into());
+        }
+        if let Some(option) = unsaved_option(&self.options) {
+            return Err(
+                format!("the {} option cannot be saved in a tokenizer.json", option).into(),
+            );
+        }
+        let vocab: Map<String, Value> = self
+            .tokens
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token.trim().to_string(), id.into()))
+            .collect();
+        let added_tokens: Vec<Value> = self
+            .special_tokens
+            .iter()
+            .map(|&id| {
+                json!({
+                    "id": id,
+                    "content": self.tokens[id as usize].trim(),
+                    "single_word": false,
+                    "lstrip": false,
+                    "rstrip": false,
+                    "normalized": false,
+                    "special": true,
+                })
+            })
+            .collect();
+        let text_and_id = |id: u32| json!([self.tokens[id as usize].trim(), id]);
+        let cls = self.prefix.filter(|_| self.options.add_prefix);
+        let sep = self.suffix.filter(|_| self.options.add_suffix);
+        let post_processor = match (cls, sep) {
+            (Some(cls), Some(sep)) => json!({
+                "type": "BertProcessing",
+                "sep": text_and_id(sep),
+                "cls": text_and_id(cls),
+            }),
+            (None, None) => Value::Null,
+            _ => return Err("a BertProcessing post-processor adds both [CLS] and [SEP]".into()),
+        };
+        let options = &self.options;
+        let normalizer = if options.lowercase || options.split_punctuation {
+            json!({
+                "type": "BertNormalizer",
+                "clean_text": false,
+                "handle_chinese_chars": options.split_punctuation,
+                "strip_accents": false,
+                "lowercase": options.lowercase,
+            })
+        } else {
+            Value::Null
+        };
+        let pre_tokenizer = if options.split_punctuation {
+            "BertPreTokenizer"
+        } else {
+            "WhitespaceSplit"
+        };
+        let max_chars = self.options.max_chars_per_word.unwrap_or(usize::MAX);
+        let tokenizer = json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": added_tokens,
+            "normalizer": normalizer,
+            "pre_tokenizer": {"type": pre_tokenizer},
+            "post_processor": post_processor,
+            "decoder": {
+                "type": "WordPiece",
+                "prefix": self.continuing_subword_prefix,
+                "cleanup": true,
+            },
+            "model": {
+                "type": "WordPiece",
+                "unk_token": self.tokens[self.unk_id as usize].trim(),
+                "continuing_subword_prefix": self.continuing_subword_prefix,
+                "max_input_chars_per_word": max_chars,
+                "vocab": vocab,
+            },
+        });
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &tokenizer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Saves the vocabulary like
+    /// [`save_vocab`](AlephAlphaTokenizer::save_vocab) into the folder as
+    /// `vocab.txt`, together with the `special_tokens_map.json` and
+    /// `tokenizer_config.json` that transformers'
+    /// `BertTokenizer.from_pretrained` reads, and returns the paths of the
+    /// three files. With a name, each file name starts with it and a dash, as
+    /// with transformers' `filename_prefix`.
+    ///
+    /// The special tokens map has the texts of the `[UNK]`, `[CLS]`,
+    /// `[SEP]`, `[PAD]` and `[MASK]` tokens the tokenizer has, and the config
+    /// tells whether words are lowercased and the continuing subword prefix.
+    /// The tokenizer has no maximum length, so the config leaves
+    /// `model_max_length` to transformers. This is also what saving through
+    /// huggingface's `Model::save` does.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use serde_json::Value;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .build()?;
+    /// let dir = std::env::temp_dir().join(format!("aat-pretrained-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir)?;
+    /// let files = tokenizer.save_pretrained(&dir, None)?;
+    /// let names = ["vocab.txt", "special_tokens_map.json", "tokenizer_config.json"];
+    /// assert_eq!(names.iter().map(|name| dir.join(name)).collect::<Vec<_>>(), files);
+    ///
+    /// let read = |path| -> Result<Value, Box<dyn Error + Send + Sync>> {
+    ///     Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    /// };
+    /// let map = read(&files[1])?;
+    /// let ids = [
+    ///     ("unk_token", Some(tokenizer.unk_id())),
+    ///     ("cls_token", tokenizer.cls_id()),
+    ///     ("sep_token", tokenizer.sep_id()),
+    ///     ("pad_token", tokenizer.pad_id()),
+    ///     ("mask_token", tokenizer.mask_id()),
+    /// ];
+    /// for (key, id) in &ids {
+    ///     assert_eq!(Some(tokenizer.text_of(id.unwrap())), map[key].as_str());
+    /// }
+    /// assert_eq!(Some("[UNK]"), map["unk_token"].as_str());
+    ///
+    /// let config = read(&files[2])?;
+    /// assert_eq!(Some("BertTokenizer"), config["tokenizer_class"].as_str());
+    /// assert_eq!(Some(true), config["do_lower_case"].as_bool());
+    /// assert_eq!(Some("##"), config["continuing_subword_prefix"].as_str());
+    /// assert_eq!(Some("[MASK]"), config["mask_token"].as_str());
+    /// assert!(config.get("model_max_length").is_none());
+    ///
+    /// // with a name, that vocabulary stays apart
+    /// let files = tokenizer.save_pretrained(&dir, Some("small"))?;
+    /// assert_eq!(dir.join("small-special_tokens_map.json"), files[1]);
+    /// let saved = AlephAlphaTokenizer::from_vocab(files[0].to_str().unwrap())?;
+    /// assert_eq!(tokenizer.vocab_size(), saved.vocab_size());
+    /// std::fs::remove_dir_all(&dir)?;
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn save_pretrained(
+        &self,
+        folder: &Path,
+        name: Option<&str>,
+    ) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+        let path = |file: &str| match name {
+            Some(name) => folder.join(format!("{}-{}", name, file)),
+            None => folder.join(file),
+        };
+        let vocab_path = self.save_vocab(path("vocab.txt"))?;
+        let mut special_tokens = Map::new();
+        for (key, id) in &[
+            ("unk_token", Some(self.unk_id)),
+            ("cls_token", self.prefix),
+            ("sep_token", self.suffix),
+            ("pad_token", self.pad_id),
+            ("mask_token", self.mask_id),
+        ] {
+            if let Some(id) = id {
+                let token = self.tokens[*id as usize].trim();
+                special_tokens.insert(key.to_string(), token.into());
+            }
+        }
+        let mut config = special_tokens.clone();
+        config.insert("tokenizer_class".to_string(), "BertTokenizer".into());
+        config.insert("do_lower_case".to_string(), self.options.lowercase.into());
+        config.insert(
+            "continuing_subword_prefix".to_string(),
+            self.continuing_subword_prefix.as_str().into(),
+        );
+        let mut paths = vec![vocab_path];
+        for (file, value) in &[
+            ("special_tokens_map.json", special_tokens),
+            ("tokenizer_config.json", config),
+        ] {
+            let file_path = path(file);
+            let mut writer = BufWriter::new(File::create(&file_path)?);
+            serde_json::to_writer_pretty(&mut writer, value)?;
+            writer.flush()?;
+            paths.push(file_path);
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,230 @@
+use crate::AlephAlphaTokenizer;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// How [`align_labels`](AlephAlphaTokenizer::align_labels) spreads the label
+/// of a word over its tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelAlignStrategy {
+    /// Only the first token of each word gets its label, the others get the
+    /// ignored label, as in huggingface's token classification examples
+    #[default]
+    FirstSubtoken,
+    /// Every token of each word gets its label
+    AllSubtokens,
+}
+
+/// The errors of aligning word labels with the tokens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LabelError {
+    /// There are not as many labels as words
+    LengthMismatch {
+        /// the number of words
+        words: usize,
+        /// the number of labels
+        labels: usize,
+    },
+    /// A word has tokens beyond the number of tokens
+    WordOutOfRange {
+        /// the index of the word
+        word: usize,
+    },
+}
+
+impl fmt::Display for LabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelError::LengthMismatch { words, labels } => {
+                write!(f, "there are {} labels for {} words", labels, words)
+            }
+            LabelError::WordOutOfRange { word } => {
+                write!(f, "word {} has tokens beyond the last token", word)
+            }
+        }
+    }
+}
+
+impl Error for LabelError {}
+
+// checks that there's a label for each word and that the tokens of each
+// word are among the `total_tokens`
+fn check_words<L>(
+    words: &[Range<usize>],
+    labels: &[L],
+    total_tokens: usize,
+) -> Result<(), LabelError> {
+    if words.len() != labels.len() {
+        return Err(LabelError::LengthMismatch {
+            words: words.len(),
+            labels: labels.len(),
+        });
+    }
+    match words.iter().position(|word| word.end > total_tokens) {
+        Some(word) => Err(LabelError::WordOutOfRange { word }),
+        None => Ok(()),
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Expands the labels of the words to labels of the tokens, e.g. to
+    /// train a model for named entity recognition, given the `words` of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into) and the number of
+    /// tokens.
+    ///
+    /// The tokens that belong to no word, like `[CLS]`, `[SEP]` or any
+    /// padding up to `total_tokens`, get the `ignore` label (e.g. `-100`),
+    /// as do the tokens after the first of each word with
+    /// [`LabelAlignStrategy::FirstSubtoken`]. A word that became `[UNK]` is a
+    /// single token with the label of the word.
+    ///
+    /// # Errors
+    ///
+    /// Fails if there are not as many labels as words, or if a word has
+    /// tokens beyond `total_tokens`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, EncodeOptions, LabelAlignStrategy, LabelError};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ein Xylophon Beispiel";
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!("[CLS] Ein [UNK] Bei ##spiel [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// let labels = [0, 1, 2];
+    /// let first = tokenizer
+    ///     .align_labels(&words, &labels, ids.len(), LabelAlignStrategy::FirstSubtoken, -100)
+    ///     .unwrap();
+    /// assert_eq!(vec![-100, 0, 1, 2, -100, -100], first);
+    /// let all = tokenizer
+    ///     .align_labels(&words, &labels, ids.len(), LabelAlignStrategy::AllSubtokens, -100)
+    ///     .unwrap();
+    /// assert_eq!(vec![-100, 0, 1, 2, 2, -100], all);
+    ///
+    /// // padded to 8 tokens
+    /// let padded = tokenizer
+    ///     .align_labels(&words, &labels, 8, LabelAlignStrategy::AllSubtokens, -100)
+    ///     .unwrap();
+    /// assert_eq!(vec![-100, 0, 1, 2, 2, -100, -100, -100], padded);
+    ///
+    /// // without `[CLS]` and `[SEP]`
+    /// let options = EncodeOptions {
+    ///     skip_special_tokens: true,
+    ///     ..EncodeOptions::default()
+    /// };
+    /// tokenizer
+    ///     .tokens_into_with(text, &mut ids, &mut ranges, Some(&mut words), &options)
+    ///     .unwrap();
+    /// let first = tokenizer
+    ///     .align_labels(&words, &labels, ids.len(), LabelAlignStrategy::FirstSubtoken, -100)
+    ///     .unwrap();
+    /// assert_eq!(vec![0, 1, 2, -100], first);
+    ///
+    /// assert_eq!(
+    ///     Err(LabelError::LengthMismatch { words: 3, labels: 2 }),
+    ///     tokenizer.align_labels(&words, &labels[..2], ids.len(), LabelAlignStrategy::default(), -100)
+    /// );
+    /// assert_eq!(
+    ///     Err(LabelError::WordOutOfRange { word: 2 }),
+    ///     tokenizer.align_labels(&words, &labels, 3, LabelAlignStrategy::default(), -100)
+    /// );
+    /// ```
+    pub fn align_labels<L: Copy>(
+        &self,
+        words: &[Range<usize>],
+        labels: &[L],
+        total_tokens: usize,
+        strategy: LabelAlignStrategy,
+        ignore: L,
+    ) -> Result<Vec<L>, LabelError> {
+        check_words(words, labels, total_tokens)?;
+        let mut aligned = vec![ignore; total_tokens];
+        for (word, &label) in words.iter().zip(labels) {
+            let tokens = match strategy {
+                LabelAlignStrategy::FirstSubtoken => word.start..word.end.min(word.start + 1),
+                LabelAlignStrategy::AllSubtokens => word.clone(),
+            };
+            aligned[tokens].fill(label);
+        }
+        Ok(aligned)
+    }
+
+    /// Like [`align_labels`](AlephAlphaTokenizer::align_labels) with
+    /// [`LabelAlignStrategy::AllSubtokens`], but for the tags of the BIO or
+    /// BILOU schemes (e.g. `B-PER`), which are converted so the tokens
+    /// within a word continue the entity.
+    ///
+    /// Only the first token of a word keeps a `B-` tag, and only the last a
+    /// `L-` tag, while the other tokens get `I-` tags; a `U-` tag (a single
+    /// token entity) becomes `B-` and `L-` around `I-` tags. Other tags like
+    /// `O` and `I-` tags are copied to every token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Beispiel Ein Xylophon Beispiel Beispiel";
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!(
+    ///     "[CLS] Bei ##spiel Ein [UNK] Bei ##spiel Bei ##spiel [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    ///
+    /// let bio = ["B-LOC", "O", "B-MISC", "B-PER", "I-PER"];
+    /// let tags = tokenizer.align_tagged_labels(&words, &bio, ids.len(), "").unwrap();
+    /// assert_eq!(
+    ///     vec!["", "B-LOC", "I-LOC", "O", "B-MISC", "B-PER", "I-PER", "I-PER", "I-PER", ""],
+    ///     tags
+    /// );
+    ///
+    /// let bilou = ["U-LOC", "O", "U-MISC", "B-PER", "L-PER"];
+    /// let tags = tokenizer.align_tagged_labels(&words, &bilou, ids.len(), "").unwrap();
+    /// assert_eq!(
+    ///     vec!["", "B-LOC", "L-LOC", "O", "U-MISC", "B-PER", "I-PER", "I-PER", "L-PER", ""],
+    ///     tags
+    /// );
+    /// assert!(tokenizer.align_tagged_labels(&words, &bilou[1..], ids.len(), "").is_err());
+    /// ```
+    pub fn align_tagged_labels(
+        &self,
+        words: &[Range<usize>],
+        labels: &[&str],
+        total_tokens: usize,
+        ignore: &str,
+    ) -> Result<Vec<String>, LabelError> {
+        check_words(words, labels, total_tokens)?;
+        let mut aligned = vec![ignore.to_string(); total_tokens];
+        for (word, &label) in words.iter().zip(labels) {
+            let len = word.len();
+            let (scheme, entity) = match label.split_once('-') {
+                Some((scheme, entity)) if len > 1 => (scheme, entity),
+                // a single token keeps the tag of its word
+                _ => ("", label),
+            };
+            for (i, tag) in aligned[word.clone()].iter_mut().enumerate() {
+                let (first, last) = (i == 0, i + 1 == len);
+                let scheme = match scheme {
+                    "B" if !first => "I",
+                    "L" if !last => "I",
+                    "U" if first => "B",
+                    "U" if last => "L",
+                    "U" => "I",
+                    "" => {
+                        *tag = entity.to_string();
+                        continue;
+                    }
+                    scheme => scheme,
+                };
+                *tag = format!("{}-{}", scheme, entity);
+            }
+        }
+        Ok(aligned)
+    }
+}
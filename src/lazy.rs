@@ -0,0 +1,184 @@
+use crate::AlephAlphaTokenizer;
+#[cfg(feature = "datrie")]
+use crate::{datrie::DoubleArray, MatcherBackend};
+use fst::Streamer;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// Indexes that are only built on first use, so workloads that never need
+/// them don't pay for their memory.
+#[derive(Debug, Default)]
+pub(crate) struct LazyIndexes {
+    // the ids of the special tokens by their (trimmed) text, for `id_of`
+    special_ids: OnceLock<HashMap<String, u64>>,
+    // one bit per token id, for `is_special`
+    special_bits: OnceLock<Vec<u64>>,
+    // one bit per token id each for starters and followers, for
+    // `is_starter` and `is_follower`
+    word_bits: OnceLock<(Vec<u64>, Vec<u64>)>,
+    // the double-array tries of the starters and followers, for
+    // `MatcherBackend::DoubleArray`
+    #[cfg(feature = "datrie")]
+    tries: OnceLock<(DoubleArray, DoubleArray)>,
+    // the number of indexes built so far
+    builds: AtomicUsize,
+}
+
+impl LazyIndexes {
+    fn built(&self) {
+        self.builds.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// sets one bit per id, ignoring ids beyond `len`
+fn bitset(len: usize, ids: impl Iterator<Item = u64>) -> Vec<u64> {
+    let mut bits = vec![0u64; len.div_ceil(64)];
+    for id in ids {
+        if let Some(word) = bits.get_mut(id as usize / 64) {
+            *word |= 1 << (id % 64);
+        }
+    }
+    bits
+}
+
+fn has_bit(bits: &[u64], id: u64) -> bool {
+    bits.get((id / 64) as usize)
+        .is_some_and(|word| word & (1 << (id % 64)) != 0)
+}
+
+impl AlephAlphaTokenizer {
+    // looks up a special token by its text
+    pub(crate) fn special_id(&self, token: &str) -> Option<u64> {
+        let indexes = &self.indexes;
+        let ids = indexes.special_ids.get_or_init(|| {
+            indexes.built();
+            self.special_tokens
+                .iter()
+                .map(|&id| (self.tokens[id as usize].trim().to_string(), id))
+                .collect()
+        });
+        ids.get(token).cloned()
+    }
+
+    // determines whether the id belongs to a special token
+    pub(crate) fn special_bit(&self, id: u64) -> bool {
+        let indexes = &self.indexes;
+        let bits = indexes.special_bits.get_or_init(|| {
+            indexes.built();
+            bitset(self.tokens.len(), self.special_tokens.iter().cloned())
+        });
+        has_bit(bits, id)
+    }
+
+    // finds an id whose special bit disagrees with the special tokens, and
+    // whether it is one of them
+    pub(crate) fn special_bit_mismatch(&self) -> Option<(u64, bool)> {
+        self.special_bit(0);
+        let bits = self.indexes.special_bits.get()?;
+        let specials: HashSet<u64> = self.special_tokens.iter().cloned().collect();
+        (0..bits.len() as u64 * 64)
+            .map(|id| (id, specials.contains(&id)))
+            .find(|&(id, special)| has_bit(bits, id) != special)
+    }
+
+    // looks up the bits of the tokens in the starter and follower FSTs
+    fn word_bits(&self) -> &(Vec<u64>, Vec<u64>) {
+        let indexes = &self.indexes;
+        indexes.word_bits.get_or_init(|| {
+            indexes.built();
+            let ids = |fst: &fst::raw::Fst<_>| {
+                let (mut ids, mut stream) = (Vec::new(), fst.stream());
+                while let Some((_, output)) = stream.next() {
+                    ids.push(output.value());
+                }
+                ids
+            };
+            let len = self.tokens.len();
+            (
+                bitset(len, ids(&self.starters).into_iter()),
+                bitset(len, ids(&self.followers).into_iter()),
+            )
+        })
+    }
+
+    // determines whether the id can start a word
+    pub(crate) fn starter_bit(&self, id: u64) -> bool {
+        has_bit(&self.word_bits().0, id)
+    }
+
+    // determines whether the id can continue a word
+    pub(crate) fn follower_bit(&self, id: u64) -> bool {
+        has_bit(&self.word_bits().1, id)
+    }
+
+    // gets the double-array tries of the starters and followers, if words
+    // are matched against them
+    #[cfg(feature = "datrie")]
+    pub(crate) fn tries(&self) -> Option<(&DoubleArray, &DoubleArray)> {
+        if self.backend != MatcherBackend::DoubleArray {
+            return None;
+        }
+        let indexes = &self.indexes;
+        let (starters, followers) = indexes.tries.get_or_init(|| {
+            indexes.built();
+            (
+                DoubleArray::from_fst(&self.starters),
+                DoubleArray::from_fst(&self.followers),
+            )
+        });
+        Some((starters, followers))
+    }
+
+    // builds all lazy indexes now
+    pub(crate) fn build_indexes(&self) {
+        self.special_id("");
+        self.special_bit(0);
+        self.starter_bit(0);
+    }
+
+    /// Gets the number of lazy indexes built so far, to check that each is
+    /// only built once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use std::sync::{Arc, Barrier};
+    ///
+    /// let tokenizer = Arc::new(AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap());
+    /// assert_eq!(0, tokenizer.lazy_index_builds());
+    /// let barrier = Arc::new(Barrier::new(16));
+    /// let threads: Vec<_> = (0..16)
+    ///     .map(|_| {
+    ///         let (tokenizer, barrier) = (tokenizer.clone(), barrier.clone());
+    ///         std::thread::spawn(move || {
+    ///             barrier.wait();
+    ///             assert_eq!(Some(3), tokenizer.id_of("[CLS]"));
+    ///             assert_eq!(None, tokenizer.id_of("Supercalifragilistic"));
+    ///             assert!(tokenizer.is_special(4u64));
+    ///             assert!(!tokenizer.is_special(42u64));
+    ///             assert!(tokenizer.is_follower(6u64));
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for thread in threads {
+    ///     thread.join().unwrap();
+    /// }
+    /// assert_eq!(3, tokenizer.lazy_index_builds());
+    ///
+    /// let eager = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .eager_indexes(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(3, eager.lazy_index_builds());
+    /// assert!(eager.is_special(0u64));
+    /// assert!(eager.is_starter(0u64));
+    /// assert_eq!(3, eager.lazy_index_builds());
+    /// ```
+    #[doc(hidden)]
+    pub fn lazy_index_builds(&self) -> usize {
+        self.indexes.builds.load(Ordering::Relaxed)
+    }
+}
@@ -20,39 +20,58 @@
 //! aleph-alpha-tokenizers = { version = "0.3", features = ["huggingface"] }
 //! ```
 //!
+//! Enable the `rayon` feature to tokenize batches of texts in parallel.
+//! Enable the `serde` feature to serialize [`WordAlignment`]s, the
+//! [`EncodeOptions`] and [`TokenizerOptions`], and the tokenizer itself.
+//! Enable the `json` feature to load huggingface `vocab.json` and
+//! `tokenizer.json` files (see `AlephAlphaTokenizer::from_tokenizer_json`).
+//! Enable the `hub` feature to download vocabularies from the Hugging Face
+//! Hub (see `AlephAlphaTokenizer::from_pretrained`).
+//! Enable the `mmap` feature to share the FSTs between processes by mapping
+//! them from files (see `AlephAlphaTokenizer::from_fst_files`).
+//! Enable the `ndarray` feature to tokenize batches into `ndarray` arrays
+//! (see `AlephAlphaTokenizer::encode_to_arrays`), e.g. for ONNX Runtime.
+//! Enable the `arrow` feature to tokenize batches into Apache Arrow
+//! `ListArray`s (see `AlephAlphaTokenizer::encode_batch_arrow`).
+//! Enable the `rand` feature to mask tokens for masked language modeling
+//! (see `AlephAlphaTokenizer::mask_tokens`).
+//! Enable the `torch` feature to tokenize batches straight into `tch`
+//! tensors (see `AlephAlphaTokenizer::encode_to_tensors`); this needs
+//! libtorch, as described by the `tch` crate.
+//!
+//! The default `std-fs` feature adds everything that reads or writes files,
+//! like [`AlephAlphaTokenizer::from_vocab`] and the [`corpus`] module.
+//! Without it (e.g. for `wasm32-unknown-unknown`), load the vocabulary from
+//! memory with [`AlephAlphaTokenizer::from_vocab_bytes`].
+//!
 //! # Examples
 //!
-//! To use as a [`Model`](../tokenizers/tokenizer/trait.Model.html), you need
-//! to box it:
+//! To use as a [`Model`](../tokenizers/tokenizer/trait.Model.html), create
+//! an `HfTokenizer`, huggingface's `TokenizerImpl` with this model:
 //!
 //! ```
 //!# use std::error::Error;
 //!
 //!# #[cfg(feature = "huggingface")] {
-//! use tokenizers::{
-//!     tokenizer::{EncodeInput, Model, Tokenizer},
-//!     pre_tokenizers::bert::BertPreTokenizer,
-//! };
-//! use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+//! use tokenizers::pre_tokenizers::bert::BertPreTokenizer;
+//! use aleph_alpha_tokenizer::{AlephAlphaTokenizer, HfTokenizer};
 //!
-//! let mut tokenizer = Tokenizer::new(
-//!     Box::new(AlephAlphaTokenizer::from_vocab("vocab.txt")?));
-//! tokenizer.with_pre_tokenizer(Box::new(BertPreTokenizer));
-//! let _result = tokenizer.encode(
-//!     EncodeInput::Single("Some Test".to_string()), true)?;
+//! let mut tokenizer = HfTokenizer::new(AlephAlphaTokenizer::from_vocab("vocab.txt")?);
+//! tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
+//! let _result = tokenizer.encode("Some Test", true)?;
 //!# }
 //!# Ok::<_, Box<dyn Error + Send + Sync>>(())
 //! ```
 //!
 //! Remember this depends on the `huggingface` feature. Otherwise, you can use
-//! it directly:
+//! it directly (here with the tiny vocabulary of the [`examples`] module):
 //!
 //! ```
 //!# use std::error::Error;
-//! use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+//! use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
 //!
 //! let source_text = "Ein interessantes Beispiel";
-//! let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt")?;
+//! let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(examples::VOCAB.as_bytes())?;
 //! let mut ids: Vec<i64> = Vec::new();
 //! let mut ranges = Vec::new();
 //! tokenizer.tokens_into(source_text, &mut ids, &mut ranges, None);
@@ -64,44 +83,304 @@
 //! }
 //!# Ok::<_, Box<dyn Error + Send + Sync>>(())
 //! ```
+//!
+//! To configure the tokenizer, e.g. for vocabularies with other special
+//! tokens, use the [`AlephAlphaTokenizerBuilder`]:
+//!
+//! ```
+//!# use std::error::Error;
+//! use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+//!
+//! let tokenizer = AlephAlphaTokenizer::builder()
+//!     .vocab_reader(examples::VOCAB.as_bytes())
+//!     .add_special_tokens(false)
+//!     .max_chars_per_word(Some(10))
+//!     .build()?;
+//! let mut ids: Vec<i64> = Vec::new();
+//! let mut ranges = Vec::new();
+//! tokenizer.tokens_into("Ein interessantes Beispiel", &mut ids, &mut ranges, None);
+//! assert_eq!(&[5, examples::UNK as i64, 8, 9], &ids[..]);
+//!# Ok::<_, Box<dyn Error + Send + Sync>>(())
+//! ```
 
 use fst::raw::{Fst, Output};
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::mem::replace;
+use matcher::{ends_on_char, Matchers, PrefixMatcher};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+#[cfg(feature = "std-fs")]
+use std::fs::{self, File};
+#[cfg(feature = "std-fs")]
+use std::io;
+use std::io::BufRead;
+#[cfg(feature = "std-fs")]
+use std::io::{BufWriter, Write};
 use std::ops::Range;
+#[cfg(feature = "std-fs")]
 use std::path::PathBuf;
+use std::sync::Arc;
+
+mod added;
+mod alignment;
+mod analysis;
+#[cfg(feature = "ndarray")]
+mod arrays;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "tokio")]
+mod async_batch;
+mod batch;
+mod binary;
+mod builder;
+mod bytes;
+mod clean;
+mod compact;
+mod complete;
+mod config;
+mod consistency;
+#[cfg(feature = "std-fs")]
+pub mod corpus;
+mod coverage;
+#[cfg(feature = "datrie")]
+mod datrie;
+mod decode;
+#[cfg(feature = "huggingface")]
+mod differential;
+mod encode;
+mod encoding;
+mod error;
+mod estimate;
+pub mod examples;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "hub")]
+mod hub;
+#[cfg(feature = "huggingface")]
+mod huggingface;
+mod incremental;
+mod iter;
+#[cfg(feature = "json")]
+mod json;
+mod labels;
+mod lazy;
+mod limit;
+mod lookup;
+#[cfg(feature = "rand")]
+mod masking;
+pub mod matcher;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod modes;
+mod normalize;
+mod offsets;
+mod pair;
+mod presplit;
+mod prune;
+#[cfg(feature = "python")]
+mod python;
+mod reconstruct;
+#[cfg(feature = "std-fs")]
+mod sentencepiece;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sink;
+mod slice;
+mod special;
+mod stats;
+mod stream;
+mod suffix;
+mod texts;
+#[cfg(feature = "torch")]
+mod torch;
+mod trainer;
+mod version;
+mod window;
 
+pub use alignment::{Alignment, WordAlignment};
+pub use analysis::CoverageReport;
+pub use batch::{PackedBatch, PaddedBatch};
+pub use binary::BinaryFormatError;
+pub use builder::{AlephAlphaTokenizerBuilder, Preset, TokenizerOptions};
+pub use clean::CleanAction;
+pub use compact::OffsetOverflow;
+pub use config::OptionsError;
+pub use consistency::{ConsistencyError, VocabWarning};
+pub use coverage::RangeError;
+pub use decode::{StreamingDecoder, UnknownIdError};
 #[cfg(feature = "huggingface")]
-use tokenizers::tokenizer::{Model, Token as HfToken};
+pub use differential::{compare_paths, Divergence, PathToken};
+pub use encode::{EncodeError, EncodeOptions, TruncationStrategy};
+pub use encoding::Encoding;
+pub use error::TokenizerError;
+#[cfg(feature = "hub")]
+pub use hub::HubError;
+#[cfg(feature = "huggingface")]
+pub use huggingface::{AlephAlphaDecoder, AlephAlphaTrainer, HfTokenizer};
+pub use incremental::EncodingPatch;
+pub use labels::{LabelAlignStrategy, LabelError};
+pub use limit::LimitExceeded;
+pub use lookup::UnknownToken;
+#[cfg(feature = "rand")]
+pub use masking::MaskingConfig;
+pub use modes::{
+    LiteralMode, MarkerScheme, MatcherBackend, ModeReport, SegmentationMode, UnicodeNormalization,
+    UnkMode, UnknownSeparator, WhitespaceMode,
+};
+pub use offsets::{OffsetKind, OffsetMap};
+#[cfg(feature = "python")]
+pub use python::PyAlephAlphaTokenizer;
+pub use sink::TokenSink;
+pub use slice::{CapacityError, SliceTokens};
+pub use special::SpecialTokens;
+pub use stats::{TokenizeStats, VocabStats};
+pub use trainer::{TrainerConfig, VocabTrainer};
+pub use version::{features, has_feature, VersionInfo};
 
-// TODO: this should be upstreamed into fst
-//
-// For now, we'll keep it here.
-#[inline]
-fn find_longest_prefix<D: AsRef<[u8]>>(fst: &Fst<D>, input: &[u8]) -> Option<(usize, u64)> {
+use estimate::Calibration;
+use lazy::LazyIndexes;
+use normalize::NormalizedWord;
+use texts::TokenTexts;
+
+// like `longest_prefix`, but collect all matches as (length, id), from
+// the shortest to the longest
+fn find_prefixes<D: AsRef<[u8]>>(fst: &Fst<D>, input: &[u8], matches: &mut Vec<(usize, u64)>) {
+    matches.clear();
     let mut node = fst.root();
     let mut out = Output::zero();
-    let mut last_match: Option<(usize, Output)> = None;
     for (i, &b) in input.iter().enumerate() {
         if let Some(trans_index) = node.find_input(b) {
             let t = node.transition(trans_index);
             node = fst.node(t.addr);
             out = out.cat(t.out);
-            if node.is_final() {
-                last_match = Some((i + 1, out.cat(node.final_output())));
+            if node.is_final() && ends_on_char(input, i + 1) {
+                matches.push((i + 1, out.cat(node.final_output()).value()));
             }
         } else {
             break;
         }
     }
-    last_match.map(|(i, o)| (i, o.value()))
+}
+
+// where `tokenize_word` puts the token ids: a `Vec`, or a slice
+pub(crate) trait IdSink<T> {
+    fn push(&mut self, id: T);
+
+    fn truncate_to(&mut self, len: usize);
+
+    fn len(&self) -> usize;
+}
+
+impl<T> IdSink<T> for Vec<T> {
+    #[inline]
+    fn push(&mut self, id: T) {
+        Vec::push(self, id);
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+// where `tokenize_word` puts the token ranges: a `Vec`, or nowhere if only
+// the ids are needed, so the ranges cost nothing then
+pub(crate) trait RangeSink {
+    fn push(&mut self, range: Range<usize>);
+
+    fn truncate_to(&mut self, len: usize);
+
+    // the end of the last range, or `0` without ranges
+    fn last_end(&self) -> usize;
+}
+
+impl RangeSink for Vec<Range<usize>> {
+    #[inline]
+    fn push(&mut self, range: Range<usize>) {
+        Vec::push(self, range);
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn last_end(&self) -> usize {
+        self.last().map_or(0, |range| range.end)
+    }
+}
+
+pub(crate) struct NoRanges;
+
+impl RangeSink for NoRanges {
+    #[inline]
+    fn push(&mut self, _range: Range<usize>) {}
+
+    #[inline]
+    fn truncate_to(&mut self, _len: usize) {}
+
+    #[inline]
+    fn last_end(&self) -> usize {
+        0
+    }
+}
+
+// maps the ranges within a normalized word to the original text as they
+// are pushed
+struct Mapped<'r, R, F> {
+    ranges: &'r mut R,
+    map: F,
+}
+
+impl<R: RangeSink, F: Fn(Range<usize>) -> Range<usize>> RangeSink for Mapped<'_, R, F> {
+    #[inline]
+    fn push(&mut self, range: Range<usize>) {
+        self.ranges.push((self.map)(range));
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.ranges.truncate_to(len);
+    }
+
+    #[inline]
+    fn last_end(&self) -> usize {
+        self.ranges.last_end()
+    }
+}
+
+// is the byte ASCII whitespace, including the vertical tab that
+// `u8::is_ascii_whitespace` leaves out (unlike `char::is_whitespace`)?
+#[inline]
+fn is_ascii_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t'..=b'\r')
+}
+
+// skip the whitespace at `pos` in one go, so long runs of it stay linear
+fn skip_whitespace(text: &str, mut pos: usize) -> usize {
+    let bytes = text.as_bytes();
+    loop {
+        // ASCII whitespace is by far the most common, so avoid decoding it
+        while pos < bytes.len() && is_ascii_whitespace(bytes[pos]) {
+            pos += 1;
+        }
+        match text[pos..].chars().next() {
+            Some(c) if c.is_whitespace() => pos += c.len_utf8(),
+            _ => return pos,
+        }
+    }
 }
 
 // we use this to calculate offsets in characters instead of bytes
+// (counting the bytes that start a char, as byte fallback tokens may end
+// within a char)
 fn char_offs(text: &str, last_known_char: usize, range: Range<usize>) -> usize {
-    text[range].chars().count() + last_known_char
+    let starts = text.as_bytes()[range].iter().filter(|&&b| b & 0xC0 != 0x80);
+    starts.count() + last_known_char
 }
 
 /// A trait to be able to convert token IDs on the fly
@@ -114,6 +393,27 @@ pub trait TokenID: PartialEq + Clone {
 
     /// Convert back into `u64`
     fn restore(self) -> u64;
+
+    /// Convert back into `u64` if `self` is a valid id, i.e. neither
+    /// negative nor fractional
+    fn try_restore(self) -> Option<u64> {
+        let t = self.clone().restore();
+        if Self::coerce(t) == self {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// Convert a `u64` to `Self` if it can be represented exactly
+    fn try_coerce(t: u64) -> Option<Self> {
+        let id = Self::coerce(t);
+        if id.clone().restore() == t {
+            Some(id)
+        } else {
+            None
+        }
+    }
 }
 
 impl TokenID for u64 {
@@ -135,41 +435,193 @@ impl TokenID for u64 {
 // This can be used in torch Tensors
 macro_rules! impl_token_id {
     ($ty:ty, $zero:expr) => {
+        impl_token_id!($ty, $zero, {
+            #[inline(always)]
+            fn try_restore(self) -> Option<u64> {
+                u64::try_from(self).ok()
+            }
+        });
+    };
+    ($ty:ty, $zero:expr, { $($try_restore:tt)* }) => {
         impl TokenID for $ty {
             #[inline(always)]
             fn zero() -> Self {
                 $zero
             }
-            
+
             #[inline(always)]
             fn coerce(t: u64) -> Self {
                 t as $ty
             }
-            
+
             #[inline(always)]
             fn restore(self) -> u64 {
                 self as u64
             }
+
+            $($try_restore)*
         }
     };
 }
-            
+
 impl_token_id!(i64, 0);
 impl_token_id!(i32, 0);
-impl_token_id!(f64, 0.0);
-impl_token_id!(f32, 0.0);
+impl_token_id!(u32, 0);
+impl_token_id!(u16, 0);
+impl_token_id!(usize, 0);
+// floats keep the default `try_restore`, which rejects fractions
+impl_token_id!(f64, 0.0, {});
+impl_token_id!(f32, 0.0, {});
+
+// a token id without data, so a `Vec` of them only counts the tokens and
+// never allocates
+#[derive(Clone, PartialEq)]
+struct Counted;
+
+impl TokenID for Counted {
+    fn zero() -> Self {
+        Counted
+    }
+
+    #[inline(always)]
+    fn coerce(_t: u64) -> Self {
+        Counted
+    }
+
+    #[inline(always)]
+    fn restore(self) -> u64 {
+        0
+    }
+}
+
+// the bytes of an FST, either in memory, embedded in the binary or mapped
+// from a file
+enum FstData {
+    Owned(Vec<u8>),
+    Static(&'static [u8]),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl AsRef<[u8]> for FstData {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            FstData::Owned(bytes) => bytes,
+            FstData::Static(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            FstData::Mapped(map) => map,
+        }
+    }
+}
+
+// the keys of an FST with their ids, borrowed from the token texts
+type FstKeys<'k> = Vec<(&'k [u8], u64)>;
+
+// sorts the keys and builds their FST, failing on the first duplicate
+fn sorted_fst(mut keys: FstKeys<'_>) -> Result<Fst<FstData>, TokenizerError> {
+    // the ids are unique, so duplicates stay in line order
+    keys.sort_unstable();
+    if let Some(pair) = keys.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(TokenizerError::DuplicateToken {
+            token: String::from_utf8_lossy(pair[0].0).into_owned(),
+            first_line: pair[0].1 as usize + 1,
+            second_line: pair[1].1 as usize + 1,
+        });
+    }
+    Ok(Fst::from_iter_map(keys)?.map_data(FstData::Owned)?)
+}
+
+// puts a token that isn't special into the starter or follower keys: with an
+// end-of-word suffix, the followers are the tokens that end a word (without
+// the suffix), otherwise those that continue one (without the prefix)
+fn push_keys<'k>(
+    token: &'k str,
+    id: u64,
+    (prefix, suffix): (&str, &str),
+    starter: &mut FstKeys<'k>,
+    follower: &mut FstKeys<'k>,
+) {
+    if !suffix.is_empty() {
+        match token.strip_suffix(suffix) {
+            Some(ends) => follower.push((ends.as_bytes(), id)),
+            None => starter.push((token.as_bytes(), id)),
+        }
+    } else if prefix.is_empty() {
+        // without a prefix, every token can start or continue a word
+        follower.push((token.as_bytes(), id));
+        starter.push((token.as_bytes(), id));
+    } else if let Some(follows) = token.strip_prefix(prefix) {
+        follower.push((follows.as_bytes(), id));
+    } else {
+        starter.push((token.as_bytes(), id));
+    }
+}
 
 /// The Tokenizer. Use [`AlephAlphaTokenizer::from_vocab`] to create an
 /// instance.
+///
+/// Cloning is cheap, as the clones share the vocabulary and its FSTs, so a
+/// web framework can clone the tokenizer into each handler. Methods that
+/// change the vocabulary, like
+/// [`add_tokens`](AlephAlphaTokenizer::add_tokens), only change the
+/// tokenizer they are called on.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+///
+/// fn shareable<T: Clone + Send + Sync + 'static>() {}
+/// shareable::<AlephAlphaTokenizer>();
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let clone = tokenizer.clone();
+/// assert!(clone.shares_data_with(&tokenizer));
+/// let text = "Ein Junge im Kindergarten spielt mit dem Ball. ☃";
+/// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+/// let (mut clone_ids, mut clone_ranges) = (Vec::<u32>::new(), Vec::new());
+/// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+/// std::thread::spawn(move || {
+///     clone.tokens_into(text, &mut clone_ids, &mut clone_ranges, None);
+///     assert_eq!((ids, ranges), (clone_ids, clone_ranges));
+/// })
+/// .join()
+/// .unwrap();
+///
+/// // adding tokens leaves the other clones as they are
+/// let mut changed = tokenizer.clone();
+/// changed.add_tokens(&["Supercalifragilistic"]).unwrap();
+/// assert!(!changed.shares_data_with(&tokenizer));
+/// assert!(tokenizer.id_of("Supercalifragilistic").is_none());
+/// assert!(changed.id_of("Supercalifragilistic").is_some());
+/// ```
+#[derive(Clone)]
 pub struct AlephAlphaTokenizer {
-    tokens: Vec<String>,
-    starters: Fst<Vec<u8>>,
-    followers: Fst<Vec<u8>>,
+    // the vocabulary is shared between clones, so cloning is cheap
+    tokens: Arc<TokenTexts>,
+    starters: Arc<Fst<FstData>>,
+    followers: Arc<Fst<FstData>>,
     //TODO: perhaps use a SmallVec here
-    special_tokens: Vec<u64>,
+    special_tokens: Arc<Vec<u64>>,
     unk_id: u32,
     prefix: Option<u32>,
     suffix: Option<u32>,
+    pad_id: Option<u32>,
+    mask_id: Option<u32>,
+    // the ids of the byte tokens with byte fallback, or empty
+    byte_ids: Vec<u32>,
+    // the ids of the tokens left out of the FSTs, sorted
+    suppressed_ids: Vec<u64>,
+    continuing_subword_prefix: String,
+    // the suffix of the tokens that end a word, if the vocabulary marks
+    // those instead of the continuing ones
+    end_of_word_suffix: String,
+    options: TokenizerOptions,
+    calibration: Calibration,
+    indexes: Arc<LazyIndexes>,
+    // whether to split ASCII texts byte by byte, which only tests disable
+    ascii_fast_path: bool,
+    backend: MatcherBackend,
 }
 
 impl AlephAlphaTokenizer {
@@ -179,225 +631,1669 @@ impl AlephAlphaTokenizer {
     ///
     /// * `[CLS]` is classification (and if present is used as prefix)
     /// * `[SEP]` is separator (and if present is used as suffix)
-    /// * `[PAD]` is padding and is in position `0`
-    /// * `[UNK]` is the *unknonw* token specifier
-    pub fn from_vocab(path: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let vocab = File::open(path)?;
-        let tokens = BufReader::new(vocab)
-            .lines()
-            .collect::<Result<Vec<String>, std::io::Error>>()?;
-        let mut starter: Vec<(Vec<u8>, u64)> = Vec::new();
-        let mut follower: Vec<(Vec<u8>, u64)> = Vec::new();
-        let mut special_tokens = Vec::new();
-        let mut unk_id = None;
-        let mut prefix = None;
-        let mut suffix = None;
-        for (i, tok) in tokens.iter().enumerate() {
-            let token = tok.trim().as_bytes();
-            if token.starts_with(b"[") && token.ends_with(b"]") {
-                if token.starts_with(b"[unused") {
-                    continue;
-                }
-                if token == b"[UNK]" {
-                    unk_id = Some(i as u32);
-                } else if token == b"[CLS]" {
-                    prefix = Some(i as u32);
-                } else if token == b"[SEP]" {
-                    suffix = Some(i as u32);
-                }
-                special_tokens.push(i as u64);
-            }
-            if token.starts_with(b"##") {
-                follower.push((token[2..].to_vec(), i as u64));
-            } else {
-                starter.push((token.to_vec(), i as u64));
-            }
-        }
-        let unk_id = if let Some(u) = unk_id {
-            u
-        } else {
-            return Err(Box::new(std::env::VarError::NotPresent));
-        };
-        starter.sort_by(|(k, _), (j, _)| k.cmp(j));
-        follower.sort_by(|(k, _), (j, _)| k.cmp(j));
-        let starters = Fst::from_iter_map(starter)?;
-        let followers = Fst::from_iter_map(follower)?;
-        Ok(AlephAlphaTokenizer {
-            tokens,
-            starters,
-            followers,
-            special_tokens,
-            unk_id,
-            prefix,
-            suffix,
-        })
-    }
-
-    /// Wraps a UTF8 byte range iterator to produce a tuple of (byte-range, character-range).
+    /// * `[PAD]` is padding, found by its text in any position
+    /// * `[UNK]` is the *unknown* token specifier
+    ///
+    /// Use [`from_vocab_with_special_tokens`] for vocabularies with other
+    /// special tokens.
+    ///
+    /// The id of each token is its line number, counting from `0`. Whitespace
+    /// around the tokens, a byte order mark and Windows line endings are
+    /// ignored. A blank line still takes up an id, so the ids of the lines
+    /// after it stay the same, but no text ever gets that id.
     ///
     /// # Examples
     ///
     /// ```
-    ///# use aleph_alpha_tokenizer::AlephAlphaTokenizer;
-    /// let text = "äußerst";
-    /// let ranges = &[0usize..3, 3..7, 7..9];
-    /// assert_eq!(&[(0..3, 0..2), (3..7, 2..5), (7..9, 5..7)],
-    ///     &AlephAlphaTokenizer::char_ranges(text, ranges.iter()).collect::<Vec<_>>()[..]);
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let clean = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// let texts = ["Ich esse Steak.", "Der Hund spielt im Garten. ☃ [MASK]"];
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut clean_ids, mut clean_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for exported in &[
+    ///     vocab.replace('\n', "\r\n"),
+    ///     format!("\u{feff}{}", vocab),
+    ///     format!("\u{feff}{}", vocab.replace('\n', " \t\r\n")),
+    ///     vocab.trim_end().to_string(),
+    /// ] {
+    ///     let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(exported.as_bytes()).unwrap();
+    ///     assert_eq!(clean.fingerprint(), tokenizer.fingerprint());
+    ///     assert_eq!("[PAD]", tokenizer.text_of(0u64));
+    ///     for text in &texts {
+    ///         clean.tokens_into(text, &mut clean_ids, &mut clean_ranges, None);
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         assert_eq!((&clean_ids, &clean_ranges), (&ids, &ranges));
+    ///     }
+    /// }
+    ///
+    /// // blank lines keep their ids, but match nothing
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\n\nfoo\n\n##bar\n").unwrap();
+    /// tokenizer.tokens_into("foobar", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 4], &ids[..]);
+    /// assert_eq!(5, tokenizer.vocab_size());
     /// ```
-    pub fn char_ranges<'i>(
-        text: &'i str,
-        ranges: impl Iterator<Item = &'i Range<usize>> + 'i,
-    ) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + 'i {
-        let (mut last_char, mut last_byte) = (0, 0);
-        ranges.map(move |r| {
-            let (s, e) = (r.start, r.end);
-            let cs = char_offs(text, last_char, last_byte..s);
-            last_char = char_offs(text, cs, s..e);
-            last_byte = e;
-            (r.clone(), cs..last_char)
-        })
-    }
-
-    #[inline]
-    fn add_prefix<T: TokenID>(&self, token_ids: &mut Vec<T>, token_ranges: &mut Vec<Range<usize>>) {
-        if let Some(id) = self.prefix {
-            token_ids.push(T::coerce(u64::from(id)));
-            token_ranges.push(0..0);
-        }
-    }
-
-    #[inline]
-    fn add_suffix<T: TokenID>(&self, token_ids: &mut Vec<T>, token_ranges: &mut Vec<Range<usize>>) {
-        if let Some(id) = self.suffix {
-            let pos = token_ranges.last().map_or(0, |range| range.end);
-            token_ids.push(T::coerce(u64::from(id)));
-            token_ranges.push(pos..pos);
-        }
-    }
-
-    fn tokenize_word<T: TokenID>(
-        &self,
-        text: &str,
-        range: Range<usize>,
-        token_ids: &mut Vec<T>,
-        token_ranges: &mut Vec<Range<usize>>,
-    ) {
-        let (start, end) = (range.start, range.end);
-        let word_index = token_ids.len();
-        let mut last_index = start;
-        if let Some((len, id)) = find_longest_prefix(&self.starters, text[start..end].as_bytes()) {
-            last_index = start + len;
-            token_ids.push(T::coerce(id));
-            token_ranges.push(start..last_index);
-            while last_index < end {
-                if let Some((len, id)) =
-                    find_longest_prefix(&self.followers, &text[last_index..end].as_bytes())
-                {
-                    let next_index = last_index + len;
-                    token_ids.push(T::coerce(id));
-                    token_ranges.push(last_index..replace(&mut last_index, next_index));
-                } else {
-                    break;
-                }
-            }
-        }
-        if last_index < end {
-            assert!(word_index <= token_ids.len());
-            token_ids.truncate(word_index);
-            token_ids.push(T::coerce(u64::from(self.unk_id)));
-            token_ranges.truncate(word_index);
-            token_ranges.push(range);
-        }
+    ///
+    /// [`from_vocab_with_special_tokens`]: AlephAlphaTokenizer::from_vocab_with_special_tokens
+    #[cfg(feature = "std-fs")]
+    pub fn from_vocab(path: &str) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_path(path).build()
     }
 
-    /// tokenize the given text into a `&mut Vec<u64>` for ids and
-    /// `&mut Vec<Range<usize>>` for source ranges respectively, optionally
-    /// filling a `words` `&mut Vec<Range>` with ranges into the tokens array
-    /// with the words' token indices.
+    /// Creates a tokenizer from a vocabulary in memory.
     ///
-    /// This works by first splitting by whitespace, then gathering the longest
-    /// prefix in our token tree (first the starters, then the followers) until
-    /// the word is complete, or inserting a `[UNK]` token if the word couldn't
-    /// fully be tokenized. This is what wordpiece does, too.
+    /// The vocabulary is parsed exactly like [`from_vocab`] does. This
+    /// doesn't touch the filesystem, so it also works without the `std-fs`
+    /// feature:
     ///
-    /// Note: The output `Vec`s will be cleared before appending tokens.
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
     ///
-    /// # Examples
+    /// let vocab = include_str!("../vocab.txt");
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ich esse Steak.", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[3, 1671, 229, 145, 601, 464, 26914, 4], &ids[..]);
+    /// ```
+    ///
+    /// The tokens are the same as those of [`from_vocab`]:
     ///
     /// ```
     /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
     ///
-    /// let source_text = "Ein interessantes Beispiel";
-    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
-    /// let mut ids: Vec<i32> = Vec::new();
-    /// let mut ranges = Vec::new();
-    /// tokenizer.tokens_into(source_text, &mut ids, &mut ranges, None);
-    /// assert_eq!(&[3, 198, 23181, 26902, 2249, 4], &ids[..]);
+    /// let from_path = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let from_bytes =
+    ///     AlephAlphaTokenizer::from_vocab_bytes(include_bytes!("../vocab.txt")).unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut bytes_ids, mut bytes_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    /// ] {
+    ///     from_path.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     from_bytes.tokens_into(text, &mut bytes_ids, &mut bytes_ranges, None);
+    ///     assert_eq!(ids, bytes_ids);
+    ///     assert_eq!(ranges, bytes_ranges);
+    /// }
     /// ```
-    pub fn tokens_into<T: TokenID>(
-        &self,
-        text: &str,
-        token_ids: &mut Vec<T>,
-        token_ranges: &mut Vec<Range<usize>>,
-        words: Option<&mut Vec<Range<usize>>>,
-    ) {
-        token_ids.clear();
-        token_ranges.clear();
-        let text_len = text.len();
-        let mut words = words;
-        if let Some(w) = words.as_mut() {
-            w.clear();
-        }
-        let mut last_offs = 0;
-        self.add_prefix(token_ids, token_ranges);
-        let mut last_token = token_ids.len();
-        //TODO: there may be a faster version of this using SIMD
-        while let Some(next_ws) = text[last_offs..].find(char::is_whitespace) {
-            if next_ws != 0 {
-                self.tokenize_word(
-                    text,
-                    last_offs..last_offs + next_ws,
-                    token_ids,
-                    token_ranges,
-                );
-                if let Some(w) = words.as_mut() {
-                    w.push(last_token..replace(&mut last_token, token_ids.len()));
-                }
-            }
-            last_offs += next_ws;
-            last_offs += text[last_offs..].chars().next().unwrap_or('\0').len_utf8();
-            if let Some(non_ws) = text[last_offs..].find(|c: char| !c.is_whitespace()) {
-                last_offs += non_ws;
-            }
-        }
-        if last_offs < text_len {
-            self.tokenize_word(text, last_offs..text_len, token_ids, token_ranges);
-        }
-        self.add_suffix(token_ids, token_ranges);
+    ///
+    /// [`from_vocab`]: AlephAlphaTokenizer::from_vocab
+    pub fn from_vocab_bytes(vocab: &[u8]) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_reader(vocab).build()
     }
 
-    /// Gets the text of this token.
+    /// Creates a tokenizer from a vocabulary read from the given reader, one
+    /// token per line.
+    ///
+    /// The vocabulary is parsed exactly like [`from_vocab`] does.
     ///
     /// # Examples
     ///
     /// ```
     /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
-    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// use std::io::Cursor;
     ///
-    /// assert_eq!("[PAD]", tokenizer.text_of(0));
+    /// let vocab = Cursor::new("[PAD]\n[UNK]\nSuper\n##man\n");
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_reader(vocab).unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Superman", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 3], &ids[..]);
     /// ```
-    #[inline]
-    pub fn text_of<T: TokenID>(&self, token_id: T) -> &str {
-        &self.tokens[token_id.restore() as usize]
+    ///
+    /// [`from_vocab`]: AlephAlphaTokenizer::from_vocab
+    pub fn from_vocab_reader<R: BufRead>(reader: R) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_reader(reader).build()
     }
 
-    /// Gets the texts of the tokens.
+    /// Creates a tokenizer from a vocabulary file with a token and its id,
+    /// separated by a tab, on each line, e.g. `Super\t4285`.
+    ///
+    /// The lines may come in any order, and the ids may leave gaps (e.g.
+    /// reserved for tokens added later). The ids in a gap are in the
+    /// vocabulary like blank lines of [`from_vocab`]: their text is empty and
+    /// [`try_text_of`] gives `None` for them. Blank lines are skipped, and
+    /// the special tokens are found like in [`from_vocab`]. A line without a
+    /// tab and a `u32` id after it is a [`TokenizerError::InvalidVocab`], an
+    /// id given twice a [`TokenizerError::DuplicateId`].
     ///
     /// # Examples
     ///
     /// ```
     /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
-    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// // the vocabulary with ids, backwards
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// let mut lines: Vec<String> = vocab
+    ///     .lines()
+    ///     .enumerate()
+    ///     // its blank line becomes a gap
+    ///     .filter(|(_, token)| !token.is_empty())
+    ///     .map(|(id, token)| format!("{}\t{}\n", token, id))
+    ///     .collect();
+    /// lines.reverse();
+    /// let path = std::env::temp_dir().join("aat-vocab-with-ids.tsv");
+    /// std::fs::write(&path, lines.concat()).unwrap();
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_with_ids(path.to_str().unwrap()).unwrap();
+    /// let expected = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(expected.fingerprint(), tokenizer.fingerprint());
+    /// assert_eq!(Some(4285), tokenizer.id_of("Super"));
+    /// assert_eq!("Super", tokenizer.text_of(4285u32));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// [`from_vocab`]: AlephAlphaTokenizer::from_vocab
+    /// [`try_text_of`]: AlephAlphaTokenizer::try_text_of
+    #[cfg(feature = "std-fs")]
+    pub fn from_vocab_with_ids(path: &str) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_path_with_ids(path).build()
+    }
+
+    /// Creates a tokenizer from a vocabulary with ids in memory.
+    ///
+    /// The vocabulary is parsed exactly like [`from_vocab_with_ids`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let vocab = b"Super\t7\n[UNK]\t1\n\n##man\t3\n[PAD]\t0\n[MASK]\t10\n";
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_with_ids_bytes(vocab).unwrap();
+    /// assert_eq!(11, tokenizer.vocab_size());
+    /// for &(token, id) in &[("[PAD]", 0), ("[UNK]", 1), ("##man", 3), ("Super", 7), ("[MASK]", 10)] {
+    ///     assert_eq!(Some(id), tokenizer.id_of(token));
+    ///     assert_eq!(Some(token), tokenizer.try_text_of(id));
+    ///     assert_eq!(token, tokenizer.text_of(id));
+    /// }
+    /// assert_eq!(Some(10), tokenizer.mask_id());
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Superman", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[7, 3], &ids[..]);
+    ///
+    /// // the gaps have no text
+    /// for id in &[2u64, 4, 5, 6, 8, 9] {
+    ///     assert_eq!(None, tokenizer.try_text_of(*id));
+    ///     assert_eq!("", tokenizer.text_of(*id));
+    /// }
+    /// assert_eq!(None, tokenizer.try_text_of(11u64));
+    ///
+    /// let error = AlephAlphaTokenizer::from_vocab_with_ids_bytes(b"[UNK]\t0\nfoo\t1\nbar\t0\n")
+    ///     .err()
+    ///     .unwrap();
+    /// assert!(matches!(error, TokenizerError::DuplicateId { id: 0, first_line: 1, second_line: 3 }));
+    /// // duplicate tokens are reported with their lines, too
+    /// let error = AlephAlphaTokenizer::from_vocab_with_ids_bytes(b"[UNK]\t0\nfoo\t9\nfoo\t5\n")
+    ///     .err()
+    ///     .unwrap();
+    /// assert!(matches!(
+    ///     error,
+    ///     TokenizerError::DuplicateToken { ref token, first_line: 2, second_line: 3 } if token == "foo"
+    /// ));
+    /// for invalid in &[&b"[UNK]\t0\nfoo\n"[..], b"[UNK]\t0\nfoo\t-1\n", b"[UNK]\t0\n\t1\n"] {
+    ///     let error = AlephAlphaTokenizer::from_vocab_with_ids_bytes(invalid).err().unwrap();
+    ///     assert!(matches!(error, TokenizerError::InvalidVocab(_)), "{:?}", error);
+    /// }
+    /// ```
+    ///
+    /// [`from_vocab_with_ids`]: AlephAlphaTokenizer::from_vocab_with_ids
+    pub fn from_vocab_with_ids_bytes(vocab: &[u8]) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_reader_with_ids(vocab).build()
+    }
+
+    /// Creates a tokenizer from a vocabulary with ids read from the given
+    /// reader.
+    ///
+    /// The vocabulary is parsed exactly like [`from_vocab_with_ids`] does.
+    ///
+    /// [`from_vocab_with_ids`]: AlephAlphaTokenizer::from_vocab_with_ids
+    pub fn from_vocab_with_ids_reader<R: BufRead>(reader: R) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_reader_with_ids(reader).build()
+    }
+
+    /// Creates a tokenizer from the tokens in the order of their ids, e.g. a
+    /// generated vocabulary.
+    ///
+    /// The tokens are parsed exactly like the lines of [`from_vocab`], so the
+    /// special tokens are found the same way and `[unused…]` tokens are
+    /// skipped, and the errors are the same, too: the line numbers of a
+    /// [`TokenizerError::DuplicateToken`] count the tokens from `1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenizerError};
+    ///
+    /// let tokens = ["[PAD]", "[UNK]", "[unused0]", "Super", "##man"];
+    /// let tokenizer =
+    ///     AlephAlphaTokenizer::from_tokens(tokens.iter().map(|t| t.to_string()).collect())
+    ///         .unwrap();
+    /// assert_eq!(1, tokenizer.unk_id());
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Superman [unused0]", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[3, 4, 1], &ids[..]);
+    ///
+    /// // the same tokenizer as from the file
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// let tokens = vocab.lines().map(str::to_string).collect();
+    /// let from_tokens = AlephAlphaTokenizer::from_tokens(tokens).unwrap();
+    /// let from_path = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(from_path.fingerprint(), from_tokens.fingerprint());
+    /// let (mut path_ids, mut path_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for text in &["Ich esse Steak.", "Der Hund spielt im Garten. ☃ [MASK]"] {
+    ///     from_path.tokens_into(text, &mut path_ids, &mut path_ranges, None);
+    ///     from_tokens.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     assert_eq!((&path_ids, &path_ranges), (&ids, &ranges));
+    /// }
+    ///
+    /// let error = AlephAlphaTokenizer::from_tokens(vec!["foo".to_string()]).err().unwrap();
+    /// assert!(matches!(error, TokenizerError::MissingUnkToken(ref unk) if unk == "[UNK]"));
+    /// let tokens = ["[UNK]", "foo", "##bar", "foo"];
+    /// let error = AlephAlphaTokenizer::from_tokens(tokens.iter().map(|t| t.to_string()).collect())
+    ///     .err()
+    ///     .unwrap();
+    /// assert!(matches!(
+    ///     error,
+    ///     TokenizerError::DuplicateToken { ref token, first_line: 2, second_line: 4 } if token == "foo"
+    /// ));
+    /// ```
+    ///
+    /// [`from_vocab`]: AlephAlphaTokenizer::from_vocab
+    pub fn from_tokens(tokens: Vec<String>) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_tokens(Ok(tokens)).build()
+    }
+
+    /// Creates a tokenizer from the vocabulary, using the given texts for the
+    /// special tokens instead of the BERT names.
+    ///
+    /// See [`SpecialTokens`] for an example.
+    #[cfg(feature = "std-fs")]
+    pub fn from_vocab_with_special_tokens(
+        path: &str,
+        special: SpecialTokens,
+    ) -> Result<Self, TokenizerError> {
+        Self::builder()
+            .vocab_path(path)
+            .special_tokens(&special)
+            .build()
+    }
+
+    fn from_token_lines(
+        tokens: TokenTexts,
+        special: &SpecialTokens,
+        markers: &MarkerScheme,
+        parallel: bool,
+        suppressed: &[u64],
+    ) -> Result<Self, TokenizerError> {
+        Self::from_token_lines_with(tokens, special, markers, |mut starter, mut follower| {
+            if !suppressed.is_empty() {
+                // no word can match what isn't in the FSTs
+                let suppressed: HashSet<u64> = suppressed.iter().copied().collect();
+                starter.retain(|(_, id)| !suppressed.contains(id));
+                follower.retain(|(_, id)| !suppressed.contains(id));
+            }
+            let (starters, followers) = if parallel {
+                std::thread::scope(|scope| {
+                    let followers = scope.spawn(|| sorted_fst(follower));
+                    let starters = sorted_fst(starter);
+                    (
+                        starters,
+                        followers.join().expect("building the FST panicked"),
+                    )
+                })
+            } else {
+                (sorted_fst(starter), sorted_fst(follower))
+            };
+            // the starters fail first either way
+            Ok((starters?, followers?))
+        })
+    }
+
+    // parse the tokens, getting the FSTs from the (unsorted) starter and
+    // follower keys
+    fn from_token_lines_with<F>(
+        tokens: TokenTexts,
+        special: &SpecialTokens,
+        markers: &MarkerScheme,
+        fsts: F,
+    ) -> Result<Self, TokenizerError>
+    where
+        F: FnOnce(FstKeys<'_>, FstKeys<'_>) -> Result<(Fst<FstData>, Fst<FstData>), TokenizerError>,
+    {
+        // huggingface's tokenizers (and the special token ids) use `u32`
+        let max_len = u64::from(u32::MAX) + 1;
+        if tokens.len() as u64 > max_len {
+            return Err(TokenizerError::Inconsistent(ConsistencyError::IdTooLarge {
+                key: tokens[max_len as usize].trim().to_string(),
+                id: max_len,
+            }));
+        }
+        // most tokens are either, only the prefix (or suffix) tells how many
+        let mut starter: FstKeys<'_> = Vec::with_capacity(tokens.len());
+        let mut follower: FstKeys<'_> = Vec::with_capacity(tokens.len());
+        let mut special_tokens = Vec::new();
+        let mut unk_id = None;
+        let mut prefix = None;
+        let mut suffix = None;
+        let mut pad_id = None;
+        let mut mask_id = None;
+        // the ids of the additional special tokens found so far
+        let mut additional = vec![None; special.additional.len()];
+        for (i, token) in tokens.iter().enumerate() {
+            if token.is_empty() {
+                // a blank line keeps the ids of the lines after it
+                continue;
+            }
+            let slot = if token == special.unk {
+                Some(&mut unk_id)
+            } else if Some(token) == special.cls {
+                Some(&mut prefix)
+            } else if Some(token) == special.sep {
+                Some(&mut suffix)
+            } else if Some(token) == special.pad {
+                Some(&mut pad_id)
+            } else if Some(token) == special.mask {
+                Some(&mut mask_id)
+            } else if let Some(index) = special.additional.iter().position(|&t| t == token) {
+                if let Some(first) = additional[index] {
+                    return Err(TokenizerError::DuplicateToken {
+                        token: token.to_string(),
+                        first_line: first as usize + 1,
+                        second_line: i + 1,
+                    });
+                }
+                // in no FST, so no word can match it
+                additional[index] = Some(i as u32);
+                special_tokens.push(i as u64);
+                continue;
+            } else {
+                None
+            };
+            if let Some(slot) = slot {
+                // the starters would only keep one of them
+                if let Some(first) = *slot {
+                    return Err(TokenizerError::DuplicateToken {
+                        token: token.to_string(),
+                        first_line: first as usize + 1,
+                        second_line: i + 1,
+                    });
+                }
+                *slot = Some(i as u32);
+            } else if !(special.bracketed && token.starts_with('[') && token.ends_with(']')) {
+                push_keys(
+                    token,
+                    i as u64,
+                    markers.markers(),
+                    &mut starter,
+                    &mut follower,
+                );
+                continue;
+            } else if token.starts_with("[unused") {
+                continue;
+            }
+            special_tokens.push(i as u64);
+            starter.push((token.as_bytes(), i as u64));
+        }
+        let unk_id =
+            unk_id.ok_or_else(|| TokenizerError::MissingUnkToken(special.unk.to_string()))?;
+        if let Some(index) = additional.iter().position(Option::is_none) {
+            let token = special.additional[index].to_string();
+            return Err(TokenizerError::MissingSpecialToken(token));
+        }
+        let (starters, followers) = fsts(starter, follower)?;
+        Ok(AlephAlphaTokenizer {
+            tokens: Arc::new(tokens),
+            starters: Arc::new(starters),
+            followers: Arc::new(followers),
+            special_tokens: Arc::new(special_tokens),
+            unk_id,
+            prefix,
+            suffix,
+            pad_id,
+            mask_id,
+            byte_ids: Vec::new(),
+            suppressed_ids: Vec::new(),
+            continuing_subword_prefix: markers.markers().0.to_string(),
+            end_of_word_suffix: markers.markers().1.to_string(),
+            options: TokenizerOptions::default(),
+            calibration: Calibration::default(),
+            indexes: Arc::default(),
+            ascii_fast_path: true,
+            backend: MatcherBackend::Fst,
+        })
+    }
+
+    /// Creates a builder to configure the tokenizer.
+    pub fn builder() -> AlephAlphaTokenizerBuilder {
+        AlephAlphaTokenizerBuilder::new()
+    }
+
+    /// Determines whether the tokenizers share their vocabulary, i.e. one is
+    /// an unchanged clone of the other (for tests).
+    #[doc(hidden)]
+    pub fn shares_data_with(&self, other: &AlephAlphaTokenizer) -> bool {
+        Arc::ptr_eq(&self.tokens, &other.tokens)
+            && Arc::ptr_eq(&self.starters, &other.starters)
+            && Arc::ptr_eq(&self.followers, &other.followers)
+            && Arc::ptr_eq(&self.special_tokens, &other.special_tokens)
+    }
+
+    /// Gets the prefix that marks tokens continuing a word (`##` by default),
+    /// which is empty with an end-of-word suffix.
+    pub fn continuing_subword_prefix(&self) -> &str {
+        &self.continuing_subword_prefix
+    }
+
+    /// Gets how the vocabulary marks the tokens within a word, see
+    /// [`marker_scheme`](AlephAlphaTokenizerBuilder::marker_scheme).
+    pub fn marker_scheme(&self) -> MarkerScheme {
+        if self.end_of_word_suffix.is_empty() {
+            MarkerScheme::ContinuationPrefix(self.continuing_subword_prefix.clone())
+        } else {
+            MarkerScheme::EndOfWordSuffix(self.end_of_word_suffix.clone())
+        }
+    }
+
+    /// Gets the ids of the tokens that are suppressed, sorted, see
+    /// [`suppressed_ids`](AlephAlphaTokenizerBuilder::suppressed_ids).
+    ///
+    /// Like the options, these are not saved in the binary format, whose
+    /// FSTs still lack the tokens.
+    pub fn suppressed_ids(&self) -> &[u64] {
+        &self.suppressed_ids
+    }
+
+    /// Gets the options this tokenizer was built with.
+    pub fn options(&self) -> &TokenizerOptions {
+        &self.options
+    }
+
+    /// Gets what words are matched against, see
+    /// [`matcher_backend`](AlephAlphaTokenizerBuilder::matcher_backend).
+    pub fn matcher_backend(&self) -> MatcherBackend {
+        self.backend
+    }
+
+    /// Gets the id of the token for unknown words.
+    ///
+    /// Unlike the other special tokens, every vocabulary has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(2, tokenizer.unk_id());
+    /// assert_eq!(Some(0), tokenizer.pad_id());
+    /// assert_eq!(Some(3), tokenizer.cls_id());
+    /// assert_eq!(Some(4), tokenizer.sep_id());
+    /// assert_eq!(Some(5), tokenizer.mask_id());
+    /// assert_eq!(Some("[MASK]"), tokenizer.mask_id().map(|id| tokenizer.text_of(id)));
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// assert_eq!(vocab.lines().count(), tokenizer.vocab_size());
+    /// assert_eq!(&[0, 2, 3, 4, 5], &tokenizer.special_token_ids()[..5]);
+    ///
+    /// // e.g. to pad a batch
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ich esse", &mut ids, &mut ranges, None);
+    /// ids.resize(8, tokenizer.pad_id().unwrap());
+    /// assert_eq!(&[3, 1671, 229, 145, 4, 0, 0, 0], &ids[..]);
+    ///
+    /// let small = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\n").unwrap();
+    /// assert_eq!(0, small.unk_id());
+    /// assert_eq!((None, None, None, None), (small.pad_id(), small.cls_id(), small.sep_id(), small.mask_id()));
+    /// assert_eq!(&[0], small.special_token_ids());
+    /// ```
+    pub fn unk_id(&self) -> u64 {
+        u64::from(self.unk_id)
+    }
+
+    /// Gets the id of the padding token, if the vocabulary has one.
+    pub fn pad_id(&self) -> Option<u64> {
+        self.pad_id.map(u64::from)
+    }
+
+    /// Gets the id of the classification token, if the vocabulary has one.
+    ///
+    /// This is the prefix added by [`tokens_into`](AlephAlphaTokenizer::tokens_into)
+    /// unless it was turned off.
+    pub fn cls_id(&self) -> Option<u64> {
+        self.prefix.map(u64::from)
+    }
+
+    /// Gets the id of the separator token, if the vocabulary has one.
+    ///
+    /// This is the suffix added by [`tokens_into`](AlephAlphaTokenizer::tokens_into)
+    /// unless it was turned off.
+    pub fn sep_id(&self) -> Option<u64> {
+        self.suffix.map(u64::from)
+    }
+
+    /// Gets the id of the mask token, if the vocabulary has one.
+    pub fn mask_id(&self) -> Option<u64> {
+        self.mask_id.map(u64::from)
+    }
+
+    /// Gets the number of tokens in the vocabulary.
+    pub fn vocab_size(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Iterates over the tokens of the vocabulary with their ids, in the
+    /// order of the ids. This includes the tokens that no text ever gets,
+    /// like the `[unused…]` tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(tokenizer.vocab_size(), tokenizer.vocab_iter().len());
+    /// let first: Vec<_> = tokenizer.vocab_iter().take(6).collect();
+    /// assert_eq!(
+    ///     &[("[PAD]", 0), ("[unused3001]", 1), ("[UNK]", 2), ("[CLS]", 3), ("[SEP]", 4), ("[MASK]", 5)],
+    ///     &first[..]
+    /// );
+    /// assert_eq!(Some(("Super", 4285)), tokenizer.vocab_iter().find(|&(token, _)| token == "Super"));
+    /// assert!(tokenizer.vocab_iter().all(|(token, id)| tokenizer.text_of(id) == token));
+    /// ```
+    pub fn vocab_iter(&self) -> impl ExactSizeIterator<Item = (&str, u64)> + '_ {
+        self.tokens
+            .iter()
+            .enumerate()
+            .map(|(id, token)| (token, id as u64))
+    }
+
+    /// Gets the ids of the tokens by their text, like the `get_vocab` of
+    /// huggingface's tokenizers.
+    ///
+    /// The map is built on each call, so keep it if it's needed more than
+    /// once. Blank lines of the vocabulary have no text, so they are left
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let vocab = tokenizer.get_vocab();
+    /// assert_eq!(tokenizer.vocab_size() - 1, vocab.len());
+    /// assert_eq!(Some(&2), vocab.get("[UNK]"));
+    /// assert_eq!(Some(&1), vocab.get("[unused3001]"));
+    /// assert_eq!(Some(&4285), vocab.get("Super"));
+    /// assert_eq!(Some(&464), vocab.get("##ak"));
+    /// assert_eq!(None, vocab.get("Supercalifragilistic"));
+    ///# #[cfg(feature = "huggingface")]
+    /// assert_eq!(vocab, tokenizers::tokenizer::Model::get_vocab(&tokenizer));
+    ///
+    /// // e.g. to find the tokens one vocabulary has and the other lacks
+    /// let other = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nSuper\nBatman\n").unwrap();
+    /// let missing: Vec<_> = other
+    ///     .vocab_iter()
+    ///     .filter(|(token, _)| !vocab.contains_key(*token))
+    ///     .collect();
+    /// assert_eq!(&[("Batman", 2)], &missing[..]);
+    /// ```
+    pub fn get_vocab(&self) -> HashMap<String, u32> {
+        self.vocab_iter()
+            .filter(|(token, _)| !token.is_empty())
+            .map(|(token, id)| (token.to_string(), id as u32))
+            .collect()
+    }
+
+    /// Gets the ids of all special tokens in the order of the vocabulary.
+    pub fn special_token_ids(&self) -> &[u64] {
+        &self.special_tokens
+    }
+
+    /// Iterates over the special tokens with their texts, in the order of
+    /// the vocabulary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let special: Vec<_> = tokenizer.special_tokens().take(5).collect();
+    /// assert_eq!(
+    ///     vec![(0, "[PAD]"), (2, "[UNK]"), (3, "[CLS]"), (4, "[SEP]"), (5, "[MASK]")],
+    ///     special
+    /// );
+    /// assert_eq!(tokenizer.special_token_ids().len(), tokenizer.special_tokens().count());
+    /// ```
+    pub fn special_tokens(&self) -> impl Iterator<Item = (u64, &str)> + '_ {
+        self.special_tokens
+            .iter()
+            .map(move |&id| (id, self.tokens[id as usize].trim()))
+    }
+
+    /// Wraps a UTF8 byte range iterator to produce a tuple of (byte-range, character-range).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let text = "äußerst";
+    /// let ranges = &[0usize..3, 3..7, 7..9];
+    /// assert_eq!(&[(0..3, 0..2), (3..7, 2..5), (7..9, 5..7)],
+    ///     &AlephAlphaTokenizer::char_ranges(text, ranges.iter()).collect::<Vec<_>>()[..]);
+    /// ```
+    pub fn char_ranges<'i>(
+        text: &'i str,
+        ranges: impl Iterator<Item = &'i Range<usize>> + 'i,
+    ) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + 'i {
+        let (mut last_char, mut last_byte) = (0, 0);
+        ranges.map(move |r| {
+            let (s, e) = (r.start, r.end);
+            let cs = char_offs(text, last_char, last_byte..s);
+            last_char = char_offs(text, cs, s..e);
+            last_byte = e;
+            (r.clone(), cs..last_char)
+        })
+    }
+
+    // the number of tokens added around each text
+    fn affix_len(&self) -> usize {
+        (self.options.add_prefix && self.prefix.is_some()) as usize
+            + (self.options.add_suffix && self.suffix.is_some()) as usize
+    }
+
+    #[inline]
+    fn add_prefix<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) {
+        if !self.options.add_prefix {
+            return;
+        }
+        if let Some(id) = self.prefix {
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(0..0);
+        }
+    }
+
+    #[inline]
+    fn add_suffix<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) {
+        if !self.options.add_suffix {
+            return;
+        }
+        if let Some(id) = self.suffix {
+            let pos = token_ranges.last_end();
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(pos..pos);
+        }
+    }
+
+    // tokenize a word in the given mode, telling whether the vocabulary
+    // doesn't cover it
+    fn tokenize_word<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        #[cfg(feature = "datrie")]
+        if let Some((starters, followers)) = self.tries() {
+            let matchers = Matchers {
+                starters,
+                followers,
+            };
+            return self.tokenize_word_or_parts(
+                matchers,
+                mode,
+                text,
+                range,
+                token_ids,
+                token_ranges,
+            );
+        }
+        let matchers = Matchers {
+            starters: &*self.starters,
+            followers: &*self.followers,
+        };
+        self.tokenize_word_or_parts(matchers, mode, text, range, token_ids, token_ranges)
+    }
+
+    // tokenize a word, or else its parts between the fallback separators,
+    // telling whether the vocabulary covers neither
+    fn tokenize_word_or_parts<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let word_index = token_ids.len();
+        let unknown =
+            self.tokenize_word_with(matchers, mode, text, range.clone(), token_ids, token_ranges);
+        let separators = self.options.fallback_separators.as_slice();
+        if !unknown || separators.is_empty() || !text[range.clone()].contains(separators) {
+            return unknown;
+        }
+        token_ids.truncate_to(word_index);
+        token_ranges.truncate_to(word_index);
+        if self.tokenize_parts(matchers, mode, text, range.clone(), token_ids, token_ranges) {
+            return false;
+        }
+        // tokenize the word again, as the parts left no trace of it
+        token_ids.truncate_to(word_index);
+        token_ranges.truncate_to(word_index);
+        self.tokenize_word_with(matchers, mode, text, range, token_ids, token_ranges)
+    }
+
+    // tokenize the parts of a word between the fallback separators as words
+    // of their own, telling whether the vocabulary covers all of them
+    fn tokenize_parts<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let separators = self.options.fallback_separators.as_slice();
+        // a separator is a word of its own, so with an end-of-word suffix it
+        // ends one
+        let words = if self.end_of_word_suffix.is_empty() {
+            matchers.starters
+        } else {
+            matchers.followers
+        };
+        let mut start = range.start;
+        for (offset, separator) in text[range.clone()].match_indices(separators) {
+            let pos = range.start + offset;
+            if start < pos
+                && self.tokenize_word_with(
+                    matchers,
+                    mode,
+                    text,
+                    start..pos,
+                    token_ids,
+                    token_ranges,
+                )
+            {
+                return false;
+            }
+            let end = pos + separator.len();
+            match words.longest_prefix(separator.as_bytes()) {
+                Some((len, id)) if len == separator.len() && !self.is_special(id) => {
+                    token_ids.push(T::coerce(id));
+                    token_ranges.push(pos..end);
+                }
+                _ => match self.options.unknown_separator {
+                    UnknownSeparator::Unk => {
+                        self.push_unknown(text, pos..end, token_ids, token_ranges)
+                    }
+                    UnknownSeparator::Drop => {}
+                },
+            }
+            start = end;
+        }
+        start == range.end
+            || !self.tokenize_word_with(
+                matchers,
+                mode,
+                text,
+                start..range.end,
+                token_ids,
+                token_ranges,
+            )
+    }
+
+    fn tokenize_word_with<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let (start, end) = (range.start, range.end);
+        if self.options.max_chars_per_word.is_some_and(|max| {
+            // a word has at least as many bytes as chars, and at most four
+            // times as many
+            let word = &text[range.clone()];
+            word.len() > max
+                && (word.len() / 4 > max || word.is_ascii() || word.chars().count() > max)
+        }) {
+            self.push_unknown(text, range, token_ids, token_ranges);
+            return true;
+        }
+        if self.options.literal_hash_hash == LiteralMode::Unk
+            && !self.continuing_subword_prefix.is_empty()
+            && text[range.clone()].starts_with(self.continuing_subword_prefix.as_str())
+        {
+            self.push_unknown(text, range, token_ids, token_ranges);
+            return true;
+        }
+        if !self.end_of_word_suffix.is_empty() {
+            return self.tokenize_word_to_end(matchers, mode, text, range, token_ids, token_ranges);
+        }
+        if self.followers.is_empty() {
+            // a word-level vocabulary can only ever match whole words
+            return match self.starters.get(&text[range.clone()]) {
+                Some(id) => {
+                    token_ids.push(T::coerce(id.value()));
+                    token_ranges.push(range);
+                    false
+                }
+                None => {
+                    self.push_unknown(text, range, token_ids, token_ranges);
+                    true
+                }
+            };
+        }
+        let word_index = token_ids.len();
+        let max_tokens = self.options.max_tokens_per_word.unwrap_or(usize::MAX);
+        let mut last_index = start;
+        if let Some((len, id)) = self.find_first_token(matchers, &text.as_bytes()[start..end]) {
+            last_index = start + len;
+            token_ids.push(T::coerce(id));
+            token_ranges.push(start..last_index);
+            while last_index < end {
+                if token_ids.len() - word_index >= max_tokens
+                    && mode != SegmentationMode::Backtracking
+                {
+                    // there's more to the word than the limit allows
+                    token_ids.truncate_to(word_index);
+                    token_ranges.truncate_to(word_index);
+                    self.push_unknown(text, range, token_ids, token_ranges);
+                    return true;
+                }
+                if let Some((len, id)) = matchers
+                    .followers
+                    .longest_prefix(&text.as_bytes()[last_index..end])
+                {
+                    let next_index = last_index + len;
+                    token_ids.push(T::coerce(id));
+                    token_ranges.push(last_index..next_index);
+                    last_index = next_index;
+                } else {
+                    break;
+                }
+            }
+        }
+        let mut covered = last_index == end;
+        if !covered {
+            assert!(word_index <= token_ids.len());
+            if mode == SegmentationMode::Backtracking {
+                token_ids.truncate_to(word_index);
+                token_ranges.truncate_to(word_index);
+                covered =
+                    self.backtrack_word(matchers, text, range.clone(), token_ids, token_ranges);
+                last_index = start;
+            }
+        }
+        if !covered && self.options.unk_mode == UnkMode::PerChar {
+            // keep the tokens so far, and go on after the first character
+            // that doesn't match
+            self.tokenize_per_char(
+                matchers,
+                text,
+                range.clone(),
+                last_index,
+                token_ids,
+                token_ranges,
+            );
+        }
+        if (!covered && self.options.unk_mode == UnkMode::WholeWord)
+            || token_ids.len() - word_index > max_tokens
+        {
+            token_ids.truncate_to(word_index);
+            token_ranges.truncate_to(word_index);
+            self.push_unknown(text, range, token_ids, token_ranges);
+            return true;
+        }
+        !covered
+    }
+
+    // go on tokenizing a word from `pos`, where each character that starts
+    // no token becomes an unknown token of its own
+    fn tokenize_per_char<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        text: &str,
+        range: Range<usize>,
+        mut pos: usize,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) {
+        let bytes = &text.as_bytes()[..range.end];
+        while pos < range.end {
+            let token = if pos == range.start {
+                self.find_first_token(matchers, &bytes[pos..])
+            } else {
+                matchers.followers.longest_prefix(&bytes[pos..])
+            };
+            if let Some((len, id)) = token {
+                token_ids.push(T::coerce(id));
+                token_ranges.push(pos..pos + len);
+                pos += len;
+            } else {
+                let len = text[pos..].chars().next().map_or(1, char::len_utf8);
+                self.push_unknown(text, pos..pos + len, token_ids, token_ranges);
+                pos += len;
+            }
+        }
+    }
+
+    // find the segmentation of a word with the fewest tokens, trying every
+    // match instead of only the longest, and tell whether there is one
+    fn backtrack_word<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let word = &text.as_bytes()[range.clone()];
+        let mut matches = Vec::new();
+        // the fewest followers covering the word from each position, as
+        // (count, length, id) of the first of them; on a tie, the longer
+        // token wins, as in greedy matching
+        let mut best: Vec<Option<(usize, usize, u64)>> = vec![None; word.len() + 1];
+        best[word.len()] = Some((0, 0, 0));
+        let shortest = |matches: &[(usize, u64)], best: &[Option<(usize, usize, u64)>]| {
+            matches
+                .iter()
+                .filter_map(|&(len, id)| best[len].map(|(count, _, _)| (count + 1, len, id)))
+                .min_by_key(|&(count, len, _)| (count, usize::MAX - len))
+        };
+        for pos in (1..word.len()).rev() {
+            matchers.followers.find_prefixes(&word[pos..], &mut matches);
+            best[pos] = shortest(&matches, &best[pos..]);
+        }
+        matchers.starters.find_prefixes(word, &mut matches);
+        let prefix = self.continuing_subword_prefix.as_bytes();
+        if self.options.leading_prefix_as_follower && !prefix.is_empty() && word.starts_with(prefix)
+        {
+            let mut followers = Vec::new();
+            matchers
+                .followers
+                .find_prefixes(&word[prefix.len()..], &mut followers);
+            // the starter wins a tie, so it goes first
+            matches.extend(followers.iter().map(|&(len, id)| (prefix.len() + len, id)));
+        }
+        let mut next = match shortest(&matches, &best) {
+            Some(first) => first,
+            None => return false,
+        };
+        let mut pos = 0;
+        while pos < word.len() {
+            let (_, len, id) = next;
+            token_ids.push(T::coerce(id));
+            token_ranges.push(range.start + pos..range.start + pos + len);
+            pos += len;
+            // each token was chosen because the rest could be covered
+            next = best[pos].expect("the rest of the word is covered");
+        }
+        true
+    }
+
+    // find the longest token at the start of a word as (length, id)
+    fn find_first_token<M: PrefixMatcher>(
+        &self,
+        matchers: Matchers<'_, M>,
+        word: &[u8],
+    ) -> Option<(usize, u64)> {
+        let starter = matchers.starters.longest_prefix(word);
+        let prefix = self.continuing_subword_prefix.as_bytes();
+        if !self.options.leading_prefix_as_follower
+            || prefix.is_empty()
+            || !word.starts_with(prefix)
+        {
+            return starter;
+        }
+        let follower = matchers
+            .followers
+            .longest_prefix(&word[prefix.len()..])
+            .map(|(len, id)| (prefix.len() + len, id));
+        // the starter wins a tie
+        follower
+            .into_iter()
+            .chain(starter)
+            .max_by_key(|&(len, _)| len)
+    }
+
+    // tokenize a word found by `tokens_into`, normalizing it if needed, and
+    // tell whether the vocabulary doesn't cover it
+    #[inline]
+    fn tokenize_span<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let mode = self.options.segmentation_mode;
+        self.tokenize_span_as(mode, text, range, token_ids, token_ranges)
+    }
+
+    fn tokenize_span_as<T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        let span = &text[range.clone()];
+        if self.options.parse_special_tokens {
+            if let Some(id) = self.special_id(span) {
+                token_ids.push(T::coerce(id));
+                token_ranges.push(range);
+                return false;
+            }
+        }
+        let whitespace = &self.options.whitespace;
+        let dehyphenate = (self.options.rejoin_hyphenation
+            && normalize::is_hyphenated(span, whitespace))
+        .then_some(whitespace);
+        let lowercase = self.options.lowercase && normalize::has_uppercase(span);
+        // ASCII stays as it is in every normalization form
+        let unicode = (self.options.strip_accents || self.options.normalize.is_some())
+            && cfg!(feature = "unicode-normalization")
+            && !span.is_ascii();
+        // the spans don't start or end with removed characters
+        let clean = self.clean_chars().filter(|&chars| {
+            span.chars()
+                .any(|c| clean::action(chars, c) == CleanAction::Remove)
+        });
+        if !(dehyphenate.is_some() || lowercase || unicode || clean.is_some()) {
+            return self.tokenize_word(mode, text, range, token_ids, token_ranges);
+        }
+        let mut word = NormalizedWord::default();
+        normalize::normalize(
+            text,
+            range.clone(),
+            dehyphenate,
+            lowercase,
+            clean,
+            &mut word,
+        );
+        #[cfg(feature = "unicode-normalization")]
+        if unicode {
+            let mut normalized = NormalizedWord::default();
+            let (form, strip_accents) = (self.options.normalize, self.options.strip_accents);
+            normalize::normalize_unicode(&word, form, strip_accents, &mut normalized);
+            word = normalized;
+        }
+        if word.text.is_empty() {
+            // e.g. a word of soft hyphens, which has no tokens to cover it
+            self.push_unknown(text, range, token_ids, token_ranges);
+            return true;
+        }
+        let mut mapped = Mapped {
+            ranges: token_ranges,
+            map: |token_range| {
+                let original = word.original(token_range, &range);
+                match clean {
+                    // the removed characters before the next token are in no range
+                    Some(chars) => clean::trim_removed(text, original, chars),
+                    None => original,
+                }
+            },
+        };
+        self.tokenize_word(mode, &word.text, 0..word.text.len(), token_ids, &mut mapped)
+    }
+
+    // the characters to clean from the text, if the tokenizer cleans it
+    #[inline]
+    pub(crate) fn clean_chars(&self) -> Option<&[(char, CleanAction)]> {
+        if self.options.clean_text {
+            Some(&self.options.clean_chars)
+        } else {
+            None
+        }
+    }
+
+    /// tokenize the given text into a `&mut Vec<u64>` for ids and
+    /// `&mut Vec<Range<usize>>` for source ranges respectively, optionally
+    /// filling a `words` `&mut Vec<Range>` with ranges into the tokens array
+    /// with the words' token indices.
+    ///
+    /// This works by first splitting by whitespace, then gathering the longest
+    /// prefix in our token tree (first the starters, then the followers) until
+    /// the word is complete, or inserting a `[UNK]` token if the word couldn't
+    /// fully be tokenized. This is what wordpiece does, too.
+    ///
+    /// Note: The output `Vec`s will be cleared before appending tokens.
+    ///
+    /// # Unknown words
+    ///
+    /// Each word is resolved in the following order:
+    ///
+    /// 1. The input is taken as-is, so a word starting with `##` is looked up
+    ///    among the starters like any other text, e.g. as `#` followed by
+    ///    `###` (see [`leading_prefix_as_follower`] for huggingface's
+    ///    WordPiece behavior).
+    /// 2. If the vocabulary has no follower (`##`-prefixed) tokens at all,
+    ///    the word must match a starter completely.
+    /// 3. Otherwise the longest matching starter is extended by the longest
+    ///    matching followers until the word is covered.
+    /// 4. If the word could not be covered, all tokens for it are replaced by
+    ///    a single `[UNK]` spanning the whole word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let source_text = "Ein interessantes Beispiel";
+    /// let tokenizer = examples::tokenizer();
+    /// let mut ids: Vec<i32> = Vec::new();
+    /// let mut ranges = Vec::new();
+    /// tokenizer.tokens_into(source_text, &mut ids, &mut ranges, None);
+    /// assert_eq!(&[2, 5, 6, 7, 8, 9, 3], &ids[..]);
+    /// ```
+    ///
+    /// The `words` give the indices of the tokens of each word, leaving out
+    /// `[CLS]` and `[SEP]`:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u64>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into("Ein interessantes Beispiel", &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!(&[1..2, 2..4, 4..6], &words[..]);
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// for text in &[
+    ///     "Ein interessantes Beispiel",
+    ///     "Ich esse Steak. ",
+    ///     "  Grüße ☃\t\n",
+    ///     "Einzelwort",
+    ///     "",
+    ///     "   ",
+    /// ] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    ///     assert_eq!(text.split_whitespace().count(), words.len(), "{:?}", text);
+    ///     // the words cover all tokens but `[CLS]` and `[SEP]` in order
+    ///     let mut next = 1;
+    ///     for word in &words {
+    ///         assert_eq!(next, word.start, "{:?}", text);
+    ///         assert!(word.start < word.end, "{:?}", text);
+    ///         next = word.end;
+    ///     }
+    ///     assert_eq!(next, ids.len() - 1, "{:?}", text);
+    /// }
+    /// ```
+    ///
+    /// The resolution order with and without follower tokens:
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let word_level = dir.join("aat-word-level-vocab.txt");
+    /// std::fs::write(&word_level, "[PAD]\n[UNK]\nfoo\n#\nbar\n").unwrap();
+    /// let word_piece = dir.join("aat-word-piece-vocab.txt");
+    /// std::fs::write(&word_piece, "[PAD]\n[UNK]\nfoo\n#\nbar\n##bar\n##foo\n").unwrap();
+    /// let word_level = AlephAlphaTokenizer::from_vocab(word_level.to_str().unwrap()).unwrap();
+    /// let word_piece = AlephAlphaTokenizer::from_vocab(word_piece.to_str().unwrap()).unwrap();
+    ///
+    /// let cases: &[(&AlephAlphaTokenizer, &str, &[u64])] = &[
+    ///     (&word_level, "foo", &[2]),
+    ///     (&word_level, "#", &[3]),
+    ///     (&word_level, "foobar", &[1]),
+    ///     (&word_level, "##foo", &[1]),
+    ///     (&word_level, "##", &[1]),
+    ///     (&word_piece, "foobar", &[2, 5]),
+    ///     (&word_piece, "##foo", &[1]),
+    ///     (&word_piece, "#foo", &[3, 6]),
+    ///     (&word_piece, "barbaz", &[1]),
+    /// ];
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for (tokenizer, text, expected) in cases {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     assert_eq!(&ids[..], *expected, "{}", text);
+    ///     assert_eq!(ranges.first().unwrap().start, 0);
+    ///     assert_eq!(ranges.last().unwrap().end, text.len());
+    /// }
+    /// ```
+    ///
+    /// [`leading_prefix_as_follower`]: AlephAlphaTokenizerBuilder::leading_prefix_as_follower
+    pub fn tokens_into<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut words = words;
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        self.tokens_with_words(text, token_ids, token_ranges, words);
+        debug_assert_eq!(
+            Ok(()),
+            self.validate_ranges(text, token_ids, token_ranges),
+            "{:?}",
+            text
+        );
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but only
+    /// fills the token ids, which is faster if the ranges aren't needed,
+    /// e.g. to count the tokens or to feed them to a model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let lowercase = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .rejoin_hyphenation(true)
+    ///     .max_chars_per_word(Some(10))
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges, mut only_ids) = (Vec::<u32>::new(), Vec::new(), Vec::<u32>::new());
+    /// for text in &[
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+    ///         FSME zur Verfügung?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "Grüße aus dem Kranken-\nhaus ☃ Supercalifragilistic",
+    ///     "",
+    /// ] {
+    ///     for tokenizer in &[&tokenizer, &lowercase] {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         tokenizer.ids_into(text, &mut only_ids);
+    ///         assert_eq!(ids, only_ids, "{:?}", text);
+    ///     }
+    /// }
+    /// ```
+    pub fn ids_into<T: TokenID>(&self, text: &str, token_ids: &mut Vec<T>) {
+        token_ids.clear();
+        if let (true, Some(id)) = (self.options.add_prefix, self.prefix) {
+            token_ids.push(T::coerce(u64::from(id)));
+        }
+        for span in self.spans(text) {
+            self.tokenize_span(text, span, token_ids, &mut NoRanges);
+        }
+        if let (true, Some(id)) = (self.options.add_suffix, self.suffix) {
+            token_ids.push(T::coerce(u64::from(id)));
+        }
+    }
+
+    /// Counts the tokens of the text, i.e. the length of the ids that
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into) would give,
+    /// including `[CLS]`, `[SEP]` and the `[UNK]` of each unknown word.
+    ///
+    /// This doesn't allocate, unless the tokenizer lowercases words, rejoins
+    /// hyphenated words or backtracks, which takes a scratch buffer for some
+    /// words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode, UnkMode};
+    /// use std::alloc::{GlobalAlloc, Layout, System};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Counting;
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let others = [
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .lowercase(true)
+    ///         .rejoin_hyphenation(true)
+    ///         .max_chars_per_word(Some(12))
+    ///         .build()
+    ///         .unwrap(),
+    ///     AlephAlphaTokenizer::builder()
+    ///         .vocab_path("vocab.txt")
+    ///         .segmentation_mode(SegmentationMode::Backtracking)
+    ///         .unk_mode(UnkMode::PerChar)
+    ///         .add_special_tokens(false)
+    ///         .build()
+    ///         .unwrap(),
+    /// ];
+    /// let mut texts: Vec<String> = [
+    ///     "Ich esse Steak.",
+    ///     "Der Hund spielt im Garten.",
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+    ///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+    ///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+    ///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+    ///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+    ///         Borreliose vor und wenn ja, wie lauten diese?",
+    ///     "",
+    ///     " \t ",
+    /// ]
+    /// .iter()
+    /// .map(|text| text.to_string())
+    /// .collect();
+    /// // and random strings of letters, umlauts, punctuation and whitespace
+    /// let alphabet: Vec<char> = "aeinrstEISüß-.,#☃ \n\u{a0}".chars().collect();
+    /// let mut seed = 42u64;
+    /// for _ in 0..200 {
+    ///     seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    ///     let len = (seed >> 59) as usize;
+    ///     texts.push(
+    ///         (0..len)
+    ///             .map(|i| alphabet[((seed >> (i * 3 % 48)) % alphabet.len() as u64) as usize])
+    ///             .collect(),
+    ///     );
+    /// }
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// for text in &texts {
+    ///     for tokenizer in std::iter::once(&tokenizer).chain(&others) {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         assert_eq!(ids.len(), tokenizer.count_tokens(text), "{:?}", text);
+    ///     }
+    /// }
+    ///
+    /// let before = ALLOCATIONS.load(Ordering::Relaxed);
+    /// let count: usize = texts.iter().map(|text| tokenizer.count_tokens(text)).sum();
+    /// assert_eq!(before, ALLOCATIONS.load(Ordering::Relaxed));
+    /// assert!(count > 0);
+    /// ```
+    pub fn count_tokens(&self, text: &str) -> usize {
+        let mut counted = Vec::<Counted>::new();
+        self.ids_into(text, &mut counted);
+        counted.len()
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but appends
+    /// the tokens to the output `Vec`s instead of clearing them, e.g. to pack
+    /// several texts into one sequence.
+    ///
+    /// Whether the `[CLS]` and `[SEP]` tokens (if present) are added to this
+    /// text is up to `add_prefix` and `add_suffix`, regardless of the
+    /// tokenizer's options. The new token ranges are byte ranges within
+    /// `text`. The `words` (if given) are appended, too, and index into the
+    /// whole `token_ids`, so they start after the tokens that were already
+    /// there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u64>::new(), Vec::new(), Vec::new());
+    /// // [CLS] doc1 [SEP] doc2 [SEP]
+    /// tokenizer.extend_tokens_into("Ein Beispiel", &mut ids, &mut ranges, Some(&mut words), true, true);
+    /// tokenizer.extend_tokens_into("Ich esse", &mut ids, &mut ranges, Some(&mut words), false, true);
+    /// assert_eq!(
+    ///     "[CLS] Ein Bei ##spiel [SEP] Ich es ##se [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(&[1..2, 2..4, 5..6, 6..8], &words[..]);
+    ///
+    /// // the same as concatenating both texts' tokens without the second [CLS]
+    /// let (mut first_ids, mut first_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut second_ids, mut second_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ein Beispiel", &mut first_ids, &mut first_ranges, None);
+    /// tokenizer.tokens_into("Ich esse", &mut second_ids, &mut second_ranges, None);
+    /// first_ids.extend_from_slice(&second_ids[1..]);
+    /// first_ranges.extend_from_slice(&second_ranges[1..]);
+    /// assert_eq!(first_ids, ids);
+    /// assert_eq!(first_ranges, ranges);
+    /// ```
+    pub fn extend_tokens_into<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+        add_prefix: bool,
+        add_suffix: bool,
+    ) {
+        let start = token_ids.len();
+        if let (true, Some(id)) = (add_prefix, self.prefix) {
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(0..0);
+        }
+        self.words_into(text, 0, token_ids, token_ranges, words);
+        if let (true, Some(id)) = (add_suffix, self.suffix) {
+            let pos = token_ranges[start..].last().map_or(0, |range| range.end);
+            token_ids.push(T::coerce(u64::from(id)));
+            token_ranges.push(pos..pos);
+        }
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but the
+    /// token ranges count `char`s instead of bytes, e.g. to hand them to
+    /// Python code. Note that JavaScript strings count UTF-16 code units,
+    /// which differ for characters outside the Basic Multilingual Plane.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let text = "Überraschung änders";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut char_ids, mut char_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// tokenizer.tokens_into_chars(text, &mut char_ids, &mut char_ranges, None);
+    /// assert_eq!(ids, char_ids);
+    ///
+    /// let chars: Vec<char> = text.chars().collect();
+    /// for (bytes, chars_range) in ranges.iter().zip(&char_ranges) {
+    ///     let surface: String = chars[chars_range.clone()].iter().collect();
+    ///     assert_eq!(&text[bytes.clone()], surface);
+    /// }
+    /// assert_eq!(0..0, char_ranges[0]);
+    /// assert_eq!(19..19, char_ranges[char_ranges.len() - 1]);
+    /// ```
+    pub fn tokens_into_chars<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        self.tokens_into(text, token_ids, token_ranges, words);
+        if self.ascii_fast_path && text.is_ascii() {
+            // each byte is a char
+            return;
+        }
+        // the starts never decrease, so we only need to count each char once
+        // (the ranges of lowercased tokens may overlap, though)
+        let (mut last_byte, mut last_char) = (0, 0);
+        for range in token_ranges.iter_mut() {
+            last_char = char_offs(text, last_char, last_byte..range.start);
+            last_byte = range.start;
+            *range = last_char..char_offs(text, last_char, range.clone());
+        }
+    }
+
+    // tokenize the words of `text[start..]`, appending the tokens
+    fn words_into<T: TokenID, R: RangeSink>(
+        &self,
+        text: &str,
+        start: usize,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut R,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        self.spans_into(
+            text,
+            self.spans_from(text, start),
+            token_ids,
+            token_ranges,
+            words,
+        );
+    }
+
+    // tokenize the words of the spans, appending the tokens
+    fn spans_into<T: TokenID, R: RangeSink>(
+        &self,
+        text: &str,
+        spans: iter::Spans<'_>,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut R,
+        mut words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        //TODO: there may be a faster version of this using SIMD
+        for span in spans {
+            let first_token = token_ids.len();
+            self.tokenize_span(text, span, token_ids, token_ranges);
+            if let Some(w) = words.as_mut() {
+                w.push(first_token..token_ids.len());
+            }
+        }
+    }
+
+    /// Gets the text of this token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert_eq!("[PAD]", tokenizer.text_of(0));
+    ///
+    /// // each id has the text of its line, blank lines included
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// for (id, line) in vocab.lines().enumerate() {
+    ///     assert_eq!(line.trim(), tokenizer.text_of(id as u64));
+    /// }
+    /// assert_eq!("", tokenizer.text_of(26896));
+    /// assert_eq!(vocab.lines().count(), tokenizer.vocab_size());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not in the vocabulary, e.g. an id of another
+    /// tokenizer. Negative ids wrap around to huge ones, which panic, too.
+    /// Use [`try_text_of`](AlephAlphaTokenizer::try_text_of) for ids that
+    /// may be invalid.
+    #[inline]
+    pub fn text_of<T: TokenID>(&self, token_id: T) -> &str {
+        &self.tokens[token_id.restore() as usize]
+    }
+
+    /// Gets the text of this token, or `None` if the id is not in the
+    /// vocabulary (including negative and fractional ids) or has no text,
+    /// like a blank line of the vocabulary file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert_eq!(Some("Super"), tokenizer.try_text_of(4285i32));
+    /// assert_eq!(None, tokenizer.try_text_of(-1i64));
+    /// assert_eq!(None, tokenizer.try_text_of(-1i32));
+    /// assert_eq!(None, tokenizer.try_text_of(u64::MAX));
+    /// assert_eq!(None, tokenizer.try_text_of(tokenizer.vocab_size() as u64));
+    /// assert_eq!(None, tokenizer.try_text_of(-1.0f64));
+    /// assert_eq!(None, tokenizer.try_text_of(0.5f64));
+    /// assert_eq!(Some("[PAD]"), tokenizer.try_text_of(0.0f64));
+    /// ```
+    #[inline]
+    pub fn try_text_of<T: TokenID>(&self, token_id: T) -> Option<&str> {
+        let id = usize::try_from(token_id.try_restore()?).ok()?;
+        self.tokens.get(id).filter(|token| !token.is_empty())
+    }
+
+    /// Looks up the id of a token by its text.
+    ///
+    /// Tokens starting with the
+    /// [continuing subword prefix](AlephAlphaTokenizer::continuing_subword_prefix)
+    /// are looked up among the followers. Special tokens are found, too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert_eq!(Some(4285), tokenizer.id_of("Super"));
+    /// assert_eq!(Some(26914), tokenizer.id_of("##."));
+    /// assert_eq!(Some(3), tokenizer.id_of("[CLS]"));
+    /// assert_eq!(Some(0), tokenizer.id_of("[PAD]"));
+    /// assert_eq!(Some(19862), tokenizer.id_of("##Super"));
+    /// assert_eq!(None, tokenizer.id_of("Supercalifragilistic"));
+    /// assert_eq!(Some(4285i32), tokenizer.id_of_as("Super"));
+    /// ```
+    pub fn id_of(&self, token: &str) -> Option<u64> {
+        let follower = if self.end_of_word_suffix.is_empty() {
+            token.strip_prefix(self.continuing_subword_prefix.as_str())
+        } else {
+            token.strip_suffix(self.end_of_word_suffix.as_str())
+        };
+        if let Some(follower) = follower {
+            self.followers.get(follower)
+        } else {
+            self.starters.get(token)
+        }
+        .map(|o| o.value())
+        .or_else(|| self.special_id(token))
+    }
+
+    /// Like [`id_of`](AlephAlphaTokenizer::id_of), but converts the id.
+    pub fn id_of_as<T: TokenID>(&self, token: &str) -> Option<T> {
+        self.id_of(token).map(T::coerce)
+    }
+
+    /// Gets the texts of the tokens.
+    ///
+    /// Like [`text_of`](AlephAlphaTokenizer::text_of), this panics on ids
+    /// that are not in the vocabulary; see
+    /// [`try_texts_of`](AlephAlphaTokenizer::try_texts_of).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
     ///
     /// assert_eq!(
     ///     vec!["[CLS]", "Super", "[SEP]"],
@@ -412,6 +2308,28 @@ impl AlephAlphaTokenizer {
             .collect()
     }
 
+    /// Gets the texts of the tokens, with `None` for each id that is not in
+    /// the vocabulary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert_eq!(
+    ///     vec![Some("[CLS]"), None, Some("Super"), None, Some("[SEP]")],
+    ///     tokenizer.try_texts_of(&[3, -1, 4285, 1 << 40, 4i64])
+    /// );
+    /// ```
+    pub fn try_texts_of<'t, T: TokenID>(&'t self, token_ids: &[T]) -> Vec<Option<&'t str>> {
+        token_ids
+            .iter()
+            .cloned()
+            .map(|id| self.try_text_of(id))
+            .collect()
+    }
+
     /// Determines whether this token is a special token.
     ///
     /// Special tokens are e.g. `[CLS]`, `[SEP]`, `[PAD]` or `[UNK]`.
@@ -426,14 +2344,130 @@ impl AlephAlphaTokenizer {
     /// assert!(tokenizer.is_special(3i32));  // [CLS]
     /// assert!(tokenizer.is_special(4i32));  // [SEP]
     /// assert!(!tokenizer.is_special(42i32));
+    ///
+    /// // exactly the registered special tokens are special, including added ones
+    /// let mut tokenizer = tokenizer;
+    /// let added = tokenizer.add_special_tokens(&["<doc>", "</doc>"]).unwrap();
+    /// let special = tokenizer.special_token_ids();
+    /// assert!(added.iter().all(|id| special.contains(id)));
+    /// for id in 0..tokenizer.vocab_size() as u64 {
+    ///     assert_eq!(special.contains(&id), tokenizer.is_special(id), "{}", id);
+    /// }
+    ///
+    /// // ids beyond the vocabulary aren't special
+    /// assert!(!tokenizer.is_special(tokenizer.vocab_size() as u64));
+    /// assert!(!tokenizer.is_special(u64::MAX));
+    /// assert!(!tokenizer.is_special(-1i32));
     /// ```
+    ///
+    /// The lookup is a bit per id, set up on the first call, so it takes the
+    /// same time however many special tokens there are.
     #[inline]
     pub fn is_special<T: TokenID>(&self, token_id: T) -> bool {
-        self.special_tokens.contains(&token_id.restore())
+        self.special_bit(token_id.restore())
+    }
+
+    /// Given a slice of ids, appends `1` for each special token (like
+    /// [`is_special`](AlephAlphaTokenizer::is_special)) and `0` for any
+    /// other token to the given `Vec`, after clearing it.
+    ///
+    /// This is the `special_tokens_mask` of huggingface's encodings, e.g.
+    /// to leave the special tokens out of a loss.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ich esse Steak.", &mut ids, &mut ranges, None);
+    /// ids.resize(16, tokenizer.pad_id().unwrap() as i64);
+    /// let mut mask: Vec<u32> = vec![7; 3];
+    /// tokenizer.special_tokens_mask_into(&ids, &mut mask);
+    /// assert_eq!(&[1, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1], &mask[..]);
+    ///
+    /// tokenizer.special_tokens_mask_into(&[2i64, 5, 4285], &mut mask); // [UNK], [MASK]
+    /// assert_eq!(&[1, 1, 0], &mask[..]);
+    /// ```
+    pub fn special_tokens_mask_into<T: TokenID, U: TokenID>(
+        &self,
+        token_ids: &[T],
+        mask: &mut Vec<U>,
+    ) {
+        mask.clear();
+        mask.extend(token_ids.iter().cloned().map(|id| {
+            if self.is_special(id) {
+                U::coerce(1)
+            } else {
+                U::zero()
+            }
+        }));
+    }
+
+    /// Determines whether this token can start a word.
+    ///
+    /// Special tokens count as starters, as they stand alone. The
+    /// `[unused…]` tokens are neither starters nor followers, because they
+    /// never match, and neither are ids outside the vocabulary. Without a
+    /// [continuing subword prefix](AlephAlphaTokenizer::continuing_subword_prefix),
+    /// all other tokens are both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert!(tokenizer.is_starter(4285i32)); // Super
+    /// assert!(!tokenizer.is_starter(464i32)); // ##ak
+    /// assert!(tokenizer.is_starter(3i32)); // [CLS]
+    /// assert!(!tokenizer.is_starter(1i32)); // [unused3001]
+    /// assert!(!tokenizer.is_starter(-1i64));
+    /// assert!(!tokenizer.is_starter(u64::MAX));
+    /// ```
+    #[inline]
+    pub fn is_starter<T: TokenID>(&self, token_id: T) -> bool {
+        self.starter_bit(token_id.restore())
+    }
+
+    /// Determines whether this token continues a word, like the tokens
+    /// starting with `##`.
+    ///
+    /// Special tokens, `[unused…]` tokens and ids outside the vocabulary
+    /// are not followers (see [`is_starter`](AlephAlphaTokenizer::is_starter)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// assert!(tokenizer.is_follower(464i32)); // ##ak
+    /// assert!(!tokenizer.is_follower(4285i32)); // Super
+    /// assert!(!tokenizer.is_follower(3i32)); // [CLS]
+    /// assert!(!tokenizer.is_follower(1i32)); // [unused3001]
+    /// assert!(!tokenizer.is_follower(-1i64));
+    /// assert!(!tokenizer.is_follower(u64::MAX));
+    ///
+    /// // group the tokens into words
+    /// let tokenizer = examples::tokenizer();
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.tokens_into("Ein interessantes Beispiel", &mut ids, &mut ranges, None);
+    /// let words = ids.iter().filter(|&&id| !tokenizer.is_follower(id)).count();
+    /// assert_eq!(5, words); // [CLS], Ein, interessantes, Beispiel, [SEP]
+    /// ```
+    #[inline]
+    pub fn is_follower<T: TokenID>(&self, token_id: T) -> bool {
+        self.follower_bit(token_id.restore())
     }
 
     /// Calculates the required attention for this token.
     ///
+    /// This treats the id `0` as padding, which only fits vocabularies with
+    /// `[PAD]` first; [`attention_for`](AlephAlphaTokenizer::attention_for)
+    /// uses the actual padding token of a tokenizer.
+    ///
     /// # Examples
     ///
     /// ```
@@ -455,6 +2489,10 @@ impl AlephAlphaTokenizer {
 
     /// Given a slice of `[u64]`s, appends the attentions to the given `Vec`.
     ///
+    /// Like [`attention`](AlephAlphaTokenizer::attention), this treats the
+    /// id `0` as padding; see
+    /// [`attentions_into_for`](AlephAlphaTokenizer::attentions_into_for).
+    ///
     /// # Examples
     ///
     /// ```
@@ -474,115 +2512,130 @@ impl AlephAlphaTokenizer {
         );
     }
 
-    /// Save the vocabulary back to a file
-    pub fn save_vocab(&self, vocab_path: PathBuf) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
-        let vocab = File::create(&vocab_path)?;
-        let mut vocab_writer = BufWriter::new(vocab);
-        for token in &self.tokens {
-            writeln!(vocab_writer, "{}", token)?;
-        }
-        //TODO: write out FSTs to reduce load time
-        Ok(vocab_path)
-    }
-}
-
-#[cfg(feature = "huggingface")]
-use std::{borrow::Cow, path::Path};
-
-/// This type implements the [`Model`] trait so you can use it within
-/// huggingface's tokenizers framework.
-#[cfg(feature = "huggingface")]
-impl Model for AlephAlphaTokenizer {
-    fn tokenize(
-        &self,
-        tokens: Vec<(String, (usize, usize))>,
-    ) -> Result<Vec<HfToken>, Box<dyn Error + Send + Sync>> {
-        // we expect at least one token per word.
-        let mut result = Vec::with_capacity(tokens.len());
-        for (index, (word_str, offsets)) in tokens.into_iter().enumerate() {
-            let word = index as u32;
-            let word_index = result.len();
-            let word_bytes = word_str.as_bytes();
-            let word_len = word_bytes.len();
-            let mut last_index = 0;
-            if let Some((start_index, id)) = find_longest_prefix(&self.starters, word_bytes) {
-                let value = word_str[..start_index].to_string();
-                let mut last_offset = offsets.0 + value.chars().count();
-                result.push(HfToken {
-                    id: id as u32,
-                    value,
-                    offsets: (offsets.0, last_offset),
-                    word,
-                });
-                last_index = start_index;
-                while last_index < word_len {
-                    if let Some((len, id)) =
-                        find_longest_prefix(&self.followers, &word_bytes[last_index..])
-                    {
-                        let value = &word_str[last_index..last_index + len];
-                        let start = last_offset;
-                        last_offset += value.chars().count();
-                        result.push(HfToken {
-                            id: id as u32,
-                            value: "##".to_string() + value,
-                            offsets: (start, last_offset),
-                            word,
-                        });
-                        last_index += len;
-                    } else {
-                        break;
-                    }
-                }
-            }
-            // in case we couldn't match the whole word, replace all we have so far with an [UNK] token
-            if last_index < word_len {
-                assert!(word_index <= result.len());
-                result.truncate(word_index);
-                result.push(HfToken {
-                    id: self.unk_id,
-                    value: "[UNK]".to_string(),
-                    offsets: (offsets.0, offsets.1),
-                    word,
-                });
-            }
-        }
-        Ok(result)
-    }
-
-    fn token_to_id(&self, token: &str) -> Option<u32> {
-        if token.starts_with("##") {
-            self.followers.get(&token[2..])
+    /// Calculates the required attention for this token: `0` for the
+    /// [padding token](AlephAlphaTokenizer::pad_id) of this tokenizer, `1`
+    /// for any other id (including all ids if the vocabulary has no
+    /// `[PAD]` token).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer =
+    ///     AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\n[PAD]\n[CLS]\n[SEP]\nSuper\n").unwrap();
+    /// assert_eq!(1, tokenizer.attention_for::<_, i32>(0u64)); // [UNK]
+    /// assert_eq!(0, tokenizer.attention_for::<_, i32>(1u64)); // [PAD]
+    /// assert_eq!(1.0, tokenizer.attention_for::<_, f64>(4i64));
+    ///
+    /// let no_pad = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\n").unwrap();
+    /// assert_eq!(1, no_pad.attention_for::<_, i32>(0u64));
+    /// ```
+    #[inline]
+    pub fn attention_for<T: TokenID, U: TokenID>(&self, token_id: T) -> U {
+        if self.pad_id.is_some() && token_id.try_restore() == self.pad_id() {
+            U::zero()
         } else {
-            self.starters.get(token)
+            U::coerce(1)
         }
-        .map(|x| x.value() as u32)
-    }
-
-    fn id_to_token(&self, id: u32) -> Option<String> {
-        self.tokens.get(id as usize).cloned()
     }
 
-    fn get_vocab_size(&self) -> usize {
-        self.tokens.len()
+    /// Given a slice of ids, appends their attentions by
+    /// [`attention_for`](AlephAlphaTokenizer::attention_for) to the given
+    /// `Vec`, after clearing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer =
+    ///     AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\n[PAD]\n[CLS]\n[SEP]\nSuper\n").unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Super Duper", &mut ids, &mut ranges, None);
+    /// ids.resize(6, tokenizer.pad_id().unwrap() as i64);
+    /// assert_eq!(&[2, 4, 0, 3, 1, 1], &ids[..]);
+    ///
+    /// let mut attns: Vec<i32> = Vec::new();
+    /// tokenizer.attentions_into_for(&ids, &mut attns);
+    /// // the static version masks the `[UNK]` token instead of the padding
+    /// assert_eq!(&[1, 1, 1, 1, 0, 0], &attns[..]);
+    /// AlephAlphaTokenizer::attentions_into(&ids, &mut attns);
+    /// assert_eq!(&[1, 1, 0, 1, 1, 1], &attns[..]);
+    /// ```
+    pub fn attentions_into_for<T: TokenID, U: TokenID>(&self, token_ids: &[T], attns: &mut Vec<U>) {
+        attns.clear();
+        attns.extend(token_ids.iter().cloned().map(|id| self.attention_for(id)));
     }
 
-    /// We won't implement this method because we don't store the tokens in
-    /// a `HashMap`, and doing so would increase our memory footprint
-    /// considerably.
-    fn get_vocab(&self) -> &std::collections::HashMap<String, u32> {
-        unimplemented!()
+    /// Save the vocabulary back to a file
+    ///
+    /// The vocabulary is written to a `.tmp` file next to the given path,
+    /// synced to disk and then renamed, so the file at the path (if any) is
+    /// only ever replaced by a complete vocabulary, even if the process
+    /// crashes while writing.
+    ///
+    /// Loading the saved file with
+    /// [`from_vocab`](AlephAlphaTokenizer::from_vocab) gives the same
+    /// tokenizer, as every token (including `[unusedX]` and blank ones) is
+    /// written on the line of its id, so the special tokens keep their ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let dir = std::env::temp_dir().join(format!("save-vocab-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("vocab.txt");
+    /// // an older vocabulary is replaced
+    /// std::fs::write(&path, "[UNK]\n[PAD]\n").unwrap();
+    /// assert_eq!(path, tokenizer.save_vocab(path.clone()).unwrap());
+    /// assert!(!dir.join("vocab.txt.tmp").exists());
+    /// assert_eq!(std::fs::read("vocab.txt").unwrap(), std::fs::read(&path).unwrap());
+    ///
+    /// let reloaded = AlephAlphaTokenizer::from_vocab(path.to_str().unwrap()).unwrap();
+    /// assert_eq!(tokenizer.fingerprint(), reloaded.fingerprint());
+    /// assert_eq!(tokenizer.vocab_size(), reloaded.vocab_size());
+    /// for id in 0..tokenizer.vocab_size() as u64 {
+    ///     assert_eq!(tokenizer.text_of(id), reloaded.text_of(id));
+    ///     assert_eq!(tokenizer.is_special(id), reloaded.is_special(id));
+    /// }
+    /// assert_eq!(tokenizer.pad_id(), reloaded.pad_id());
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let (mut reloaded_ids, mut reloaded_ranges) = (Vec::<u32>::new(), Vec::new());
+    /// for text in &["Ich esse Steak.", "Grüße aus dem Krankenhaus ☃ [MASK] [unused3]"] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     reloaded.tokens_into(text, &mut reloaded_ids, &mut reloaded_ranges, None);
+    ///     assert_eq!((&ids, &ranges), (&reloaded_ids, &reloaded_ranges));
+    /// }
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    #[cfg(feature = "std-fs")]
+    pub fn save_vocab(&self, vocab_path: PathBuf) -> Result<PathBuf, TokenizerError> {
+        let mut tmp_path = vocab_path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let saved = self
+            .write_vocab(&tmp_path)
+            .and_then(|()| fs::rename(&tmp_path, &vocab_path));
+        if let Err(e) = saved {
+            // the error of the write matters more than that of the cleanup
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e.into());
+        }
+        Ok(vocab_path)
     }
 
-    fn save(
-        &self,
-        folder: &Path,
-        name: Option<&str>,
-    ) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
-        let vocab_name = name.map_or(Cow::Borrowed("vocab.txt"), |n| {
-            Cow::Borrowed(n) + "-vocab.txt"
-        });
-        let mut vocab_path = folder.to_path_buf();
-        vocab_path.push(&Path::new(vocab_name.as_ref()));
-        self.save_vocab(vocab_path).map(|p| vec![p])
+    // writes the tokens one per line, and syncs the file to disk
+    #[cfg(feature = "std-fs")]
+    fn write_vocab(&self, path: &std::path::Path) -> io::Result<()> {
+        let mut vocab_writer = BufWriter::new(File::create(path)?);
+        for token in self.tokens.iter() {
+            writeln!(vocab_writer, "{}", token)?;
+        }
+        let vocab = vocab_writer.into_inner().map_err(|e| e.into_error())?;
+        vocab.sync_all()
     }
 }
@@ -0,0 +1,231 @@
+use crate::{AlephAlphaTokenizer, IdSink, RangeSink, TokenID};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The error of [`tokens_into_limited`](AlephAlphaTokenizer::tokens_into_limited)
+/// if the text has more tokens than the limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// the number of tokens kept, i.e. those of the words before the one
+    /// that exceeded the limit
+    pub tokens: usize,
+    /// the byte offset where the text of the kept tokens ends
+    pub offset: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the token limit was exceeded after {} tokens at byte {}",
+            self.tokens, self.offset
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
+
+// pushes onto a `Vec` until it holds `limit` elements, and counts the rest,
+// so the `Vec` never grows beyond the limit, but a word that is rolled back
+// to `[UNK]` is still counted right
+struct Limited<'a, E> {
+    vec: &'a mut Vec<E>,
+    len: usize,
+    limit: usize,
+}
+
+impl<'a, E> Limited<'a, E> {
+    fn new(vec: &'a mut Vec<E>, limit: usize) -> Self {
+        Limited {
+            len: vec.len(),
+            vec,
+            limit,
+        }
+    }
+
+    #[inline]
+    fn push_limited(&mut self, e: E) {
+        if self.len < self.limit {
+            self.vec.push(e);
+        }
+        self.len += 1;
+    }
+
+    #[inline]
+    fn truncate_limited(&mut self, len: usize) {
+        self.len = self.len.min(len);
+        self.vec.truncate(len);
+    }
+
+    fn exceeded(&self) -> bool {
+        self.len > self.limit
+    }
+}
+
+impl<T> IdSink<T> for Limited<'_, T> {
+    #[inline]
+    fn push(&mut self, id: T) {
+        self.push_limited(id);
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.truncate_limited(len);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl RangeSink for Limited<'_, Range<usize>> {
+    #[inline]
+    fn push(&mut self, range: Range<usize>) {
+        self.push_limited(range);
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.truncate_limited(len);
+    }
+
+    // only the suffix needs this, and its range doesn't matter once the
+    // limit is exceeded
+    #[inline]
+    fn last_end(&self) -> usize {
+        self.vec.last_end()
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but fails
+    /// instead of giving more than `limit` tokens (including `[CLS]` and
+    /// `[SEP]`), e.g. to guard a service against texts that would take a lot
+    /// of memory to tokenize.
+    ///
+    /// The output never grows beyond the limit: tokenizing stops after the
+    /// word that exceeds it, and the output is left with the tokens (and
+    /// words) of the words before it, without `[SEP]`. The
+    /// [`LimitExceeded`] error tells how many tokens there are and where
+    /// their text ends, e.g. to tokenize the rest of the text separately.
+    /// Texts within the limit get the tokens of `tokens_into`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, LimitExceeded};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ich esse Steak.";
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!("[CLS] Ich es ##se Ste ##ak ##. [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// // exactly at the limit
+    /// let (mut limited_ids, mut limited_ranges, mut limited_words) =
+    ///     (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// let out = (&mut limited_ids, &mut limited_ranges, Some(&mut limited_words));
+    /// assert_eq!(Ok(()), tokenizer.tokens_into_limited(text, out.0, out.1, out.2, 8));
+    /// assert_eq!((&ids, &ranges, &words), (&limited_ids, &limited_ranges, &limited_words));
+    ///
+    /// // one token less leaves out `[SEP]`
+    /// let out = (&mut limited_ids, &mut limited_ranges, Some(&mut limited_words));
+    /// let error = tokenizer.tokens_into_limited(text, out.0, out.1, out.2, 7);
+    /// assert_eq!(Err(LimitExceeded { tokens: 7, offset: 15 }), error);
+    /// assert_eq!(&ids[..7], &limited_ids[..]);
+    ///
+    /// // and two keep the words that fit
+    /// let out = (&mut limited_ids, &mut limited_ranges, Some(&mut limited_words));
+    /// let error = tokenizer.tokens_into_limited(text, out.0, out.1, out.2, 6).unwrap_err();
+    /// assert_eq!(LimitExceeded { tokens: 4, offset: 8 }, error);
+    /// assert_eq!("the token limit was exceeded after 4 tokens at byte 8", error.to_string());
+    /// assert_eq!("[CLS] Ich es ##se", tokenizer.texts_of(&limited_ids).join(" "));
+    /// assert_eq!((&ranges[..4], &words[..2]), (&limited_ranges[..], &limited_words[..]));
+    ///
+    /// // even `[CLS]` and `[SEP]` count
+    /// let error = tokenizer.tokens_into_limited("", &mut limited_ids, &mut limited_ranges, None, 1);
+    /// assert_eq!(Err(LimitExceeded { tokens: 1, offset: 0 }), error);
+    /// let error = tokenizer.tokens_into_limited("", &mut limited_ids, &mut limited_ranges, None, 0);
+    /// assert_eq!(Err(LimitExceeded { tokens: 0, offset: 0 }), error);
+    ///
+    /// // a huge text doesn't take more memory than the limit
+    /// let huge = "Steak ".repeat(1_000_000);
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// let error = tokenizer.tokens_into_limited(&huge, &mut ids, &mut ranges, None, 100);
+    /// assert_eq!(Err(LimitExceeded { tokens: 99, offset: 293 }), error);
+    /// assert!(ids.capacity() <= 128 && ranges.capacity() <= 128);
+    ///
+    /// // within the limit, the tokens are those of `tokens_into`
+    /// for text in &[
+    ///     "Der Hund spielt im Garten.",
+    ///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///     "Grüße aus dem Krankenhaus ☃ Supercalifragilistic",
+    ///     "  ",
+    /// ] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    ///     for limit in 0..ids.len() + 2 {
+    ///         let out = (&mut limited_ids, &mut limited_ranges, Some(&mut limited_words));
+    ///         match tokenizer.tokens_into_limited(text, out.0, out.1, out.2, limit) {
+    ///             Ok(()) => {
+    ///                 assert!(limit >= ids.len(), "{:?}", text);
+    ///                 assert_eq!((&ids, &ranges), (&limited_ids, &limited_ranges), "{:?}", text);
+    ///                 assert_eq!(words, limited_words, "{:?}", text);
+    ///             }
+    ///             Err(LimitExceeded { tokens, offset }) => {
+    ///                 assert!(limit < ids.len(), "{:?}", text);
+    ///                 assert!(tokens <= limit && tokens == limited_ids.len(), "{:?}", text);
+    ///                 assert_eq!(&ids[..tokens], &limited_ids[..], "{:?}", text);
+    ///                 assert!(limited_ranges.iter().all(|r| r.end <= offset), "{:?}", text);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn tokens_into_limited<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+        limit: usize,
+    ) -> Result<(), LimitExceeded> {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut words = words;
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        let mut ids = Limited::new(token_ids, limit);
+        let mut ranges = Limited::new(token_ranges, limit);
+        let mut offset = 0;
+        // keeps the tokens before `first` if the last ones exceeded the limit
+        let check = |ids: &mut Limited<'_, T>, ranges: &mut Limited<'_, _>, first, offset| {
+            if ids.exceeded() {
+                ids.truncate_limited(first);
+                ranges.truncate_limited(first);
+                Err(LimitExceeded {
+                    tokens: first,
+                    offset,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        self.add_prefix(&mut ids, &mut ranges);
+        check(&mut ids, &mut ranges, 0, 0)?;
+        for span in self.spans(text) {
+            let (first, end) = (ids.len, span.end);
+            self.tokenize_span(text, span, &mut ids, &mut ranges);
+            check(&mut ids, &mut ranges, first, offset)?;
+            if let Some(w) = words.as_mut() {
+                w.push(first..ids.len);
+            }
+            offset = end;
+        }
+        let first = ids.len;
+        self.add_suffix(&mut ids, &mut ranges);
+        check(&mut ids, &mut ranges, first, offset)
+    }
+}
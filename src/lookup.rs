@@ -0,0 +1,83 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::error::Error;
+use std::fmt;
+
+/// The error of [`try_ids_of`](AlephAlphaTokenizer::try_ids_of) if a text
+/// is no token of the vocabulary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownToken {
+    /// the index of the first text that is no token
+    pub index: usize,
+    /// that text
+    pub token: String,
+}
+
+impl fmt::Display for UnknownToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} (at index {}) is no token of the vocabulary",
+            self.token, self.index
+        )
+    }
+}
+
+impl Error for UnknownToken {}
+
+impl AlephAlphaTokenizer {
+    /// Looks up the ids of tokens by their texts, like
+    /// [`id_of`](AlephAlphaTokenizer::id_of), with `None` for each text that
+    /// is no token, e.g. to get the ids of label tokens up front.
+    ///
+    /// This is the inverse of
+    /// [`try_texts_of`](AlephAlphaTokenizer::try_texts_of). Each text is
+    /// looked up once, without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// let tokens = ["[CLS]", "Super", "##man", "Supercalifragilistic", "##.", "", "[SEP]"];
+    /// let ids = tokenizer.ids_of::<u32>(&tokens);
+    /// assert_eq!(
+    ///     vec![Some(3), Some(4285), Some(1363), None, Some(26914), None, Some(4)],
+    ///     ids
+    /// );
+    /// let found: Vec<u32> = ids.iter().flatten().copied().collect();
+    /// assert_eq!(vec!["[CLS]", "Super", "##man", "##.", "[SEP]"], tokenizer.texts_of(&found));
+    /// ```
+    pub fn ids_of<T: TokenID>(&self, tokens: &[&str]) -> Vec<Option<T>> {
+        tokens.iter().map(|token| self.id_of_as(token)).collect()
+    }
+
+    /// Like [`ids_of`](AlephAlphaTokenizer::ids_of), but fails on the first
+    /// text that is no token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, UnknownToken};
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    ///
+    /// let tokens = ["[CLS]", "Super", "##man", "[SEP]"];
+    /// assert_eq!(Ok(vec![3i64, 4285, 1363, 4]), tokenizer.try_ids_of(&tokens));
+    /// let error = tokenizer.try_ids_of::<i64>(&["Super", "##xyzzy", "☃"]).unwrap_err();
+    /// assert_eq!(UnknownToken { index: 1, token: "##xyzzy".to_string() }, error);
+    /// assert_eq!("\"##xyzzy\" (at index 1) is no token of the vocabulary", error.to_string());
+    /// assert_eq!(Ok(vec![]), tokenizer.try_ids_of::<i64>(&[]));
+    /// ```
+    pub fn try_ids_of<T: TokenID>(&self, tokens: &[&str]) -> Result<Vec<T>, UnknownToken> {
+        tokens
+            .iter()
+            .enumerate()
+            .map(|(index, token)| {
+                self.id_of_as(token).ok_or_else(|| UnknownToken {
+                    index,
+                    token: token.to_string(),
+                })
+            })
+            .collect()
+    }
+}
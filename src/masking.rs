@@ -0,0 +1,177 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use rand::Rng;
+
+/// How [`mask_tokens`](AlephAlphaTokenizer::mask_tokens) selects and
+/// replaces tokens for masked language modeling.
+///
+/// The defaults are BERT's: 15% of the tokens are selected, of which 80% are
+/// replaced by `[MASK]`, 10% by a random token and 10% are left unchanged.
+/// The probabilities must be between `0.0` and `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaskingConfig {
+    /// The probability of selecting each (non-special) token
+    pub mlm_probability: f64,
+    /// The share of selected tokens replaced by `[MASK]`
+    pub mask_probability: f64,
+    /// The share of selected tokens replaced by a random token
+    pub random_probability: f64,
+    /// The label of tokens that were not selected, e.g. the `-100` that
+    /// PyTorch's cross entropy ignores (converted like
+    /// [`TokenID::coerce`], so it wraps around for unsigned ids)
+    pub ignore_label: i64,
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        MaskingConfig {
+            mlm_probability: 0.15,
+            mask_probability: 0.8,
+            random_probability: 0.1,
+            ignore_label: -100,
+        }
+    }
+}
+
+// the number of random ids to try before searching for a regular token
+const RANDOM_TRIES: usize = 64;
+
+impl AlephAlphaTokenizer {
+    // whether the id belongs to a token that can be produced and is not
+    // special, i.e. neither special nor `[unused…]`
+    fn is_regular(&self, id: u64) -> bool {
+        !self.is_special(id) && (self.is_starter(id) || self.is_follower(id))
+    }
+
+    // picks a random regular token, or `None` if there is none
+    fn random_token<R: Rng>(&self, rng: &mut R) -> Option<u64> {
+        let len = self.tokens.len() as u64;
+        let mut id = 0;
+        for _ in 0..RANDOM_TRIES {
+            id = rng.gen_range(0..len);
+            if self.is_regular(id) {
+                return Some(id);
+            }
+        }
+        // vocabularies of (almost) only special tokens would take forever
+        (0..len)
+            .map(|i| (id + i) % len)
+            .find(|&id| self.is_regular(id))
+    }
+
+    /// Masks the tokens for masked language modeling, with the `rand`
+    /// feature, returning the labels.
+    ///
+    /// Each token is selected with the `mlm_probability` of the `config`.
+    /// Selected tokens are replaced by `[MASK]` or a random token (neither
+    /// special nor `[unused…]`), or stay unchanged, and are labeled with
+    /// their original id. All other tokens are labeled with the
+    /// `ignore_label`. Special tokens like `[CLS]`, `[SEP]` and `[PAD]` are
+    /// never selected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vocabulary has no `[MASK]` token, or if a probability
+    /// of the `config` is not between `0.0` and `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "rand")] {
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, MaskingConfig};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<i64>::new(), Vec::new());
+    /// tokenizer.tokens_into(
+    ///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+    ///     &mut ids,
+    ///     &mut ranges,
+    ///     None,
+    /// );
+    /// ids.resize(16, 0);
+    /// let original = ids.clone();
+    ///
+    /// let config = MaskingConfig { mlm_probability: 0.5, ..MaskingConfig::default() };
+    /// let mut rng = StdRng::seed_from_u64(42);
+    /// let labels = tokenizer.mask_tokens(&mut ids, &mut rng, &config);
+    /// assert_eq!(
+    ///     &[3, 198, 10828, 106, 5, 5, 114, 5, 4106, 26914, 4, 0, 0, 0, 0, 0],
+    ///     &ids[..]
+    /// );
+    /// let ignored = -100;
+    /// assert_eq!(&[ignored; 4], &labels[..4]);
+    /// assert_eq!(&[16236, 3727, ignored, 128], &labels[4..8]);
+    /// assert_eq!(&[ignored; 8], &labels[8..]);
+    /// for ((&id, &label), &orig) in ids.iter().zip(&labels).zip(&original) {
+    ///     if label == -100 {
+    ///         assert_eq!(orig, id);
+    ///     } else {
+    ///         assert_eq!(orig, label);
+    ///         assert!(!tokenizer.is_special(orig));
+    ///     }
+    /// }
+    ///
+    /// // masking everything leaves the special tokens alone
+    /// let config = MaskingConfig {
+    ///     mlm_probability: 1.0,
+    ///     mask_probability: 1.0,
+    ///     random_probability: 0.0,
+    ///     ignore_label: -1,
+    /// };
+    /// let mut ids = original.clone();
+    /// let labels = tokenizer.mask_tokens(&mut ids, &mut rng, &config);
+    /// let mask = tokenizer.mask_id().unwrap() as i64;
+    /// for ((&id, &label), &orig) in ids.iter().zip(&labels).zip(&original) {
+    ///     if tokenizer.is_special(orig) {
+    ///         assert_eq!((orig, -1), (id, label));
+    ///     } else {
+    ///         assert_eq!((mask, orig), (id, label));
+    ///     }
+    /// }
+    ///
+    /// // random replacements are regular tokens
+    /// let config = MaskingConfig { mask_probability: 0.0, random_probability: 1.0, ..config };
+    /// let mut ids = original.clone();
+    /// tokenizer.mask_tokens(&mut ids, &mut rng, &config);
+    /// for (&id, &orig) in ids.iter().zip(&original) {
+    ///     assert!(tokenizer.is_special(orig) == (id == orig));
+    ///     assert!(tokenizer.is_starter(id) || tokenizer.is_follower(id));
+    ///     assert!(tokenizer.is_special(id) == tokenizer.is_special(orig));
+    /// }
+    ///# }
+    /// ```
+    pub fn mask_tokens<T: TokenID, R: Rng>(
+        &self,
+        token_ids: &mut [T],
+        rng: &mut R,
+        config: &MaskingConfig,
+    ) -> Vec<T> {
+        let mask = match self.mask_id {
+            Some(mask) => u64::from(mask),
+            None => panic!("the vocabulary has no [MASK] token"),
+        };
+        let ignore = T::coerce(config.ignore_label as u64);
+        let mut labels = vec![ignore; token_ids.len()];
+        for (id, label) in token_ids.iter_mut().zip(&mut labels) {
+            if !id
+                .clone()
+                .try_restore()
+                .is_some_and(|id| self.is_regular(id))
+                || !rng.gen_bool(config.mlm_probability)
+            {
+                continue;
+            }
+            *label = id.clone();
+            let replace = rng.gen::<f64>();
+            if replace < config.mask_probability {
+                *id = T::coerce(mask);
+            } else if replace < config.mask_probability + config.random_probability {
+                if let Some(random) = self.random_token(rng) {
+                    *id = T::coerce(random);
+                }
+            }
+        }
+        labels
+    }
+}
@@ -0,0 +1,386 @@
+//! The longest-prefix matching the tokenizer is built on, for tokenization
+//! strategies of your own.
+//!
+//! The tokenizer keeps two FSTs mapping token texts to their ids: the
+//! starters, which can begin a word, and the followers, which continue it
+//! (stored without their continuing subword prefix, e.g. `man` for
+//! `##man`). [`longest_prefix`] finds the longest key of an FST at the start
+//! of some input, and [`AlephAlphaTokenizer::match_starter`] and
+//! [`AlephAlphaTokenizer::match_follower`] do so with the tokenizer's FSTs.
+//! For input that comes a byte at a time, e.g. as it is typed, a
+//! [`TokenMatcher`] from [`AlephAlphaTokenizer::starter_matcher`] or
+//! [`AlephAlphaTokenizer::follower_matcher`] tells after each byte whether
+//! it is a token and whether it may still become one.
+//!
+//! # Examples
+//!
+//! ```
+//! use aleph_alpha_tokenizer::examples;
+//!
+//! // tokenize greedily, like the tokenizer does (without normalizing)
+//! let tokenizer = examples::tokenizer();
+//! let word = "Superman";
+//! let (len, id) = tokenizer.match_starter(word).unwrap();
+//! let mut ids = vec![id];
+//! let mut pos = len;
+//! while let Some((len, id)) = tokenizer.match_follower(&word[pos..]) {
+//!     ids.push(id);
+//!     pos += len;
+//! }
+//! assert_eq!(word.len(), pos);
+//! assert_eq!(&[16, 17], &ids[..]);
+//! ```
+
+use crate::{AlephAlphaTokenizer, FstData};
+use fst::raw::{Fst, Node, Output};
+
+// whether a match of `len` bytes ends on a char boundary of the input, so
+// the token range doesn't split a char even if an FST key does (which only
+// unchecked binary files can have); the input always ends with a word
+#[inline]
+pub(crate) fn ends_on_char(input: &[u8], len: usize) -> bool {
+    // the continuation bytes of UTF-8 are `0b10xx_xxxx`
+    input.get(len).is_none_or(|&b| (b as i8) >= -0x40)
+}
+
+// TODO: this should be upstreamed into fst
+//
+// For now, we'll keep it here.
+/// Finds the longest key of the FST that is a prefix of the input, as its
+/// length in bytes and its value (the token id in the tokenizer's FSTs).
+///
+/// Only prefixes that end on a char boundary of UTF-8 input match, so a
+/// token never ends within a char. As the keys of an FST are unique, there
+/// is at most one match of each length. The empty prefix never matches,
+/// even if the FST has an empty key; `None` means no key is a (non-empty)
+/// prefix.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::matcher::longest_prefix;
+/// use fst::raw::Fst;
+///
+/// let keys = vec![
+///     (b"".to_vec(), 0),
+///     (b"S".to_vec(), 1),
+///     (b"Sup".to_vec(), 2),
+///     (b"Super".to_vec(), 3),
+///     (b"\xc3".to_vec(), 4),
+/// ];
+/// let fst = Fst::from_iter_map(keys).unwrap();
+///
+/// // the longest of several matches
+/// assert_eq!(Some((5, 3)), longest_prefix(&fst, b"Superman"));
+/// assert_eq!(Some((3, 2)), longest_prefix(&fst, b"Supe"));
+/// assert_eq!(Some((1, 1)), longest_prefix(&fst, b"Sonne"));
+/// // the whole input
+/// assert_eq!(Some((5, 3)), longest_prefix(&fst, b"Super"));
+/// // no match, not even the empty key
+/// assert_eq!(None, longest_prefix(&fst, b"Batman"));
+/// assert_eq!(None, longest_prefix(&fst, b""));
+/// // `\xc3` is only the first byte of `é`
+/// assert_eq!(None, longest_prefix(&fst, "é".as_bytes()));
+/// assert_eq!(Some((1, 4)), longest_prefix(&fst, b"\xc3"));
+/// ```
+#[inline]
+pub fn longest_prefix<D: AsRef<[u8]>>(fst: &Fst<D>, input: &[u8]) -> Option<(usize, u64)> {
+    let mut node = fst.root();
+    let mut out = Output::zero();
+    let mut last_match: Option<(usize, Output)> = None;
+    for (i, &b) in input.iter().enumerate() {
+        if let Some(trans_index) = node.find_input(b) {
+            let t = node.transition(trans_index);
+            node = fst.node(t.addr);
+            out = out.cat(t.out);
+            if node.is_final() && ends_on_char(input, i + 1) {
+                last_match = Some((i + 1, out.cat(node.final_output())));
+            }
+        } else {
+            break;
+        }
+    }
+    last_match.map(|(i, o)| (i, o.value()))
+}
+
+// what words are matched against: the FSTs, or the double-array tries built
+// from them with the `datrie` feature
+pub(crate) trait PrefixMatcher {
+    // the longest key that is a prefix of the input, see `longest_prefix`
+    fn longest_prefix(&self, input: &[u8]) -> Option<(usize, u64)>;
+
+    // all keys that are prefixes of the input, shortest first
+    fn find_prefixes(&self, input: &[u8], matches: &mut Vec<(usize, u64)>);
+}
+
+impl<D: AsRef<[u8]>> PrefixMatcher for Fst<D> {
+    #[inline]
+    fn longest_prefix(&self, input: &[u8]) -> Option<(usize, u64)> {
+        longest_prefix(self, input)
+    }
+
+    fn find_prefixes(&self, input: &[u8], matches: &mut Vec<(usize, u64)>) {
+        crate::find_prefixes(self, input, matches)
+    }
+}
+
+// the starters and followers of a tokenizer in one of the backends
+pub(crate) struct Matchers<'m, M> {
+    pub(crate) starters: &'m M,
+    pub(crate) followers: &'m M,
+}
+
+impl<M> Clone for Matchers<'_, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for Matchers<'_, M> {}
+
+/// How the input fed to a [`TokenMatcher`] relates to the tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchState {
+    /// The input is no token, but more input may make it one
+    Partial,
+    /// The input is the token with the given id, which may be the start of
+    /// longer tokens if it is `extendable`
+    Complete {
+        /// the id of the token
+        id: u64,
+        /// whether more input may make a longer token
+        extendable: bool,
+    },
+    /// No token starts with the input
+    Dead,
+}
+
+/// Matches the tokens of one of the tokenizer's FSTs against input that
+/// comes a byte at a time, see [`AlephAlphaTokenizer::starter_matcher`].
+///
+/// The matcher borrows the FST and keeps only its position in it, so it
+/// is cheap to clone, e.g. to look ahead without losing the current state.
+/// Like [`longest_prefix`], it only matches input that ends on a char
+/// boundary, and once [`Dead`](MatchState::Dead), it stays so until it is
+/// [`reset`](TokenMatcher::reset).
+#[derive(Clone)]
+pub struct TokenMatcher<'t> {
+    fst: &'t Fst<FstData>,
+    // `None` once no key starts with the input
+    node: Option<Node<'t>>,
+    out: Output,
+    len: usize,
+    // the continuation bytes the last char of the input lacks
+    missing: u8,
+    last_match: Option<(usize, u64)>,
+}
+
+impl<'t> TokenMatcher<'t> {
+    fn new(fst: &'t Fst<FstData>) -> Self {
+        TokenMatcher {
+            fst,
+            node: Some(fst.root()),
+            out: Output::zero(),
+            len: 0,
+            missing: 0,
+            last_match: None,
+        }
+    }
+
+    /// Feeds the next byte of the input and tells how the input relates to
+    /// the tokens now.
+    pub fn feed(&mut self, byte: u8) -> MatchState {
+        self.len += 1;
+        self.missing = match byte {
+            0x80..=0xbf => self.missing.saturating_sub(1),
+            0xc0..=0xdf => 1,
+            0xe0..=0xef => 2,
+            0xf0..=0xff => 3,
+            _ => 0,
+        };
+        self.node = self.node.and_then(|node| {
+            let t = node.transition(node.find_input(byte)?);
+            Some(self.fst.node(t.addr)).inspect(|_| self.out = self.out.cat(t.out))
+        });
+        if let Some(node) = self.node {
+            if node.is_final() && self.missing == 0 {
+                self.last_match = Some((self.len, self.out.cat(node.final_output()).value()));
+            }
+        }
+        self.state()
+    }
+
+    /// Feeds the bytes of the next char of the input, see
+    /// [`feed`](TokenMatcher::feed).
+    pub fn feed_char(&mut self, c: char) -> MatchState {
+        let mut buf = [0; 4];
+        for &b in c.encode_utf8(&mut buf).as_bytes() {
+            self.feed(b);
+        }
+        self.state()
+    }
+
+    /// Tells how the input fed so far relates to the tokens.
+    pub fn state(&self) -> MatchState {
+        let extendable = self.node.is_some_and(|node| !node.is_empty());
+        match self.last_match {
+            Some((len, id)) if len == self.len && self.node.is_some() => {
+                MatchState::Complete { id, extendable }
+            }
+            _ if extendable => MatchState::Partial,
+            _ => MatchState::Dead,
+        }
+    }
+
+    /// The longest token the input fed so far starts with, as its length in
+    /// bytes and its id, like [`longest_prefix`] of the input.
+    pub fn longest_match(&self) -> Option<(usize, u64)> {
+        self.last_match
+    }
+
+    /// The number of bytes fed since the matcher was made or reset.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no byte was fed since the matcher was made or reset.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forgets the input, to match the next one from its start.
+    pub fn reset(&mut self) {
+        *self = TokenMatcher::new(self.fst);
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Finds the longest token that can start a word at the start of the
+    /// text, as its length in bytes and its id, see [`longest_prefix`].
+    ///
+    /// This matches the text as it is, without the normalization the options
+    /// may ask for. The special tokens are among the starters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// assert_eq!(Some((5, 4285)), tokenizer.match_starter("Superman"));
+    /// assert_eq!(Some((5, 3)), tokenizer.match_starter("[CLS]"));
+    /// assert_eq!(None, tokenizer.match_starter("☃"));
+    /// assert_eq!(None, tokenizer.match_starter(""));
+    /// ```
+    pub fn match_starter(&self, text: &str) -> Option<(usize, u64)> {
+        longest_prefix(&self.starters, text.as_bytes())
+    }
+
+    /// Finds the longest token that can continue a word at the start of the
+    /// text, as its length in bytes and its id, see [`longest_prefix`].
+    ///
+    /// The text is matched without the continuing subword prefix, i.e. to
+    /// find `##man`, match `man`, and without normalizing it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let (len, id) = tokenizer.match_follower("ak.").unwrap();
+    /// assert_eq!(("##ak", 2), (tokenizer.text_of(id), len));
+    /// assert_eq!(Some(id), tokenizer.id_of("##ak"));
+    /// assert_eq!(None, tokenizer.match_follower(""));
+    /// ```
+    pub fn match_follower(&self, text: &str) -> Option<(usize, u64)> {
+        longest_prefix(&self.followers, text.as_bytes())
+    }
+
+    /// Makes a [`TokenMatcher`] for the tokens that can start a word, which
+    /// borrows the tokenizer's starters.
+    ///
+    /// Feeding it the bytes of a text one by one gives the same matches as
+    /// [`match_starter`](AlephAlphaTokenizer::match_starter), without
+    /// matching the text again after each byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::matcher::MatchState;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let mut matcher = tokenizer.starter_matcher();
+    /// assert_eq!(MatchState::Partial, matcher.state());
+    /// for &b in b"Supe" {
+    ///     matcher.feed(b);
+    /// }
+    /// assert_eq!(Some((3, tokenizer.id_of("Sup").unwrap())), matcher.longest_match());
+    /// // look ahead without losing the state
+    /// let mut lookahead = matcher.clone();
+    /// let id = tokenizer.id_of("Super").unwrap();
+    /// assert_eq!(MatchState::Complete { id, extendable: true }, lookahead.feed(b'r'));
+    /// assert_eq!(MatchState::Dead, lookahead.feed_char('☃'));
+    /// assert_eq!(Some((5, id)), lookahead.longest_match());
+    /// assert_eq!(4, matcher.len());
+    ///
+    /// // a token never ends within a char
+    /// matcher.reset();
+    /// assert!(matcher.is_empty());
+    /// assert_eq!(MatchState::Partial, matcher.feed("ü".as_bytes()[0]));
+    /// assert!(matches!(matcher.feed("ü".as_bytes()[1]), MatchState::Complete { .. }));
+    ///
+    /// // segment words byte by byte, like the tokenizer does
+    /// fn segment(tokenizer: &AlephAlphaTokenizer, word: &str) -> Option<Vec<u64>> {
+    ///     let (mut ids, mut pos) = (Vec::new(), 0);
+    ///     let mut matcher = tokenizer.starter_matcher();
+    ///     while pos < word.len() {
+    ///         for &b in &word.as_bytes()[pos..] {
+    ///             if matcher.feed(b) == MatchState::Dead {
+    ///                 break;
+    ///             }
+    ///         }
+    ///         let (len, id) = matcher.longest_match()?;
+    ///         ids.push(id);
+    ///         pos += len;
+    ///         matcher = tokenizer.follower_matcher();
+    ///     }
+    ///     Some(ids)
+    /// }
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// for word in &["Superman", "Clankriminalität", "Verfügung", "Borreliose", "Steak"] {
+    ///     tokenizer.tokens_into(word, &mut ids, &mut ranges, None);
+    ///     assert_eq!(Some(&ids[1..ids.len() - 1]), segment(&tokenizer, word).as_deref());
+    /// }
+    /// assert_eq!(None, segment(&tokenizer, "Superman☃"));
+    /// ```
+    pub fn starter_matcher(&self) -> TokenMatcher<'_> {
+        TokenMatcher::new(&self.starters)
+    }
+
+    /// Makes a [`TokenMatcher`] for the tokens that can continue a word,
+    /// which borrows the tokenizer's followers, see
+    /// [`starter_matcher`](AlephAlphaTokenizer::starter_matcher).
+    ///
+    /// Like [`match_follower`](AlephAlphaTokenizer::match_follower), it
+    /// matches the input without the continuing subword prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::matcher::MatchState;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let mut matcher = tokenizer.follower_matcher();
+    /// matcher.feed(b'a');
+    /// let id = tokenizer.id_of("##ak").unwrap();
+    /// assert!(matches!(matcher.feed(b'k'), MatchState::Complete { id: found, .. } if found == id));
+    /// assert_eq!(tokenizer.match_follower("ak"), matcher.longest_match());
+    /// ```
+    pub fn follower_matcher(&self) -> TokenMatcher<'_> {
+        TokenMatcher::new(&self.followers)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{AlephAlphaTokenizer, AlephAlphaTokenizerBuilder, FstData, TokenizerError};
+use fst::raw::Fst;
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+// map an FST file into memory
+//
+// Safety: the file must not be changed while it is mapped
+pub(crate) unsafe fn map_fst(path: &str) -> Result<Fst<FstData>, TokenizerError> {
+    let map = Mmap::map(&File::open(path)?)?;
+    Ok(Fst::new(FstData::Mapped(map))?)
+}
+
+impl AlephAlphaTokenizer {
+    /// Saves the starter and follower FSTs and the tokens to separate files,
+    /// to be loaded with [`from_fst_files`](AlephAlphaTokenizer::from_fst_files).
+    pub fn save_fst_files(
+        &self,
+        starters_path: &str,
+        followers_path: &str,
+        tokens_path: &str,
+    ) -> Result<(), TokenizerError> {
+        fs::write(starters_path, self.starters.as_bytes())?;
+        fs::write(followers_path, self.followers.as_bytes())?;
+        self.save_vocab(PathBuf::from(tokens_path))?;
+        Ok(())
+    }
+
+    /// Loads a tokenizer from files written by
+    /// [`save_fst_files`](AlephAlphaTokenizer::save_fst_files), mapping the
+    /// FSTs into memory, so processes loading the same files share them.
+    ///
+    /// This uses the default special tokens; use
+    /// [`AlephAlphaTokenizerBuilder::fst_files`] to configure them. The FSTs
+    /// are checked against the tokens (see [`verify_internal_consistency`]).
+    ///
+    /// This is only available with the `mmap` feature.
+    ///
+    /// # Safety
+    ///
+    /// The FST files must not be changed while the tokenizer is alive,
+    /// otherwise the behavior is undefined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::error::Error;
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+    /// let (starters, followers, tokens) = (
+    ///     path("aat-starters.fst"),
+    ///     path("aat-followers.fst"),
+    ///     path("aat-tokens.txt"),
+    /// );
+    /// let in_memory = AlephAlphaTokenizer::from_vocab("vocab.txt")?;
+    /// in_memory.save_fst_files(&starters, &followers, &tokens)?;
+    /// let mapped = unsafe { AlephAlphaTokenizer::from_fst_files(&starters, &followers, &tokens)? };
+    ///
+    /// let text = "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?";
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let (mut mapped_ids, mut mapped_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// in_memory.tokens_into(text, &mut ids, &mut ranges, None);
+    /// mapped.tokens_into(text, &mut mapped_ids, &mut mapped_ranges, None);
+    /// assert_eq!(ids, mapped_ids);
+    /// assert_eq!(ranges, mapped_ranges);
+    /// assert_eq!(in_memory.texts_of(&ids), mapped.texts_of(&mapped_ids));
+    /// assert_eq!(in_memory.id_of("##se"), mapped.id_of("##se"));
+    ///# #[cfg(feature = "huggingface")] {
+    /// use tokenizers::tokenizer::Model;
+    ///
+    /// assert_eq!(in_memory.tokenize("Impfstoff")?, mapped.tokenize("Impfstoff")?);
+    ///# }
+    ///
+    /// // the files must belong together
+    /// let other = path("aat-other-tokens.txt");
+    /// std::fs::write(&other, "[UNK]\nfoo\n")?;
+    /// assert!(unsafe { AlephAlphaTokenizer::from_fst_files(&starters, &followers, &other) }.is_err());
+    ///
+    /// // the FSTs are used as they are, so no tokens can be suppressed
+    /// let error = unsafe { AlephAlphaTokenizer::builder().fst_files(&starters, &followers) }
+    ///     .vocab_path(&tokens)
+    ///     .suppressed_ids(&[5383])
+    ///     .build()
+    ///     .err()
+    ///     .unwrap();
+    /// assert!(matches!(error, aleph_alpha_tokenizer::TokenizerError::SuppressedWithFstFiles));
+    ///# Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    ///
+    /// [`verify_internal_consistency`]: AlephAlphaTokenizer::verify_internal_consistency
+    pub unsafe fn from_fst_files(
+        starters_path: &str,
+        followers_path: &str,
+        tokens_path: &str,
+    ) -> Result<Self, TokenizerError> {
+        Self::builder()
+            .vocab_path(tokens_path)
+            .fst_files(starters_path, followers_path)
+            .build()
+    }
+}
+
+impl AlephAlphaTokenizerBuilder {
+    /// Maps the starter and follower FSTs from the given files instead of
+    /// building them from the vocabulary, see
+    /// [`AlephAlphaTokenizer::from_fst_files`].
+    ///
+    /// This is only available with the `mmap` feature. As the FSTs are used
+    /// as they are, building fails if
+    /// [`suppressed_ids`](AlephAlphaTokenizerBuilder::suppressed_ids) are
+    /// set, too.
+    ///
+    /// # Safety
+    ///
+    /// The FST files must not be changed while the tokenizer is alive,
+    /// otherwise the behavior is undefined.
+    pub unsafe fn fst_files(mut self, starters_path: &str, followers_path: &str) -> Self {
+        self.fst_files = Some((starters_path.to_string(), followers_path.to_string()));
+        self
+    }
+}
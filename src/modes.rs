@@ -0,0 +1,303 @@
+use crate::{is_ascii_whitespace, AlephAlphaTokenizer};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// The ways of splitting a word into tokens, as set by
+/// [`segmentation_mode`](crate::AlephAlphaTokenizerBuilder::segmentation_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SegmentationMode {
+    /// Take the longest matching starter, then the longest matching
+    /// followers, like WordPiece does
+    #[default]
+    Greedy,
+    /// Like `Greedy`, but if that leaves a part of the word unmatched, try
+    /// the shorter matches, too; the word only becomes `[UNK]` if the
+    /// vocabulary cannot cover it at all, and the fewest tokens win
+    Backtracking,
+}
+
+/// How a vocabulary marks the tokens within a word, as set by
+/// [`marker_scheme`](crate::AlephAlphaTokenizerBuilder::marker_scheme).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MarkerScheme {
+    /// The tokens that continue a word start with this prefix (e.g. `##man`
+    /// after `Super`), as in WordPiece; an empty prefix lets every token
+    /// start or continue a word
+    ContinuationPrefix(String),
+    /// The tokens that end a word end with this suffix (e.g. `low` and
+    /// `er</w>`), as in some BPE vocabularies; the other tokens can start or
+    /// continue a word, but not end it
+    EndOfWordSuffix(String),
+}
+
+impl Default for MarkerScheme {
+    fn default() -> Self {
+        MarkerScheme::ContinuationPrefix("##".to_string())
+    }
+}
+
+impl MarkerScheme {
+    // the continuing subword prefix and the end-of-word suffix, one of
+    // which is empty
+    pub(crate) fn markers(&self) -> (&str, &str) {
+        match self {
+            MarkerScheme::ContinuationPrefix(prefix) => (prefix, ""),
+            MarkerScheme::EndOfWordSuffix(suffix) => ("", suffix),
+        }
+    }
+}
+
+/// What words are matched against, as set by
+/// [`matcher_backend`](crate::AlephAlphaTokenizerBuilder::matcher_backend).
+/// All backends give the same tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MatcherBackend {
+    /// The FSTs of the starters and followers, which are compact
+    #[default]
+    Fst,
+    /// Double-array tries built from the FSTs, which are faster to match
+    /// against, but take several times their memory; this needs the `datrie`
+    /// feature
+    #[cfg(feature = "datrie")]
+    DoubleArray,
+}
+
+/// What becomes of the words that the vocabulary cannot cover, as set by
+/// [`unk_mode`](crate::AlephAlphaTokenizerBuilder::unk_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum UnkMode {
+    /// The whole word becomes a single `[UNK]`, like in WordPiece
+    #[default]
+    WholeWord,
+    /// The word keeps the tokens that match, and each character where no
+    /// token matches becomes an `[UNK]` of its own
+    PerChar,
+}
+
+/// What becomes of the words that start with the continuing subword prefix
+/// (e.g. `##` in `## Heading` or `##define`), as set by
+/// [`literal_hash_hash`](crate::AlephAlphaTokenizerBuilder::literal_hash_hash).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum LiteralMode {
+    /// The prefix is text like any other, matched by the starters (or by a
+    /// follower with
+    /// [`leading_prefix_as_follower`](crate::AlephAlphaTokenizerBuilder::leading_prefix_as_follower),
+    /// as in WordPiece)
+    #[default]
+    Text,
+    /// The whole word is unknown, as if the vocabulary didn't cover it
+    Unk,
+}
+
+/// What becomes of a separator of
+/// [`fallback_separators`](crate::AlephAlphaTokenizerBuilder::fallback_separators)
+/// that the vocabulary has no token for, as set by
+/// [`unknown_separator`](crate::AlephAlphaTokenizerBuilder::unknown_separator).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum UnknownSeparator {
+    /// The separator becomes an `[UNK]` of its own
+    #[default]
+    Unk,
+    /// The separator is in no token, like whitespace
+    Drop,
+}
+
+/// The Unicode normalization forms words can be brought into before matching
+/// them, as set by [`normalize`](crate::AlephAlphaTokenizerBuilder::normalize).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum UnicodeNormalization {
+    /// Canonical composition, so `e` followed by a combining acute accent
+    /// becomes `é`
+    Nfc,
+    /// Compatibility decomposition followed by canonical composition, so
+    /// `ﬁ` also becomes `fi` and `²` becomes `2`
+    Nfkc,
+}
+
+/// The characters that separate words, as set by
+/// [`whitespace`](crate::AlephAlphaTokenizerBuilder::whitespace).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum WhitespaceMode {
+    /// The whitespace of `char::is_whitespace`, including the no-break and
+    /// ideographic spaces
+    #[default]
+    Unicode,
+    /// Only the ASCII whitespace: space, tab, line feed, vertical tab, form
+    /// feed and carriage return
+    AsciiOnly,
+    /// Exactly the given characters
+    Custom(Vec<char>),
+}
+
+impl WhitespaceMode {
+    /// Determines whether the character separates words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::WhitespaceMode;
+    ///
+    /// assert!(WhitespaceMode::Unicode.is_whitespace('\u{a0}'));
+    /// assert!(!WhitespaceMode::AsciiOnly.is_whitespace('\u{a0}'));
+    /// assert!(WhitespaceMode::AsciiOnly.is_whitespace('\x0b'));
+    /// let custom = WhitespaceMode::Custom(vec![' ', '\u{200b}']);
+    /// assert!(custom.is_whitespace('\u{200b}') && !custom.is_whitespace('\n'));
+    /// ```
+    pub fn is_whitespace(&self, c: char) -> bool {
+        match self {
+            WhitespaceMode::Unicode => c.is_whitespace(),
+            WhitespaceMode::AsciiOnly => c.is_ascii() && is_ascii_whitespace(c as u8),
+            WhitespaceMode::Custom(chars) => chars.contains(&c),
+        }
+    }
+}
+
+/// The results of one [`SegmentationMode`] on a corpus, as returned by
+/// [`bench_modes`](AlephAlphaTokenizer::bench_modes).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModeReport {
+    /// The mode
+    pub mode: SegmentationMode,
+    /// The number of words in the corpus
+    pub words: usize,
+    /// The number of tokens, without `[CLS]` and `[SEP]`
+    pub tokens: usize,
+    /// The number of `[UNK]` tokens
+    pub unknown: usize,
+    /// The time spent tokenizing
+    pub elapsed: Duration,
+    /// For each of the benchmarked modes, the number of words this mode
+    /// tokenized differently
+    pub disagreements: Vec<usize>,
+}
+
+impl ModeReport {
+    /// Gets the number of tokens per second.
+    pub fn tokens_per_sec(&self) -> f64 {
+        self.tokens as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Gets the average number of tokens per word.
+    pub fn tokens_per_word(&self) -> f64 {
+        self.tokens as f64 / self.words as f64
+    }
+
+    /// Gets the fraction of tokens that are `[UNK]`.
+    pub fn unknown_rate(&self) -> f64 {
+        self.unknown as f64 / self.tokens as f64
+    }
+
+    /// Gets the fraction of words that the `index`th benchmarked mode
+    /// tokenized differently.
+    pub fn disagreement_rate(&self, index: usize) -> f64 {
+        self.disagreements[index] as f64 / self.words as f64
+    }
+}
+
+// the tokens of a text in one mode, with the token index after each word
+#[derive(Default)]
+struct ModeOutput {
+    ids: Vec<u64>,
+    ranges: Vec<Range<usize>>,
+    word_ends: Vec<usize>,
+}
+
+impl ModeOutput {
+    fn word(&self, index: usize) -> (&[u64], &[Range<usize>]) {
+        let start = if index == 0 {
+            0
+        } else {
+            self.word_ends[index - 1]
+        };
+        let end = self.word_ends[index];
+        (&self.ids[start..end], &self.ranges[start..end])
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes each text of the corpus in each of the given modes and
+    /// compares their speed and results.
+    ///
+    /// The reports are in the order of the `modes`, and so are the
+    /// [`disagreements`](ModeReport::disagreements) within each report.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let corpus = vec!["Ich esse Steak".to_string(), "☃ Hund".to_string()];
+    /// let modes = [SegmentationMode::Greedy, SegmentationMode::Backtracking];
+    /// let reports = tokenizer.bench_modes(corpus, &modes);
+    /// assert_eq!(2, reports.len());
+    /// for report in &reports {
+    ///     assert_eq!(5, report.words);
+    ///     assert_eq!(7, report.tokens);
+    ///     assert_eq!(1, report.unknown);
+    ///     assert_eq!(vec![0, 0], report.disagreements);
+    ///     assert_eq!(1.4, report.tokens_per_word());
+    ///     assert_eq!(1.0 / 7.0, report.unknown_rate());
+    /// }
+    /// ```
+    pub fn bench_modes(
+        &self,
+        corpus: impl IntoIterator<Item = String>,
+        modes: &[SegmentationMode],
+    ) -> Vec<ModeReport> {
+        let mut reports: Vec<ModeReport> = modes
+            .iter()
+            .map(|&mode| ModeReport {
+                mode,
+                words: 0,
+                tokens: 0,
+                unknown: 0,
+                elapsed: Duration::default(),
+                disagreements: vec![0; modes.len()],
+            })
+            .collect();
+        let mut outputs: Vec<ModeOutput> = modes.iter().map(|_| ModeOutput::default()).collect();
+        let unk = u64::from(self.unk_id);
+        for text in corpus {
+            for ((&mode, output), report) in modes.iter().zip(&mut outputs).zip(&mut reports) {
+                output.ids.clear();
+                output.ranges.clear();
+                output.word_ends.clear();
+                let start = Instant::now();
+                for span in self.spans(&text) {
+                    self.tokenize_span_as(mode, &text, span, &mut output.ids, &mut output.ranges);
+                    output.word_ends.push(output.ids.len());
+                }
+                report.elapsed += start.elapsed();
+                report.words += output.word_ends.len();
+                report.tokens += output.ids.len();
+                report.unknown += output.ids.iter().filter(|&&id| id == unk).count();
+            }
+            let words = outputs.first().map_or(0, |o| o.word_ends.len());
+            for word in 0..words {
+                for (i, report) in reports.iter_mut().enumerate() {
+                    for (j, other) in outputs.iter().enumerate() {
+                        if outputs[i].word(word) != other.word(word) {
+                            report.disagreements[j] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        reports
+    }
+}
@@ -0,0 +1,201 @@
+//! Matching words in a changed form while keeping offsets into the original
+//! text
+
+use crate::clean::{self, CleanAction};
+#[cfg(feature = "unicode-normalization")]
+use crate::UnicodeNormalization;
+use crate::WhitespaceMode;
+use std::ops::Range;
+
+pub(crate) const SOFT_HYPHEN: char = '\u{AD}';
+
+/// A word as it is matched against the vocabulary, together with the
+/// original byte range of the character each of its bytes came from.
+#[derive(Default)]
+pub(crate) struct NormalizedWord {
+    pub(crate) text: String,
+    sources: Vec<(usize, usize)>,
+}
+
+impl NormalizedWord {
+    /// Appends a character that stems from the given original range.
+    pub(crate) fn push(&mut self, c: char, source: Range<usize>) {
+        self.text.push(c);
+        self.sources
+            .resize(self.text.len(), (source.start, source.end));
+    }
+
+    /// Gets the original range of the (non-empty) range of characters.
+    #[cfg(feature = "unicode-normalization")]
+    fn source(&self, range: Range<usize>) -> Range<usize> {
+        self.sources[range.start].0..self.sources[range.end - 1].1
+    }
+
+    /// Maps a range within the normalized text back to the original text.
+    ///
+    /// The range ends where the next remaining character starts, so bytes
+    /// that were left out belong to the preceding range. The first and last
+    /// ranges are extended to the bounds of the original word.
+    pub(crate) fn original(&self, range: Range<usize>, word: &Range<usize>) -> Range<usize> {
+        let start = if range.start == 0 {
+            word.start
+        } else {
+            self.sources[range.start].0
+        };
+        let end = if range.end == self.text.len() {
+            word.end
+        } else {
+            self.sources[range.end].0.max(self.sources[range.end - 1].1)
+        };
+        start..end
+    }
+}
+
+// is the (non-empty) word hyphenated at its end so it continues after the
+// whitespace?
+fn continues_after(text: &str, word: Range<usize>, whitespace: &WhitespaceMode) -> bool {
+    let mut chars = text[word.clone()].chars().rev();
+    if !matches!(chars.next(), Some('-') | Some(SOFT_HYPHEN))
+        || !chars.next().is_some_and(char::is_alphabetic)
+    {
+        return false;
+    }
+    let rest = &text[word.end..];
+    let ws_len = rest
+        .find(|c: char| !whitespace.is_whitespace(c))
+        .unwrap_or(rest.len());
+    rest[..ws_len].matches('\n').count() == 1
+        && rest[ws_len..]
+            .chars()
+            .next()
+            .is_some_and(char::is_alphabetic)
+}
+
+/// Extends the word starting at `start` and ending (before whitespace) at
+/// `end` across all hyphenated line breaks, returning the new end.
+pub(crate) fn rejoined_word_end(
+    text: &str,
+    start: usize,
+    mut end: usize,
+    whitespace: &WhitespaceMode,
+) -> usize {
+    while continues_after(text, start..end, whitespace) {
+        let next = end
+            + text[end..]
+                .find(|c: char| !whitespace.is_whitespace(c))
+                .unwrap();
+        end = text[next..]
+            .find(|c: char| whitespace.is_whitespace(c))
+            .map_or(text.len(), |ws| next + ws);
+    }
+    end
+}
+
+/// Does the word need to be rejoined before matching?
+pub(crate) fn is_hyphenated(word: &str, whitespace: &WhitespaceMode) -> bool {
+    word.contains(|c: char| c == SOFT_HYPHEN || whitespace.is_whitespace(c))
+}
+
+/// Does lowercasing change the word?
+pub(crate) fn has_uppercase(word: &str) -> bool {
+    if word.is_ascii() {
+        word.bytes().any(|b| b.is_ascii_uppercase())
+    } else {
+        word.chars().any(|c| c.to_lowercase().ne(Some(c)))
+    }
+}
+
+/// Copies the word into `normalized`, optionally removing soft hyphens and
+/// hyphenated line breaks, lowercasing it and removing the characters that
+/// cleaning removes.
+///
+/// A hyphen at a line break is kept if the next line starts with an
+/// uppercase letter, because then it is likely part of a compound (e.g.
+/// "FSME-Impfstoff").
+pub(crate) fn normalize(
+    text: &str,
+    word: Range<usize>,
+    dehyphenate: Option<&WhitespaceMode>,
+    lowercase: bool,
+    clean: Option<&[(char, CleanAction)]>,
+    normalized: &mut NormalizedWord,
+) {
+    let span = &text[word.clone()];
+    for (i, c) in span.char_indices() {
+        let end = i + c.len_utf8();
+        if clean.is_some_and(|chars| clean::action(chars, c) == CleanAction::Remove) {
+            continue;
+        }
+        if let Some(whitespace) = dehyphenate {
+            let is_whitespace = |c: char| whitespace.is_whitespace(c);
+            if c == SOFT_HYPHEN || is_whitespace(c) {
+                continue;
+            }
+            if c == '-' && span[end..].starts_with(is_whitespace) {
+                let next = span[end..].trim_start_matches(is_whitespace).chars().next();
+                if next.is_some_and(char::is_lowercase) {
+                    continue;
+                }
+            }
+        }
+        let source = word.start + i..word.start + end;
+        if lowercase {
+            // a few characters become more than one when lowercased
+            for l in c.to_lowercase() {
+                normalized.push(l, source.clone());
+            }
+        } else {
+            normalized.push(c, source);
+        }
+    }
+}
+
+// the diaeresis makes umlauts, which the vocabularies keep
+#[cfg(feature = "unicode-normalization")]
+fn is_accent(c: char) -> bool {
+    use unicode_categories::UnicodeCategories;
+    c != '\u{308}' && c.is_mark_nonspacing()
+}
+
+/// Copies the word into `normalized` in the given normalization form (or
+/// NFC, if only stripping accents), optionally removing the accents.
+///
+/// Each character is normalized together with the combining marks after it,
+/// so all the characters that come out stem from their original range.
+#[cfg(feature = "unicode-normalization")]
+pub(crate) fn normalize_unicode(
+    word: &NormalizedWord,
+    form: Option<UnicodeNormalization>,
+    strip_accents: bool,
+    normalized: &mut NormalizedWord,
+) {
+    use unicode_normalization::char::canonical_combining_class;
+    use unicode_normalization::UnicodeNormalization as _;
+
+    let mut chars = word.text.char_indices().peekable();
+    let mut segment = String::new();
+    while let Some((start, c)) = chars.next() {
+        segment.clear();
+        segment.push(c);
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, mark)) = chars.peek() {
+            if canonical_combining_class(mark) == 0 {
+                break;
+            }
+            segment.push(mark);
+            end = i + mark.len_utf8();
+            chars.next();
+        }
+        let source = word.source(start..end);
+        let decomposed = match form {
+            Some(UnicodeNormalization::Nfkc) => segment.nfkd(),
+            Some(UnicodeNormalization::Nfc) | None => segment.nfd(),
+        };
+        for c in decomposed
+            .filter(|&c| !(strip_accents && is_accent(c)))
+            .nfc()
+        {
+            normalized.push(c, source.clone());
+        }
+    }
+}
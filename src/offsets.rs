@@ -0,0 +1,115 @@
+use std::ops::Range;
+
+/// The unit of the offsets [`OffsetMap::convert`] converts byte offsets to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffsetKind {
+    /// Chars, i.e. Unicode scalar values, as Python strings count them
+    Chars,
+    /// UTF-16 code units, as JavaScript strings count them
+    Utf16,
+}
+
+/// Converts the byte ranges of a text's tokens into char or UTF-16 ranges.
+///
+/// Building the map takes one pass over the text, after which each
+/// conversion takes constant time, no matter the order of the ranges. A
+/// byte offset within a char (as byte fallback tokens may have) counts that
+/// char as started, like
+/// [`tokens_into_chars`](crate::AlephAlphaTokenizer::tokens_into_chars)
+/// does.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, OffsetKind, OffsetMap};
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// // umlauts, an emoji outside the BMP and a combining accent
+/// let text = "Grüße 🦀 aus Cafe\u{301} Köln";
+/// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+/// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+///
+/// let map = OffsetMap::new(text);
+/// let utf16: Vec<u16> = text.encode_utf16().collect();
+/// let chars: Vec<char> = text.chars().collect();
+/// for range in &ranges {
+///     let units = map.to_utf16(range.clone());
+///     assert_eq!(&text[range.clone()], String::from_utf16(&utf16[units]).unwrap());
+///     let char_range = map.to_char(range.clone());
+///     assert_eq!(&text[range.clone()], chars[char_range].iter().collect::<String>());
+/// }
+/// assert_eq!(map.to_utf16(text.find('🦀').unwrap()..text.find(" aus").unwrap()), 6..8);
+/// assert_eq!(map.to_char(text.find('🦀').unwrap()..text.find(" aus").unwrap()), 6..7);
+///
+/// // the [CLS] and [SEP] tokens stay empty, at the start and the end
+/// let converted = map.convert(&ranges, OffsetKind::Utf16);
+/// assert_eq!(0..0, converted[0]);
+/// assert_eq!(utf16.len()..utf16.len(), converted[converted.len() - 1]);
+///
+/// let mut char_ranges = Vec::new();
+/// tokenizer.tokens_into_chars(text, &mut ids, &mut char_ranges, None);
+/// assert_eq!(char_ranges, map.convert(&ranges, OffsetKind::Chars));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffsetMap {
+    // the char and UTF-16 offset of each byte offset, including the end
+    chars: Vec<usize>,
+    utf16: Vec<usize>,
+}
+
+impl OffsetMap {
+    /// Builds the map of the text.
+    pub fn new(text: &str) -> Self {
+        let bytes = text.as_bytes();
+        let mut chars = Vec::with_capacity(bytes.len() + 1);
+        let mut utf16 = Vec::with_capacity(bytes.len() + 1);
+        let (mut char_pos, mut utf16_pos) = (0, 0);
+        chars.push(0);
+        utf16.push(0);
+        for &byte in bytes {
+            // count each char at its first byte; chars of four bytes are
+            // surrogate pairs in UTF-16
+            if byte & 0xC0 != 0x80 {
+                char_pos += 1;
+                utf16_pos += if byte >= 0xF0 { 2 } else { 1 };
+            }
+            chars.push(char_pos);
+            utf16.push(utf16_pos);
+        }
+        OffsetMap { chars, utf16 }
+    }
+
+    /// Converts a byte range into a char range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range ends beyond the text.
+    pub fn to_char(&self, range: Range<usize>) -> Range<usize> {
+        self.chars[range.start]..self.chars[range.end]
+    }
+
+    /// Converts a byte range into a range of UTF-16 code units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range ends beyond the text.
+    pub fn to_utf16(&self, range: Range<usize>) -> Range<usize> {
+        self.utf16[range.start]..self.utf16[range.end]
+    }
+
+    /// Converts all byte ranges into the given kind of ranges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range ends beyond the text.
+    pub fn convert(&self, ranges: &[Range<usize>], kind: OffsetKind) -> Vec<Range<usize>> {
+        let offsets = match kind {
+            OffsetKind::Chars => &self.chars,
+            OffsetKind::Utf16 => &self.utf16,
+        };
+        ranges
+            .iter()
+            .map(|range| offsets[range.start]..offsets[range.end])
+            .collect()
+    }
+}
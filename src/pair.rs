@@ -0,0 +1,80 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::ops::Range;
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a pair of texts as `[CLS] A [SEP] B [SEP]`, e.g. a question
+    /// and a context, filling `type_ids` with the segment of each token.
+    ///
+    /// The tokens of the first text (including `[CLS]` and the first
+    /// `[SEP]`) have the type id 0, the others have type id 1. The ranges of
+    /// the tokens of the second text are byte ranges within `text_b`, not
+    /// within some concatenation of both texts. The `words` (if given) are
+    /// filled with the words of both texts.
+    ///
+    /// Note: The output `Vec`s will be cleared before appending tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let (mut ids, mut ranges, mut type_ids) = (Vec::<u64>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into_pair(
+    ///     "Wer isst?",
+    ///     "Ich esse Steak",
+    ///     &mut ids,
+    ///     &mut ranges,
+    ///     &mut type_ids,
+    ///     None,
+    /// );
+    /// assert_eq!(
+    ///     vec!["[CLS]", "Wer", "is", "##st", "##?", "[SEP]", "Ich", "es", "##se", "Ste", "##ak", "[SEP]"],
+    ///     tokenizer.texts_of(&ids)
+    /// );
+    /// assert_eq!(&[0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1], &type_ids[..]);
+    /// assert_eq!(9..9, ranges[5]);
+    /// assert_eq!(&[0..3, 4..6, 6..8, 9..12, 12..14, 14..14], &ranges[6..]);
+    ///
+    /// let mut words = Vec::new();
+    /// tokenizer.tokens_into_pair(
+    ///     "Wer isst?",
+    ///     "Ich esse Steak",
+    ///     &mut ids,
+    ///     &mut ranges,
+    ///     &mut type_ids,
+    ///     Some(&mut words),
+    /// );
+    /// assert_eq!(&[1..2, 2..5, 6..7, 7..9, 9..11], &words[..]);
+    ///
+    /// // the second [SEP] is at the start of an empty second text
+    /// tokenizer.tokens_into_pair("Ich", "", &mut ids, &mut ranges, &mut type_ids, None);
+    /// assert_eq!(&[3, 1671, 4, 4], &ids[..]);
+    /// assert_eq!(&[0..0, 0..3, 3..3, 0..0], &ranges[..]);
+    /// assert_eq!(&[0, 0, 0, 1], &type_ids[..]);
+    /// ```
+    pub fn tokens_into_pair<T: TokenID>(
+        &self,
+        text_a: &str,
+        text_b: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        type_ids: &mut Vec<T>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        let mut words = words;
+        self.tokens_into(text_a, token_ids, token_ranges, words.as_deref_mut());
+        let b_start = token_ids.len();
+        self.words_into(text_b, 0, token_ids, token_ranges, words);
+        if self.options.add_suffix {
+            if let Some(id) = self.suffix {
+                let pos = token_ranges[b_start..].last().map_or(0, |range| range.end);
+                token_ids.push(T::coerce(u64::from(id)));
+                token_ranges.push(pos..pos);
+            }
+        }
+        type_ids.clear();
+        type_ids.resize(b_start, T::zero());
+        type_ids.resize(token_ids.len(), T::coerce(1));
+    }
+}
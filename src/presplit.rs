@@ -0,0 +1,95 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::ops::Range;
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes words that another pre-tokenizer has split the text into,
+    /// given with their byte ranges in the text, into ids and ranges (and
+    /// optionally the token indices of each word), after clearing them.
+    ///
+    /// Each word is tokenized as [`tokens_into`] tokenizes a word, including
+    /// the normalization the options ask for and the `[UNK]` for words the
+    /// vocabulary doesn't cover, but isn't split any further (even with
+    /// [`split_punctuation`]). The `[CLS]` and `[SEP]` tokens go around the
+    /// whole sequence as in `tokens_into`, if the options add them.
+    ///
+    /// The token ranges are those within the word moved to the start of its
+    /// range. If a word is longer than its range (e.g. because the
+    /// pre-tokenizer normalized it), they are cut off at the end of the
+    /// range, and the last token of each word always ends there. An empty
+    /// word has no tokens, but its (empty) entry in `word_spans`, so these
+    /// line up with the words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let text = "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?";
+    /// let mut offset = 0;
+    /// let words: Vec<(&str, std::ops::Range<usize>)> = text
+    ///     .split(' ')
+    ///     .map(|word| {
+    ///         let range = offset..offset + word.len();
+    ///         offset = range.end + 1;
+    ///         (word, range)
+    ///     })
+    ///     .collect();
+    ///
+    /// let (mut ids, mut ranges, mut word_spans) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_for_words_into(&words, &mut ids, &mut ranges, Some(&mut word_spans));
+    /// let (mut expected_ids, mut expected_ranges, mut expected_words) =
+    ///     (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut expected_ids, &mut expected_ranges, Some(&mut expected_words));
+    /// assert_eq!(expected_ids, ids);
+    /// assert_eq!(expected_ranges, ranges);
+    /// assert_eq!(expected_words, word_spans);
+    ///
+    /// // the words the vocabulary doesn't cover become `[UNK]`
+    /// let words = [("Grüße", 0..7), ("", 7..7), ("☃", 8..11)];
+    /// tokenizer.tokens_for_words_into(&words, &mut ids, &mut ranges, Some(&mut word_spans));
+    /// assert_eq!("[CLS] Grü ##ße [UNK] [SEP]", tokenizer.texts_of(&ids).join(" "));
+    /// assert_eq!(&[0..0, 0..4, 4..7, 8..11, 11..11], &ranges[..]);
+    /// assert_eq!(&[1..3, 3..3, 3..4], &word_spans[..]);
+    ///
+    /// // a word longer than its range stays within it
+    /// tokenizer.tokens_for_words_into(&[("Grüße", 0..5)], &mut ids, &mut ranges, None);
+    /// assert_eq!(&[0..0, 0..4, 4..5, 5..5], &ranges[..]);
+    /// ```
+    ///
+    /// [`tokens_into`]: AlephAlphaTokenizer::tokens_into
+    /// [`split_punctuation`]: crate::AlephAlphaTokenizerBuilder::split_punctuation
+    pub fn tokens_for_words_into<T: TokenID>(
+        &self,
+        words: &[(&str, Range<usize>)],
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        word_spans: Option<&mut Vec<Range<usize>>>,
+    ) {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut word_spans = word_spans;
+        if let Some(w) = word_spans.as_mut() {
+            w.clear();
+        }
+        self.add_prefix(token_ids, token_ranges);
+        for (word, range) in words {
+            let first_token = token_ids.len();
+            if !word.is_empty() {
+                self.tokenize_span(word, 0..word.len(), token_ids, token_ranges);
+                for token_range in &mut token_ranges[first_token..] {
+                    let start = range.start + token_range.start.min(range.len());
+                    let end = range.start + token_range.end.min(range.len());
+                    *token_range = start..end;
+                }
+                if token_ids.len() > first_token {
+                    token_ranges[token_ids.len() - 1].end = range.end;
+                }
+            }
+            if let Some(w) = word_spans.as_mut() {
+                w.push(first_token..token_ids.len());
+            }
+        }
+        self.add_suffix(token_ids, token_ranges);
+    }
+}
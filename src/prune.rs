@@ -0,0 +1,182 @@
+use crate::estimate::{Calibration, BUILTIN_SAMPLE};
+use crate::texts::TokenTexts;
+use crate::{AlephAlphaTokenizer, FstData};
+use fst::raw::Fst;
+use fst::Streamer;
+use std::sync::Arc;
+
+// the keys of the FST whose tokens survive, with their new ids
+fn pruned_fst(fst: &Fst<FstData>, remap: &[Option<u64>]) -> Fst<FstData> {
+    let mut keys = Vec::new();
+    let mut stream = fst.stream();
+    while let Some((key, output)) = stream.next() {
+        if let Some(&Some(id)) = remap.get(output.value() as usize) {
+            keys.push((key.to_vec(), id));
+        }
+    }
+    Fst::from_iter_map(keys)
+        .and_then(|fst| fst.map_data(FstData::Owned))
+        .expect("the keys of an FST are sorted and unique")
+}
+
+impl AlephAlphaTokenizer {
+    /// Creates a tokenizer with only the tokens for which `keep` returns
+    /// `true`, given the id and text of each token, together with the new id
+    /// of each old id (or `None` if the token was removed).
+    ///
+    /// The special tokens, including `[UNK]`, `[CLS]`, `[SEP]`, `[PAD]` and
+    /// `[MASK]`, and the byte tokens for byte fallback always survive. The
+    /// surviving tokens keep their order, but get dense ids, so the rows of an
+    /// embedding matrix for the new tokenizer are those of the old ids that
+    /// map to something. The options and the continuing subword prefix stay
+    /// the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let keep = ["Ich", "es", "##se"];
+    /// let (pruned, remap) = tokenizer.prune(&|_, token| keep.contains(&token));
+    /// assert_eq!(8, pruned.vocab_size());
+    /// assert_eq!(tokenizer.vocab_size(), remap.len());
+    /// assert_eq!(&[Some(0), Some(1), Some(2), Some(3), Some(4), None], &remap[..6]);
+    /// assert_eq!(Some(5), remap[10]);
+    ///
+    /// // the remap table gives the same tokens …
+    /// for (old, new) in remap.iter().enumerate() {
+    ///     if let Some(new) = *new {
+    ///         assert_eq!(tokenizer.text_of(old as u64), pruned.text_of(new));
+    ///     }
+    /// }
+    /// // … and, the other way around, the rows to slice from an embedding matrix
+    /// let rows: Vec<usize> = (0..remap.len()).filter(|&old| remap[old].is_some()).collect();
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 10, 11, 12], rows);
+    ///
+    /// // the removed tokens give `[UNK]` now
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// pruned.tokens_into("Ich esse Steak.", &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     vec!["[CLS]", "Ich", "es", "##se", "[UNK]", "[SEP]"],
+    ///     pruned.texts_of(&ids)
+    /// );
+    /// assert_eq!(Ok(()), pruned.verify_internal_consistency());
+    /// ```
+    pub fn prune(
+        &self,
+        keep: &dyn Fn(u64, &str) -> bool,
+    ) -> (AlephAlphaTokenizer, Vec<Option<u64>>) {
+        let mut survives = vec![false; self.tokens.len()];
+        let fixed = [
+            Some(self.unk_id),
+            self.prefix,
+            self.suffix,
+            self.pad_id,
+            self.mask_id,
+        ];
+        let always = self
+            .special_tokens
+            .iter()
+            .cloned()
+            .chain(fixed.iter().flatten().map(|&id| u64::from(id)))
+            .chain(self.byte_ids.iter().map(|&id| u64::from(id)));
+        for id in always {
+            survives[id as usize] = true;
+        }
+        for (id, token) in self.tokens.iter().enumerate() {
+            survives[id] = survives[id] || keep(id as u64, token.trim());
+        }
+
+        let mut tokens = TokenTexts::default();
+        let remap: Vec<Option<u64>> = self
+            .tokens
+            .iter()
+            .zip(survives)
+            .map(|(token, survives)| {
+                survives.then(|| {
+                    tokens.push(token);
+                    tokens.len() as u64 - 1
+                })
+            })
+            .collect();
+        // the special token ids survive, and there are no more new ids than
+        // old ones
+        let new_id = |id: u32| remap[id as usize].unwrap() as u32;
+        let pruned = AlephAlphaTokenizer {
+            tokens: Arc::new(tokens),
+            starters: Arc::new(pruned_fst(&self.starters, &remap)),
+            followers: Arc::new(pruned_fst(&self.followers, &remap)),
+            special_tokens: Arc::new(
+                self.special_tokens
+                    .iter()
+                    .map(|&id| remap[id as usize].unwrap())
+                    .collect(),
+            ),
+            unk_id: new_id(self.unk_id),
+            prefix: self.prefix.map(new_id),
+            suffix: self.suffix.map(new_id),
+            pad_id: self.pad_id.map(new_id),
+            mask_id: self.mask_id.map(new_id),
+            byte_ids: self.byte_ids.iter().map(|&id| new_id(id)).collect(),
+            suppressed_ids: self
+                .suppressed_ids
+                .iter()
+                .filter_map(|&id| remap[id as usize])
+                .collect(),
+            continuing_subword_prefix: self.continuing_subword_prefix.clone(),
+            end_of_word_suffix: self.end_of_word_suffix.clone(),
+            options: self.options.clone(),
+            calibration: Calibration::default(),
+            indexes: Arc::default(),
+            ascii_fast_path: self.ascii_fast_path,
+            backend: self.backend,
+        };
+        pruned.calibrate(BUILTIN_SAMPLE);
+        (pruned, remap)
+    }
+
+    /// Prunes the tokens that occur less than `min_count` times when
+    /// tokenizing the corpus, see [`prune`](AlephAlphaTokenizer::prune).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let corpus = ["Ich esse Steak.", "Ein Beispiel"];
+    /// let (pruned, remap) = tokenizer.prune_to_corpus(&corpus, 1);
+    /// let tokens: Vec<&str> = pruned.vocab_iter().map(|(token, _)| token).collect();
+    /// assert_eq!(
+    ///     vec!["[PAD]", "[UNK]", "[CLS]", "[SEP]", "[MASK]", "Ein", "Bei", "##spiel", "Ich", "es"],
+    ///     tokens[..10].to_vec()
+    /// );
+    /// assert_eq!(14, tokens.len());
+    /// assert_eq!(None, remap[6]); // "interess"
+    ///
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// pruned.tokens_into("Ein interessantes Beispiel", &mut ids, &mut ranges, None);
+    /// assert_eq!(vec!["[CLS]", "Ein", "[UNK]", "Bei", "##spiel", "[SEP]"], pruned.texts_of(&ids));
+    ///
+    /// // tokens that occur too rarely are removed, too
+    /// let (pruned, _) = tokenizer.prune_to_corpus(&["Ich esse", "Ich"], 2);
+    /// assert_eq!(6, pruned.vocab_size());
+    /// assert_eq!(Some(5), pruned.id_of("Ich"));
+    /// ```
+    pub fn prune_to_corpus<S: AsRef<str>>(
+        &self,
+        corpus: impl IntoIterator<Item = S>,
+        min_count: u64,
+    ) -> (AlephAlphaTokenizer, Vec<Option<u64>>) {
+        let mut counts = vec![0u64; self.tokens.len()];
+        let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+        for text in corpus {
+            self.tokens_into(text.as_ref(), &mut ids, &mut ranges, None);
+            for &id in &ids {
+                counts[id as usize] += 1;
+            }
+        }
+        self.prune(&|id, _| counts[id as usize] >= min_count)
+    }
+}
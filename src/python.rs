@@ -0,0 +1,158 @@
+//! Python bindings, built with the `python` feature
+
+// the `pymethods` expansion converts `PyResult`s into themselves
+#![allow(clippy::useless_conversion)]
+
+use crate::{AlephAlphaTokenizer, TokenizerError};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+impl From<TokenizerError> for PyErr {
+    fn from(e: TokenizerError) -> Self {
+        match e {
+            TokenizerError::Io(e) => PyIOError::new_err(e.to_string()),
+            e => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+type Offsets = Vec<(usize, usize)>;
+
+/// The tokenizer as a Python class, `aleph_alpha_tokenizer.AlephAlphaTokenizer`.
+///
+/// Register it with a Python module using `add_class`; the
+/// `aleph-alpha-tokenizer-python` crate in the `python` directory builds
+/// such a module with [maturin](https://github.com/PyO3/maturin).
+///
+/// The offsets of `encode` and `encode_batch` are byte offsets by default.
+/// Pass `char_offsets=True` to get offsets in characters instead, which can
+/// be used to slice Python strings.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::PyAlephAlphaTokenizer;
+/// use pyo3::prelude::*;
+/// use pyo3::types::IntoPyDict;
+///
+/// pyo3::prepare_freethreaded_python();
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let module = PyModule::new_bound(py, "aleph_alpha_tokenizer")?;
+///     module.add_class::<PyAlephAlphaTokenizer>()?;
+///     let globals = [("aat", module)].into_py_dict_bound(py);
+///     py.run_bound(
+///         r#"
+/// tokenizer = aat.AlephAlphaTokenizer("vocab.txt")
+/// ids, offsets = tokenizer.encode("Ich esse Steak.")
+/// assert ids == [3, 1671, 229, 145, 601, 464, 26914, 4], ids
+/// assert offsets == [(0, 0), (0, 3), (4, 6), (6, 8), (9, 12), (12, 14), (14, 15), (15, 15)]
+///
+/// text = "Grüße aus Köln"
+/// _, byte_offsets = tokenizer.encode(text)
+/// _, char_offsets = tokenizer.encode(text, char_offsets=True)
+/// assert byte_offsets[-1] == (17, 17) and char_offsets[-1] == (14, 14)
+/// assert [text[s:e] for s, e in char_offsets[1:-1]] == ["Grü", "ße", "aus", "Köln"]
+///
+/// batch = tokenizer.encode_batch(["Ich esse Steak.", text])
+/// assert batch == [tokenizer.encode("Ich esse Steak."), tokenizer.encode(text)]
+///
+/// assert tokenizer.decode(ids) == "Ich esse Steak."
+/// assert tokenizer.decode(ids, skip_special_tokens=False) == "[CLS] Ich esse Steak. [SEP]"
+/// assert tokenizer.token_to_id("Super") == 4285
+/// assert tokenizer.token_to_id("Supercalifragilistic") is None
+/// assert tokenizer.id_to_token(4285) == "Super"
+/// assert tokenizer.id_to_token(10 ** 9) is None
+/// assert tokenizer.vocab_size() == len(open("vocab.txt", encoding="utf-8").read().splitlines())
+///
+/// try:
+///     aat.AlephAlphaTokenizer("no/such/vocab.txt")
+///     assert False
+/// except IOError:
+///     pass
+/// "#,
+///         Some(&globals),
+///         None,
+///     )
+/// })
+/// .unwrap();
+/// ```
+#[pyclass(name = "AlephAlphaTokenizer", module = "aleph_alpha_tokenizer", frozen)]
+pub struct PyAlephAlphaTokenizer {
+    tokenizer: AlephAlphaTokenizer,
+}
+
+impl PyAlephAlphaTokenizer {
+    fn encode_text(&self, text: &str, char_offsets: bool) -> (Vec<u64>, Offsets) {
+        let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+        if char_offsets {
+            self.tokenizer
+                .tokens_into_chars(text, &mut ids, &mut ranges, None);
+        } else {
+            self.tokenizer
+                .tokens_into(text, &mut ids, &mut ranges, None);
+        }
+        (ids, ranges.into_iter().map(|r| (r.start, r.end)).collect())
+    }
+}
+
+#[pymethods]
+impl PyAlephAlphaTokenizer {
+    /// Loads the tokenizer from a vocabulary file with one token per line.
+    #[new]
+    fn new(vocab_path: &str) -> PyResult<Self> {
+        let tokenizer = AlephAlphaTokenizer::from_vocab(vocab_path)?;
+        Ok(PyAlephAlphaTokenizer { tokenizer })
+    }
+
+    /// Tokenizes the text into the token ids and their offsets.
+    #[pyo3(signature = (text, char_offsets = false))]
+    fn encode(&self, text: &str, char_offsets: bool) -> (Vec<u64>, Offsets) {
+        self.encode_text(text, char_offsets)
+    }
+
+    /// Tokenizes the texts like `encode`, releasing the GIL meanwhile.
+    #[pyo3(signature = (texts, char_offsets = false))]
+    fn encode_batch(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        char_offsets: bool,
+    ) -> Vec<(Vec<u64>, Offsets)> {
+        py.allow_threads(|| {
+            texts
+                .iter()
+                .map(|text| self.encode_text(text, char_offsets))
+                .collect()
+        })
+    }
+
+    /// Joins the tokens of the ids, merging follower tokens onto the token
+    /// before them.
+    #[pyo3(signature = (ids, skip_special_tokens = true))]
+    fn decode(&self, ids: Vec<u64>, skip_special_tokens: bool) -> PyResult<String> {
+        let tokenizer = &self.tokenizer;
+        if let Some(id) = ids.iter().find(|&&id| id >= tokenizer.vocab_size() as u64) {
+            return Err(PyValueError::new_err(format!("unknown token id {}", id)));
+        }
+        Ok(tokenizer.decode(&ids, skip_special_tokens))
+    }
+
+    /// Looks up the id of a token, or `None`.
+    fn token_to_id(&self, token: &str) -> Option<u64> {
+        self.tokenizer.id_of(token)
+    }
+
+    /// Looks up the text of a token id, or `None`.
+    fn id_to_token(&self, id: u64) -> Option<String> {
+        if id < self.tokenizer.vocab_size() as u64 {
+            Some(self.tokenizer.text_of(id).to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Gets the number of tokens in the vocabulary.
+    fn vocab_size(&self) -> usize {
+        self.tokenizer.vocab_size()
+    }
+}
@@ -0,0 +1,173 @@
+use crate::AlephAlphaTokenizer;
+use std::ops::Range;
+
+impl AlephAlphaTokenizer {
+    /// Gets the exact part of the text that a run of tokens covers, given
+    /// the token ranges of [`tokens_into`](AlephAlphaTokenizer::tokens_into)
+    /// and the indices of the tokens, e.g. to show the tokens a model picked
+    /// with the original whitespace and punctuation.
+    ///
+    /// Unlike [`decode`](AlephAlphaTokenizer::decode), this doesn't need to
+    /// guess the spacing: the slice goes from the start of the first token to
+    /// the end of the last one. Tokens with empty ranges (like `[CLS]` and
+    /// `[SEP]`) at either end of the selection are left out; if all of them
+    /// are empty, so is the slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the selection is out of bounds of the ranges, or the ranges
+    /// are not those of the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "  Ich esse\n Steak.  ";
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Ich es ##se Ste ##ak ##. [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// assert_eq!("esse\n Steak", AlephAlphaTokenizer::reconstruct(text, &ranges, 2..6));
+    /// // the selection starts or ends on [CLS] and [SEP]
+    /// assert_eq!("Ich es", AlephAlphaTokenizer::reconstruct(text, &ranges, 0..3));
+    /// assert_eq!("Steak.", AlephAlphaTokenizer::reconstruct(text, &ranges, 4..8));
+    /// assert_eq!(text.trim(), AlephAlphaTokenizer::reconstruct(text, &ranges, 0..8));
+    /// assert_eq!("", AlephAlphaTokenizer::reconstruct(text, &ranges, 7..8));
+    /// assert_eq!("", AlephAlphaTokenizer::reconstruct(text, &ranges, 3..3));
+    /// ```
+    pub fn reconstruct<'t>(
+        text: &'t str,
+        ranges: &[Range<usize>],
+        selection: Range<usize>,
+    ) -> &'t str {
+        let selected = &ranges[selection];
+        let mut nonempty = selected.iter().filter(|range| !range.is_empty());
+        match (nonempty.next(), nonempty.next_back()) {
+            (Some(first), Some(last)) => &text[first.start..last.end],
+            (Some(only), None) => &text[only.clone()],
+            _ => "",
+        }
+    }
+
+    /// Finds the fewest tokens that cover the byte range of the text, as a
+    /// range of token indices into the token ranges of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into), i.e. the tokens
+    /// that overlap it.
+    ///
+    /// A range that overlaps no token, e.g. only whitespace between tokens,
+    /// gives an empty range of indices where such tokens would be. Tokens
+    /// with empty ranges (like `[CLS]` and `[SEP]`) are never included. This
+    /// takes logarithmic time, as the token ranges are sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ich esse Steak.";
+    /// let (mut ids, mut ranges, mut words) = (Vec::<u32>::new(), Vec::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, Some(&mut words));
+    /// assert_eq!("[CLS] Ich es ##se Ste ##ak ##. [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// // "ss" is within "es" and "##se"
+    /// assert_eq!(2..4, AlephAlphaTokenizer::cover_range(&ranges, 5..7));
+    /// assert_eq!(1..7, AlephAlphaTokenizer::cover_range(&ranges, 0..text.len()));
+    /// // the range starts in the whitespace
+    /// assert_eq!(4..5, AlephAlphaTokenizer::cover_range(&ranges, 8..10));
+    /// // the range falls between the tokens
+    /// assert_eq!(2..2, AlephAlphaTokenizer::cover_range(&ranges, 3..4));
+    /// assert_eq!(4..4, AlephAlphaTokenizer::cover_range(&ranges, 8..9));
+    /// assert_eq!(1..1, AlephAlphaTokenizer::cover_range(&ranges, 0..0));
+    ///
+    /// // the words come back exactly
+    /// let mut start = 0;
+    /// for (word, tokens) in text.split(' ').zip(&words) {
+    ///     let range = start..start + word.len();
+    ///     start = range.end + 1;
+    ///     let covered = AlephAlphaTokenizer::cover_range(&ranges, range);
+    ///     assert_eq!(tokens, &covered);
+    ///     assert_eq!(word, AlephAlphaTokenizer::reconstruct(text, &ranges, covered));
+    /// }
+    /// ```
+    pub fn cover_range(ranges: &[Range<usize>], byte_range: Range<usize>) -> Range<usize> {
+        let first = ranges.partition_point(|r| r.end <= byte_range.start);
+        let last = ranges.partition_point(|r| r.start < byte_range.end);
+        first..last.max(first)
+    }
+
+    /// Finds the token whose range contains the byte position, given the
+    /// token ranges of [`tokens_into`](AlephAlphaTokenizer::tokens_into),
+    /// e.g. for the start of an answer span in question answering.
+    ///
+    /// A position within a char gives the token of the char, while positions
+    /// in whitespace or past the end of the text give `None`, as no token
+    /// contains them. Tokens with empty ranges (like `[CLS]` and `[SEP]`)
+    /// contain no position. This takes logarithmic time, as the token ranges
+    /// are sorted; with the ranges of
+    /// [`tokens_into_chars`](AlephAlphaTokenizer::tokens_into_chars), the
+    /// position is in chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, AlephAlphaTokenizer};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ich esse Steak.";
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// assert_eq!("[CLS] Ich es ##se Ste ##ak ##. [SEP]", tokenizer.texts_of(&ids).join(" "));
+    ///
+    /// assert_eq!(Some(1), AlephAlphaTokenizer::char_to_token(&ranges, 0));
+    /// // a token ends where the next one starts
+    /// assert_eq!(Some(2), AlephAlphaTokenizer::char_to_token(&ranges, 5));
+    /// assert_eq!(Some(3), AlephAlphaTokenizer::char_to_token(&ranges, 6));
+    /// assert_eq!(Some(6), AlephAlphaTokenizer::char_to_token(&ranges, 14));
+    /// // in the whitespace and past the end
+    /// assert_eq!(None, AlephAlphaTokenizer::char_to_token(&ranges, 3));
+    /// assert_eq!(None, AlephAlphaTokenizer::char_to_token(&ranges, 15));
+    /// assert_eq!(None, AlephAlphaTokenizer::char_to_token(&ranges, 100));
+    /// assert_eq!(None, AlephAlphaTokenizer::char_to_token(&[], 0));
+    ///
+    /// // every position of a token gives it back, even within a char
+    /// let text = "Grüße aus dem Krankenhaus ☃";
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// for (i, range) in ranges.iter().enumerate() {
+    ///     match AlephAlphaTokenizer::token_to_chars(&ranges, i) {
+    ///         Some(chars) => {
+    ///             assert_eq!(range, &chars);
+    ///             for pos in chars {
+    ///                 assert_eq!(Some(i), AlephAlphaTokenizer::char_to_token(&ranges, pos));
+    ///             }
+    ///         }
+    ///         None => assert!(tokenizer.is_special(ids[i])),
+    ///     }
+    /// }
+    /// let snowman = text.find('☃').unwrap();
+    /// let unk = AlephAlphaTokenizer::char_to_token(&ranges, snowman + 1).unwrap();
+    /// assert_eq!("[UNK]", tokenizer.text_of(ids[unk]));
+    /// assert_eq!(Some(snowman..text.len()), AlephAlphaTokenizer::token_to_chars(&ranges, unk));
+    /// ```
+    pub fn char_to_token(ranges: &[Range<usize>], byte_pos: usize) -> Option<usize> {
+        let index = ranges.partition_point(|r| r.end <= byte_pos);
+        ranges
+            .get(index)
+            .filter(|range| range.start <= byte_pos)
+            .map(|_| index)
+    }
+
+    /// Gets the range of the token in the text, given the token ranges of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into), or `None` if the
+    /// index is out of bounds or the token has an empty range (like `[CLS]`
+    /// and `[SEP]`). See
+    /// [`char_to_token`](AlephAlphaTokenizer::char_to_token) for examples.
+    pub fn token_to_chars(ranges: &[Range<usize>], token_index: usize) -> Option<Range<usize>> {
+        ranges
+            .get(token_index)
+            .filter(|range| !range.is_empty())
+            .cloned()
+    }
+}
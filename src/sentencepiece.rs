@@ -0,0 +1,114 @@
+use crate::{AlephAlphaTokenizer, AlephAlphaTokenizerBuilder, SpecialTokens, TokenizerError};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// the marker of the pieces that start a word
+const WORD_START: char = '▁';
+
+// converts the pieces of a `.vocab` file to the `##` convention, keeping
+// their ids
+fn convert_pieces<R: BufRead>(reader: R) -> Result<Vec<String>, TokenizerError> {
+    let special = SpecialTokens::roberta();
+    let mut tokens = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = if i == 0 {
+            line.trim_start_matches('\u{feff}')
+        } else {
+            &line
+        };
+        let piece = line.split_once('\t').map_or(line, |(piece, _)| piece);
+        let is_special =
+            [Some(special.unk), special.cls, special.sep, special.pad].contains(&Some(piece));
+        tokens.push(if let Some(starter) = piece.strip_prefix(WORD_START) {
+            // a lone marker can't match anything after the whitespace split,
+            // so it becomes a blank line that just keeps its id
+            starter.to_string()
+        } else if is_special || is_byte_piece(piece) || piece.is_empty() {
+            piece.to_string()
+        } else {
+            format!("##{}", piece)
+        });
+    }
+    Ok(tokens)
+}
+
+// whether the piece is one of `<0x00>` to `<0xFF>`
+fn is_byte_piece(piece: &str) -> bool {
+    piece.len() == 6
+        && piece.starts_with("<0x")
+        && piece.ends_with('>')
+        && u8::from_str_radix(&piece[3..5], 16).is_ok()
+}
+
+impl AlephAlphaTokenizerBuilder {
+    /// Reads the vocabulary from a SentencePiece `.vocab` file, where each
+    /// line is a piece and its score, separated by a tab.
+    ///
+    /// The scores are ignored, and the pieces keep their line numbers as
+    /// ids. Pieces that start with `▁` start a word, the others continue
+    /// one, so they are converted to the `##` convention: `▁Super` becomes
+    /// `Super` and `man` becomes `##man` (and the continuing subword prefix
+    /// is set to `##`). A lone `▁` matches nothing, since the words are
+    /// already split at whitespace. This also sets the special tokens to
+    /// [`SpecialTokens::roberta`], and turns on [`byte_fallback`] if the
+    /// vocabulary has all byte pieces `<0x00>` to `<0xFF>`. Like
+    /// [`vocab_reader`], errors are only returned by
+    /// [`build`](AlephAlphaTokenizerBuilder::build).
+    ///
+    /// [`byte_fallback`]: AlephAlphaTokenizerBuilder::byte_fallback
+    /// [`vocab_reader`]: AlephAlphaTokenizerBuilder::vocab_reader
+    pub fn vocab_sentencepiece(self, path: &str) -> Self {
+        let tokens = File::open(path)
+            .map_err(TokenizerError::from)
+            .and_then(|file| convert_pieces(BufReader::new(file)));
+        let byte_fallback = tokens
+            .as_ref()
+            .is_ok_and(|tokens| tokens.iter().filter(|token| is_byte_piece(token)).count() == 256);
+        self.vocab_tokens(tokens)
+            .special_tokens(&SpecialTokens::roberta())
+            .continuing_subword_prefix("##")
+            .byte_fallback(byte_fallback)
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Creates a tokenizer from a SentencePiece `.vocab` file, see
+    /// [`AlephAlphaTokenizerBuilder::vocab_sentencepiece`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let path = std::env::temp_dir().join("aat-sentencepiece.vocab");
+    /// let pieces = ["<unk>", "<s>", "</s>", "▁", "▁Super", "man", "▁Bat", "▁Ich", "▁es", "se"];
+    /// let scores = pieces.iter().enumerate().map(|(i, piece)| format!("{}\t-{}\n", piece, i));
+    /// std::fs::write(&path, scores.collect::<String>()).unwrap();
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_sentencepiece_vocab(path.to_str().unwrap()).unwrap();
+    /// assert_eq!(pieces.len(), tokenizer.vocab_size());
+    /// assert_eq!(0, tokenizer.unk_id());
+    /// assert_eq!((Some(1), Some(2)), (tokenizer.cls_id(), tokenizer.sep_id()));
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into("Superman Ich esse Batman", &mut ids, &mut ranges, None);
+    /// assert_eq!(&[1, 4, 5, 7, 8, 9, 6, 5, 2], &ids[..]);
+    /// assert_eq!(
+    ///     vec!["<s>", "Super", "##man", "Ich", "es", "##se", "Bat", "##man", "</s>"],
+    ///     tokenizer.texts_of(&ids)
+    /// );
+    /// assert_eq!(Ok(()), tokenizer.verify_internal_consistency());
+    ///
+    /// // with all byte pieces, unknown words fall back to bytes
+    /// let bytes = (0..=255u8).map(|byte| format!("<0x{:02X}>\t0\n", byte));
+    /// let vocab = std::fs::read_to_string(&path).unwrap();
+    /// std::fs::write(&path, vocab + &bytes.collect::<String>()).unwrap();
+    /// let tokenizer = AlephAlphaTokenizer::from_sentencepiece_vocab(path.to_str().unwrap()).unwrap();
+    /// assert!(tokenizer.options().byte_fallback);
+    /// tokenizer.tokens_into("Ich Hi", &mut ids, &mut ranges, None);
+    /// assert_eq!(vec!["<s>", "Ich", "<0x48>", "<0x69>", "</s>"], tokenizer.texts_of(&ids));
+    /// ```
+    pub fn from_sentencepiece_vocab(path: &str) -> Result<Self, TokenizerError> {
+        Self::builder().vocab_sentencepiece(path).build()
+    }
+}
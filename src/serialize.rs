@@ -0,0 +1,187 @@
+use crate::estimate::{Calibration, BUILTIN_SAMPLE};
+use crate::texts::TokenTexts;
+use crate::{AlephAlphaTokenizer, FstData, MatcherBackend, TokenizerOptions};
+use fst::raw::Fst;
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::{Serialize, Serializer};
+use std::sync::Arc;
+
+// the FST bytes, serialized as bytes rather than a sequence of numbers
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+// the tokens, serialized as a sequence of strings like a `Vec<String>`
+impl Serialize for TokenTexts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// the fields of both structs below must stay in the same order for
+// non-self-describing formats like bincode
+#[derive(serde::Serialize)]
+#[serde(rename = "AlephAlphaTokenizer")]
+struct TokenizerRef<'a> {
+    tokens: &'a TokenTexts,
+    special_tokens: &'a [u64],
+    unk_id: u32,
+    prefix: Option<u32>,
+    suffix: Option<u32>,
+    pad_id: Option<u32>,
+    mask_id: Option<u32>,
+    continuing_subword_prefix: &'a str,
+    options: &'a TokenizerOptions,
+    starters: Bytes<'a>,
+    followers: Bytes<'a>,
+    end_of_word_suffix: &'a str,
+    suppressed_ids: &'a [u64],
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "AlephAlphaTokenizer")]
+struct TokenizerData {
+    tokens: Vec<String>,
+    special_tokens: Vec<u64>,
+    unk_id: u32,
+    prefix: Option<u32>,
+    suffix: Option<u32>,
+    pad_id: Option<u32>,
+    mask_id: Option<u32>,
+    continuing_subword_prefix: String,
+    options: TokenizerOptions,
+    starters: Vec<u8>,
+    followers: Vec<u8>,
+    // missing from the data of earlier versions
+    #[serde(default)]
+    end_of_word_suffix: String,
+    #[serde(default)]
+    suppressed_ids: Vec<u64>,
+}
+
+// reconstructs an FST, checking its structure and checksum
+fn fst(bytes: Vec<u8>) -> Result<Fst<FstData>, String> {
+    // `fst::Error` only displays as "FST error", so describe the cause
+    let describe = |e| match e {
+        fst::Error::Fst(e) => format!("invalid FST: {}", e),
+        e => e.to_string(),
+    };
+    let fst = Fst::new(FstData::Owned(bytes)).map_err(describe)?;
+    fst.verify().map_err(describe)?;
+    Ok(fst)
+}
+
+/// With the `serde` feature, the tokenizer can be serialized, e.g. to send
+/// it to workers instead of having them read the vocabulary file.
+///
+/// This includes the tokens, the special token ids, the
+/// [`TokenizerOptions`] and the FSTs, so deserializing needn't rebuild the
+/// FSTs. It verifies them and the [internal
+/// consistency](AlephAlphaTokenizer::verify_internal_consistency) of the
+/// tokenizer instead, so corrupted data fails to deserialize.
+///
+/// # Examples
+///
+/// ```
+///# #[cfg(feature = "serde")] {
+/// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let from_bincode: AlephAlphaTokenizer =
+///     bincode::deserialize(&bincode::serialize(&tokenizer).unwrap()).unwrap();
+/// let from_json: AlephAlphaTokenizer =
+///     serde_json::from_str(&serde_json::to_string(&tokenizer).unwrap()).unwrap();
+///
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// let (mut other_ids, mut other_ranges) = (Vec::<u64>::new(), Vec::new());
+/// for text in &[
+///     "Ich esse Steak.",
+///     "Der Hund spielt im Garten.",
+///     "Ein Junge im Kindergarten spielt mit dem Ball.",
+///     "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+///     "Welche Vereinbarungen auf Landesebene bestehen mit Drittstaaten?",
+///     "Wie viele Menschen starben durch die Folgen der Borreliose-Erkrankung?",
+///     "Welche Abkommen mit auswärtigen Staaten bestehen seitens welcher Länder aktuell?",
+///     "Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?",
+///     "Steht vor dem Hintergrund der gestiegenen Infektionen ausreichend Impfstoff gegen \
+///         FSME zur Verfügung?",
+///     "Liegen der Bundesregierung statistische Daten zu Todesfällen in Folge von \
+///         Borreliose vor und wenn ja, wie lauten diese?",
+/// ] {
+///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+///     for other in &[&from_bincode, &from_json] {
+///         other.tokens_into(text, &mut other_ids, &mut other_ranges, None);
+///         assert_eq!((&ids, &ranges), (&other_ids, &other_ranges));
+///     }
+/// }
+/// assert_eq!(tokenizer.options(), from_json.options());
+/// assert_eq!(tokenizer.mask_id(), from_bincode.mask_id());
+///
+/// // flip a bit within the follower FST at the end
+/// let mut bytes = bincode::serialize(&tokenizer).unwrap();
+/// let len = bytes.len();
+/// bytes[len - 20] ^= 1;
+/// let error = bincode::deserialize::<AlephAlphaTokenizer>(&bytes).err().unwrap();
+/// assert!(error.to_string().starts_with("invalid FST: FST verification failed"));
+///# }
+/// ```
+impl Serialize for AlephAlphaTokenizer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TokenizerRef {
+            tokens: &self.tokens,
+            special_tokens: &self.special_tokens,
+            unk_id: self.unk_id,
+            prefix: self.prefix,
+            suffix: self.suffix,
+            pad_id: self.pad_id,
+            mask_id: self.mask_id,
+            continuing_subword_prefix: &self.continuing_subword_prefix,
+            options: &self.options,
+            starters: Bytes(self.starters.as_bytes()),
+            followers: Bytes(self.followers.as_bytes()),
+            end_of_word_suffix: &self.end_of_word_suffix,
+            suppressed_ids: &self.suppressed_ids,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AlephAlphaTokenizer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = TokenizerData::deserialize(deserializer)?;
+        let starters = fst(data.starters).map_err(D::Error::custom)?;
+        let followers = fst(data.followers).map_err(D::Error::custom)?;
+        let mut tokenizer = AlephAlphaTokenizer {
+            tokens: Arc::new(data.tokens.into_iter().collect()),
+            starters: Arc::new(starters),
+            followers: Arc::new(followers),
+            special_tokens: Arc::new(data.special_tokens),
+            unk_id: data.unk_id,
+            prefix: data.prefix,
+            suffix: data.suffix,
+            pad_id: data.pad_id,
+            mask_id: data.mask_id,
+            byte_ids: Vec::new(),
+            suppressed_ids: data.suppressed_ids,
+            continuing_subword_prefix: data.continuing_subword_prefix,
+            end_of_word_suffix: data.end_of_word_suffix,
+            options: data.options,
+            calibration: Calibration::default(),
+            indexes: Arc::default(),
+            ascii_fast_path: true,
+            backend: MatcherBackend::Fst,
+        };
+        tokenizer
+            .verify_internal_consistency()
+            .map_err(D::Error::custom)?;
+        if tokenizer.options.byte_fallback {
+            tokenizer.byte_ids = tokenizer.byte_token_ids().map_err(D::Error::custom)?;
+        }
+        tokenizer.calibrate(BUILTIN_SAMPLE);
+        Ok(tokenizer)
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{AlephAlphaTokenizer, IdSink, RangeSink, TokenID};
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Where [`tokenize_to`](AlephAlphaTokenizer::tokenize_to) puts the tokens,
+/// e.g. a buffer owned by foreign code, one token at a time.
+///
+/// The tokens of each word come between [`begin_word`](TokenSink::begin_word)
+/// and [`end_word`](TokenSink::end_word), while those of `[CLS]` and `[SEP]`
+/// come before the first and after the last word. Matching a word may push
+/// tokens that turn out not to cover it, in which case
+/// [`rollback_word`](TokenSink::rollback_word) takes them back (all of them,
+/// or those after some of the word's tokens) before the tokens that replace
+/// them (e.g. `[UNK]`) are pushed.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TokenSink};
+/// use std::ops::Range;
+///
+/// // counts the tokens of each word and the tokens taken back
+/// #[derive(Default)]
+/// struct Counting {
+///     words: Vec<usize>,
+///     in_word: bool,
+///     rolled_back: usize,
+/// }
+///
+/// impl TokenSink for Counting {
+///     fn push(&mut self, _id: u64, _range: Range<usize>) {
+///         if self.in_word {
+///             *self.words.last_mut().unwrap() += 1;
+///         }
+///     }
+///
+///     fn begin_word(&mut self) {
+///         self.words.push(0);
+///         self.in_word = true;
+///     }
+///
+///     fn end_word(&mut self) {
+///         self.in_word = false;
+///     }
+///
+///     fn rollback_word(&mut self, tokens: usize) {
+///         *self.words.last_mut().unwrap() -= tokens;
+///         self.rolled_back += tokens;
+///     }
+/// }
+///
+/// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+/// let mut counting = Counting::default();
+/// tokenizer.tokenize_to("Ich esse Steak.", &mut counting);
+/// assert_eq!(vec![1, 2, 3], counting.words);
+/// assert_eq!(0, counting.rolled_back);
+///
+/// // "Superman☃" starts with "Super" and "##man", but no follower matches
+/// // "☃", so these tokens are taken back for a single `[UNK]`
+/// let mut counting = Counting::default();
+/// tokenizer.tokenize_to("Ich Superman☃ ☃", &mut counting);
+/// assert_eq!(vec![1, 1, 1], counting.words);
+/// assert_eq!(2, counting.rolled_back);
+///
+/// // the sinks for `Vec`s see the tokens after the rollback
+/// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+/// tokenizer.tokenize_to("Ich Superman☃ ☃", &mut (&mut ids, &mut ranges));
+/// assert_eq!("[CLS] Ich [UNK] [UNK] [SEP]", tokenizer.texts_of(&ids).join(" "));
+/// assert_eq!(&[0..0, 0..3, 4..15, 16..19, 19..19], &ranges[..]);
+/// let mut only_ids = Vec::<u64>::new();
+/// tokenizer.tokenize_to("Ich Superman☃ ☃", &mut only_ids);
+/// assert_eq!(ids.iter().map(|&id| u64::from(id)).collect::<Vec<_>>(), only_ids);
+/// ```
+pub trait TokenSink {
+    /// Takes the next token, as its id and its byte range in the text.
+    fn push(&mut self, id: u64, range: Range<usize>);
+
+    /// Tells that the tokens of the next word follow.
+    fn begin_word(&mut self) {}
+
+    /// Tells that the word has all its tokens.
+    fn end_word(&mut self) {}
+
+    /// Takes back the last `tokens` tokens, which are tokens of the current
+    /// word pushed so far.
+    fn rollback_word(&mut self, tokens: usize);
+}
+
+impl<S: TokenSink + ?Sized> TokenSink for &mut S {
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        (**self).push(id, range);
+    }
+
+    #[inline]
+    fn begin_word(&mut self) {
+        (**self).begin_word();
+    }
+
+    #[inline]
+    fn end_word(&mut self) {
+        (**self).end_word();
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        (**self).rollback_word(tokens);
+    }
+}
+
+impl<T: TokenID> TokenSink for Vec<T> {
+    #[inline]
+    fn push(&mut self, id: u64, _range: Range<usize>) {
+        Vec::push(self, T::coerce(id));
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.truncate(self.len() - tokens);
+    }
+}
+
+impl<T: TokenID> TokenSink for (Vec<T>, Vec<Range<usize>>) {
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        self.0.push(T::coerce(id));
+        self.1.push(range);
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.0.truncate(self.0.len() - tokens);
+        self.1.truncate(self.1.len() - tokens);
+    }
+}
+
+impl<T: TokenID> TokenSink for (&mut Vec<T>, &mut Vec<Range<usize>>) {
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        self.0.push(T::coerce(id));
+        self.1.push(range);
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.0.truncate(self.0.len() - tokens);
+        self.1.truncate(self.1.len() - tokens);
+    }
+}
+
+// the id side of `Forward`: the tokenizer pushes each id right before its
+// range, so the id waits in a cell shared with the range side
+struct PendingIds<'c> {
+    pending: &'c Cell<u64>,
+    len: usize,
+}
+
+impl IdSink<u64> for PendingIds<'_> {
+    #[inline]
+    fn push(&mut self, id: u64) {
+        self.pending.set(id);
+        self.len += 1;
+    }
+
+    #[inline]
+    fn truncate_to(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// forwards the tokens to a `TokenSink` as they come
+struct Forward<'c, S> {
+    sink: S,
+    pending: &'c Cell<u64>,
+    len: usize,
+    // the number of tokens before the current word
+    word_start: usize,
+    last_end: usize,
+}
+
+impl<S: TokenSink> Forward<'_, S> {
+    fn begin_word(&mut self) {
+        self.word_start = self.len;
+        self.sink.begin_word();
+    }
+}
+
+impl<S: TokenSink> RangeSink for Forward<'_, S> {
+    #[inline]
+    fn push(&mut self, range: Range<usize>) {
+        self.len += 1;
+        self.last_end = range.end;
+        self.sink.push(self.pending.get(), range);
+    }
+
+    fn truncate_to(&mut self, len: usize) {
+        if len < self.len {
+            // the tokenizer only ever takes back tokens of the current
+            // word, e.g. those of a part of it split at a fallback separator
+            debug_assert!(self.word_start <= len);
+            self.sink.rollback_word(self.len - len);
+            self.len = len;
+        }
+    }
+
+    // only the suffix needs this, after the last word, whose last token
+    // cannot be rolled back anymore
+    #[inline]
+    fn last_end(&self) -> usize {
+        self.last_end
+    }
+}
+
+// collects the tokens into `Vec`s, together with the token indices of each
+// word
+struct WithWords<'v, T> {
+    ids: &'v mut Vec<T>,
+    ranges: &'v mut Vec<Range<usize>>,
+    words: Option<&'v mut Vec<Range<usize>>>,
+}
+
+impl<T: TokenID> TokenSink for WithWords<'_, T> {
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        self.ids.push(T::coerce(id));
+        self.ranges.push(range);
+    }
+
+    #[inline]
+    fn begin_word(&mut self) {
+        let start = self.ids.len();
+        if let Some(words) = self.words.as_mut() {
+            words.push(start..start);
+        }
+    }
+
+    #[inline]
+    fn end_word(&mut self) {
+        let end = self.ids.len();
+        if let Some(word) = self.words.as_mut().and_then(|words| words.last_mut()) {
+            word.end = end;
+        }
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.ids.truncate(self.ids.len() - tokens);
+        self.ranges.truncate(self.ranges.len() - tokens);
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes the text into the given [`TokenSink`], with `[CLS]` and
+    /// `[SEP]` (if the tokenizer adds them) as the first and last token.
+    ///
+    /// This is how [`tokens_into`](AlephAlphaTokenizer::tokens_into) gets
+    /// its tokens, so any sink gets the same tokens as its output. Like
+    /// [`count_tokens`](AlephAlphaTokenizer::count_tokens), this doesn't
+    /// allocate, unless the tokenizer lowercases words, rejoins hyphenated
+    /// words or backtracks.
+    ///
+    /// See [`TokenSink`] for examples.
+    pub fn tokenize_to<S: TokenSink>(&self, text: &str, sink: &mut S) {
+        let pending = Cell::new(0);
+        let mut ids = PendingIds {
+            pending: &pending,
+            len: 0,
+        };
+        let mut ranges = Forward {
+            sink,
+            pending: &pending,
+            len: 0,
+            word_start: 0,
+            last_end: 0,
+        };
+        self.add_prefix(&mut ids, &mut ranges);
+        for span in self.spans(text) {
+            ranges.begin_word();
+            self.tokenize_span(text, span, &mut ids, &mut ranges);
+            ranges.sink.end_word();
+        }
+        self.add_suffix(&mut ids, &mut ranges);
+    }
+
+    // the tokens of `tokens_into`
+    pub(crate) fn tokens_with_words<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) {
+        let mut sink = WithWords {
+            ids: token_ids,
+            ranges: token_ranges,
+            words,
+        };
+        self.tokenize_to(text, &mut sink);
+    }
+}
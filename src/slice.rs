@@ -0,0 +1,200 @@
+use crate::{AlephAlphaTokenizer, TokenID, TokenSink};
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The error of
+/// [`tokens_into_slice`](AlephAlphaTokenizer::tokens_into_slice) if the
+/// buffers are too small for the tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError {
+    /// the number of tokens of the text, i.e. the length the buffers need
+    pub required: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the buffers are too small for the {} tokens",
+            self.required
+        )
+    }
+}
+
+impl Error for CapacityError {}
+
+/// A [`TokenSink`] that writes the token ids (and ranges, if given) into
+/// preallocated buffers, as long as they fit, and counts the tokens, see
+/// [`tokens_into_slice`](AlephAlphaTokenizer::tokens_into_slice).
+#[derive(Debug)]
+pub struct SliceTokens<'s, T> {
+    ids: &'s mut [T],
+    ranges: Option<&'s mut [Range<usize>]>,
+    len: usize,
+}
+
+impl<'s, T> SliceTokens<'s, T> {
+    /// Creates a sink writing into the buffers from their start.
+    pub fn new(ids: &'s mut [T], ranges: Option<&'s mut [Range<usize>]>) -> Self {
+        SliceTokens {
+            ids,
+            ranges,
+            len: 0,
+        }
+    }
+
+    /// Gets the number of tokens pushed, whether they fit or not.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Tells whether no tokens were pushed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tells whether the tokens fit into both buffers.
+    pub fn fits(&self) -> bool {
+        let ranges = self
+            .ranges
+            .as_ref()
+            .map_or(usize::MAX, |ranges| ranges.len());
+        self.len <= self.ids.len().min(ranges)
+    }
+}
+
+impl<T: TokenID> TokenSink for SliceTokens<'_, T> {
+    #[inline]
+    fn push(&mut self, id: u64, range: Range<usize>) {
+        let len = self.len;
+        if let Some(slot) = self.ids.get_mut(len) {
+            *slot = T::coerce(id);
+        }
+        if let Some(slot) = self.ranges.as_mut().and_then(|ranges| ranges.get_mut(len)) {
+            *slot = range;
+        }
+        self.len += 1;
+    }
+
+    #[inline]
+    fn rollback_word(&mut self, tokens: usize) {
+        self.len -= tokens;
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but writes
+    /// the token ids (and ranges, if given) into preallocated buffers
+    /// instead of `Vec`s, e.g. for latency-critical code or buffers owned by
+    /// foreign code. This returns the number of tokens, which are at the
+    /// start of the buffers.
+    ///
+    /// The tokens are those of `tokens_into`. If they don't fit into either
+    /// buffer, this returns a [`CapacityError`] with the number of tokens,
+    /// so the buffers can be grown to try again; the contents of the buffers
+    /// are unspecified then. Like
+    /// [`count_tokens`](AlephAlphaTokenizer::count_tokens), this doesn't
+    /// allocate, unless the tokenizer lowercases words, rejoins hyphenated
+    /// words or backtracks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, CapacityError, UnkMode};
+    /// use std::alloc::{GlobalAlloc, Layout, System};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Counting;
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         System.alloc(layout)
+    ///     }
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         System.dealloc(ptr, layout)
+    ///     }
+    /// }
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let text = "Ich esse Steak.";
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///
+    /// // an exact fit
+    /// let (mut id_buf, mut range_buf) = ([0u32; 8], vec![0..0; 8]);
+    /// let before = ALLOCATIONS.load(Ordering::Relaxed);
+    /// let len = tokenizer.tokens_into_slice(text, &mut id_buf, Some(&mut range_buf));
+    /// assert_eq!(before, ALLOCATIONS.load(Ordering::Relaxed));
+    /// assert_eq!(Ok(ids.len()), len);
+    /// assert_eq!((&ids[..], &ranges[..]), (&id_buf[..], &range_buf[..]));
+    ///
+    /// // a larger buffer, and no ranges
+    /// let mut large = [0u32; 64];
+    /// assert_eq!(Ok(8), tokenizer.tokens_into_slice(text, &mut large, None));
+    /// assert_eq!(&ids[..], &large[..8]);
+    ///
+    /// // too small, so grow the buffer and try again
+    /// let mut buf = vec![0u32; 4];
+    /// let error = tokenizer.tokens_into_slice(text, &mut buf, None).unwrap_err();
+    /// assert_eq!(CapacityError { required: 8 }, error);
+    /// assert_eq!("the buffers are too small for the 8 tokens", error.to_string());
+    /// buf.resize(error.required, 0);
+    /// assert_eq!(Ok(8), tokenizer.tokens_into_slice(text, &mut buf, None));
+    /// assert_eq!(ids, buf);
+    /// // either buffer may be too small
+    /// let error = tokenizer.tokens_into_slice(text, &mut large, Some(&mut range_buf[..7]));
+    /// assert_eq!(Err(CapacityError { required: 8 }), error);
+    ///
+    /// // the same tokens as `tokens_into`, even if the buffer is only too
+    /// // small while tokenizing a word that turns out to be unknown
+    /// let per_char = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .unk_mode(UnkMode::PerChar)
+    ///     .build()
+    ///     .unwrap();
+    /// for tokenizer in &[&tokenizer, &per_char] {
+    ///     for text in &[
+    ///         "Der Hund spielt im Garten.",
+    ///         "Wie definiert die Bundesregierung Clans und Clankriminalität?",
+    ///         "Grüße aus dem Krankenhaus ☃ Supercalifragilistic",
+    ///         "  ",
+    ///         "",
+    ///     ] {
+    ///         tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///         for capacity in 0..ids.len() + 2 {
+    ///             let (mut id_buf, mut range_buf) = (vec![0u32; capacity], vec![0..0; capacity]);
+    ///             match tokenizer.tokens_into_slice(text, &mut id_buf, Some(&mut range_buf)) {
+    ///                 Ok(len) => {
+    ///                     assert_eq!(ids.len(), len, "{:?}", text);
+    ///                     assert_eq!(&ids[..], &id_buf[..len], "{:?}", text);
+    ///                     assert_eq!(&ranges[..], &range_buf[..len], "{:?}", text);
+    ///                 }
+    ///                 Err(CapacityError { required }) => {
+    ///                     assert!(capacity < ids.len(), "{:?}", text);
+    ///                     assert_eq!(ids.len(), required, "{:?}", text);
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn tokens_into_slice<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut [T],
+        token_ranges: Option<&mut [Range<usize>]>,
+    ) -> Result<usize, CapacityError> {
+        let mut sink = SliceTokens::new(token_ids, token_ranges);
+        self.tokenize_to(text, &mut sink);
+        if sink.fits() {
+            Ok(sink.len)
+        } else {
+            Err(CapacityError { required: sink.len })
+        }
+    }
+}
@@ -0,0 +1,88 @@
+/// The texts of the special tokens in a vocabulary.
+///
+/// The [`Default`] are the BERT names (`[UNK]`, `[CLS]`, `[SEP]`, `[PAD]`
+/// and `[MASK]`), which [`from_vocab`] uses. Tokens that match one of these texts
+/// are registered as special tokens; the `[CLS]` and `[SEP]` tokens are
+/// added around each text if present.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SpecialTokens};
+///
+/// let path = std::env::temp_dir().join("aat-roberta-vocab.txt");
+/// std::fs::write(&path, "<s>\n<pad>\n</s>\n<unk>\nSuper\n##man\n").unwrap();
+/// let tokenizer = AlephAlphaTokenizer::from_vocab_with_special_tokens(
+///     path.to_str().unwrap(),
+///     SpecialTokens {
+///         unk: "<unk>",
+///         cls: Some("<s>"),
+///         sep: Some("</s>"),
+///         pad: Some("<pad>"),
+///         ..SpecialTokens::default()
+///     },
+/// )
+/// .unwrap();
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// tokenizer.tokens_into("Superman Batman", &mut ids, &mut ranges, None);
+/// assert_eq!(&[0, 4, 5, 3, 2], &ids[..]);
+/// assert!((0..4u64).all(|id| tokenizer.is_special(id)));
+/// assert!(!tokenizer.is_special(4u64));
+///
+/// // the BERT names are not found in this vocabulary
+/// assert!(AlephAlphaTokenizer::from_vocab(path.to_str().unwrap()).is_err());
+/// ```
+///
+/// [`from_vocab`]: crate::AlephAlphaTokenizer::from_vocab
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpecialTokens<'a> {
+    /// The token for unknown words, which must be in the vocabulary
+    pub unk: &'a str,
+    /// The classification token, used as prefix
+    pub cls: Option<&'a str>,
+    /// The separator token, used as suffix
+    pub sep: Option<&'a str>,
+    /// The padding token
+    pub pad: Option<&'a str>,
+    /// The mask token for masked language modeling
+    pub mask: Option<&'a str>,
+    /// Whether all other tokens in square brackets (like `[MASK]`) are
+    /// special tokens, too, except for `[unused…]` tokens, which aren't
+    /// matched at all
+    pub bracketed: bool,
+    /// The texts of other tokens of the vocabulary that are special (e.g.
+    /// `<|endofprompt|>`); unlike the others, they can't be matched within
+    /// words, only with
+    /// [`parse_special_tokens`](crate::AlephAlphaTokenizerBuilder::parse_special_tokens)
+    pub additional: &'a [&'a str],
+}
+
+impl<'a> Default for SpecialTokens<'a> {
+    fn default() -> Self {
+        SpecialTokens {
+            unk: "[UNK]",
+            cls: Some("[CLS]"),
+            sep: Some("[SEP]"),
+            pad: Some("[PAD]"),
+            mask: Some("[MASK]"),
+            bracketed: true,
+            additional: &[],
+        }
+    }
+}
+
+impl<'a> SpecialTokens<'a> {
+    /// The RoBERTa names: `<unk>`, `<s>`, `</s>`, `<pad>` and `<mask>`, without
+    /// treating bracketed tokens as special.
+    pub fn roberta() -> Self {
+        SpecialTokens {
+            unk: "<unk>",
+            cls: Some("<s>"),
+            sep: Some("</s>"),
+            pad: Some("<pad>"),
+            mask: Some("<mask>"),
+            bracketed: false,
+            additional: &[],
+        }
+    }
+}
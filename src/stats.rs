@@ -0,0 +1,253 @@
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::fmt;
+use std::ops::Range;
+
+/// How well the vocabulary covered a text, as returned by
+/// [`tokens_into_with_stats`](AlephAlphaTokenizer::tokens_into_with_stats).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenizeStats {
+    /// The number of tokens, including `[CLS]` and `[SEP]`
+    pub num_tokens: usize,
+    /// The number of words
+    pub num_words: usize,
+    /// The number of words the vocabulary doesn't cover, which became
+    /// `[UNK]` (or byte tokens with byte fallback)
+    pub num_unk_words: usize,
+    /// The byte ranges of these words within the text
+    pub unk_word_ranges: Vec<Range<usize>>,
+}
+
+/// The makeup and size of a vocabulary, as returned by
+/// [`stats`](AlephAlphaTokenizer::stats).
+///
+/// Each id is exactly one of a starter, a follower, a special token, a
+/// placeholder or blank, unless the continuing subword prefix is empty: then
+/// the starters and followers are the same tokens.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct VocabStats {
+    /// The number of ids, as [`vocab_size`](AlephAlphaTokenizer::vocab_size)
+    pub num_tokens: usize,
+    /// The number of tokens that start a word, without the special tokens
+    pub num_starters: usize,
+    /// The number of tokens that continue a word (e.g. `##man`)
+    pub num_followers: usize,
+    /// The number of special tokens, including `[UNK]`
+    pub num_special: usize,
+    /// The number of placeholders, which no text matches (e.g. `[unused0]`)
+    pub num_unused: usize,
+    /// The number of ids without a text, e.g. blank lines
+    pub num_blank: usize,
+    /// The size of the FST of the starters in bytes
+    pub starters_bytes: usize,
+    /// The size of the FST of the followers in bytes
+    pub followers_bytes: usize,
+    /// The size of the texts of all tokens in bytes
+    pub text_bytes: usize,
+    /// The length of the longest token in bytes
+    pub max_token_bytes: usize,
+    /// The length of the longest token in chars
+    pub max_token_chars: usize,
+    /// The id of the token put in front of each text, e.g. `[CLS]`
+    pub prefix_id: Option<u64>,
+    /// The id of the token put after each text, e.g. `[SEP]`
+    pub suffix_id: Option<u64>,
+    /// The id of the token for unknown words
+    pub unk_id: u64,
+}
+
+impl fmt::Display for VocabStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} tokens ({} starters, {} followers, {} special, {} unused, {} blank), \
+             FSTs of {} and {} bytes, {} bytes of text, longest token {} bytes ({} chars), \
+             unk id {}",
+            self.num_tokens,
+            self.num_starters,
+            self.num_followers,
+            self.num_special,
+            self.num_unused,
+            self.num_blank,
+            self.starters_bytes,
+            self.followers_bytes,
+            self.text_bytes,
+            self.max_token_bytes,
+            self.max_token_chars,
+            self.unk_id,
+        )?;
+        for (name, id) in &[("prefix", self.prefix_id), ("suffix", self.suffix_id)] {
+            match id {
+                Some(id) => write!(f, ", {} id {}", name, id)?,
+                None => write!(f, ", no {}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AlephAlphaTokenizer {
+    /// Counts the kinds of tokens in the vocabulary and measures its size,
+    /// e.g. for capacity planning or a health endpoint.
+    ///
+    /// This looks at every token, so it takes about as long as tokenizing a
+    /// text of the size of the vocabulary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let stats = tokenizer.stats();
+    /// assert_eq!(tokenizer.vocab_size(), stats.num_tokens);
+    /// assert_eq!(
+    ///     stats.num_tokens,
+    ///     stats.num_starters + stats.num_followers + stats.num_special + stats.num_unused
+    ///         + stats.num_blank
+    /// );
+    /// assert_eq!(tokenizer.special_token_ids().len(), stats.num_special);
+    /// assert_eq!(1, stats.num_blank);
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// assert_eq!(vocab.lines().filter(|l| l.starts_with("##")).count(), stats.num_followers);
+    /// assert_eq!(vocab.lines().map(str::len).sum::<usize>(), stats.text_bytes);
+    /// assert_eq!(vocab.lines().map(str::len).max(), Some(stats.max_token_bytes));
+    /// assert!(stats.max_token_chars <= stats.max_token_bytes);
+    /// assert!(stats.starters_bytes > 0 && stats.followers_bytes > 0);
+    /// assert_eq!((Some(3), Some(4), 2), (stats.prefix_id, stats.suffix_id, stats.unk_id));
+    ///
+    /// let tiny = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\n\n[unused0]\nfoo\n##bar\n").unwrap();
+    /// let stats = tiny.stats();
+    /// assert_eq!(
+    ///     (5, 1, 1, 1, 1, 1),
+    ///     (
+    ///         stats.num_tokens,
+    ///         stats.num_starters,
+    ///         stats.num_followers,
+    ///         stats.num_special,
+    ///         stats.num_unused,
+    ///         stats.num_blank
+    ///     )
+    /// );
+    /// assert_eq!((22, 9, 9), (stats.text_bytes, stats.max_token_bytes, stats.max_token_chars));
+    /// assert_eq!(
+    ///     format!(
+    ///         "5 tokens (1 starters, 1 followers, 1 special, 1 unused, 1 blank), \
+    ///          FSTs of {} and {} bytes, 22 bytes of text, longest token 9 bytes (9 chars), \
+    ///          unk id 0, no prefix, no suffix",
+    ///         stats.starters_bytes, stats.followers_bytes
+    ///     ),
+    ///     stats.to_string()
+    /// );
+    /// ```
+    pub fn stats(&self) -> VocabStats {
+        let mut stats = VocabStats {
+            num_tokens: self.vocab_size(),
+            num_special: self.special_tokens.len(),
+            starters_bytes: self.starters.as_bytes().len(),
+            followers_bytes: self.followers.as_bytes().len(),
+            prefix_id: self.cls_id(),
+            suffix_id: self.sep_id(),
+            unk_id: self.unk_id(),
+            ..VocabStats::default()
+        };
+        for (id, token) in self.tokens.iter().enumerate() {
+            stats.text_bytes += token.len();
+            stats.max_token_bytes = stats.max_token_bytes.max(token.len());
+            stats.max_token_chars = stats.max_token_chars.max(token.chars().count());
+            if token.is_empty() {
+                stats.num_blank += 1;
+            } else if self.is_special(id as u64) {
+                // these are among the starters
+            } else if if self.end_of_word_suffix.is_empty() {
+                token.strip_prefix(self.continuing_subword_prefix.as_str())
+            } else {
+                token.strip_suffix(self.end_of_word_suffix.as_str())
+            }
+            .is_some_and(|follower| {
+                self.followers.get(follower).map(|o| o.value()) == Some(id as u64)
+            }) {
+                stats.num_followers += 1;
+            } else if self.starters.get(token).map(|o| o.value()) == Some(id as u64) {
+                stats.num_starters += 1;
+            } else {
+                stats.num_unused += 1;
+            }
+        }
+        stats
+    }
+
+    /// Like [`tokens_into`](AlephAlphaTokenizer::tokens_into), but also
+    /// counts the words the vocabulary doesn't cover, e.g. to monitor how
+    /// well it fits the texts in production.
+    ///
+    /// Words are counted where they are tokenized, so a `[UNK]` token in the
+    /// text itself doesn't count as an unknown word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{examples, TokenizeStats};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// let text = "Batman Robin esse Joker [UNK]";
+    /// let stats = tokenizer.tokens_into_with_stats(text, &mut ids, &mut ranges, None);
+    /// assert_eq!(
+    ///     "[CLS] Bat ##man [UNK] es ##se [UNK] [UNK] [SEP]",
+    ///     tokenizer.texts_of(&ids).join(" ")
+    /// );
+    /// assert_eq!(
+    ///     TokenizeStats {
+    ///         num_tokens: 9,
+    ///         num_words: 5,
+    ///         num_unk_words: 2,
+    ///         unk_word_ranges: vec![7..12, 18..23],
+    ///     },
+    ///     stats
+    /// );
+    /// assert_eq!("Robin", &text[stats.unk_word_ranges[0].clone()]);
+    /// assert_eq!("Joker", &text[stats.unk_word_ranges[1].clone()]);
+    ///
+    /// // the tokens are those of `tokens_into`
+    /// let (mut plain_ids, mut plain_ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut plain_ids, &mut plain_ranges, None);
+    /// assert_eq!((&plain_ids, &plain_ranges), (&ids, &ranges));
+    ///
+    /// let stats = tokenizer.tokens_into_with_stats("Ich esse", &mut ids, &mut ranges, None);
+    /// assert_eq!(0, stats.num_unk_words);
+    /// assert_eq!((5, 2), (stats.num_tokens, stats.num_words));
+    /// ```
+    pub fn tokens_into_with_stats<T: TokenID>(
+        &self,
+        text: &str,
+        token_ids: &mut Vec<T>,
+        token_ranges: &mut Vec<Range<usize>>,
+        words: Option<&mut Vec<Range<usize>>>,
+    ) -> TokenizeStats {
+        token_ids.clear();
+        token_ranges.clear();
+        let mut words = words;
+        if let Some(w) = words.as_mut() {
+            w.clear();
+        }
+        let mut stats = TokenizeStats::default();
+        self.add_prefix(token_ids, token_ranges);
+        for span in self.spans(text) {
+            let first_token = token_ids.len();
+            stats.num_words += 1;
+            if self.tokenize_span(text, span.clone(), token_ids, token_ranges) {
+                stats.num_unk_words += 1;
+                stats.unk_word_ranges.push(span);
+            }
+            if let Some(w) = words.as_mut() {
+                w.push(first_token..token_ids.len());
+            }
+        }
+        self.add_suffix(token_ids, token_ranges);
+        stats.num_tokens = token_ids.len();
+        stats
+    }
+}
@@ -0,0 +1,172 @@
+use crate::normalize::SOFT_HYPHEN;
+use crate::{AlephAlphaTokenizer, TokenID};
+use std::io::{self, BufRead};
+use std::ops::Range;
+use std::str;
+
+impl AlephAlphaTokenizer {
+    // the end of the words of `text` that the rest of the stream can't
+    // change: the last word may continue in the next chunk, and with
+    // `rejoin_hyphenation`, so may hyphenated words before it
+    pub(crate) fn complete_words_end(&self, text: &str) -> usize {
+        let is_whitespace = |c: char| self.options.whitespace.is_whitespace(c);
+        let mut end = text
+            .char_indices()
+            .rev()
+            .find(|&(_, c)| is_whitespace(c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        if self.options.rejoin_hyphenation {
+            loop {
+                let word = text[..end].trim_end_matches(is_whitespace);
+                if !word.ends_with(['-', SOFT_HYPHEN]) {
+                    break;
+                }
+                end = word.rfind(is_whitespace).map_or(0, |i| {
+                    i + word[i..].chars().next().map_or(0, char::len_utf8)
+                });
+            }
+        }
+        end
+    }
+
+    /// Tokenizes the UTF-8 text of a reader chunk by chunk, e.g. to tokenize
+    /// files too large to read into memory.
+    ///
+    /// The `sink` gets the tokens of each chunk, and their byte ranges
+    /// within the whole stream. Words that straddle a chunk boundary are
+    /// tokenized with the next chunk, so the tokens are the same as those of
+    /// [`tokens_into`](AlephAlphaTokenizer::tokens_into) on the whole text:
+    /// `[CLS]` and `[SEP]` (as configured) start and end the stream, not
+    /// each chunk. The chunks are those of the reader's buffer, extended
+    /// to the end of the last complete word, so a text without whitespace
+    /// is kept in memory until its end.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading fails or the text is not valid UTF-8, after giving
+    /// the tokens before the error to the `sink`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use std::fs::File;
+    /// use std::io::{BufReader, ErrorKind};
+    ///
+    /// let path = std::env::temp_dir().join("aat-stream-corpus.txt");
+    /// let text = "Ich esse Steak.\nDer Hund spielt im Garten.  \n\
+    ///     Wie definiert die Bundesregierung Clans und Clankriminalität?\n\
+    ///     Gibt es genügend Impfstoff gegen FSME angesichts der steigenden Infektionszahlen?\n\
+    ///     Grüße aus dem Kranken-\nhaus ☃ Supercalifragilisticexpialidocious\n";
+    /// std::fs::write(&path, text).unwrap();
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let hyphenating = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .rejoin_hyphenation(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let (mut ids, mut ranges) = (Vec::<u32>::new(), Vec::new());
+    /// for tokenizer in &[&tokenizer, &hyphenating] {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     // small buffers split words and characters
+    ///     for &capacity in &[1, 2, 3, 7, 64, 8192] {
+    ///         let reader = BufReader::with_capacity(capacity, File::open(&path).unwrap());
+    ///         let (mut stream_ids, mut stream_ranges) = (Vec::new(), Vec::new());
+    ///         tokenizer
+    ///             .tokens_from_reader(reader, |ids: &[u32], ranges: &[_]| {
+    ///                 stream_ids.extend_from_slice(ids);
+    ///                 stream_ranges.extend_from_slice(ranges);
+    ///             })
+    ///             .unwrap();
+    ///         assert_eq!((&ids, &ranges), (&stream_ids, &stream_ranges), "{}", capacity);
+    ///     }
+    /// }
+    ///
+    /// let mut chunks = Vec::new();
+    /// let error = tokenizer
+    ///     .tokens_from_reader(&b"Ich esse \xff"[..], |ids: &[u32], _: &[_]| chunks.push(ids.len()))
+    ///     .err()
+    ///     .unwrap();
+    /// assert_eq!(ErrorKind::InvalidData, error.kind());
+    /// assert_eq!(vec![4], chunks); // [CLS] Ich es ##se
+    /// ```
+    pub fn tokens_from_reader<R: BufRead, T: TokenID>(
+        &self,
+        mut reader: R,
+        mut sink: impl FnMut(&[T], &[Range<usize>]),
+    ) -> io::Result<()> {
+        let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+        self.add_prefix(&mut ids, &mut ranges);
+        // the decoded text after the last complete word, the bytes of a char
+        // split by the chunk boundary, and their position in the stream
+        let (mut text, mut partial, mut pos) = (String::new(), Vec::new(), 0);
+        let mut last_end = 0;
+        loop {
+            let chunk = reader.fill_buf()?;
+            let done = chunk.is_empty();
+            partial.extend_from_slice(chunk);
+            let len = chunk.len();
+            reader.consume(len);
+            let valid = match str::from_utf8(&partial) {
+                Ok(valid) => valid,
+                Err(e) if e.error_len().is_none() && !done => {
+                    str::from_utf8(&partial[..e.valid_up_to()]).unwrap()
+                }
+                Err(e) => {
+                    // tokenize the text before the error anyway
+                    text.push_str(str::from_utf8(&partial[..e.valid_up_to()]).unwrap());
+                    let end = self.complete_words_end(&text);
+                    self.stream_words(&text[..end], pos, &mut ids, &mut ranges);
+                    if !ids.is_empty() {
+                        sink(&ids, &ranges);
+                    }
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+            };
+            text.push_str(valid);
+            let valid_len = valid.len();
+            partial.drain(..valid_len);
+            let end = if done {
+                text.len()
+            } else {
+                self.complete_words_end(&text)
+            };
+            self.stream_words(&text[..end], pos, &mut ids, &mut ranges);
+            text.drain(..end);
+            pos += end;
+            if let Some(range) = ranges.last() {
+                last_end = range.end;
+            }
+            if done && self.options.add_suffix {
+                if let Some(id) = self.suffix {
+                    ids.push(T::coerce(u64::from(id)));
+                    ranges.push(last_end..last_end);
+                }
+            }
+            if !ids.is_empty() {
+                sink(&ids, &ranges);
+                ids.clear();
+                ranges.clear();
+            }
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    // tokenizes the words of `text`, which starts at `pos` in the stream
+    fn stream_words<T: TokenID>(
+        &self,
+        text: &str,
+        pos: usize,
+        ids: &mut Vec<T>,
+        ranges: &mut Vec<Range<usize>>,
+    ) {
+        let first = ranges.len();
+        self.words_into(text, 0, ids, ranges, None);
+        for range in &mut ranges[first..] {
+            *range = range.start + pos..range.end + pos;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Matching words against a vocabulary that marks the tokens ending a word
+//! with a suffix (e.g. `er</w>`) instead of those continuing it, see
+//! [`MarkerScheme::EndOfWordSuffix`](crate::MarkerScheme::EndOfWordSuffix).
+//!
+//! The followers are the tokens that end a word (without the suffix), and
+//! the starters are those that don't, so a word is some starters followed by
+//! a follower that reaches its end.
+
+use crate::matcher::{Matchers, PrefixMatcher};
+use crate::{AlephAlphaTokenizer, IdSink, RangeSink, SegmentationMode, TokenID};
+use std::ops::Range;
+
+impl AlephAlphaTokenizer {
+    // tokenize a word into tokens that don't end a word followed by one that
+    // does, and tell whether the vocabulary doesn't cover it, in which case
+    // the word becomes a single `[UNK]`
+    pub(crate) fn tokenize_word_to_end<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+        &self,
+        matchers: Matchers<'_, M>,
+        mode: SegmentationMode,
+        text: &str,
+        range: Range<usize>,
+        token_ids: &mut I,
+        token_ranges: &mut R,
+    ) -> bool {
+        // the special tokens are starters, but are words of their own
+        let word = &text.as_bytes()[range.clone()];
+        if let Some((len, id)) = matchers.starters.longest_prefix(word) {
+            if len == word.len() && self.is_special(id) {
+                token_ids.push(T::coerce(id));
+                token_ranges.push(range);
+                return false;
+            }
+        }
+        let word_index = token_ids.len();
+        let max_tokens = self.options.max_tokens_per_word.unwrap_or(usize::MAX);
+        let covered = if mode == SegmentationMode::Backtracking {
+            backtrack_to_end(matchers, text, range.clone(), token_ids, token_ranges)
+        } else {
+            let (bytes, end) = (text.as_bytes(), range.end);
+            let mut pos = range.start;
+            loop {
+                let rest = &bytes[pos..end];
+                if let Some((len, id)) = matchers.followers.longest_prefix(rest) {
+                    if len == rest.len() {
+                        token_ids.push(T::coerce(id));
+                        token_ranges.push(pos..end);
+                        break true;
+                    }
+                }
+                if token_ids.len() - word_index >= max_tokens {
+                    // there's more to the word than the limit allows
+                    break false;
+                }
+                // the longest token that leaves something to end the word
+                let last = text[pos..end].chars().next_back().map_or(0, char::len_utf8);
+                match matchers.starters.longest_prefix(&rest[..rest.len() - last]) {
+                    Some((len, id)) => {
+                        token_ids.push(T::coerce(id));
+                        token_ranges.push(pos..pos + len);
+                        pos += len;
+                    }
+                    None => break false,
+                }
+            }
+        };
+        if !covered || token_ids.len() - word_index > max_tokens {
+            token_ids.truncate_to(word_index);
+            token_ranges.truncate_to(word_index);
+            self.push_unknown(text, range, token_ids, token_ranges);
+            return true;
+        }
+        false
+    }
+}
+
+// find the segmentation of a word with the fewest tokens, trying every match
+// instead of only the longest, and tell whether there is one
+fn backtrack_to_end<M: PrefixMatcher, T: TokenID, I: IdSink<T>, R: RangeSink>(
+    matchers: Matchers<'_, M>,
+    text: &str,
+    range: Range<usize>,
+    token_ids: &mut I,
+    token_ranges: &mut R,
+) -> bool {
+    let word = &text.as_bytes()[range.clone()];
+    let mut matches = Vec::new();
+    // the fewest tokens covering the word from each position, as (count,
+    // length, id) of the first of them; on a tie, the longer token wins, as
+    // in greedy matching
+    let mut best: Vec<Option<(usize, usize, u64)>> = vec![None; word.len() + 1];
+    for pos in (0..word.len()).rev() {
+        let rest = &word[pos..];
+        best[pos] = match matchers.followers.longest_prefix(rest) {
+            // nothing beats ending the word right away
+            Some((len, id)) if len == rest.len() => Some((1, len, id)),
+            _ => {
+                matchers.starters.find_prefixes(rest, &mut matches);
+                matches
+                    .iter()
+                    .filter(|&&(len, _)| len < rest.len())
+                    .filter_map(|&(len, id)| {
+                        best[pos + len].map(|(count, _, _)| (count + 1, len, id))
+                    })
+                    .min_by_key(|&(count, len, _)| (count, usize::MAX - len))
+            }
+        };
+    }
+    if best[0].is_none() {
+        return false;
+    }
+    let mut pos = 0;
+    while pos < word.len() {
+        let (_, len, id) = best[pos].expect("the rest of the word is covered");
+        token_ids.push(T::coerce(id));
+        token_ranges.push(range.start + pos..range.start + pos + len);
+        pos += len;
+    }
+    true
+}
@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+/// The texts of the tokens in one string, so a vocabulary takes two
+/// allocations instead of one per token, or only one if the string is
+/// embedded in the binary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TokenTexts {
+    text: Cow<'static, str>,
+    // the end of each token within `text`, the start is the previous end
+    ends: Vec<u32>,
+}
+
+impl TokenTexts {
+    // the texts with the given token ends within the text, or the first
+    // token whose end is before its start or not on a char boundary
+    pub(crate) fn from_parts(text: Cow<'static, str>, ends: Vec<u32>) -> Result<Self, usize> {
+        let mut start = 0;
+        for (id, &end) in ends.iter().enumerate() {
+            let end = end as usize;
+            if end < start || !text.is_char_boundary(end) {
+                return Err(id);
+            }
+            start = end;
+        }
+        Ok(TokenTexts { text, ends })
+    }
+
+    // empty texts with room for `bytes` of text and `tokens` tokens
+    pub(crate) fn with_capacity(bytes: usize, tokens: usize) -> Self {
+        TokenTexts {
+            text: Cow::Owned(String::with_capacity(bytes)),
+            ends: Vec::with_capacity(tokens),
+        }
+    }
+
+    // the text of all tokens and the end of each one within it
+    pub(crate) fn parts(&self) -> (&str, &[u32]) {
+        (&self.text, &self.ends)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    // adds the text of the next token, panicking if the texts get longer
+    // than 4 GiB
+    pub(crate) fn push(&mut self, token: &str) {
+        assert!(self.fits(token), "the token texts exceed 4 GiB");
+        self.text.to_mut().push_str(token);
+        self.ends.push(self.text.len() as u32);
+    }
+
+    // whether the token can be added without the ends overflowing
+    pub(crate) fn fits(&self, token: &str) -> bool {
+        (self.text.len() + token.len()) as u64 <= u64::from(u32::MAX)
+    }
+
+    pub(crate) fn get(&self, id: usize) -> Option<&str> {
+        let end = *self.ends.get(id)? as usize;
+        let start = id.checked_sub(1).map_or(0, |prev| self.ends[prev] as usize);
+        Some(&self.text[start..end])
+    }
+
+    pub(crate) fn iter(&self) -> impl ExactSizeIterator<Item = &str> + DoubleEndedIterator + '_ {
+        (0..self.len()).map(move |id| &self[id])
+    }
+}
+
+impl Index<usize> for TokenTexts {
+    type Output = str;
+
+    fn index(&self, id: usize) -> &str {
+        match self.get(id) {
+            Some(token) => token,
+            None => panic!(
+                "token id {} is out of range for a vocabulary of {} tokens",
+                id,
+                self.len()
+            ),
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for TokenTexts {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, tokens: I) {
+        for token in tokens {
+            self.push(token);
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for TokenTexts {
+    fn from_iter<I: IntoIterator<Item = S>>(tokens: I) -> Self {
+        let mut texts = TokenTexts::default();
+        for token in tokens {
+            texts.push(token.as_ref());
+        }
+        texts
+    }
+}
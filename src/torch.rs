@@ -0,0 +1,63 @@
+use crate::AlephAlphaTokenizer;
+use tch::{Device, Tensor};
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a batch of texts into the `input_ids` and `attention_mask`
+    /// tensors of a model, with the `torch` feature.
+    ///
+    /// Both are int64 tensors of shape `[batch, seq]` on the CPU, padded and
+    /// truncated like [`encode_batch_padded`]. Use
+    /// [`encode_to_tensors_on`](AlephAlphaTokenizer::encode_to_tensors_on)
+    /// to place them on another device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `[CLS]` and `[SEP]` don't fit into `max_length`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# #[cfg(feature = "torch")] {
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    /// use std::convert::TryFrom;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let texts = ["Ich esse Steak.", "Der Hund spielt im Garten.", ""];
+    /// let (input_ids, attention_mask) = tokenizer.encode_to_tensors(&texts, None);
+    /// let seq_len = input_ids.size()[1] as usize;
+    /// assert_eq!(vec![3, seq_len as i64], attention_mask.size());
+    /// assert_eq!(tch::Kind::Int64, input_ids.kind());
+    ///
+    /// let (mut ids, mut ranges, mut attns) = (Vec::<i64>::new(), Vec::new(), Vec::<i64>::new());
+    /// for (i, text) in texts.iter().enumerate() {
+    ///     tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    ///     ids.resize(seq_len, 0);
+    ///     AlephAlphaTokenizer::attentions_into(&ids, &mut attns);
+    ///     assert_eq!(ids, Vec::<i64>::try_from(&input_ids.get(i as i64)).unwrap());
+    ///     assert_eq!(attns, Vec::<i64>::try_from(&attention_mask.get(i as i64)).unwrap());
+    /// }
+    ///
+    /// let (input_ids, _) = tokenizer.encode_to_tensors(&texts, Some(4));
+    /// assert_eq!(vec![3, 4], input_ids.size());
+    ///# }
+    /// ```
+    ///
+    /// [`encode_batch_padded`]: AlephAlphaTokenizer::encode_batch_padded
+    pub fn encode_to_tensors(&self, texts: &[&str], max_length: Option<usize>) -> (Tensor, Tensor) {
+        self.encode_to_tensors_on(texts, max_length, Device::Cpu)
+    }
+
+    /// Like [`encode_to_tensors`](AlephAlphaTokenizer::encode_to_tensors),
+    /// but places the tensors on the given `device`.
+    pub fn encode_to_tensors_on(
+        &self,
+        texts: &[&str],
+        max_length: Option<usize>,
+        device: Device,
+    ) -> (Tensor, Tensor) {
+        let batch = self.encode_batch_padded::<i64>(texts, max_length);
+        let shape = [texts.len() as i64, batch.seq_len as i64];
+        let tensor = |values: &[i64]| Tensor::from_slice(values).view(shape).to_device(device);
+        (tensor(&batch.ids), tensor(&batch.attention_mask))
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The settings of a [`VocabTrainer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrainerConfig {
+    /// The number of tokens to aim for, including the special tokens. The
+    /// alphabet is always kept whole, so the vocabulary can be larger, and
+    /// it is smaller if the corpus runs out of pairs to merge.
+    pub vocab_size: usize,
+    /// How often a pair of tokens must occur in the corpus to be merged
+    pub min_frequency: u64,
+    /// The special tokens, which come first in the vocabulary, in this order
+    pub special_tokens: Vec<String>,
+    /// The number of distinct characters to keep, the most frequent first;
+    /// words with any other character are left out of the training
+    pub limit_alphabet: Option<usize>,
+    /// The prefix of the tokens that continue a word
+    pub continuing_subword_prefix: String,
+}
+
+impl Default for TrainerConfig {
+    fn default() -> Self {
+        TrainerConfig {
+            vocab_size: 30000,
+            min_frequency: 0,
+            special_tokens: ["[PAD]", "[UNK]", "[CLS]", "[SEP]", "[MASK]"]
+                .iter()
+                .map(|token| token.to_string())
+                .collect(),
+            limit_alphabet: None,
+            continuing_subword_prefix: "##".to_string(),
+        }
+    }
+}
+
+// the tokens so far, where the trained ones also get consecutive ids that
+// skip the special tokens
+struct Vocab {
+    tokens: Vec<String>,
+    ids: HashMap<String, u32>,
+    symbols: Vec<String>,
+}
+
+impl Vocab {
+    // adds a trained token unless it exists, returning its id
+    fn add(&mut self, token: String) -> u32 {
+        if let Some(&id) = self.ids.get(&token) {
+            return id;
+        }
+        let id = self.symbols.len() as u32;
+        if !self.tokens.contains(&token) {
+            self.tokens.push(token.clone());
+        }
+        self.ids.insert(token.clone(), id);
+        self.symbols.push(token);
+        id
+    }
+}
+
+// a distinct word of the corpus as the ids of its current tokens
+struct Word {
+    symbols: Vec<u32>,
+    count: u64,
+}
+
+/// Builds a WordPiece vocabulary from a corpus.
+///
+/// The words are split at whitespace. Each word starts out as its
+/// characters, where all but the first one get the continuing subword
+/// prefix. Then the pair of adjacent tokens with the best likelihood score
+/// (the pair's count divided by the product of the counts of its tokens) is
+/// merged into a new token, until the vocabulary is the configured size.
+/// Ties go to the more frequent pair, then to the pair of older tokens, so
+/// the vocabulary only depends on the words and their counts.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, TrainerConfig, VocabTrainer};
+///
+/// let corpus = "Der Hund spielt im Garten.\n\
+///               Die Kinder spielen im Kindergarten.\n\
+///               Der Garten des Kindergartens ist groß.\n\
+///               Im Garten spielt der Hund mit den Kindern.";
+/// let mut trainer = VocabTrainer::new(TrainerConfig {
+///     vocab_size: 80,
+///     ..TrainerConfig::default()
+/// });
+/// let tokens = trainer.train(corpus.lines().map(String::from));
+/// assert_eq!(80, tokens.len());
+/// assert_eq!(&["[PAD]", "[UNK]", "[CLS]", "[SEP]", "[MASK]"], &tokens[..5]);
+/// assert!(tokens.contains(&"Garten".to_string()));
+///
+/// // the tokens load as they are and cover every word of the corpus
+/// let tokenizer = AlephAlphaTokenizer::from_tokens(tokens.clone()).unwrap();
+/// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+/// for line in corpus.lines() {
+///     tokenizer.tokens_into(line, &mut ids, &mut ranges, None);
+///     assert!(!ids.contains(&tokenizer.unk_id()), "{}", line);
+/// }
+/// tokenizer.tokens_into("Der Garten des Kindergartens", &mut ids, &mut ranges, None);
+/// assert_eq!(
+///     vec!["[CLS]", "Der", "Garten", "des", "Kindergart", "##ens", "[SEP]"],
+///     tokenizer.texts_of(&ids)
+/// );
+///
+/// // training again on the same corpus gives the same vocabulary
+/// let mut again = VocabTrainer::new(TrainerConfig {
+///     vocab_size: 80,
+///     ..TrainerConfig::default()
+/// });
+/// assert_eq!(tokens, again.train(corpus.lines().rev().map(String::from)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct VocabTrainer {
+    config: TrainerConfig,
+    word_counts: HashMap<String, u64>,
+}
+
+impl VocabTrainer {
+    /// Creates a trainer with the given settings.
+    pub fn new(config: TrainerConfig) -> Self {
+        VocabTrainer {
+            config,
+            word_counts: HashMap::new(),
+        }
+    }
+
+    /// Counts the words of the lines and trains the vocabulary on all words
+    /// counted so far, so a corpus can be fed in parts.
+    ///
+    /// The tokens are returned in id order, ready for
+    /// [`AlephAlphaTokenizer::from_tokens`](crate::AlephAlphaTokenizer::from_tokens)
+    /// or to be written as a `vocab.txt`, one token per line. Load them with the
+    /// same [`continuing_subword_prefix`](TrainerConfig::continuing_subword_prefix).
+    pub fn train<I: Iterator<Item = String>>(&mut self, word_counts_or_lines: I) -> Vec<String> {
+        for line in word_counts_or_lines {
+            for word in line.split_whitespace() {
+                *self.word_counts.entry(word.to_string()).or_insert(0) += 1;
+            }
+        }
+        let mut vocab = Vocab {
+            tokens: self.config.special_tokens.clone(),
+            ids: HashMap::new(),
+            symbols: Vec::new(),
+        };
+
+        let alphabet = self.alphabet();
+        let prefix = &self.config.continuing_subword_prefix;
+        let mut sorted: Vec<(&String, &u64)> = self.word_counts.iter().collect();
+        sorted.sort();
+        let kept = sorted
+            .into_iter()
+            .filter(|(word, _)| word.chars().all(|c| alphabet.contains(&c)));
+        let initial: Vec<(Vec<String>, u64)> = kept
+            .map(|(word, &count)| {
+                let chars = word.char_indices().map(|(i, c)| {
+                    if i == 0 {
+                        c.to_string()
+                    } else {
+                        format!("{}{}", prefix, c)
+                    }
+                });
+                (chars.collect(), count)
+            })
+            .collect();
+        // the alphabet comes in a fixed order, starters first
+        let mut letters: Vec<&String> = initial.iter().flat_map(|(s, _)| s).collect();
+        letters.sort_by_key(|letter| (letter.starts_with(prefix.as_str()), letter.as_str()));
+        letters.dedup();
+        for letter in letters {
+            vocab.add(letter.clone());
+        }
+        let mut words: Vec<Word> = initial
+            .into_iter()
+            .map(|(letters, count)| Word {
+                symbols: letters.iter().map(|letter| vocab.ids[letter]).collect(),
+                count,
+            })
+            .collect();
+
+        let mut counts = vec![0u64; vocab.symbols.len()];
+        let mut pairs: HashMap<(u32, u32), u64> = HashMap::new();
+        let mut places: HashMap<(u32, u32), HashSet<usize>> = HashMap::new();
+        for (index, word) in words.iter().enumerate() {
+            count_word(word, true, &mut counts, &mut pairs);
+            for pair in word.symbols.windows(2) {
+                places.entry((pair[0], pair[1])).or_default().insert(index);
+            }
+        }
+
+        let min_frequency = self.config.min_frequency.max(1);
+        while vocab.tokens.len() < self.config.vocab_size {
+            let best = pairs
+                .iter()
+                .filter(|&(_, &count)| count >= min_frequency)
+                .map(|(&pair, &count)| {
+                    let score = count as f64
+                        / (counts[pair.0 as usize] as f64 * counts[pair.1 as usize] as f64);
+                    (pair, count, score)
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2).then(a.1.cmp(&b.1)).then(b.0.cmp(&a.0)));
+            let (pair, _, _) = match best {
+                Some(best) => best,
+                None => break,
+            };
+            let (first, second) = (
+                &vocab.symbols[pair.0 as usize],
+                &vocab.symbols[pair.1 as usize],
+            );
+            // the second token continues the word, so it has the prefix
+            let merged = format!("{}{}", first, &second[prefix.len()..]);
+            let id = vocab.add(merged);
+            counts.resize(vocab.symbols.len(), 0);
+            let mut indexes: Vec<usize> = places.remove(&pair).into_iter().flatten().collect();
+            indexes.sort_unstable();
+            for index in indexes {
+                let word = &mut words[index];
+                count_word(word, false, &mut counts, &mut pairs);
+                merge(&mut word.symbols, pair, id);
+                count_word(word, true, &mut counts, &mut pairs);
+                for new in word.symbols.windows(2).filter(|p| p.contains(&id)) {
+                    places.entry((new[0], new[1])).or_default().insert(index);
+                }
+            }
+        }
+        vocab.tokens
+    }
+
+    // gets the characters to train on, the most frequent ones if limited
+    fn alphabet(&self) -> HashSet<char> {
+        let mut chars: BTreeMap<char, u64> = BTreeMap::new();
+        for (word, &count) in &self.word_counts {
+            for c in word.chars() {
+                *chars.entry(c).or_insert(0) += count;
+            }
+        }
+        let mut chars: Vec<(char, u64)> = chars.into_iter().collect();
+        chars.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let limit = self.config.limit_alphabet.unwrap_or(chars.len());
+        chars.into_iter().take(limit).map(|(c, _)| c).collect()
+    }
+}
+
+// adds (or removes) the counts of a word's tokens and
+// of its pairs of adjacent tokens
+fn count_word(word: &Word, add: bool, counts: &mut [u64], pairs: &mut HashMap<(u32, u32), u64>) {
+    let apply = |n: &mut u64| {
+        *n = if add {
+            *n + word.count
+        } else {
+            *n - word.count
+        }
+    };
+    for &symbol in &word.symbols {
+        apply(&mut counts[symbol as usize]);
+    }
+    for pair in word.symbols.windows(2) {
+        let pair = (pair[0], pair[1]);
+        let count = pairs.entry(pair).or_insert(0);
+        apply(count);
+        if *count == 0 {
+            pairs.remove(&pair);
+        }
+    }
+}
+
+// replaces each occurrence of the pair by the merged token
+fn merge(symbols: &mut Vec<u32>, pair: (u32, u32), id: u32) {
+    let mut i = 0;
+    while i + 1 < symbols.len() {
+        if (symbols[i], symbols[i + 1]) == pair {
+            symbols[i] = id;
+            symbols.remove(i + 1);
+        }
+        i += 1;
+    }
+}
@@ -0,0 +1,364 @@
+use crate::{
+    AlephAlphaTokenizer, LiteralMode, SegmentationMode, TokenizerOptions, UnicodeNormalization,
+    UnkMode, UnknownSeparator, WhitespaceMode,
+};
+
+const FEATURES: &[&str] = &[
+    #[cfg(feature = "arrow")]
+    "arrow",
+    #[cfg(feature = "cli")]
+    "cli",
+    #[cfg(feature = "datrie")]
+    "datrie",
+    #[cfg(feature = "ffi")]
+    "ffi",
+    #[cfg(feature = "hub")]
+    "hub",
+    #[cfg(feature = "huggingface")]
+    "huggingface",
+    #[cfg(feature = "json")]
+    "json",
+    #[cfg(feature = "mmap")]
+    "mmap",
+    #[cfg(feature = "ndarray")]
+    "ndarray",
+    #[cfg(feature = "python")]
+    "python",
+    #[cfg(feature = "rand")]
+    "rand",
+    #[cfg(feature = "rayon")]
+    "rayon",
+    #[cfg(feature = "serde")]
+    "serde",
+    #[cfg(feature = "std-fs")]
+    "std-fs",
+    #[cfg(feature = "tokio")]
+    "tokio",
+    #[cfg(feature = "torch")]
+    "torch",
+    #[cfg(feature = "unicode-normalization")]
+    "unicode-normalization",
+];
+
+/// Gets the names of the optional features this crate was built with.
+///
+/// # Examples
+///
+/// ```
+/// use aleph_alpha_tokenizer::{features, has_feature};
+///
+/// assert_eq!(cfg!(feature = "arrow"), has_feature("arrow"));
+/// assert_eq!(cfg!(feature = "cli"), has_feature("cli"));
+/// assert_eq!(cfg!(feature = "datrie"), has_feature("datrie"));
+/// assert_eq!(cfg!(feature = "ffi"), has_feature("ffi"));
+/// assert_eq!(cfg!(feature = "hub"), has_feature("hub"));
+/// assert_eq!(cfg!(feature = "huggingface"), has_feature("huggingface"));
+/// assert_eq!(cfg!(feature = "rand"), has_feature("rand"));
+/// assert_eq!(cfg!(feature = "rayon"), has_feature("rayon"));
+/// assert_eq!(cfg!(feature = "serde"), has_feature("serde"));
+/// assert_eq!(cfg!(feature = "std-fs"), has_feature("std-fs"));
+/// assert_eq!(cfg!(feature = "json"), has_feature("json"));
+/// assert_eq!(cfg!(feature = "mmap"), has_feature("mmap"));
+/// assert_eq!(cfg!(feature = "ndarray"), has_feature("ndarray"));
+/// assert_eq!(cfg!(feature = "python"), has_feature("python"));
+/// assert_eq!(cfg!(feature = "tokio"), has_feature("tokio"));
+/// assert_eq!(cfg!(feature = "torch"), has_feature("torch"));
+/// assert_eq!(
+///     cfg!(feature = "unicode-normalization"),
+///     has_feature("unicode-normalization")
+/// );
+/// assert!(!has_feature("simd"));
+/// assert_eq!(
+///     [
+///         cfg!(feature = "arrow"),
+///         cfg!(feature = "cli"),
+///         cfg!(feature = "datrie"),
+///         cfg!(feature = "ffi"),
+///         cfg!(feature = "hub"),
+///         cfg!(feature = "huggingface"),
+///         cfg!(feature = "json"),
+///         cfg!(feature = "mmap"),
+///         cfg!(feature = "ndarray"),
+///         cfg!(feature = "python"),
+///         cfg!(feature = "rand"),
+///         cfg!(feature = "rayon"),
+///         cfg!(feature = "serde"),
+///         cfg!(feature = "std-fs"),
+///         cfg!(feature = "tokio"),
+///         cfg!(feature = "torch"),
+///         cfg!(feature = "unicode-normalization"),
+///     ]
+///     .iter()
+///     .filter(|&&enabled| enabled)
+///     .count(),
+///     features().len()
+/// );
+/// ```
+pub fn features() -> &'static [&'static str] {
+    FEATURES
+}
+
+/// Determines whether this crate was built with the given optional feature.
+pub fn has_feature(name: &str) -> bool {
+    FEATURES.contains(&name)
+}
+
+/// The version of this crate and the vocabulary of a tokenizer, as returned
+/// by [`AlephAlphaTokenizer::version_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// The version of this crate
+    pub version: &'static str,
+    /// The optional features this crate was built with
+    pub features: &'static [&'static str],
+    /// The number of tokens in the vocabulary
+    pub vocab_size: usize,
+    /// A hash of the vocabulary, which is stable between versions and
+    /// platforms
+    pub vocab_fingerprint: u64,
+}
+
+impl AlephAlphaTokenizer {
+    /// Gets the crate version and features as well as a fingerprint of the
+    /// vocabulary, e.g. to log which tokenizer a service runs with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let info = tokenizer.version_info();
+    /// assert_eq!(env!("CARGO_PKG_VERSION"), info.version);
+    /// assert_eq!(aleph_alpha_tokenizer::features(), info.features);
+    /// let vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// assert_eq!(vocab.lines().count(), info.vocab_size);
+    ///
+    /// let foo = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nfoo\n").unwrap();
+    /// let bar = AlephAlphaTokenizer::from_vocab_bytes(b"[UNK]\nbar\n").unwrap();
+    /// assert_ne!(foo.version_info().vocab_fingerprint, bar.version_info().vocab_fingerprint);
+    /// ```
+    pub fn version_info(&self) -> VersionInfo {
+        VersionInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            features: FEATURES,
+            vocab_size: self.vocab_size(),
+            vocab_fingerprint: self.vocab_fingerprint(),
+        }
+    }
+
+    /// Gets a hash of everything that determines the tokens of a text: the
+    /// vocabulary, the ids of the special tokens, the continuing subword
+    /// prefix and the options, e.g. as a key for cached tokenizations.
+    ///
+    /// The hash is stable between processes and platforms, as it depends on
+    /// neither the byte order nor the order of a `HashMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, SegmentationMode, UnkMode};
+    ///
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab("vocab.txt").unwrap();
+    /// let same = AlephAlphaTokenizer::from_vocab_bytes(&std::fs::read("vocab.txt").unwrap()).unwrap();
+    /// assert_eq!(tokenizer.fingerprint(), same.fingerprint());
+    /// assert!(tokenizer.is_compatible_with(&same));
+    /// // the same on every platform
+    /// assert_eq!(0x1e3d_dd0c_bfcb_53ec, tokenizer.fingerprint());
+    ///
+    /// // another token
+    /// let mut vocab = std::fs::read_to_string("vocab.txt").unwrap();
+    /// vocab.push_str("Supercalifragilistic\n");
+    /// let more = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// assert_ne!(tokenizer.fingerprint(), more.fingerprint());
+    ///
+    /// // another unknown token, with the same vocabulary
+    /// let unk = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .unk_token("[MASK]")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(tokenizer.version_info().vocab_fingerprint, unk.version_info().vocab_fingerprint);
+    /// assert_ne!(tokenizer.fingerprint(), unk.fingerprint());
+    /// assert!(!tokenizer.is_compatible_with(&unk));
+    ///
+    /// // another option
+    /// let lowercase = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .lowercase(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_ne!(tokenizer.fingerprint(), lowercase.fingerprint());
+    /// let backtracking = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .segmentation_mode(SegmentationMode::Backtracking)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_ne!(tokenizer.fingerprint(), backtracking.fingerprint());
+    /// let per_char = AlephAlphaTokenizer::builder()
+    ///     .vocab_path("vocab.txt")
+    ///     .unk_mode(UnkMode::PerChar)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_ne!(tokenizer.fingerprint(), per_char.fingerprint());
+    /// assert_ne!(backtracking.fingerprint(), per_char.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        // list the options field by field, so a new one can't be forgotten
+        let TokenizerOptions {
+            add_prefix,
+            add_suffix,
+            rejoin_hyphenation,
+            max_chars_per_word,
+            max_tokens_per_word,
+            lowercase,
+            leading_prefix_as_follower,
+            split_punctuation,
+            byte_fallback,
+            segmentation_mode,
+            unk_mode,
+            strip_accents,
+            normalize,
+            clean_text,
+            ref clean_chars,
+            parse_special_tokens,
+            ref whitespace,
+            literal_hash_hash,
+            ref fallback_separators,
+            unknown_separator,
+        } = self.options;
+        // numbers are hashed as little-endian `u64`s, and `None` as a `0`
+        // tag where `Some` has a `1` before the value
+        let number = |hash, n: u64| fnv1a(hash, &n.to_le_bytes());
+        let option = |hash, n: Option<u64>| match n {
+            Some(n) => number(number(hash, 1), n),
+            None => number(hash, 0),
+        };
+        let mut hash = number(self.vocab_fingerprint(), self.special_tokens.len() as u64);
+        for &id in self.special_tokens.iter() {
+            hash = number(hash, id);
+        }
+        hash = number(hash, u64::from(self.unk_id));
+        for id in &[self.prefix, self.suffix, self.pad_id, self.mask_id] {
+            hash = option(hash, id.map(u64::from));
+        }
+        hash = number(hash, self.continuing_subword_prefix.len() as u64);
+        hash = fnv1a(hash, self.continuing_subword_prefix.as_bytes());
+        // only a suffix changes the fingerprint, so earlier ones stay valid
+        if !self.end_of_word_suffix.is_empty() {
+            hash = number(hash, self.end_of_word_suffix.len() as u64);
+            hash = fnv1a(hash, self.end_of_word_suffix.as_bytes());
+        }
+        for &flag in &[
+            add_prefix,
+            add_suffix,
+            rejoin_hyphenation,
+            lowercase,
+            leading_prefix_as_follower,
+            split_punctuation,
+            byte_fallback,
+        ] {
+            hash = number(hash, u64::from(flag));
+        }
+        // the default mode adds nothing, so the fingerprints from before
+        // there were modes stay the same
+        match segmentation_mode {
+            SegmentationMode::Greedy => {}
+            SegmentationMode::Backtracking => hash = number(hash, 1),
+        }
+        match unk_mode {
+            UnkMode::WholeWord => {}
+            UnkMode::PerChar => hash = number(hash, 2),
+        }
+        if strip_accents {
+            hash = number(hash, 3);
+        }
+        match normalize {
+            None => {}
+            Some(UnicodeNormalization::Nfc) => hash = number(hash, 4),
+            Some(UnicodeNormalization::Nfkc) => hash = number(hash, 5),
+        }
+        // the characters to clean only matter when cleaning
+        if clean_text {
+            hash = number(number(hash, 6), clean_chars.len() as u64);
+            for &(c, action) in clean_chars {
+                hash = number(number(hash, u64::from(c)), action as u64);
+            }
+        }
+        if parse_special_tokens {
+            hash = number(hash, 7);
+        }
+        match whitespace {
+            WhitespaceMode::Unicode => {}
+            WhitespaceMode::AsciiOnly => hash = number(hash, 8),
+            WhitespaceMode::Custom(chars) => {
+                // the same characters in any order split the same
+                let mut chars = chars.clone();
+                chars.sort_unstable();
+                chars.dedup();
+                hash = number(number(hash, 9), chars.len() as u64);
+                for c in chars {
+                    hash = number(hash, u64::from(c));
+                }
+            }
+        }
+        match literal_hash_hash {
+            LiteralMode::Text => {}
+            LiteralMode::Unk => hash = number(hash, 10),
+        }
+        if let Some(max) = max_tokens_per_word {
+            hash = number(number(hash, 11), max as u64);
+        }
+        // the separators only matter when there are some
+        if !fallback_separators.is_empty() {
+            // the same characters in any order split the same
+            let mut separators = fallback_separators.clone();
+            separators.sort_unstable();
+            separators.dedup();
+            hash = number(number(hash, 13), separators.len() as u64);
+            for c in separators {
+                hash = number(hash, u64::from(c));
+            }
+            match unknown_separator {
+                UnknownSeparator::Unk => {}
+                UnknownSeparator::Drop => hash = number(hash, 14),
+            }
+        }
+        let suppressed = self.suppressed_ids();
+        if !suppressed.is_empty() {
+            hash = number(number(hash, 12), suppressed.len() as u64);
+            for &id in suppressed {
+                hash = number(hash, id);
+            }
+        }
+        option(hash, max_chars_per_word.map(|max| max as u64))
+    }
+
+    /// Determines whether the other tokenizer gives the same tokens for each
+    /// text, i.e. whether both have the same
+    /// [`fingerprint`](AlephAlphaTokenizer::fingerprint).
+    pub fn is_compatible_with(&self, other: &AlephAlphaTokenizer) -> bool {
+        self.fingerprint() == other.fingerprint()
+    }
+
+    // FNV-1a over the tokens, each terminated by a newline
+    fn vocab_fingerprint(&self) -> u64 {
+        let mut hash = FNV_OFFSET;
+        for token in self.tokens.iter() {
+            hash = fnv1a(fnv1a(hash, token.as_bytes()), b"\n");
+        }
+        hash
+    }
+}
+
+/// The initial state of [`fnv1a`].
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Continues an FNV-1a hash over the bytes, starting from [`FNV_OFFSET`].
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,226 @@
+use crate::{AlephAlphaTokenizer, EncodeError, TokenID};
+use std::ops::Range;
+
+// the ids and ranges of each window
+type Windows<T> = Vec<(Vec<T>, Vec<Range<usize>>)>;
+
+impl AlephAlphaTokenizer {
+    /// Tokenizes a text that may be longer than a model's maximum sequence
+    /// length into overlapping windows of at most `max_len` tokens each.
+    ///
+    /// Each window is wrapped in the `[CLS]` / `[SEP]` tokens (as far as
+    /// they are added at all) and starts at least `stride` tokens before the
+    /// end of the previous window. Windows only start and end between words,
+    /// so the follower tokens of a word stay with their starter. Only a word
+    /// with more tokens than fit into a window is split. The last window may
+    /// be shorter than `max_len`.
+    ///
+    /// The ranges of all windows are byte ranges within `text`. The `[CLS]`
+    /// token of a window gets an empty range at the start of its first
+    /// token, the `[SEP]` token an empty range at the end of its last token.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `max_len` leaves no room for any token besides the
+    /// `[CLS]` and `[SEP]` tokens, see
+    /// [`try_tokens_into_windows`](AlephAlphaTokenizer::try_tokens_into_windows)
+    /// for a variant that fails instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples::{self, CLS, SEP};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ein interessantes Beispiel Ich esse Steak. Batman";
+    /// let windows = tokenizer.tokens_into_windows::<u64>(text, 9, 2);
+    /// let texts: Vec<_> = windows.iter().map(|(ids, _)| tokenizer.texts_of(ids).join(" ")).collect();
+    /// assert_eq!(
+    ///     vec![
+    ///         "[CLS] Ein interess ##antes Bei ##spiel Ich [SEP]",
+    ///         "[CLS] Bei ##spiel Ich es ##se [SEP]",
+    ///         "[CLS] es ##se Ste ##ak ##. Bat ##man [SEP]",
+    ///     ],
+    ///     texts
+    /// );
+    ///
+    /// // each window overlaps the previous one by at least 2 tokens
+    /// for pair in windows.windows(2) {
+    ///     let (previous, next) = (&pair[0].1, &pair[1].1);
+    ///     let overlap = previous.iter().filter(|&r| !r.is_empty() && next.contains(r)).count();
+    ///     assert!(overlap >= 2, "{:?} {:?}", previous, next);
+    /// }
+    ///
+    /// // every token of `tokens_into` appears in some window
+    /// let (mut ids, mut ranges) = (Vec::<u64>::new(), Vec::new());
+    /// tokenizer.tokens_into(text, &mut ids, &mut ranges, None);
+    /// for (id, range) in ids.iter().zip(&ranges) {
+    ///     if *id != CLS && *id != SEP {
+    ///         assert!(windows.iter().any(|(window_ids, window_ranges)| window_ids
+    ///             .iter()
+    ///             .zip(window_ranges)
+    ///             .any(|(i, r)| i == id && r == range)));
+    ///     }
+    /// }
+    /// for (ids, ranges) in &windows {
+    ///     assert!(ids.len() <= 9);
+    ///     assert_eq!((Some(&CLS), Some(&SEP)), (ids.first(), ids.last()));
+    ///     assert_eq!(ids.len(), ranges.len());
+    /// }
+    /// assert_eq!(&[18..18, 18..21, 21..26, 27..30, 31..33, 33..35, 35..35], &windows[1].1[..]);
+    ///
+    /// // a text that fits gives a single window like `tokens_into`
+    /// let short = tokenizer.tokens_into_windows::<u64>("Ich esse", 9, 2);
+    /// tokenizer.tokens_into("Ich esse", &mut ids, &mut ranges, None);
+    /// assert_eq!(vec![(ids, ranges)], short);
+    /// assert_eq!(vec![(vec![CLS, SEP], vec![0..0, 0..0])], tokenizer.tokens_into_windows(" ", 9, 2));
+    /// ```
+    pub fn tokens_into_windows<T: TokenID>(
+        &self,
+        text: &str,
+        max_len: usize,
+        stride: usize,
+    ) -> Vec<(Vec<T>, Vec<Range<usize>>)> {
+        self.windows(text, max_len, stride)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`tokens_into_windows`](AlephAlphaTokenizer::tokens_into_windows),
+    /// but fails instead of panicking if `max_len` leaves no room for any
+    /// token besides `[CLS]` and `[SEP]`, and like
+    /// [`try_tokens_into`](AlephAlphaTokenizer::try_tokens_into) instead of
+    /// silently wrapping if a token id doesn't fit into `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aleph_alpha_tokenizer::examples;
+    /// use aleph_alpha_tokenizer::{AlephAlphaTokenizer, EncodeError};
+    ///
+    /// let tokenizer = examples::tokenizer();
+    /// let text = "Ein interessantes Beispiel Ich esse Steak. Batman";
+    /// assert_eq!(
+    ///     Ok(tokenizer.tokens_into_windows::<u64>(text, 9, 2)),
+    ///     tokenizer.try_tokens_into_windows(text, 9, 2)
+    /// );
+    /// // a window of 3 tokens has room for one token besides [CLS] and [SEP]
+    /// let windows = tokenizer.try_tokens_into_windows::<u64>("Ich esse", 3, 0).unwrap();
+    /// let texts: Vec<_> = windows.iter().map(|(ids, _)| tokenizer.texts_of(ids).join(" ")).collect();
+    /// assert_eq!(vec!["[CLS] Ich [SEP]", "[CLS] es [SEP]", "[CLS] ##se [SEP]"], texts);
+    ///
+    /// for max_len in 0..3 {
+    ///     assert_eq!(
+    ///         Err(EncodeError::WindowTooSmall { max_len }),
+    ///         tokenizer.try_tokens_into_windows::<u64>(text, max_len, 0)
+    ///     );
+    /// }
+    /// assert_eq!(
+    ///     "a window of 2 tokens has no room besides the special tokens",
+    ///     EncodeError::WindowTooSmall { max_len: 2 }.to_string()
+    /// );
+    ///
+    /// let mut vocab = String::from("[PAD]\n[UNK]\n");
+    /// for i in 0..70000 {
+    ///     vocab.push_str(&format!("t{}\n", i));
+    /// }
+    /// let tokenizer = AlephAlphaTokenizer::from_vocab_bytes(vocab.as_bytes()).unwrap();
+    /// let windows = tokenizer.try_tokens_into_windows::<u16>("t0 t65533", 2, 0).unwrap();
+    /// assert_eq!(vec![(vec![2, 65535], vec![0..2, 3..9])], windows);
+    /// assert_eq!(
+    ///     Err(EncodeError::IdOverflow { id: 65536 }),
+    ///     tokenizer.try_tokens_into_windows::<u16>("t0 t65534", 2, 0)
+    /// );
+    /// ```
+    pub fn try_tokens_into_windows<T: TokenID>(
+        &self,
+        text: &str,
+        max_len: usize,
+        stride: usize,
+    ) -> Result<Windows<T>, EncodeError> {
+        // if the largest id fits, all others do, too
+        let max_id = self.tokens.len().saturating_sub(1) as u64;
+        if T::try_coerce(max_id).is_some() {
+            return self.windows(text, max_len, stride);
+        }
+        let windows = self.windows::<u64>(text, max_len, stride)?;
+        windows
+            .into_iter()
+            .map(|(ids, ranges)| {
+                let ids = ids
+                    .into_iter()
+                    .map(|id| T::try_coerce(id).ok_or(EncodeError::IdOverflow { id }))
+                    .collect::<Result<_, _>>()?;
+                Ok((ids, ranges))
+            })
+            .collect()
+    }
+
+    // splits the tokens of the text into the windows
+    fn windows<T: TokenID>(
+        &self,
+        text: &str,
+        max_len: usize,
+        stride: usize,
+    ) -> Result<Windows<T>, EncodeError> {
+        let prefix = self.prefix.filter(|_| self.options.add_prefix);
+        let suffix = self.suffix.filter(|_| self.options.add_suffix);
+        let specials = usize::from(prefix.is_some()) + usize::from(suffix.is_some());
+        if max_len <= specials {
+            return Err(EncodeError::WindowTooSmall { max_len });
+        }
+        let budget = max_len - specials;
+
+        let (mut ids, mut ranges, mut words) = (Vec::<T>::new(), Vec::new(), Vec::new());
+        self.words_into(text, 0, &mut ids, &mut ranges, Some(&mut words));
+        // the token indices where a window may start or end
+        let mut bounds: Vec<usize> = words.iter().map(|word| word.start).collect();
+        bounds.push(ids.len());
+
+        let mut windows = Vec::new();
+        let mut start = 0;
+        loop {
+            let last_end = bounds.partition_point(|&b| b <= start + budget) - 1;
+            let end = if bounds[last_end] > start {
+                bounds[last_end]
+            } else {
+                // the word doesn't fit into a window
+                (start + budget).min(ids.len())
+            };
+            windows.push(self.window(&ids[start..end], &ranges[start..end], prefix, suffix));
+            if end == ids.len() {
+                return Ok(windows);
+            }
+            // the last word start that gives enough overlap, but at least the
+            // next word start (or the end of a split word)
+            let overlapping = bounds.partition_point(|&b| b + stride <= end);
+            start = match overlapping.checked_sub(1).map(|i| bounds[i]) {
+                Some(bound) if bound > start => bound,
+                _ => bounds[bounds.partition_point(|&b| b <= start)].min(end),
+            };
+        }
+    }
+
+    // wraps the tokens of a window in the special tokens
+    fn window<T: TokenID>(
+        &self,
+        ids: &[T],
+        ranges: &[Range<usize>],
+        prefix: Option<u32>,
+        suffix: Option<u32>,
+    ) -> (Vec<T>, Vec<Range<usize>>) {
+        let (mut window_ids, mut window_ranges) = (Vec::new(), Vec::new());
+        if let Some(id) = prefix {
+            let pos = ranges.first().map_or(0, |range| range.start);
+            window_ids.push(T::coerce(u64::from(id)));
+            window_ranges.push(pos..pos);
+        }
+        window_ids.extend_from_slice(ids);
+        window_ranges.extend_from_slice(ranges);
+        if let Some(id) = suffix {
+            let pos = ranges.last().map_or(0, |range| range.end);
+            window_ids.push(T::coerce(u64::from(id)));
+            window_ranges.push(pos..pos);
+        }
+        (window_ids, window_ranges)
+    }
+}
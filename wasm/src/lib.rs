@@ -0,0 +1,33 @@
+//! Tokenizes text in the browser, e.g. to estimate the length of prompts
+//! on the client. The vocabulary is compiled in, so nothing touches a
+//! filesystem. Build with [wasm-pack](https://github.com/rustwasm/wasm-pack):
+//!
+//! ```text
+//! cd wasm
+//! wasm-pack build --release --target web
+//! ```
+//!
+//! and call it from JavaScript:
+//!
+//! ```text
+//! import init, { encode } from "./pkg/aleph_alpha_tokenizer_wasm.js";
+//! await init();
+//! const ids = encode("Ich esse Steak.");  // a BigInt64Array
+//! ```
+
+use aleph_alpha_tokenizer::AlephAlphaTokenizer;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    static TOKENIZER: AlephAlphaTokenizer =
+        AlephAlphaTokenizer::from_vocab_bytes(include_bytes!("../../vocab.txt"))
+            .expect("the bundled vocabulary is valid");
+}
+
+/// Tokenizes the text into token ids, including `[CLS]` and `[SEP]`.
+#[wasm_bindgen]
+pub fn encode(text: &str) -> Vec<i64> {
+    let (mut ids, mut ranges) = (Vec::new(), Vec::new());
+    TOKENIZER.with(|tokenizer| tokenizer.tokens_into(text, &mut ids, &mut ranges, None));
+    ids
+}